@@ -1,4 +1,19 @@
 pub mod arch;
+mod bindings;
+mod call_graph;
+pub mod commands;
+mod diff;
+pub mod dwarf;
 pub mod view;
 pub mod view_type;
+pub mod workflow;
+mod name_map;
 mod parse;
+mod patched;
+pub mod platform;
+mod report;
+mod segments;
+pub mod settings;
+mod toolchain;
+mod typelib;
+mod wat;
@@ -1,5 +1,18 @@
 mod arch;
+mod assemble;
+mod calling_convention;
 mod insn_text;
 mod insn_info;
+pub(crate) mod intrinsic;
+mod lift;
+mod patch;
+mod register;
+mod relocation;
+pub(crate) mod stack_effect;
 
 pub use arch::WebAssemblyArchitecture;
+pub(crate) use calling_convention::{WebAssemblyCallingConvention, CALLING_CONVENTION_NAME};
+pub(crate) use insn_text::{instruction_text, opcode_name};
+pub(crate) use register::WebAssemblyRegister;
+pub use relocation::WebAssemblyRelocationHandler;
+pub(crate) use relocation::relocation_info;
@@ -1,12 +1,15 @@
 use binaryninja::architecture::{
     Architecture, CoreArchitecture, CoreFlag, CoreFlagClass, CoreFlagGroup, CoreFlagWrite,
-    CoreIntrinsic, CoreRegister, CoreRegisterInfo, CoreRegisterStack, CoreRegisterStackInfo,
-    CustomArchitectureHandle, InstructionInfo, RegisterId,
+    CustomArchitectureHandle, InstructionInfo, IntrinsicId, RegisterId, RegisterStack,
+    RegisterStackId,
 };
 use binaryninja::disassembly::InstructionTextToken;
 use binaryninja::Endianness;
 use binaryninja::low_level_il::LowLevelILMutableFunction;
-use crate::binja::parse::module_data::MODULE_DATA;
+use crate::binja::arch::intrinsic::WebAssemblyIntrinsic;
+use crate::binja::arch::register::{
+    OperandStack, OperandStackInfo, WebAssemblyRegister, WebAssemblyRegisterInfo,
+};
 
 #[derive(Clone)]
 pub struct WebAssemblyArchitecture {
@@ -28,20 +31,22 @@ impl AsRef<CoreArchitecture> for WebAssemblyArchitecture {
 
 impl Architecture for WebAssemblyArchitecture {
     type Handle = CustomArchitectureHandle<Self>;
-    type RegisterInfo = CoreRegisterInfo;
-    type Register = CoreRegister;
-    type RegisterStackInfo = CoreRegisterStackInfo;
-    type RegisterStack = CoreRegisterStack;
+    type RegisterInfo = WebAssemblyRegisterInfo;
+    type Register = WebAssemblyRegister;
+    type RegisterStackInfo = OperandStackInfo;
+    type RegisterStack = OperandStack;
     type Flag = CoreFlag;
     type FlagWrite = CoreFlagWrite;
     type FlagClass = CoreFlagClass;
     type FlagGroup = CoreFlagGroup;
-    type Intrinsic = CoreIntrinsic;
+    type Intrinsic = WebAssemblyIntrinsic;
 
     fn endianness(&self) -> Endianness {
         Endianness::LittleEndian
     }
 
+    // The architecture is shared by every view, so this is the size for 32-bit
+    // modules. Views of 64-bit modules report their own.
     fn address_size(&self) -> usize {
         4
     }
@@ -80,37 +85,86 @@ impl Architecture for WebAssemblyArchitecture {
 
     fn instruction_llil(
         &self,
-        _data: &[u8],
+        data: &[u8],
         addr: u64,
-        _il: &LowLevelILMutableFunction,
+        il: &LowLevelILMutableFunction,
     ) -> Option<(usize, bool)> {
-        let module_data_lock = MODULE_DATA.lock().unwrap();
-        let module_data = module_data_lock.as_ref()?;
-        let func = module_data.funcs.get(&addr)?.as_ref();
+        self._instruction_llil(data, addr, il)
+    }
+
+    fn assemble(&self, code: &str, addr: u64) -> Result<Vec<u8>, String> {
+        self._assemble(code, addr)
+    }
+
+    fn is_never_branch_patch_available(&self, data: &[u8], addr: u64) -> bool {
+        self._is_never_branch_patch_available(data, addr)
+    }
+
+    fn is_always_branch_patch_available(&self, data: &[u8], addr: u64) -> bool {
+        self._is_always_branch_patch_available(data, addr)
+    }
+
+    fn is_invert_branch_patch_available(&self, data: &[u8], addr: u64) -> bool {
+        self._is_invert_branch_patch_available(data, addr)
+    }
+
+    fn is_skip_and_return_zero_patch_available(&self, data: &[u8], addr: u64) -> bool {
+        self._is_skip_and_return_zero_patch_available(data, addr)
+    }
+
+    fn is_skip_and_return_value_patch_available(&self, data: &[u8], addr: u64) -> bool {
+        self._is_skip_and_return_value_patch_available(data, addr)
+    }
 
-        if addr == func.size_start {
-            Some(((func.locals_start - func.size_start) as usize, false))
-        } else if addr == func.locals_start {
-            Some(((func.ops_start - func.locals_start) as usize, false))
-        } else {
-            None
-        }
+    fn convert_to_nop(&self, data: &mut [u8], addr: u64) -> bool {
+        self._convert_to_nop(data, addr)
+    }
+
+    fn always_branch(&self, data: &mut [u8], addr: u64) -> bool {
+        self._always_branch(data, addr)
+    }
+
+    fn invert_branch(&self, data: &mut [u8], addr: u64) -> bool {
+        self._invert_branch(data, addr)
+    }
+
+    fn skip_and_return_value(&self, data: &mut [u8], addr: u64, value: u64) -> bool {
+        self._skip_and_return_value(data, addr, value)
     }
 
     fn registers_all(&self) -> Vec<Self::Register> {
-        Vec::new()
+        WebAssemblyRegister::all()
     }
 
     fn registers_full_width(&self) -> Vec<Self::Register> {
-        Vec::new()
+        WebAssemblyRegister::all()
+            .into_iter()
+            .filter(WebAssemblyRegister::is_full_width)
+            .collect()
     }
 
     fn stack_pointer_reg(&self) -> Option<Self::Register> {
-        None
+        Some(WebAssemblyRegister::StackPointer)
+    }
+
+    fn register_from_id(&self, id: RegisterId) -> Option<Self::Register> {
+        WebAssemblyRegister::from_id(id)
+    }
+
+    fn register_stacks(&self) -> Vec<Self::RegisterStack> {
+        vec![OperandStack]
+    }
+
+    fn register_stack_from_id(&self, id: RegisterStackId) -> Option<Self::RegisterStack> {
+        (id == OperandStack.id()).then_some(OperandStack)
+    }
+
+    fn intrinsics(&self) -> Vec<Self::Intrinsic> {
+        WebAssemblyIntrinsic::all()
     }
 
-    fn register_from_id(&self, _id: RegisterId) -> Option<Self::Register> {
-        None
+    fn intrinsic_from_id(&self, id: IntrinsicId) -> Option<Self::Intrinsic> {
+        WebAssemblyIntrinsic::from_id(id)
     }
 
     fn handle(&self) -> Self::Handle {
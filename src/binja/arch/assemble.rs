@@ -0,0 +1,727 @@
+use crate::binja::arch::insn_text::opcode_name;
+use crate::binja::arch::WebAssemblyArchitecture;
+use crate::binja::parse::func_parse::parse_operator;
+use crate::binja::parse::module_data::{FunctionData, ModuleData, MODULE_DATA};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use wasm_encoder::reencode::{Reencode, RoundtripReencoder};
+use wasm_encoder::{BlockType, Catch, Encode, MemArg};
+use wasmparser::{Operator, RefType, ValType};
+
+// What follows the opcode of an instruction, as the disassembly writes it.
+#[derive(Clone, Copy)]
+enum Immediates {
+    None,
+    BlockType,
+    TryTable,
+    Depth,
+    BrTable,
+    Func,
+    CallIndirect,
+    Local,
+    Global,
+    Tag,
+    MemArg { max_align: u8 },
+    Memory,
+    I32,
+    I64,
+    F32,
+    F64,
+    TableInit,
+    ElemDrop,
+    TableCopy,
+}
+
+fn immediates(op: &Operator) -> Immediates {
+    match op {
+        Operator::Block { .. }
+        | Operator::Loop { .. }
+        | Operator::If { .. }
+        | Operator::Try { .. } => Immediates::BlockType,
+        Operator::TryTable { .. } => Immediates::TryTable,
+        Operator::Br { .. }
+        | Operator::BrIf { .. }
+        | Operator::Rethrow { .. }
+        | Operator::Delegate { .. } => Immediates::Depth,
+        Operator::BrTable { .. } => Immediates::BrTable,
+        Operator::Call { .. } => Immediates::Func,
+        Operator::CallIndirect { .. } => Immediates::CallIndirect,
+        Operator::LocalGet { .. } | Operator::LocalSet { .. } | Operator::LocalTee { .. } => {
+            Immediates::Local
+        }
+        Operator::GlobalGet { .. } | Operator::GlobalSet { .. } => Immediates::Global,
+        Operator::Throw { .. } | Operator::Catch { .. } => Immediates::Tag,
+        Operator::MemorySize { .. } | Operator::MemoryGrow { .. } => Immediates::Memory,
+        Operator::I32Const { .. } => Immediates::I32,
+        Operator::I64Const { .. } => Immediates::I64,
+        Operator::F32Const { .. } => Immediates::F32,
+        Operator::F64Const { .. } => Immediates::F64,
+        Operator::TableInit { .. } => Immediates::TableInit,
+        Operator::ElemDrop { .. } => Immediates::ElemDrop,
+        Operator::TableCopy { .. } => Immediates::TableCopy,
+        _ => match crate::binja::parse::pointers::memory_access(op) {
+            Some((memarg, ..)) => Immediates::MemArg {
+                max_align: memarg.max_align,
+            },
+            None => Immediates::None,
+        },
+    }
+}
+
+// The opcode and immediates of each instruction the disassembler knows, by the name it
+// writes it with. They are found by disassembling every opcode, so that the two always
+// agree on names.
+static OPCODES: Lazy<HashMap<String, (Vec<u8>, Immediates)>> = Lazy::new(|| {
+    let module_data = ModuleData::new();
+    let prefixes = (0..0xfc)
+        .map(|opcode| vec![opcode])
+        .chain((0..0x20).map(|opcode| vec![0xfc, opcode]));
+    let mut opcodes = HashMap::new();
+    for prefix in prefixes {
+        // Immediates of all zeros decode as the smallest of each kind.
+        let data = [prefix.as_slice(), &[0; 16]].concat();
+        let Some(op) = parse_operator(&data) else {
+            continue;
+        };
+        if let Some(name) = opcode_name(&module_data, &op) {
+            opcodes
+                .entry(name)
+                .or_insert_with(|| (prefix, immediates(&op.op)));
+        }
+    }
+    opcodes
+});
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Char(u64),
+    Str,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Arrow,
+}
+
+// The value of a C character literal as the disassembly writes constants, e.g. 0x6162
+// for `'ab'`, from the text after its opening quote.
+fn char_literal(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<u64, String> {
+    let mut value: u64 = 0;
+    let mut len = 0;
+    loop {
+        let c = match chars.next() {
+            Some('\'') => return Ok(value),
+            Some('\\') => chars.next(),
+            c => c,
+        };
+        let Some(c) = c.filter(char::is_ascii) else {
+            return Err("Unterminated character literal".to_string());
+        };
+        len += 1;
+        if len > 8 {
+            return Err("Character literal is longer than 8 bytes".to_string());
+        }
+        value = value << 8 | c as u64;
+    }
+}
+
+// Splits instruction text into tokens, leaving out comments and the annotations the
+// disassembly adds after a `;` (e.g. the `; func[17]` in `call malloc  ; func[17]`).
+fn tokenize(code: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = code.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            ';' => while chars.next_if(|c| *c != '\n').is_some() {},
+            '(' if chars.next_if_eq(&';').is_some() => {
+                let mut depth = 1;
+                while depth > 0 {
+                    match chars.next() {
+                        Some('(') if chars.next_if_eq(&';').is_some() => depth += 1,
+                        Some(';') if chars.next_if_eq(&')').is_some() => depth -= 1,
+                        Some(_) => {}
+                        None => return Err("Unterminated block comment".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            chars.next();
+                        }
+                        Some(_) => {}
+                        None => return Err("Unterminated string".to_string()),
+                    }
+                }
+                tokens.push(Token::Str);
+            }
+            '\'' => tokens.push(Token::Char(char_literal(&mut chars)?)),
+            '(' => tokens.push(Token::LParen),
+            ')' => tokens.push(Token::RParen),
+            '[' => tokens.push(Token::LBracket),
+            ']' => tokens.push(Token::RBracket),
+            ',' => tokens.push(Token::Comma),
+            '→' => tokens.push(Token::Arrow),
+            c => {
+                let mut word = String::from(c);
+                while let Some(c) =
+                    chars.next_if(|c| !c.is_whitespace() && !"()[],;→'\"".contains(*c))
+                {
+                    word.push(c);
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// An integer as the disassembly and WAT write them: in decimal or hex, maybe negative,
+// maybe with `_` separators.
+fn parse_integer(text: &str) -> Option<i128> {
+    let text = text.replace('_', "");
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.strip_prefix('+').unwrap_or(&text)),
+    };
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => i128::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<i128>().ok()?,
+    };
+    Some(if negative { -value } else { value })
+}
+
+fn parse_val_type(text: &str) -> Option<ValType> {
+    Some(match text {
+        "i32" => ValType::I32,
+        "i64" => ValType::I64,
+        "f32" => ValType::F32,
+        "f64" => ValType::F64,
+        "v128" => ValType::V128,
+        "funcref" => ValType::Ref(RefType::FUNCREF),
+        "externref" => ValType::Ref(RefType::EXTERNREF),
+        _ => return None,
+    })
+}
+
+// The addresses that branches at `addr` go to, by relative depth: the start of a loop,
+// or else the instruction after the end of the block, and last the end of the function.
+fn label_addrs(func: &FunctionData, addr: u64) -> Vec<Option<u64>> {
+    let mut starts = Vec::new();
+    for (op_addr, op) in func.ops.range(func.ops_start..addr) {
+        match op.op {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. }
+            | Operator::TryTable { .. } => starts.push(*op_addr),
+            Operator::End | Operator::Delegate { .. } => {
+                starts.pop();
+            }
+            _ => {}
+        }
+    }
+    let ends = func
+        .block_starts
+        .iter()
+        .map(|(end, start)| (*start, *end))
+        .collect::<HashMap<_, _>>();
+    let label_addr = |start: &u64| match func.ops.get(start)?.op {
+        Operator::Loop { .. } => Some(*start),
+        _ => {
+            let end = ends.get(start)?;
+            Some(end + func.ops.get(end)?.size as u64)
+        }
+    };
+    starts
+        .iter()
+        .rev()
+        .map(label_addr)
+        .chain([Some(func.end)])
+        .collect()
+}
+
+// Reads instructions written the way the disassembly writes them, resolving the names
+// and addresses it shows to indices and depths, and encodes them.
+struct Assembler<'a> {
+    module_data: &'a ModuleData,
+    func: Option<&'a FunctionData>,
+    addr: u64,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Assembler<'_> {
+    fn peek(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n)
+    }
+
+    fn peek_word(&self, n: usize) -> Option<&str> {
+        match self.peek(n)? {
+            Token::Word(word) => Some(word),
+            _ => None,
+        }
+    }
+
+    // Consumes the next tokens if they are `expected`.
+    fn eat(&mut self, expected: &[Token]) -> bool {
+        let matches = expected
+            .iter()
+            .enumerate()
+            .all(|(n, token)| self.peek(n) == Some(token));
+        if matches {
+            self.pos += expected.len();
+        }
+        matches
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.eat(&[expected]) {
+            true => Ok(()),
+            false => Err(format!("Expected {:?}", self.tokens.get(self.pos))),
+        }
+    }
+
+    fn word(&mut self) -> Result<String, String> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Word(word)) => {
+                self.pos += 1;
+                Ok(word.clone())
+            }
+            token => Err(format!("Expected a name or number, not {token:?}")),
+        }
+    }
+
+    fn integer(&mut self) -> Result<i128, String> {
+        let word = self.word()?;
+        parse_integer(&word).ok_or_else(|| format!("`{word}` is not an integer"))
+    }
+
+    fn index(&mut self) -> Result<u32, String> {
+        let value = self.integer()?;
+        u32::try_from(value).map_err(|_| format!("{value} is not an index"))
+    }
+
+    // An integer constant, which the disassembly may write as a character literal.
+    fn constant(&mut self) -> Result<i128, String> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Char(value)) => {
+                self.pos += 1;
+                Ok(*value as i128)
+            }
+            _ => self.integer(),
+        }
+    }
+
+    fn is_integer(&self) -> bool {
+        self.peek_word(0).and_then(parse_integer).is_some()
+    }
+
+    // The index the disassembly shows in parentheses after a name or address, e.g. the
+    // `(1)` in `br 0x1234 (1)`.
+    fn parenthesized_index(&mut self) -> Result<Option<u32>, String> {
+        let is_index = self.peek(0) == Some(&Token::LParen)
+            && self.peek_word(1).and_then(parse_integer).is_some()
+            && self.peek(2) == Some(&Token::RParen);
+        if !is_index {
+            return Ok(None);
+        }
+        self.pos += 1;
+        let index = self.index()?;
+        self.pos += 1;
+        Ok(Some(index))
+    }
+
+    // An index written as a number or a name, which `names` resolves. A name may be
+    // followed by the index it stands for, which is taken over the name.
+    fn named_index(
+        &mut self,
+        kind: &str,
+        names: impl Fn(&str) -> Option<u32>,
+    ) -> Result<u32, String> {
+        if self.is_integer() {
+            return self.index();
+        }
+        let name = self.word()?;
+        if let Some(index) = self.parenthesized_index()? {
+            return Ok(index);
+        }
+        let bare = name.strip_prefix('$').unwrap_or(&name);
+        names(bare).ok_or_else(|| format!("There is no {kind} named `{bare}`"))
+    }
+
+    // The relative depth of a branch to `target`, which is either a depth or the
+    // address of the label the disassembly shows in its place.
+    fn depth_of(&self, target: i128) -> Result<u32, String> {
+        let labels = self.func.map(|func| label_addrs(func, self.addr));
+        let Some(labels) = labels.filter(|labels| target >= labels.len() as i128) else {
+            return u32::try_from(target).map_err(|_| format!("{target} is not a depth"));
+        };
+        labels
+            .iter()
+            .position(|label| label.is_some_and(|label| label as i128 == target))
+            .map(|depth| depth as u32)
+            .ok_or_else(|| format!("No block around {:#x} ends at {target:#x}", self.addr))
+    }
+
+    fn depth(&mut self) -> Result<u32, String> {
+        let target = self.integer()?;
+        match self.parenthesized_index()? {
+            Some(depth) => Ok(depth),
+            None => self.depth_of(target),
+        }
+    }
+
+    fn val_types(&mut self) -> Result<Vec<ValType>, String> {
+        let mut types = Vec::new();
+        while let Some(ty) = self.peek_word(0).and_then(parse_val_type) {
+            types.push(ty);
+            self.pos += 1;
+        }
+        self.expect(Token::RParen)?;
+        Ok(types)
+    }
+
+    // A block type, written as the params and results of the block, or as a type index.
+    fn block_type(&mut self) -> Result<BlockType, String> {
+        let mut params = Vec::new();
+        let mut results = Vec::new();
+        while self.peek(0) == Some(&Token::LParen) {
+            match self.peek_word(1) {
+                Some("param") => {
+                    self.pos += 2;
+                    params.extend(self.val_types()?);
+                }
+                Some("result") => {
+                    self.pos += 2;
+                    results.extend(self.val_types()?);
+                }
+                Some("type") => {
+                    self.pos += 2;
+                    let type_index = self.index()?;
+                    self.expect(Token::RParen)?;
+                    return Ok(BlockType::FunctionType(type_index));
+                }
+                _ => break,
+            }
+        }
+        match (params.as_slice(), results.as_slice()) {
+            ([], []) => Ok(BlockType::Empty),
+            ([], [ty]) => RoundtripReencoder
+                .val_type(*ty)
+                .map(BlockType::Result)
+                .map_err(|e| e.to_string()),
+            _ => self
+                .module_data
+                .types
+                .iter()
+                .position(|ty| ty.params() == params && ty.results() == results)
+                .map(|type_index| BlockType::FunctionType(type_index as u32))
+                .ok_or_else(|| "The module has no type for the block".to_string()),
+        }
+    }
+
+    // The cases of a `br_table`, written either as the disassembly writes them, e.g.
+    // `[0]→0x1234, [1]→0x1250, default→0x1300`, or as depths with the default last.
+    fn br_table(&mut self, bytes: &mut Vec<u8>) -> Result<(), String> {
+        let mut depths = Vec::new();
+        let default = loop {
+            if self.eat(&[Token::LBracket]) {
+                self.integer()?;
+                self.expect(Token::RBracket)?;
+                self.expect(Token::Arrow)?;
+                depths.push(self.depth()?);
+                self.eat(&[Token::Comma]);
+            } else if self.eat(&[Token::Word("default".to_string()), Token::Arrow]) {
+                break self.depth()?;
+            } else if self.is_integer() {
+                depths.push(self.depth()?);
+                if !self.is_integer() {
+                    break depths.pop().unwrap_or_default();
+                }
+            } else {
+                return Err("Expected the cases of the br_table".to_string());
+            }
+        };
+        depths.encode(bytes);
+        default.encode(bytes);
+        Ok(())
+    }
+
+    // A memarg, written as the fields that aren't the default, e.g. `offset=0x10
+    // align=1`. The alignment is in bytes, or a power of two that is too large to
+    // write out, e.g. `align=2^40`.
+    fn memarg(&mut self, max_align: u8) -> Result<MemArg, String> {
+        let mut memarg = MemArg {
+            offset: 0,
+            align: max_align as u32,
+            memory_index: 0,
+        };
+        while let Some((key, value)) = self.peek_word(0).and_then(|word| word.split_once('=')) {
+            let (key, value) = (key.to_string(), value.to_string());
+            let integer = |text: &str| {
+                parse_integer(text).ok_or_else(|| format!("`{text}` is not an integer"))
+            };
+            match key.as_str() {
+                "mem" => {
+                    memarg.memory_index =
+                        u32::try_from(integer(&value)?).map_err(|e| e.to_string())?
+                }
+                "offset" => {
+                    memarg.offset = u64::try_from(integer(&value)?).map_err(|e| e.to_string())?
+                }
+                "align" => {
+                    memarg.align = match value.strip_prefix("2^") {
+                        Some(exponent) => {
+                            u32::try_from(integer(exponent)?).map_err(|e| e.to_string())?
+                        }
+                        None => {
+                            let align =
+                                u64::try_from(integer(&value)?).map_err(|e| e.to_string())?;
+                            if !align.is_power_of_two() {
+                                return Err(format!("Alignment {align} is not a power of two"));
+                            }
+                            align.trailing_zeros()
+                        }
+                    };
+                }
+                _ => return Err(format!("Unknown memarg field `{key}`")),
+            }
+            self.pos += 1;
+        }
+        Ok(memarg)
+    }
+
+    // A catch clause of a `try_table`, e.g. `(catch tag 1)` or `(catch_all 0)`.
+    fn catch(&mut self) -> Result<Option<Catch>, String> {
+        let Some(keyword) = self.peek_word(1).map(str::to_string) else {
+            return Ok(None);
+        };
+        if self.peek(0) != Some(&Token::LParen) || !keyword.starts_with("catch") {
+            return Ok(None);
+        }
+        self.pos += 2;
+        let catch = match keyword.as_str() {
+            "catch" => Catch::One {
+                tag: self.tag()?,
+                label: self.index()?,
+            },
+            "catch_ref" => Catch::OneRef {
+                tag: self.tag()?,
+                label: self.index()?,
+            },
+            "catch_all" => Catch::All {
+                label: self.index()?,
+            },
+            "catch_all_ref" => Catch::AllRef {
+                label: self.index()?,
+            },
+            _ => return Err(format!("Unknown catch clause `{keyword}`")),
+        };
+        self.expect(Token::RParen)?;
+        Ok(Some(catch))
+    }
+
+    fn tag(&mut self) -> Result<u32, String> {
+        let tag_names = &self.module_data.tag_names;
+        let find = |name: &str| {
+            tag_names
+                .iter()
+                .find(|(_, tag_name)| *tag_name == name)
+                .map(|(tag_index, _)| *tag_index)
+        };
+        self.named_index("tag", find)
+    }
+
+    fn func(&mut self) -> Result<u32, String> {
+        let module_data = self.module_data;
+        let find = |name: &str| {
+            (0..module_data.func_types.len() as u32)
+                .find(|func_index| module_data.func_name(*func_index) == name)
+        };
+        self.named_index("function", find)
+    }
+
+    fn local(&mut self) -> Result<u32, String> {
+        let module_data = self.module_data;
+        let func = self.func;
+        let find = |name: &str| {
+            let func = func?;
+            let n_params = module_data.func_type(func.func_index)?.params().len() as u32;
+            let n_locals = n_params + func.locals.iter().map(|(count, _)| count).sum::<u32>();
+            (0..n_locals).find(|index| module_data.local_name(func.func_index, *index) == name)
+        };
+        self.named_index("local", find)
+    }
+
+    fn global(&mut self) -> Result<u32, String> {
+        let global_names = &self.module_data.global_names;
+        let find = |name: &str| {
+            global_names
+                .iter()
+                .find(|(_, global_name)| *global_name == name)
+                .map(|(global_index, _)| *global_index)
+        };
+        self.named_index("global", find)
+    }
+
+    // Two indices, which the disassembly separates with a comma.
+    fn index_pair(&mut self) -> Result<(u32, u32), String> {
+        let first = self.index()?;
+        self.eat(&[Token::Comma]);
+        Ok((first, self.index()?))
+    }
+
+    // Skips what the disassembly shows after an instruction without a `;`: where an
+    // `if` goes when its condition is false, e.g. `→ else @ 0x4e0`, a branch hint, and
+    // the `(func)` after the function's final `end`.
+    fn skip_annotations(&mut self) {
+        loop {
+            let is_false_target = self.peek(0) == Some(&Token::Arrow)
+                && matches!(self.peek_word(1), Some("else" | "end"))
+                && self.peek_word(2) == Some("@");
+            if is_false_target {
+                self.pos += 4;
+                continue;
+            }
+            let is_note = self.peek(0) == Some(&Token::LParen)
+                && matches!(self.peek_word(1), Some("likely" | "unlikely" | "func"))
+                && self.peek(2) == Some(&Token::RParen);
+            if is_note {
+                self.pos += 3;
+                continue;
+            }
+            return;
+        }
+    }
+
+    fn instruction(&mut self, bytes: &mut Vec<u8>) -> Result<(), String> {
+        let name = self.word()?;
+        let (opcode, immediates) = OPCODES
+            .get(&name)
+            .ok_or_else(|| format!("Unknown instruction `{name}`"))?;
+        bytes.extend(opcode);
+        match *immediates {
+            Immediates::None => {}
+            Immediates::BlockType => self.block_type()?.encode(bytes),
+            Immediates::TryTable => {
+                self.block_type()?.encode(bytes);
+                let mut catches = Vec::new();
+                while let Some(catch) = self.catch()? {
+                    catches.push(catch);
+                }
+                catches.encode(bytes);
+            }
+            Immediates::Depth => self.depth()?.encode(bytes),
+            Immediates::BrTable => self.br_table(bytes)?,
+            Immediates::Func => self.func()?.encode(bytes),
+            Immediates::CallIndirect => {
+                // WAT writes the table first, if it isn't table 0.
+                let mut table_index = 0;
+                if self.is_integer() && self.peek(1) == Some(&Token::LParen) {
+                    table_index = self.index()?;
+                }
+                let type_index = match self.eat(&[Token::LParen, Token::Word("type".into())]) {
+                    true => {
+                        let type_index = self.index()?;
+                        self.expect(Token::RParen)?;
+                        type_index
+                    }
+                    false => self.index()?,
+                };
+                type_index.encode(bytes);
+                table_index.encode(bytes);
+            }
+            Immediates::Local => self.local()?.encode(bytes),
+            Immediates::Global => self.global()?.encode(bytes),
+            Immediates::Tag => self.tag()?.encode(bytes),
+            Immediates::MemArg { max_align } => self.memarg(max_align)?.encode(bytes),
+            Immediates::Memory => {
+                let mem = if self.is_integer() { self.index()? } else { 0 };
+                mem.encode(bytes);
+            }
+            Immediates::I32 => {
+                let value = self.constant()?;
+                if !(i32::MIN as i128..=u32::MAX as i128).contains(&value) {
+                    return Err(format!("{value} doesn't fit in an i32"));
+                }
+                (value as i32).encode(bytes);
+            }
+            Immediates::I64 => {
+                let value = self.constant()?;
+                if !(i64::MIN as i128..=u64::MAX as i128).contains(&value) {
+                    return Err(format!("{value} doesn't fit in an i64"));
+                }
+                (value as i64).encode(bytes);
+            }
+            Immediates::F32 => {
+                let word = self.word()?;
+                let value = word
+                    .parse::<f32>()
+                    .map_err(|_| format!("`{word}` is not a number"))?;
+                bytes.extend(value.to_bits().to_le_bytes());
+            }
+            Immediates::F64 => {
+                let word = self.word()?;
+                let value = word
+                    .parse::<f64>()
+                    .map_err(|_| format!("`{word}` is not a number"))?;
+                bytes.extend(value.to_bits().to_le_bytes());
+            }
+            Immediates::TableInit => {
+                // Written table first, but encoded segment first.
+                let (table, elem_index) = self.index_pair()?;
+                elem_index.encode(bytes);
+                table.encode(bytes);
+            }
+            Immediates::ElemDrop => self.index()?.encode(bytes),
+            Immediates::TableCopy => {
+                let (dst_table, src_table) = self.index_pair()?;
+                dst_table.encode(bytes);
+                src_table.encode(bytes);
+            }
+        }
+        self.skip_annotations();
+        Ok(())
+    }
+}
+
+impl WebAssemblyArchitecture {
+    // Assembles instructions written the way the disassembly writes them, so that an
+    // instruction can be edited starting from its disassembly, or in WAT with plain
+    // indices (e.g. `i32.const 5`, `call 17`). Names of functions, locals, globals and
+    // tags are resolved in the module, and branch targets shown as addresses in the
+    // function at `addr`. The instructions aren't validated, so blocks needn't be
+    // balanced.
+    pub(crate) fn _assemble(&self, code: &str, addr: u64) -> Result<Vec<u8>, String> {
+        let module_data_lock = MODULE_DATA.lock().unwrap();
+        let empty_module_data;
+        let module_data = match module_data_lock.as_ref() {
+            Some(module_data) => module_data,
+            None => {
+                empty_module_data = ModuleData::new();
+                &empty_module_data
+            }
+        };
+        let mut assembler = Assembler {
+            module_data,
+            func: module_data.funcs.get(&addr).map(|func| func.as_ref()),
+            addr,
+            tokens: tokenize(code)?,
+            pos: 0,
+        };
+        let mut bytes = Vec::new();
+        while assembler.pos < assembler.tokens.len() {
+            assembler.instruction(&mut bytes)?;
+        }
+        if bytes.is_empty() {
+            return Err("No instructions were assembled".into());
+        }
+        Ok(bytes)
+    }
+}
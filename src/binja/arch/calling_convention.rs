@@ -0,0 +1,86 @@
+use crate::binja::arch::register::{LocalWidth, MAX_PARAMS, MAX_RESULTS};
+use crate::binja::arch::WebAssemblyRegister;
+use binaryninja::architecture::{Register, RegisterId};
+use binaryninja::calling_convention::CallingConvention;
+
+pub(crate) const CALLING_CONVENTION_NAME: &str = "wasm";
+
+// Wasm passes arguments and results on the operand stack, which calls are lifted to
+// move to and from the argument and result registers. Each argument has its own
+// register, whatever its type, and the result registers hold the first two results.
+pub struct WebAssemblyCallingConvention;
+
+fn ids(regs: impl Iterator<Item = WebAssemblyRegister>) -> Vec<RegisterId> {
+    regs.map(|reg| reg.id()).collect()
+}
+
+fn arg_registers() -> Vec<RegisterId> {
+    ids((0..MAX_PARAMS).map(|index| WebAssemblyRegister::Arg(index, LocalWidth::Bits64)))
+}
+
+fn result_register(index: u32) -> Option<RegisterId> {
+    Some(WebAssemblyRegister::Result(index, LocalWidth::Bits64).id())
+}
+
+impl CallingConvention for WebAssemblyCallingConvention {
+    // Locals and the operand stack belong to each function, so a call only changes the
+    // registers that pass values to and from it.
+    fn caller_saved_registers(&self) -> Vec<RegisterId> {
+        let results =
+            (0..MAX_RESULTS).map(|index| WebAssemblyRegister::Result(index, LocalWidth::Bits64));
+        [arg_registers(), ids(results)].concat()
+    }
+
+    fn callee_saved_registers(&self) -> Vec<RegisterId> {
+        Vec::new()
+    }
+
+    fn int_arg_registers(&self) -> Vec<RegisterId> {
+        arg_registers()
+    }
+
+    // Floats are passed in the same registers, by their position among all arguments.
+    fn float_arg_registers(&self) -> Vec<RegisterId> {
+        arg_registers()
+    }
+
+    fn arg_registers_shared_index(&self) -> bool {
+        true
+    }
+
+    fn reserved_stack_space_for_arg_registers(&self) -> bool {
+        false
+    }
+
+    fn stack_adjusted_on_return(&self) -> bool {
+        false
+    }
+
+    fn is_eligible_for_heuristics(&self) -> bool {
+        true
+    }
+
+    fn return_int_reg(&self) -> Option<RegisterId> {
+        result_register(0)
+    }
+
+    fn return_hi_int_reg(&self) -> Option<RegisterId> {
+        result_register(1)
+    }
+
+    fn return_float_reg(&self) -> Option<RegisterId> {
+        result_register(0)
+    }
+
+    fn global_pointer_reg(&self) -> Option<RegisterId> {
+        None
+    }
+
+    fn implicitly_defined_registers(&self) -> Vec<RegisterId> {
+        Vec::new()
+    }
+
+    fn are_argument_registers_used_for_var_args(&self) -> bool {
+        false
+    }
+}
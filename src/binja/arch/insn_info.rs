@@ -1,73 +1,96 @@
 use crate::binja::arch::WebAssemblyArchitecture;
-use crate::binja::parse::module_data::{BranchTargetAddr, MODULE_DATA};
+use crate::binja::parse::func_parse::parse_operator;
+use crate::binja::parse::module_data::{BranchTargetAddr, ModuleData, OperatorData, MODULE_DATA};
 use binaryninja::architecture::{BranchInfo, BranchKind, InstructionInfo};
 use wasmparser::Operator;
 
 impl WebAssemblyArchitecture {
-    pub(crate) fn _instruction_info(&self, _data: &[u8], addr: u64) -> Option<InstructionInfo> {
+    pub(crate) fn _instruction_info(&self, data: &[u8], addr: u64) -> Option<InstructionInfo> {
         let module_data_lock = MODULE_DATA.lock().unwrap();
-        let module_data = module_data_lock.as_ref()?;
-        let func = module_data.funcs.get(&addr)?.as_ref();
-
-        if addr == func.size_start {
-            Some(InstructionInfo::new(
-                (func.locals_start - func.size_start) as usize,
-                0,
-            ))
-        } else if addr == func.locals_start {
-            Some(InstructionInfo::new(
-                (func.ops_start - func.locals_start) as usize,
-                0,
-            ))
-        } else {
-            let op = func.ops.get(&addr)?;
-            let mut info = InstructionInfo::new(op.size, 0);
+        let empty_module_data;
+        let module_data = match module_data_lock.as_ref() {
+            Some(module_data) => module_data,
+            None => {
+                empty_module_data = ModuleData::new();
+                &empty_module_data
+            }
+        };
 
-            if let Some(target) = &op.target {
-                match target {
-                    BranchTargetAddr::Unconditional(addr) => {
-                        info.add_branch(BranchInfo::new(BranchKind::Unconditional(*addr)));
-                    }
-                    BranchTargetAddr::Conditional { true_target, false_target } => {
-                        info.add_branch(BranchInfo::new(BranchKind::True(*true_target)));
-                        info.add_branch(BranchInfo::new(BranchKind::False(*false_target)));
-                    }
-                    BranchTargetAddr::Table { .. } => {
-                        // Unfortunately, there's no way to tell binja about the candidate
-                        // addresses...
-                        info.add_branch(BranchInfo::new(BranchKind::Indirect));
-                    }
-                    BranchTargetAddr::FunctionEnd => {
-                        info.add_branch(BranchInfo::new(BranchKind::FunctionReturn));
-                    }
-                }
+        if let Some(func) = module_data.funcs.get(&addr).map(|func| func.as_ref()) {
+            if addr == func.size_start {
+                return Some(InstructionInfo::new(
+                    (func.locals_start - func.size_start) as usize,
+                    0,
+                ));
+            } else if addr == func.locals_start {
+                return Some(InstructionInfo::new(
+                    (func.ops_start - func.locals_start) as usize,
+                    0,
+                ));
+            } else if let Some(op) = func.unpatched_op(addr, data) {
+                return operator_info(module_data, op);
             }
+        }
 
-            // Some additional instructions that binja wants us to tell it about.
-            match &op.op {
-                Operator::Unreachable => {
-                    info.add_branch(BranchInfo::new(BranchKind::Exception));
-                }
-                Operator::Return => {
-                    info.add_branch(BranchInfo::new(BranchKind::FunctionReturn));
-                }
-                Operator::Call { function_index } => {
-                    let addr = *module_data.func_addrs.get(*function_index as usize)?;
-                    info.add_branch(BranchInfo::new(BranchKind::Call(addr)));
-                }
-                Operator::CallIndirect { type_index, table_index } => {
-                    // Technically, we should be able to deduce candidate addresses for
-                    // the call based off the func type information...
-                    //
-                    // Don't actually tell binja about the indirect call since
-                    // BranchKind::Indirect doesn't know its a call and assumes it won't
-                    // return.
-                    // info.add_branch(BranchInfo::new(BranchKind::Indirect));
-                }
-                _ => {}
+        // Bytes that aren't an instruction of the parsed module are decoded on their
+        // own. Without the enclosing blocks, only branches that don't depend on them
+        // (calls, returns and traps) are known.
+        let op = parse_operator(data)?;
+        operator_info(module_data, &op)
+    }
+}
+
+fn operator_info(module_data: &ModuleData, op: &OperatorData) -> Option<InstructionInfo> {
+    let mut info = InstructionInfo::new(op.size, 0);
+
+    if let Some(target) = &op.target {
+        match target {
+            BranchTargetAddr::Unconditional(addr) => {
+                info.add_branch(BranchInfo::new(BranchKind::Unconditional(*addr)));
+            }
+            BranchTargetAddr::Conditional { true_target, false_target } => {
+                info.add_branch(BranchInfo::new(BranchKind::True(*true_target)));
+                info.add_branch(BranchInfo::new(BranchKind::False(*false_target)));
+            }
+            BranchTargetAddr::Table { .. } => {
+                // Unfortunately, there's no way to tell binja about the candidate
+                // addresses...
+                info.add_branch(BranchInfo::new(BranchKind::Indirect));
             }
+            BranchTargetAddr::FunctionEnd => {
+                info.add_branch(BranchInfo::new(BranchKind::FunctionReturn));
+            }
+        }
+    }
 
-            Some(info)
+    // Some additional instructions that binja wants us to tell it about.
+    match &op.op {
+        Operator::Unreachable
+        | Operator::Throw { .. }
+        | Operator::ThrowRef
+        | Operator::Rethrow { .. } => {
+            info.add_branch(BranchInfo::new(BranchKind::Exception));
+        }
+        Operator::Return => {
+            info.add_branch(BranchInfo::new(BranchKind::FunctionReturn));
         }
+        Operator::Call { function_index } => {
+            // Calls decoded on their own may refer to a function the module doesn't have.
+            if let Some(addr) = module_data.func_addrs.get(*function_index as usize) {
+                info.add_branch(BranchInfo::new(BranchKind::Call(*addr)));
+            }
+        }
+        Operator::CallIndirect { type_index, table_index } => {
+            // The candidate callees are deduced from the table and type when
+            // the module is parsed, and added as code references instead.
+            //
+            // Don't actually tell binja about the indirect call since
+            // BranchKind::Indirect doesn't know its a call and assumes it won't
+            // return.
+            // info.add_branch(BranchInfo::new(BranchKind::Indirect));
+        }
+        _ => {}
     }
+
+    Some(info)
 }
@@ -1,7 +1,11 @@
 use crate::binja::arch::WebAssemblyArchitecture;
-use crate::binja::parse::module_data::MODULE_DATA;
+use crate::binja::parse::func_parse::parse_operator;
+use crate::binja::parse::module_data::{
+    BranchTargetAddr, FunctionData, ModuleData, OperatorData, MODULE_DATA,
+};
+use crate::binja::settings::ConstantDisplay;
 use binaryninja::disassembly::{InstructionTextToken, InstructionTextTokenKind};
-use wasmparser::Operator;
+use wasmparser::{BlockType, Catch, MemArg, Operator, TryTable, ValType};
 
 // https://github.com/Vector35/binaryninja-api/blob/99ed22fd9799ccfa0367b03de4d04d3b9ab26cd5/arch/x86/arch_x86.cpp#L743
 fn padding(insn_name_length: usize) -> InstructionTextToken {
@@ -13,6 +17,593 @@ fn padding(insn_name_length: usize) -> InstructionTextToken {
     InstructionTextToken::new(" ".repeat(8 - min), InstructionTextTokenKind::Text)
 }
 
+fn integer(value: u32) -> InstructionTextToken {
+    InstructionTextToken::new(
+        format!("{value}"),
+        InstructionTextTokenKind::Integer {
+            value: value as u64,
+            size: Some(4),
+        },
+    )
+}
+
+// Writes the bytes of an integer as a C character literal (e.g. `'ab'` for 0x6162),
+// if they are all printable ASCII.
+fn char_literal(value: u64) -> Option<String> {
+    let bytes = value.to_be_bytes();
+    let first = bytes.iter().position(|b| *b != 0)?;
+    let mut literal = String::from("'");
+    for b in &bytes[first..] {
+        match *b {
+            b'\'' | b'\\' => {
+                literal.push('\\');
+                literal.push(*b as char);
+            }
+            0x20..=0x7e => literal.push(*b as char),
+            _ => return None,
+        }
+    }
+    literal.push('\'');
+    Some(literal)
+}
+
+// Negative constants at least this large are sentinels or offsets (e.g. `-1`, `-16`),
+// which read better in decimal than as hex of their two's complement.
+const MIN_SIGNED_CONSTANT: i64 = -0x10000;
+
+fn is_small_negative(value: i64) -> bool {
+    (MIN_SIGNED_CONSTANT..0).contains(&value)
+}
+
+// The low `size` bytes of a constant, which are the only significant ones.
+fn constant_bits(value: i64, size: usize) -> u64 {
+    match size {
+        4 => value as u32 as u64,
+        _ => value as u64,
+    }
+}
+
+// An `i32.const` or `i64.const` operand of `size` bytes, shown as the user chose.
+fn constant(display: ConstantDisplay, value: i64, size: usize) -> InstructionTextToken {
+    let bits = constant_bits(value, size);
+    let text = match display {
+        ConstantDisplay::Hex if is_small_negative(value) => format!("{value}"),
+        ConstantDisplay::Hex => format!("{bits:#x}"),
+        ConstantDisplay::Decimal => format!("{value}"),
+        ConstantDisplay::Char => char_literal(bits).unwrap_or_else(|| format!("{bits:#x}")),
+    };
+    InstructionTextToken::new(
+        text,
+        InstructionTextTokenKind::Integer {
+            value: value as u64,
+            size: Some(size),
+        },
+    )
+}
+
+// An `i32.const` or `i64.const` operand that points into a mapped data segment, shown in
+// hex as a pointer to where the data is mapped in the view.
+fn data_pointer(module_data: &ModuleData, value: i64, size: usize) -> Option<InstructionTextToken> {
+    let bits = constant_bits(value, size);
+    if module_data.constant_display != ConstantDisplay::Hex || !module_data.is_data_address(bits) {
+        return None;
+    }
+    Some(InstructionTextToken::new(
+        format!("{bits:#x}"),
+        InstructionTextTokenKind::PossibleAddress {
+            value: module_data.memory_base + bits,
+            size: Some(size),
+        },
+    ))
+}
+
+// Annotates a small negative constant shown in decimal in place of hex with its hex
+// bits, e.g. `i32.const -1  ; 0xffffffff`.
+fn signed_constant_bits(
+    display: ConstantDisplay,
+    value: i64,
+    size: usize,
+) -> Vec<InstructionTextToken> {
+    if display != ConstantDisplay::Hex || !is_small_negative(value) {
+        return Vec::new();
+    }
+    let bits = constant_bits(value, size);
+    vec![
+        InstructionTextToken::new("  ", InstructionTextTokenKind::Text),
+        InstructionTextToken::new("; ", InstructionTextTokenKind::Annotation),
+        InstructionTextToken::new(
+            format!("{bits:#x}"),
+            InstructionTextTokenKind::Integer {
+                value: bits,
+                size: Some(size),
+            },
+        ),
+    ]
+}
+
+fn float(value: f64, size: usize) -> InstructionTextToken {
+    InstructionTextToken::new(
+        format!("{value}"),
+        InstructionTextTokenKind::FloatingPoint {
+            value,
+            size: Some(size),
+        },
+    )
+}
+
+// Annotates a float constant with its bit pattern, e.g. `f32.const 1.5  ; 0x3fc00000`,
+// as the decimal value loses NaN payloads and the sign of zero is easy to miss.
+fn float_bits(bits: u64, size: usize) -> Vec<InstructionTextToken> {
+    vec![
+        InstructionTextToken::new("  ", InstructionTextTokenKind::Text),
+        InstructionTextToken::new("; ", InstructionTextTokenKind::Annotation),
+        InstructionTextToken::new(
+            format!("{bits:#0width$x}", width = size * 2 + 2),
+            InstructionTextTokenKind::Integer {
+                value: bits,
+                size: Some(size),
+            },
+        ),
+    ]
+}
+
+// Refers to the tag's entry in the tag or import section, by name if it has one.
+fn tag(module_data: &ModuleData, tag_index: u32) -> InstructionTextToken {
+    let Some(tag) = module_data.tags.get(tag_index as usize) else {
+        return integer(tag_index);
+    };
+    let text = match module_data.tag_names.get(&tag_index) {
+        Some(name) => name.clone(),
+        None => format!("{tag_index}"),
+    };
+    InstructionTextToken::new(
+        text,
+        InstructionTextTokenKind::DataSymbol {
+            value: tag.addr,
+            size: 0,
+        },
+    )
+}
+
+// Flags alignments that compilers don't emit: more than the access's natural
+// alignment, which validation rejects, or byte alignment for an access of 4 or more
+// bytes. Both usually mean the module was written or rewritten by hand.
+fn alignment_warning(memarg: &MemArg) -> Vec<InstructionTextToken> {
+    let warning = if memarg.align > memarg.max_align {
+        "; invalid: over-aligned"
+    } else if memarg.align == 0 && memarg.max_align >= 2 {
+        "; unaligned"
+    } else {
+        return Vec::new();
+    };
+    vec![
+        InstructionTextToken::new("  ", InstructionTextTokenKind::Text),
+        InstructionTextToken::new(warning, InstructionTextTokenKind::Annotation),
+    ]
+}
+
+// Writes a load or store with its memarg in the WAT style, e.g. `i32.load mem=1
+// offset=0x10 align=1`, leaving out memory 0, a zero offset and the natural alignment.
+fn with_memarg(opcode_name: &str, memarg: &MemArg) -> Vec<InstructionTextToken> {
+    let mut operands = Vec::new();
+    // Only multi-memory modules access memories other than memory 0.
+    if memarg.memory != 0 {
+        operands.push(vec![
+            InstructionTextToken::new("mem=", InstructionTextTokenKind::Text),
+            integer(memarg.memory),
+        ]);
+    }
+    if memarg.offset != 0 {
+        operands.push(vec![
+            InstructionTextToken::new("offset=", InstructionTextTokenKind::Text),
+            InstructionTextToken::new(
+                format!("{:#x}", memarg.offset),
+                InstructionTextTokenKind::Integer {
+                    value: memarg.offset,
+                    size: Some(8),
+                },
+            ),
+        ]);
+    }
+    if memarg.align != memarg.max_align {
+        let mut operand = vec![InstructionTextToken::new(
+            "align=",
+            InstructionTextTokenKind::Text,
+        )];
+        // Malformed modules can encode an alignment too large to shift out, which is
+        // shown by its exponent instead.
+        match 1u64.checked_shl(memarg.align as u32) {
+            Some(align) => operand.push(InstructionTextToken::new(
+                format!("{align}"),
+                InstructionTextTokenKind::Integer {
+                    value: align,
+                    size: Some(8),
+                },
+            )),
+            None => operand.extend([
+                InstructionTextToken::new("2^", InstructionTextTokenKind::Text),
+                integer(memarg.align as u32),
+            ]),
+        }
+        operands.push(operand);
+    }
+
+    let mut tokens = vec![InstructionTextToken::new(
+        opcode_name,
+        InstructionTextTokenKind::Instruction,
+    )];
+    push_operands(&mut tokens, opcode_name, operands);
+    tokens.extend(alignment_warning(memarg));
+    tokens
+}
+
+// An index shown after the operand it was resolved to, e.g. the `(1)` in
+// `br 0x1234 (1)`.
+fn parenthesized(value: u32) -> Vec<InstructionTextToken> {
+    vec![
+        InstructionTextToken::new("(", InstructionTextTokenKind::Text),
+        integer(value),
+        InstructionTextToken::new(")", InstructionTextTokenKind::Text),
+    ]
+}
+
+fn code_address(addr: u64) -> InstructionTextToken {
+    InstructionTextToken::new(
+        format!("{addr:#x}"),
+        InstructionTextTokenKind::CodeRelativeAddress {
+            value: addr,
+            size: Some(8),
+        },
+    )
+}
+
+// Writes a branch as the address it goes to, followed by its label depth, e.g.
+// `br 0x1234 (1)`. Falls back to the depth alone if the target is not resolved.
+fn with_branch_target(
+    opcode_name: &str,
+    target: Option<u64>,
+    relative_depth: u32,
+) -> Vec<InstructionTextToken> {
+    let operands = match target {
+        Some(target) => vec![vec![code_address(target)], parenthesized(relative_depth)],
+        None => vec![vec![integer(relative_depth)]],
+    };
+    let mut tokens = vec![InstructionTextToken::new(
+        opcode_name,
+        InstructionTextTokenKind::Instruction,
+    )];
+    push_operands(&mut tokens, opcode_name, operands);
+    tokens
+}
+
+// Writes a `br_table` as the address of each case followed by the default, e.g.
+// `br_table [0]→0x1234, [1]→0x1250, default→0x1300`.
+fn br_table(targets: &[u64], default_target: u64) -> Vec<InstructionTextToken> {
+    let mut tokens = vec![
+        InstructionTextToken::new("br_table", InstructionTextTokenKind::Instruction),
+        padding("br_table".len()),
+    ];
+    for (i, target) in targets.iter().enumerate() {
+        tokens.push(InstructionTextToken::new(
+            format!("[{i}]→"),
+            InstructionTextTokenKind::Text,
+        ));
+        tokens.push(code_address(*target));
+        tokens.push(InstructionTextToken::new(
+            ", ",
+            InstructionTextTokenKind::OperandSeparator,
+        ));
+    }
+    tokens.push(InstructionTextToken::new(
+        "default→",
+        InstructionTextTokenKind::Text,
+    ));
+    tokens.push(code_address(default_target));
+    tokens
+}
+
+// Writes the function header as the function's index, signature and body size, e.g.
+// `func[17] (param i32 i32) (result i32)  size=0x1c3`.
+fn func_header(module_data: &ModuleData, func: &FunctionData) -> Vec<InstructionTextToken> {
+    let mut tokens = vec![
+        InstructionTextToken::new("func", InstructionTextTokenKind::Keyword),
+        InstructionTextToken::new("[", InstructionTextTokenKind::BeginMemoryOperand),
+        integer(func.func_index),
+        InstructionTextToken::new("]", InstructionTextTokenKind::EndMemoryOperand),
+    ];
+    if let Some(ty) = module_data.func_type(func.func_index) {
+        for types in value_types("param", ty.params())
+            .into_iter()
+            .chain(value_types("result", ty.results()))
+        {
+            tokens.push(InstructionTextToken::new(
+                " ",
+                InstructionTextTokenKind::Text,
+            ));
+            tokens.extend(types);
+        }
+    }
+    let size = func.end - func.locals_start;
+    tokens.push(InstructionTextToken::new(
+        "  size=",
+        InstructionTextTokenKind::Text,
+    ));
+    tokens.push(InstructionTextToken::new(
+        format!("{size:#x}"),
+        InstructionTextTokenKind::Integer {
+            value: size,
+            size: Some(4),
+        },
+    ));
+    tokens
+}
+
+// Writes the locals header as runs of locals, e.g. `_funchdr.locals  4×i32, 2×i64`.
+fn locals(func: &FunctionData) -> Vec<InstructionTextToken> {
+    let mut tokens = vec![InstructionTextToken::new(
+        "_funchdr.locals",
+        InstructionTextTokenKind::Instruction,
+    )];
+    for (i, (count, ty)) in func.locals.iter().enumerate() {
+        tokens.push(match i {
+            0 => padding("_funchdr.locals".len()),
+            _ => InstructionTextToken::new(", ", InstructionTextTokenKind::OperandSeparator),
+        });
+        tokens.push(integer(*count));
+        tokens.push(InstructionTextToken::new(
+            "×",
+            InstructionTextTokenKind::Text,
+        ));
+        tokens.push(value_type(*ty));
+    }
+    tokens
+}
+
+// Annotates an `if` with where it goes when the condition is false, which is either
+// its `else` branch or the end of the block, e.g. `if  → else @ 0x4e0`.
+fn if_false_target(
+    func: Option<&FunctionData>,
+    target: Option<&BranchTargetAddr>,
+) -> Vec<InstructionTextToken> {
+    let (Some(func), Some(BranchTargetAddr::Conditional { false_target, .. })) = (func, target)
+    else {
+        return Vec::new();
+    };
+    let label = match func.ops.range(..*false_target).next_back() {
+        Some((_, prev)) if matches!(prev.op, Operator::Else) => "else",
+        _ => "end",
+    };
+    vec![
+        InstructionTextToken::new("  ", InstructionTextTokenKind::Text),
+        InstructionTextToken::new(
+            format!("→ {label} @ "),
+            InstructionTextTokenKind::Annotation,
+        ),
+        code_address(*false_target),
+    ]
+}
+
+// Annotates an `end` with the block it closes, e.g. `end  ; loop @ 0x1a2`.
+fn closed_block(func: Option<&FunctionData>, addr: u64) -> Vec<InstructionTextToken> {
+    let Some(func) = func else {
+        return Vec::new();
+    };
+    let Some(&start) = func.block_starts.get(&addr) else {
+        return Vec::new();
+    };
+    let kind = match func.ops.get(&start).map(|op| &op.op) {
+        Some(Operator::Block { .. }) => "block",
+        Some(Operator::Loop { .. }) => "loop",
+        Some(Operator::If { .. }) => "if",
+        Some(Operator::Try { .. }) => "try",
+        Some(Operator::TryTable { .. }) => "try_table",
+        _ => return Vec::new(),
+    };
+    vec![
+        InstructionTextToken::new("  ", InstructionTextTokenKind::Text),
+        InstructionTextToken::new(format!("; {kind} @ "), InstructionTextTokenKind::Annotation),
+        code_address(start),
+    ]
+}
+
+// Annotates an `if` or `br_if` with its hint from the branch hint section.
+fn branch_hint(module_data: &ModuleData, addr: u64) -> Vec<InstructionTextToken> {
+    let Some(taken) = module_data.branch_hints.get(&addr) else {
+        return Vec::new();
+    };
+    let hint = if *taken { "(likely)" } else { "(unlikely)" };
+    vec![
+        InstructionTextToken::new(" ", InstructionTextTokenKind::Text),
+        InstructionTextToken::new(hint, InstructionTextTokenKind::Annotation),
+    ]
+}
+
+fn value_type(ty: ValType) -> InstructionTextToken {
+    InstructionTextToken::new(format!("{ty}"), InstructionTextTokenKind::TypeName)
+}
+
+// Writes operands that are each made of several tokens after the opcode, separated
+// by spaces as in WAT (e.g. `(param i32) (result i64)` or `offset=0x10 align=1`).
+fn push_operands(
+    tokens: &mut Vec<InstructionTextToken>,
+    opcode_name: &str,
+    operands: Vec<Vec<InstructionTextToken>>,
+) {
+    for (i, operand) in operands.into_iter().enumerate() {
+        tokens.push(match i {
+            0 => padding(opcode_name.len()),
+            _ => InstructionTextToken::new(" ", InstructionTextTokenKind::Text),
+        });
+        tokens.extend(operand);
+    }
+}
+
+fn value_types(keyword: &str, types: &[ValType]) -> Option<Vec<InstructionTextToken>> {
+    if types.is_empty() {
+        return None;
+    }
+    let mut tokens = vec![InstructionTextToken::new(
+        format!("({keyword}"),
+        InstructionTextTokenKind::Keyword,
+    )];
+    for ty in types {
+        tokens.push(InstructionTextToken::new(
+            " ",
+            InstructionTextTokenKind::Text,
+        ));
+        tokens.push(value_type(*ty));
+    }
+    tokens.push(InstructionTextToken::new(
+        ")",
+        InstructionTextTokenKind::Keyword,
+    ));
+    Some(tokens)
+}
+
+// The params and results of a block, which say what it takes from and leaves on the
+// stack.
+fn block_type(module_data: &ModuleData, blockty: &BlockType) -> Vec<Vec<InstructionTextToken>> {
+    match *blockty {
+        BlockType::Empty => Vec::new(),
+        BlockType::Type(ty) => value_types("result", &[ty]).into_iter().collect(),
+        BlockType::FuncType(type_index) => match module_data.types.get(type_index as usize) {
+            Some(ty) => value_types("param", ty.params())
+                .into_iter()
+                .chain(value_types("result", ty.results()))
+                .collect(),
+            None => vec![vec![
+                InstructionTextToken::new("(type ", InstructionTextTokenKind::Keyword),
+                integer(type_index),
+                InstructionTextToken::new(")", InstructionTextTokenKind::Keyword),
+            ]],
+        },
+    }
+}
+
+fn with_block_type(
+    opcode_name: &str,
+    module_data: &ModuleData,
+    blockty: &BlockType,
+) -> Vec<InstructionTextToken> {
+    let mut tokens = vec![InstructionTextToken::new(
+        opcode_name,
+        InstructionTextTokenKind::Instruction,
+    )];
+    push_operands(&mut tokens, opcode_name, block_type(module_data, blockty));
+    tokens
+}
+
+// Refers to the function by the name of its symbol.
+fn function(module_data: &ModuleData, func_index: u32) -> InstructionTextToken {
+    let Some(&addr) = module_data.func_addrs.get(func_index as usize) else {
+        return integer(func_index);
+    };
+    InstructionTextToken::new(
+        module_data.func_name(func_index),
+        InstructionTextTokenKind::CodeSymbol {
+            value: addr,
+            size: 0,
+        },
+    )
+}
+
+// Keeps the index of a function shown by name, e.g. the `; func[212]` in `call malloc
+// ; func[212]`, for correlating with tools that refer to functions by index.
+fn function_index_annotation(
+    module_data: &ModuleData,
+    func_index: u32,
+) -> Vec<InstructionTextToken> {
+    if module_data.func_addrs.get(func_index as usize).is_none() {
+        return Vec::new();
+    }
+    vec![
+        InstructionTextToken::new("  ", InstructionTextTokenKind::Text),
+        InstructionTextToken::new(
+            format!("; func[{func_index}]"),
+            InstructionTextTokenKind::Annotation,
+        ),
+    ]
+}
+
+// Writes a global by name, followed by its index, e.g. `global.get __stack_pointer
+// (0)`. Unnamed globals are written by index alone.
+fn with_global(
+    opcode_name: &str,
+    module_data: &ModuleData,
+    global_index: u32,
+) -> Vec<InstructionTextToken> {
+    let name = module_data.global_names.get(&global_index);
+    let global = module_data.globals.get(global_index as usize);
+    let operands = match (name, global) {
+        (Some(name), Some(global)) => vec![
+            vec![InstructionTextToken::new(
+                name.clone(),
+                InstructionTextTokenKind::DataSymbol {
+                    value: global.addr,
+                    size: 0,
+                },
+            )],
+            parenthesized(global_index),
+        ],
+        _ => vec![vec![integer(global_index)]],
+    };
+    let mut tokens = vec![InstructionTextToken::new(
+        opcode_name,
+        InstructionTextTokenKind::Instruction,
+    )];
+    push_operands(&mut tokens, opcode_name, operands);
+    tokens
+}
+
+// Refers to a local by the same name the decompiler gives its variable.
+fn local(
+    module_data: &ModuleData,
+    func: Option<&FunctionData>,
+    local_index: u32,
+) -> InstructionTextToken {
+    let Some(func) = func else {
+        return integer(local_index);
+    };
+    let text = module_data.local_name(func.func_index, local_index);
+    InstructionTextToken::new(text, InstructionTextTokenKind::Text)
+}
+
+fn try_table(module_data: &ModuleData, try_table: &TryTable) -> Vec<InstructionTextToken> {
+    let mut operands = block_type(module_data, &try_table.ty);
+    for catch in &try_table.catches {
+        let (keyword, tag_index, label) = match *catch {
+            Catch::One { tag, label } => ("catch", Some(tag), label),
+            Catch::OneRef { tag, label } => ("catch_ref", Some(tag), label),
+            Catch::All { label } => ("catch_all", None, label),
+            Catch::AllRef { label } => ("catch_all_ref", None, label),
+        };
+        let mut operand = vec![InstructionTextToken::new(
+            format!("({keyword} "),
+            InstructionTextTokenKind::Keyword,
+        )];
+        if let Some(tag_index) = tag_index {
+            operand.push(tag(module_data, tag_index));
+            operand.push(InstructionTextToken::new(
+                " ",
+                InstructionTextTokenKind::Text,
+            ));
+        }
+        operand.push(integer(label));
+        operand.push(InstructionTextToken::new(
+            ")",
+            InstructionTextTokenKind::Keyword,
+        ));
+        operands.push(operand);
+    }
+
+    let mut tokens = vec![InstructionTextToken::new(
+        "try_table",
+        InstructionTextTokenKind::Instruction,
+    )];
+    push_operands(&mut tokens, "try_table", operands);
+    tokens
+}
+
 macro_rules! vec_with_opcode {
     ($opcode_name:expr) => {{
         vec![
@@ -29,533 +620,443 @@ macro_rules! vec_with_opcode {
     }};
 }
 
+// The name of an operator as it is written in WAT, e.g. `i32.load8_u`, if it is one
+// that the disassembler knows.
+pub(crate) fn opcode_name(module_data: &ModuleData, op: &OperatorData) -> Option<String> {
+    let tokens = operator_text(module_data, None, 0, op)?;
+    Some(tokens.first()?.text.clone())
+}
+
+// An instruction as the disassembly shows it, as plain text.
+pub(crate) fn instruction_text(
+    module_data: &ModuleData,
+    func: &FunctionData,
+    addr: u64,
+    op: &OperatorData,
+) -> Option<String> {
+    let tokens = operator_text(module_data, Some(func), addr, op)?;
+    let text = tokens.iter().map(|token| token.text.as_str()).collect::<String>();
+    Some(text.trim_end().to_string())
+}
+
+// Writes an operator, with what is known of the function it is in and the module.
+fn operator_text(
+    module_data: &ModuleData,
+    func: Option<&FunctionData>,
+    addr: u64,
+    op: &OperatorData,
+) -> Option<Vec<InstructionTextToken>> {
+    Some(match &op.op {
+        // Control instructions
+        Operator::Unreachable => vec_with_opcode!("unreachable"),
+        Operator::Nop => vec_with_opcode!("nop"),
+        Operator::Block { blockty } => with_block_type("block", module_data, blockty),
+        Operator::Loop { blockty } => with_block_type("loop", module_data, blockty),
+        Operator::If { blockty } => {
+            let mut tokens = with_block_type("if", module_data, blockty);
+            tokens.extend(if_false_target(func, op.target.as_ref()));
+            tokens.extend(branch_hint(module_data, addr));
+            tokens
+        }
+        Operator::Else => vec_with_opcode!("else"),
+        // The `end` of the function body is where it returns.
+        Operator::End if func.is_some_and(|func| addr + op.size as u64 == func.end) => {
+            vec_with_opcode!(
+                "end",
+                InstructionTextToken::new("(func)", InstructionTextTokenKind::Keyword),
+            )
+        }
+        Operator::End => {
+            let mut tokens = vec_with_opcode!("end");
+            tokens.extend(closed_block(func, addr));
+            tokens
+        }
+        Operator::Br { relative_depth } => {
+            let target = match op.target {
+                Some(BranchTargetAddr::Unconditional(target)) => Some(target),
+                _ => None,
+            };
+            with_branch_target("br", target, *relative_depth)
+        }
+        Operator::BrIf { relative_depth } => {
+            let target = match op.target {
+                Some(BranchTargetAddr::Conditional { true_target, .. }) => Some(true_target),
+                _ => None,
+            };
+            let mut tokens = with_branch_target("br_if", target, *relative_depth);
+            tokens.extend(branch_hint(module_data, addr));
+            tokens
+        }
+        Operator::BrTable { targets } => match &op.target {
+            Some(BranchTargetAddr::Table {
+                targets,
+                default_target,
+            }) => br_table(targets, *default_target),
+            _ => vec_with_opcode!(
+                "br_table",
+                InstructionTextToken::new(format!("{targets:?}"), InstructionTextTokenKind::Text),
+            ),
+        },
+        Operator::Return => vec_with_opcode!("return"),
+
+        // Exception-handling instructions
+        Operator::TryTable { try_table: table } => try_table(module_data, table),
+        Operator::Throw { tag_index } => {
+            vec_with_opcode!("throw", tag(module_data, *tag_index))
+        }
+        Operator::ThrowRef => vec_with_opcode!("throw_ref"),
+        Operator::Try { blockty } => with_block_type("try", module_data, blockty),
+        Operator::Catch { tag_index } => {
+            vec_with_opcode!("catch", tag(module_data, *tag_index))
+        }
+        Operator::CatchAll => vec_with_opcode!("catch_all"),
+        Operator::Rethrow { relative_depth } => {
+            vec_with_opcode!("rethrow", integer(*relative_depth))
+        }
+        Operator::Delegate { relative_depth } => {
+            let mut tokens = vec_with_opcode!("delegate", integer(*relative_depth));
+            tokens.extend(closed_block(func, addr));
+            tokens
+        }
+        Operator::Call { function_index } => {
+            let mut tokens = vec_with_opcode!("call", function(module_data, *function_index));
+            tokens.extend(function_index_annotation(module_data, *function_index));
+            tokens
+        }
+        Operator::CallIndirect {
+            type_index,
+            table_index,
+        } => vec_with_opcode!(
+            "call_indirect",
+            InstructionTextToken::new(
+                format!("{type_index}"),
+                InstructionTextTokenKind::Integer {
+                    value: *type_index as u64,
+                    size: Some(4),
+                },
+            ),
+        ),
+
+        // Parametric instructions
+        Operator::Drop => vec_with_opcode!("drop"),
+        Operator::Select => vec_with_opcode!("select"),
+
+        // Variable instructions
+        Operator::LocalGet { local_index } => {
+            vec_with_opcode!("local.get", local(module_data, func, *local_index))
+        }
+        Operator::LocalSet { local_index } => {
+            vec_with_opcode!("local.set", local(module_data, func, *local_index))
+        }
+        Operator::LocalTee { local_index } => {
+            vec_with_opcode!("local.tee", local(module_data, func, *local_index))
+        }
+        Operator::GlobalGet { global_index } => {
+            with_global("global.get", module_data, *global_index)
+        }
+        Operator::GlobalSet { global_index } => {
+            with_global("global.set", module_data, *global_index)
+        }
+
+        // Table instructions
+        Operator::TableInit { elem_index, table } => vec_with_opcode!(
+            "table.init",
+            InstructionTextToken::new(
+                format!("{table}"),
+                InstructionTextTokenKind::Integer {
+                    value: *table as u64,
+                    size: Some(4),
+                },
+            ),
+            InstructionTextToken::new(", ", InstructionTextTokenKind::OperandSeparator),
+            InstructionTextToken::new(
+                format!("{elem_index}"),
+                InstructionTextTokenKind::Integer {
+                    value: *elem_index as u64,
+                    size: Some(4),
+                },
+            ),
+        ),
+        Operator::ElemDrop { elem_index } => vec_with_opcode!(
+            "elem.drop",
+            InstructionTextToken::new(
+                format!("{elem_index}"),
+                InstructionTextTokenKind::Integer {
+                    value: *elem_index as u64,
+                    size: Some(4),
+                },
+            ),
+        ),
+        Operator::TableCopy {
+            dst_table,
+            src_table,
+        } => vec_with_opcode!(
+            "table.copy",
+            InstructionTextToken::new(
+                format!("{dst_table}"),
+                InstructionTextTokenKind::Integer {
+                    value: *dst_table as u64,
+                    size: Some(4),
+                },
+            ),
+            InstructionTextToken::new(", ", InstructionTextTokenKind::OperandSeparator),
+            InstructionTextToken::new(
+                format!("{src_table}"),
+                InstructionTextTokenKind::Integer {
+                    value: *src_table as u64,
+                    size: Some(4),
+                },
+            ),
+        ),
+
+        // Memory instructions
+        Operator::I32Load { memarg } => with_memarg("i32.load", memarg),
+        Operator::I64Load { memarg } => with_memarg("i64.load", memarg),
+        Operator::F32Load { memarg } => with_memarg("f32.load", memarg),
+        Operator::F64Load { memarg } => with_memarg("f64.load", memarg),
+        Operator::I32Load8S { memarg } => with_memarg("i32.load8_s", memarg),
+        Operator::I32Load8U { memarg } => with_memarg("i32.load8_u", memarg),
+        Operator::I32Load16S { memarg } => with_memarg("i32.load16_s", memarg),
+        Operator::I32Load16U { memarg } => with_memarg("i32.load16_u", memarg),
+        Operator::I64Load8S { memarg } => with_memarg("i64.load8_s", memarg),
+        Operator::I64Load8U { memarg } => with_memarg("i64.load8_u", memarg),
+        Operator::I64Load16S { memarg } => with_memarg("i64.load16_s", memarg),
+        Operator::I64Load16U { memarg } => with_memarg("i64.load16_u", memarg),
+        Operator::I64Load32S { memarg } => with_memarg("i64.load32_s", memarg),
+        Operator::I64Load32U { memarg } => with_memarg("i64.load32_u", memarg),
+        Operator::I32Store { memarg } => with_memarg("i32.store", memarg),
+        Operator::I64Store { memarg } => with_memarg("i64.store", memarg),
+        Operator::F32Store { memarg } => with_memarg("f32.store", memarg),
+        Operator::F64Store { memarg } => with_memarg("f64.store", memarg),
+        Operator::I32Store8 { memarg } => with_memarg("i32.store8", memarg),
+        Operator::I32Store16 { memarg } => with_memarg("i32.store16", memarg),
+        Operator::I64Store8 { memarg } => with_memarg("i64.store8", memarg),
+        Operator::I64Store16 { memarg } => with_memarg("i64.store16", memarg),
+        Operator::I64Store32 { memarg } => with_memarg("i64.store32", memarg),
+        Operator::MemorySize { mem } => vec_with_opcode!(
+            "memory.size",
+            InstructionTextToken::new(
+                format!("{mem}"),
+                InstructionTextTokenKind::Integer {
+                    value: *mem as u64,
+                    size: Some(4),
+                },
+            ),
+        ),
+        Operator::MemoryGrow { mem } => vec_with_opcode!(
+            "memory.grow",
+            InstructionTextToken::new(
+                format!("{mem}"),
+                InstructionTextTokenKind::Integer {
+                    value: *mem as u64,
+                    size: Some(4),
+                },
+            ),
+        ),
+
+        // Numeric instructions
+        Operator::I32Const { value } => {
+            let display = module_data.constant_display;
+            let operand = data_pointer(module_data, *value as i64, 4)
+                .unwrap_or_else(|| constant(display, *value as i64, 4));
+            let mut tokens = vec_with_opcode!("i32.const", operand);
+            tokens.extend(signed_constant_bits(display, *value as i64, 4));
+            tokens
+        }
+        Operator::I64Const { value } => {
+            let display = module_data.constant_display;
+            let operand = data_pointer(module_data, *value, 8)
+                .unwrap_or_else(|| constant(display, *value, 8));
+            let mut tokens = vec_with_opcode!("i64.const", operand);
+            tokens.extend(signed_constant_bits(display, *value, 8));
+            tokens
+        }
+        Operator::F32Const { value } => {
+            let mut tokens = vec_with_opcode!("f32.const", float(f32::from(*value) as f64, 4));
+            tokens.extend(float_bits(value.bits() as u64, 4));
+            tokens
+        }
+        Operator::F64Const { value } => {
+            let mut tokens = vec_with_opcode!("f64.const", float(f64::from(*value), 8));
+            tokens.extend(float_bits(value.bits(), 8));
+            tokens
+        }
+        Operator::I32Eqz => vec_with_opcode!("i32.eqz"),
+        Operator::I32Eq => vec_with_opcode!("i32.eq"),
+        Operator::I32Ne => vec_with_opcode!("i32.ne"),
+        Operator::I32LtS => vec_with_opcode!("i32.lt_s"),
+        Operator::I32LtU => vec_with_opcode!("i32.lt_u"),
+        Operator::I32GtS => vec_with_opcode!("i32.gt_s"),
+        Operator::I32GtU => vec_with_opcode!("i32.gt_u"),
+        Operator::I32LeS => vec_with_opcode!("i32.le_s"),
+        Operator::I32LeU => vec_with_opcode!("i32.le_u"),
+        Operator::I32GeS => vec_with_opcode!("i32.ge_s"),
+        Operator::I32GeU => vec_with_opcode!("i32.ge_u"),
+        Operator::I64Eqz => vec_with_opcode!("i64.eqz"),
+        Operator::I64Eq => vec_with_opcode!("i64.eq"),
+        Operator::I64Ne => vec_with_opcode!("i64.ne"),
+        Operator::I64LtS => vec_with_opcode!("i64.lt_s"),
+        Operator::I64LtU => vec_with_opcode!("i64.lt_u"),
+        Operator::I64GtS => vec_with_opcode!("i64.gt_s"),
+        Operator::I64GtU => vec_with_opcode!("i64.gt_u"),
+        Operator::I64LeS => vec_with_opcode!("i64.le_s"),
+        Operator::I64LeU => vec_with_opcode!("i64.le_u"),
+        Operator::I64GeS => vec_with_opcode!("i64.ge_s"),
+        Operator::I64GeU => vec_with_opcode!("i64.ge_u"),
+        Operator::F32Eq => vec_with_opcode!("f32.eq"),
+        Operator::F32Ne => vec_with_opcode!("f32.ne"),
+        Operator::F32Lt => vec_with_opcode!("f32.lt"),
+        Operator::F32Gt => vec_with_opcode!("f32.gt"),
+        Operator::F32Le => vec_with_opcode!("f32.le"),
+        Operator::F32Ge => vec_with_opcode!("f32.ge"),
+        Operator::F64Eq => vec_with_opcode!("f64.eq"),
+        Operator::F64Ne => vec_with_opcode!("f64.ne"),
+        Operator::F64Lt => vec_with_opcode!("f64.lt"),
+        Operator::F64Gt => vec_with_opcode!("f64.gt"),
+        Operator::F64Le => vec_with_opcode!("f64.le"),
+        Operator::F64Ge => vec_with_opcode!("f64.ge"),
+        Operator::I32Clz => vec_with_opcode!("i32.clz"),
+        Operator::I32Ctz => vec_with_opcode!("i32.ctz"),
+        Operator::I32Popcnt => vec_with_opcode!("i32.popcnt"),
+        Operator::I32Add => vec_with_opcode!("i32.add"),
+        Operator::I32Sub => vec_with_opcode!("i32.sub"),
+        Operator::I32Mul => vec_with_opcode!("i32.mul"),
+        Operator::I32DivS => vec_with_opcode!("i32.div_s"),
+        Operator::I32DivU => vec_with_opcode!("i32.div_u"),
+        Operator::I32RemS => vec_with_opcode!("i32.rem_s"),
+        Operator::I32RemU => vec_with_opcode!("i32.rem_u"),
+        Operator::I32And => vec_with_opcode!("i32.and"),
+        Operator::I32Or => vec_with_opcode!("i32.or"),
+        Operator::I32Xor => vec_with_opcode!("i32.xor"),
+        Operator::I32Shl => vec_with_opcode!("i32.shl"),
+        Operator::I32ShrS => vec_with_opcode!("i32.shr_s"),
+        Operator::I32ShrU => vec_with_opcode!("i32.shr_u"),
+        Operator::I32Rotl => vec_with_opcode!("i32.rotl"),
+        Operator::I32Rotr => vec_with_opcode!("i32.rotr"),
+        Operator::I64Clz => vec_with_opcode!("i64.clz"),
+        Operator::I64Ctz => vec_with_opcode!("i64.ctz"),
+        Operator::I64Popcnt => vec_with_opcode!("i64.popcnt"),
+        Operator::I64Add => vec_with_opcode!("i64.add"),
+        Operator::I64Sub => vec_with_opcode!("i64.sub"),
+        Operator::I64Mul => vec_with_opcode!("i64.mul"),
+        Operator::I64DivS => vec_with_opcode!("i64.div_s"),
+        Operator::I64DivU => vec_with_opcode!("i64.div_u"),
+        Operator::I64RemS => vec_with_opcode!("i64.rem_s"),
+        Operator::I64RemU => vec_with_opcode!("i64.rem_u"),
+        Operator::I64And => vec_with_opcode!("i64.and"),
+        Operator::I64Or => vec_with_opcode!("i64.or"),
+        Operator::I64Xor => vec_with_opcode!("i64.xor"),
+        Operator::I64Shl => vec_with_opcode!("i64.shl"),
+        Operator::I64ShrS => vec_with_opcode!("i64.shr_s"),
+        Operator::I64ShrU => vec_with_opcode!("i64.shr_u"),
+        Operator::I64Rotl => vec_with_opcode!("i64.rotl"),
+        Operator::I64Rotr => vec_with_opcode!("i64.rotr"),
+        Operator::F32Abs => vec_with_opcode!("f32.abs"),
+        Operator::F32Neg => vec_with_opcode!("f32.neg"),
+        Operator::F32Ceil => vec_with_opcode!("f32.ceil"),
+        Operator::F32Floor => vec_with_opcode!("f32.floor"),
+        Operator::F32Trunc => vec_with_opcode!("f32.trunc"),
+        Operator::F32Nearest => vec_with_opcode!("f32.nearest"),
+        Operator::F32Sqrt => vec_with_opcode!("f32.sqrt"),
+        Operator::F32Add => vec_with_opcode!("f32.add"),
+        Operator::F32Sub => vec_with_opcode!("f32.sub"),
+        Operator::F32Mul => vec_with_opcode!("f32.mul"),
+        Operator::F32Div => vec_with_opcode!("f32.div"),
+        Operator::F32Min => vec_with_opcode!("f32.min"),
+        Operator::F32Max => vec_with_opcode!("f32.max"),
+        Operator::F32Copysign => vec_with_opcode!("f32.copysign"),
+        Operator::F64Abs => vec_with_opcode!("f64.abs"),
+        Operator::F64Neg => vec_with_opcode!("f64.neg"),
+        Operator::F64Ceil => vec_with_opcode!("f64.ceil"),
+        Operator::F64Floor => vec_with_opcode!("f64.floor"),
+        Operator::F64Trunc => vec_with_opcode!("f64.trunc"),
+        Operator::F64Nearest => vec_with_opcode!("f64.nearest"),
+        Operator::F64Sqrt => vec_with_opcode!("f64.sqrt"),
+        Operator::F64Add => vec_with_opcode!("f64.add"),
+        Operator::F64Sub => vec_with_opcode!("f64.sub"),
+        Operator::F64Mul => vec_with_opcode!("f64.mul"),
+        Operator::F64Div => vec_with_opcode!("f64.div"),
+        Operator::F64Min => vec_with_opcode!("f64.min"),
+        Operator::F64Max => vec_with_opcode!("f64.max"),
+        Operator::F64Copysign => vec_with_opcode!("f64.copysign"),
+        Operator::I32WrapI64 => vec_with_opcode!("i32.wrap_i64"),
+        Operator::I32TruncF32S => vec_with_opcode!("i32.trunc_f32_s"),
+        Operator::I32TruncF32U => vec_with_opcode!("i32.trunc_f32_u"),
+        Operator::I32TruncF64S => vec_with_opcode!("i32.trunc_f64_s"),
+        Operator::I32TruncF64U => vec_with_opcode!("i32.trunc_f64_u"),
+        Operator::I64ExtendI32S => vec_with_opcode!("i64.extend_i32_s"),
+        Operator::I64ExtendI32U => vec_with_opcode!("i64.extend_i32_u"),
+        Operator::I64TruncF32S => vec_with_opcode!("i64.trunc_f32_s"),
+        Operator::I64TruncF32U => vec_with_opcode!("i64.trunc_f32_u"),
+        Operator::I64TruncF64S => vec_with_opcode!("i64.trunc_f64_s"),
+        Operator::I64TruncF64U => vec_with_opcode!("i64.trunc_f64_u"),
+        Operator::F32ConvertI32S => vec_with_opcode!("f32.convert_i32_s"),
+        Operator::F32ConvertI32U => vec_with_opcode!("f32.convert_i32_u"),
+        Operator::F32ConvertI64S => vec_with_opcode!("f32.convert_i64_s"),
+        Operator::F32ConvertI64U => vec_with_opcode!("f32.convert_i64_u"),
+        Operator::F32DemoteF64 => vec_with_opcode!("f32.demote_f64"),
+        Operator::F64ConvertI32S => vec_with_opcode!("f64.convert_i32_s"),
+        Operator::F64ConvertI32U => vec_with_opcode!("f64.convert_i32_u"),
+        Operator::F64ConvertI64S => vec_with_opcode!("f64.convert_i64_s"),
+        Operator::F64ConvertI64U => vec_with_opcode!("f64.convert_i64_u"),
+        Operator::F64PromoteF32 => vec_with_opcode!("f64.promote_f32"),
+        Operator::I32ReinterpretF32 => vec_with_opcode!("i32.reinterpret_f32"),
+        Operator::I64ReinterpretF64 => vec_with_opcode!("i64.reinterpret_f64"),
+        Operator::F32ReinterpretI32 => vec_with_opcode!("f32.reinterpret_i32"),
+        Operator::F64ReinterpretI64 => vec_with_opcode!("f64.reinterpret_i64"),
+        Operator::I32Extend8S => vec_with_opcode!("i32.extend8_s"),
+        Operator::I32Extend16S => vec_with_opcode!("i32.extend16_s"),
+        Operator::I64Extend8S => vec_with_opcode!("i64.extend8_s"),
+        Operator::I64Extend16S => vec_with_opcode!("i64.extend16_s"),
+        Operator::I64Extend32S => vec_with_opcode!("i64.extend32_s"),
+        Operator::I32TruncSatF32S => vec_with_opcode!("i32.trunc_sat_f32_s"),
+        Operator::I32TruncSatF32U => vec_with_opcode!("i32.trunc_sat_f32_u"),
+        Operator::I32TruncSatF64S => vec_with_opcode!("i32.trunc_sat_f64_s"),
+        Operator::I32TruncSatF64U => vec_with_opcode!("i32.trunc_sat_f64_u"),
+        Operator::I64TruncSatF32S => vec_with_opcode!("i64.trunc_sat_f32_s"),
+        Operator::I64TruncSatF32U => vec_with_opcode!("i64.trunc_sat_f32_u"),
+        Operator::I64TruncSatF64S => vec_with_opcode!("i64.trunc_sat_f64_s"),
+        Operator::I64TruncSatF64U => vec_with_opcode!("i64.trunc_sat_f64_u"),
+
+        _ => {
+            return None;
+        }
+    })
+}
+
 impl WebAssemblyArchitecture {
     pub(crate) fn _instruction_text(
         &self,
-        _data: &[u8],
+        data: &[u8],
         addr: u64,
     ) -> Option<(usize, Vec<InstructionTextToken>)> {
         let module_data_lock = MODULE_DATA.lock().unwrap();
-        let module_data = module_data_lock.as_ref()?;
-        let func = module_data.funcs.get(&addr)?.as_ref();
-
-        if addr == func.size_start {
-            let size = func.end - func.locals_start;
-            Some((
-                (func.locals_start - func.size_start) as usize,
-                vec_with_opcode!(
-                    "_funchdr.size",
-                    InstructionTextToken::new(
-                        format!("{size:#x}"),
-                        InstructionTextTokenKind::Integer {
-                            value: size,
-                            size: Some(4),
-                        },
-                    ),
-                ),
-            ))
-        } else if addr == func.locals_start {
-            Some((
-                (func.ops_start - func.locals_start) as usize,
-                vec_with_opcode!("_funchdr.locals"),
-            ))
-        } else {
-            let op = func.ops.get(&addr)?;
-            Some((
-                op.size,
-                match &op.op {
-                    // Control instructions
-                    Operator::Unreachable => vec_with_opcode!("unreachable"),
-                    Operator::Nop => vec_with_opcode!("nop"),
-                    Operator::Block { blockty } => vec_with_opcode!("block"),
-                    Operator::Loop { blockty } => vec_with_opcode!("loop"),
-                    Operator::If { blockty } => vec_with_opcode!("if"),
-                    Operator::Else => vec_with_opcode!("else"),
-                    Operator::End => vec_with_opcode!("end"),
-                    Operator::Br { relative_depth } => vec_with_opcode!(
-                        "br",
-                        InstructionTextToken::new(
-                            format!("{relative_depth}"),
-                            InstructionTextTokenKind::Integer {
-                                value: *relative_depth as u64,
-                                size: Some(4),
-                            },
-                        ),
-                    ),
-                    Operator::BrIf { relative_depth } => vec_with_opcode!(
-                        "br_if",
-                        InstructionTextToken::new(
-                            format!("{relative_depth}"),
-                            InstructionTextTokenKind::Integer {
-                                value: *relative_depth as u64,
-                                size: Some(4),
-                            },
-                        ),
-                    ),
-                    Operator::BrTable { targets } => vec_with_opcode!(
-                        "br_table",
-                        InstructionTextToken::new(
-                            format!("{targets:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::Return => vec_with_opcode!("return"),
-                    Operator::Call { function_index } => vec_with_opcode!(
-                        "call",
-                        InstructionTextToken::new(
-                            format!("{function_index}"),
-                            InstructionTextTokenKind::Integer {
-                                value: *function_index as u64,
-                                size: Some(4),
-                            },
-                        ),
-                    ),
-                    Operator::CallIndirect {
-                        type_index,
-                        table_index,
-                    } => vec_with_opcode!(
-                        "call_indirect",
-                        InstructionTextToken::new(
-                            format!("{type_index}"),
-                            InstructionTextTokenKind::Integer {
-                                value: *type_index as u64,
-                                size: Some(4),
-                            },
-                        ),
-                    ),
-
-                    // Parametric instructions
-                    Operator::Drop => vec_with_opcode!("drop"),
-                    Operator::Select => vec_with_opcode!("select"),
-
-                    // Variable instructions
-                    Operator::LocalGet { local_index } => vec_with_opcode!(
-                        "local.get",
-                        InstructionTextToken::new(
-                            format!("{local_index}"),
-                            InstructionTextTokenKind::Integer {
-                                value: *local_index as u64,
-                                size: Some(4),
-                            },
-                        ),
-                    ),
-                    Operator::LocalSet { local_index } => vec_with_opcode!(
-                        "local.set",
-                        InstructionTextToken::new(
-                            format!("{local_index}"),
-                            InstructionTextTokenKind::Integer {
-                                value: *local_index as u64,
-                                size: Some(4),
-                            },
-                        ),
-                    ),
-                    Operator::LocalTee { local_index } => vec_with_opcode!(
-                        "local.tee",
-                        InstructionTextToken::new(
-                            format!("{local_index}"),
-                            InstructionTextTokenKind::Integer {
-                                value: *local_index as u64,
-                                size: Some(4),
-                            },
-                        ),
-                    ),
-                    Operator::GlobalGet { global_index } => {
-                        vec_with_opcode![
-                            "global.get",
-                            InstructionTextToken::new(
-                                format!("{global_index}"),
-                                InstructionTextTokenKind::Integer {
-                                    value: *global_index as u64,
-                                    size: Some(4),
-                                },
-                            ),
-                        ]
-                    }
-                    Operator::GlobalSet { global_index } => {
-                        vec_with_opcode![
-                            "global.set",
-                            InstructionTextToken::new(
-                                format!("{global_index}"),
-                                InstructionTextTokenKind::Integer {
-                                    value: *global_index as u64,
-                                    size: Some(4),
-                                },
-                            ),
-                        ]
-                    }
-
-                    // Memory instructions
-                    Operator::I32Load { memarg } => vec_with_opcode!(
-                        "i32.load",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I64Load { memarg } => vec_with_opcode!(
-                        "i64.load",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::F32Load { memarg } => vec_with_opcode!(
-                        "f32.load",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::F64Load { memarg } => vec_with_opcode!(
-                        "f64.load",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I32Load8S { memarg } => vec_with_opcode!(
-                        "i32.load8_s",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I32Load8U { memarg } => vec_with_opcode!(
-                        "i32.load8_u",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I32Load16S { memarg } => vec_with_opcode!(
-                        "i32.load16_s",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I32Load16U { memarg } => vec_with_opcode!(
-                        "i32.load16_u",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I64Load8S { memarg } => vec_with_opcode!(
-                        "i64.load8_s",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I64Load8U { memarg } => vec_with_opcode!(
-                        "i64.load8_u",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I64Load16S { memarg } => vec_with_opcode!(
-                        "i64.load16_s",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I64Load16U { memarg } => vec_with_opcode!(
-                        "i64.load16_u",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I64Load32S { memarg } => vec_with_opcode!(
-                        "i64.load32_s",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I64Load32U { memarg } => vec_with_opcode!(
-                        "i64.load32_u",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I32Store { memarg } => vec_with_opcode!(
-                        "i32.store",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I64Store { memarg } => vec_with_opcode!(
-                        "i64.store",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::F32Store { memarg } => vec_with_opcode!(
-                        "f32.store",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::F64Store { memarg } => vec_with_opcode!(
-                        "f64.store",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I32Store8 { memarg } => vec_with_opcode!(
-                        "i32.store8",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I32Store16 { memarg } => vec_with_opcode!(
-                        "i32.store16",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I64Store8 { memarg } => vec_with_opcode!(
-                        "i64.store8",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I64Store16 { memarg } => vec_with_opcode!(
-                        "i64.store16",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::I64Store32 { memarg } => vec_with_opcode!(
-                        "i64.store32",
-                        InstructionTextToken::new(
-                            format!("{memarg:?}"),
-                            InstructionTextTokenKind::Text
-                        ),
-                    ),
-                    Operator::MemorySize { mem } => vec_with_opcode!(
-                        "memory.size",
-                        InstructionTextToken::new(
-                            format!("{mem}"),
-                            InstructionTextTokenKind::Integer {
-                                value: *mem as u64,
-                                size: Some(4),
-                            },
-                        ),
-                    ),
-                    Operator::MemoryGrow { mem } => vec_with_opcode!(
-                        "memory.grow",
-                        InstructionTextToken::new(
-                            format!("{mem}"),
-                            InstructionTextTokenKind::Integer {
-                                value: *mem as u64,
-                                size: Some(4),
-                            },
-                        ),
-                    ),
-
-                    // Numeric instructions
-                    Operator::I32Const { value } => vec_with_opcode!(
-                        "i32.const",
-                        InstructionTextToken::new(
-                            format!("{value:#x}"),
-                            InstructionTextTokenKind::Integer {
-                                value: *value as u64,
-                                size: Some(4),
-                            },
-                        ),
-                    ),
-                    Operator::I64Const { value } => vec_with_opcode!(
-                        "i64.const",
-                        InstructionTextToken::new(
-                            format!("{value:#x}"),
-                            InstructionTextTokenKind::Integer {
-                                value: *value as u64,
-                                size: Some(8),
-                            },
-                        ),
-                    ),
-                    Operator::F32Const { value } => {
-                        let value: f32 = (*value).into();
-                        vec_with_opcode!(
-                            "f32.const",
-                            InstructionTextToken::new(
-                                format!("{value}"),
-                                InstructionTextTokenKind::FloatingPoint {
-                                    value: value as f64,
-                                    size: Some(4),
-                                },
-                            ),
-                        )
-                    }
-                    Operator::F64Const { value } => {
-                        let value: f64 = (*value).into();
-                        vec_with_opcode!(
-                            "f64.const",
-                            InstructionTextToken::new(
-                                format!("{value}"),
-                                InstructionTextTokenKind::FloatingPoint {
-                                    value,
-                                    size: Some(8),
-                                },
-                            ),
-                        )
-                    }
-                    Operator::I32Eqz => vec_with_opcode!("i32.eqz"),
-                    Operator::I32Eq => vec_with_opcode!("i32.eq"),
-                    Operator::I32Ne => vec_with_opcode!("i32.ne"),
-                    Operator::I32LtS => vec_with_opcode!("i32.lt_s"),
-                    Operator::I32LtU => vec_with_opcode!("i32.lt_u"),
-                    Operator::I32GtS => vec_with_opcode!("i32.gt_s"),
-                    Operator::I32GtU => vec_with_opcode!("i32.gt_u"),
-                    Operator::I32LeS => vec_with_opcode!("i32.le_s"),
-                    Operator::I32LeU => vec_with_opcode!("i32.le_u"),
-                    Operator::I32GeS => vec_with_opcode!("i32.ge_s"),
-                    Operator::I32GeU => vec_with_opcode!("i32.ge_u"),
-                    Operator::I64Eqz => vec_with_opcode!("i64.eqz"),
-                    Operator::I64Eq => vec_with_opcode!("i64.eq"),
-                    Operator::I64Ne => vec_with_opcode!("i64.ne"),
-                    Operator::I64LtS => vec_with_opcode!("i64.lt_s"),
-                    Operator::I64LtU => vec_with_opcode!("i64.lt_u"),
-                    Operator::I64GtS => vec_with_opcode!("i64.gt_s"),
-                    Operator::I64GtU => vec_with_opcode!("i64.gt_u"),
-                    Operator::I64LeS => vec_with_opcode!("i64.le_s"),
-                    Operator::I64LeU => vec_with_opcode!("i64.le_u"),
-                    Operator::I64GeS => vec_with_opcode!("i64.ge_s"),
-                    Operator::I64GeU => vec_with_opcode!("i64.ge_u"),
-                    Operator::F32Eq => vec_with_opcode!("f32.eq"),
-                    Operator::F32Ne => vec_with_opcode!("f32.ne"),
-                    Operator::F32Lt => vec_with_opcode!("f32.lt"),
-                    Operator::F32Gt => vec_with_opcode!("f32.gt"),
-                    Operator::F32Le => vec_with_opcode!("f32.le"),
-                    Operator::F32Ge => vec_with_opcode!("f32.ge"),
-                    Operator::F64Eq => vec_with_opcode!("f64.eq"),
-                    Operator::F64Ne => vec_with_opcode!("f64.ne"),
-                    Operator::F64Lt => vec_with_opcode!("f64.lt"),
-                    Operator::F64Gt => vec_with_opcode!("f64.gt"),
-                    Operator::F64Le => vec_with_opcode!("f64.le"),
-                    Operator::F64Ge => vec_with_opcode!("f64.ge"),
-                    Operator::I32Clz => vec_with_opcode!("i32.clz"),
-                    Operator::I32Ctz => vec_with_opcode!("i32.ctz"),
-                    Operator::I32Popcnt => vec_with_opcode!("i32.popcnt"),
-                    Operator::I32Add => vec_with_opcode!("i32.add"),
-                    Operator::I32Sub => vec_with_opcode!("i32.sub"),
-                    Operator::I32Mul => vec_with_opcode!("i32.mul"),
-                    Operator::I32DivS => vec_with_opcode!("i32.div_s"),
-                    Operator::I32DivU => vec_with_opcode!("i32.div_u"),
-                    Operator::I32RemS => vec_with_opcode!("i32.rem_s"),
-                    Operator::I32RemU => vec_with_opcode!("i32.rem_u"),
-                    Operator::I32And => vec_with_opcode!("i32.and"),
-                    Operator::I32Or => vec_with_opcode!("i32.or"),
-                    Operator::I32Xor => vec_with_opcode!("i32.xor"),
-                    Operator::I32Shl => vec_with_opcode!("i32.shl"),
-                    Operator::I32ShrS => vec_with_opcode!("i32.shr_s"),
-                    Operator::I32ShrU => vec_with_opcode!("i32.shr_u"),
-                    Operator::I32Rotl => vec_with_opcode!("i32.rotl"),
-                    Operator::I32Rotr => vec_with_opcode!("i32.rotr"),
-                    Operator::I64Clz => vec_with_opcode!("i64.clz"),
-                    Operator::I64Ctz => vec_with_opcode!("i64.ctz"),
-                    Operator::I64Popcnt => vec_with_opcode!("i64.popcnt"),
-                    Operator::I64Add => vec_with_opcode!("i64.add"),
-                    Operator::I64Sub => vec_with_opcode!("i64.sub"),
-                    Operator::I64Mul => vec_with_opcode!("i64.mul"),
-                    Operator::I64DivS => vec_with_opcode!("i64.div_s"),
-                    Operator::I64DivU => vec_with_opcode!("i64.div_u"),
-                    Operator::I64RemS => vec_with_opcode!("i64.rem_s"),
-                    Operator::I64RemU => vec_with_opcode!("i64.rem_u"),
-                    Operator::I64And => vec_with_opcode!("i64.and"),
-                    Operator::I64Or => vec_with_opcode!("i64.or"),
-                    Operator::I64Xor => vec_with_opcode!("i64.xor"),
-                    Operator::I64Shl => vec_with_opcode!("i64.shl"),
-                    Operator::I64ShrS => vec_with_opcode!("i64.shr_s"),
-                    Operator::I64ShrU => vec_with_opcode!("i64.shr_u"),
-                    Operator::I64Rotl => vec_with_opcode!("i64.rotl"),
-                    Operator::I64Rotr => vec_with_opcode!("i64.rotr"),
-                    Operator::F32Abs => vec_with_opcode!("f32.abs"),
-                    Operator::F32Neg => vec_with_opcode!("f32.neg"),
-                    Operator::F32Ceil => vec_with_opcode!("f32.ceil"),
-                    Operator::F32Floor => vec_with_opcode!("f32.floor"),
-                    Operator::F32Trunc => vec_with_opcode!("f32.trunc"),
-                    Operator::F32Nearest => vec_with_opcode!("f32.nearest"),
-                    Operator::F32Sqrt => vec_with_opcode!("f32.sqrt"),
-                    Operator::F32Add => vec_with_opcode!("f32.add"),
-                    Operator::F32Sub => vec_with_opcode!("f32.sub"),
-                    Operator::F32Mul => vec_with_opcode!("f32.mul"),
-                    Operator::F32Div => vec_with_opcode!("f32.div"),
-                    Operator::F32Min => vec_with_opcode!("f32.min"),
-                    Operator::F32Max => vec_with_opcode!("f32.max"),
-                    Operator::F32Copysign => vec_with_opcode!("f32.copysign"),
-                    Operator::F64Abs => vec_with_opcode!("f64.abs"),
-                    Operator::F64Neg => vec_with_opcode!("f64.neg"),
-                    Operator::F64Ceil => vec_with_opcode!("f64.ceil"),
-                    Operator::F64Floor => vec_with_opcode!("f64.floor"),
-                    Operator::F64Trunc => vec_with_opcode!("f64.trunc"),
-                    Operator::F64Nearest => vec_with_opcode!("f64.nearest"),
-                    Operator::F64Sqrt => vec_with_opcode!("f64.sqrt"),
-                    Operator::F64Add => vec_with_opcode!("f64.add"),
-                    Operator::F64Sub => vec_with_opcode!("f64.sub"),
-                    Operator::F64Mul => vec_with_opcode!("f64.mul"),
-                    Operator::F64Div => vec_with_opcode!("f64.div"),
-                    Operator::F64Min => vec_with_opcode!("f64.min"),
-                    Operator::F64Max => vec_with_opcode!("f64.max"),
-                    Operator::F64Copysign => vec_with_opcode!("f64.copysign"),
-                    Operator::I32WrapI64 => vec_with_opcode!("i32.wrap_i64"),
-                    Operator::I32TruncF32S => vec_with_opcode!("i32.trunc_f32_s"),
-                    Operator::I32TruncF32U => vec_with_opcode!("i32.trunc_f32_u"),
-                    Operator::I32TruncF64S => vec_with_opcode!("i32.trunc_f64_s"),
-                    Operator::I32TruncF64U => vec_with_opcode!("i32.trunc_f64_u"),
-                    Operator::I64ExtendI32S => vec_with_opcode!("i64.extend_i32_s"),
-                    Operator::I64ExtendI32U => vec_with_opcode!("i64.extend_i32_u"),
-                    Operator::I64TruncF32S => vec_with_opcode!("i64.trunc_f32_s"),
-                    Operator::I64TruncF32U => vec_with_opcode!("i64.trunc_f32_u"),
-                    Operator::I64TruncF64S => vec_with_opcode!("i64.trunc_f64_s"),
-                    Operator::I64TruncF64U => vec_with_opcode!("i64.trunc_f64_u"),
-                    Operator::F32ConvertI32S => vec_with_opcode!("f32.convert_i32_s"),
-                    Operator::F32ConvertI32U => vec_with_opcode!("f32.convert_i32_u"),
-                    Operator::F32ConvertI64S => vec_with_opcode!("f32.convert_i64_s"),
-                    Operator::F32ConvertI64U => vec_with_opcode!("f32.convert_i64_u"),
-                    Operator::F32DemoteF64 => vec_with_opcode!("f32.demote_f64"),
-                    Operator::F64ConvertI32S => vec_with_opcode!("f64.convert_i32_s"),
-                    Operator::F64ConvertI32U => vec_with_opcode!("f64.convert_i32_u"),
-                    Operator::F64ConvertI64S => vec_with_opcode!("f64.convert_i64_s"),
-                    Operator::F64ConvertI64U => vec_with_opcode!("f64.convert_i64_u"),
-                    Operator::F64PromoteF32 => vec_with_opcode!("f64.promote_f32"),
-                    Operator::I32ReinterpretF32 => vec_with_opcode!("i32.reinterpret_f32"),
-                    Operator::I64ReinterpretF64 => vec_with_opcode!("i64.reinterpret_f64"),
-                    Operator::F32ReinterpretI32 => vec_with_opcode!("f32.reinterpret_i32"),
-                    Operator::F64ReinterpretI64 => vec_with_opcode!("f64.reinterpret_i64"),
-                    Operator::I32Extend8S => vec_with_opcode!("i32.extend8_s"),
-                    Operator::I32Extend16S => vec_with_opcode!("i32.extend16_s"),
-                    Operator::I64Extend8S => vec_with_opcode!("i64.extend8_s"),
-                    Operator::I64Extend16S => vec_with_opcode!("i64.extend16_s"),
-                    Operator::I64Extend32S => vec_with_opcode!("i64.extend32_s"),
-                    Operator::I32TruncSatF32S => vec_with_opcode!("i32.trunc_sat_f32_s"),
-                    Operator::I32TruncSatF32U => vec_with_opcode!("i32.trunc_sat_f32_u"),
-                    Operator::I32TruncSatF64S => vec_with_opcode!("i32.trunc_sat_f64_s"),
-                    Operator::I32TruncSatF64U => vec_with_opcode!("i32.trunc_sat_f64_u"),
-                    Operator::I64TruncSatF32S => vec_with_opcode!("i64.trunc_sat_f32_s"),
-                    Operator::I64TruncSatF32U => vec_with_opcode!("i64.trunc_sat_f32_u"),
-                    Operator::I64TruncSatF64S => vec_with_opcode!("i64.trunc_sat_f64_s"),
-                    Operator::I64TruncSatF64U => vec_with_opcode!("i64.trunc_sat_f64_u"),
-
-                    _ => {
-                        return None;
-                    }
-                },
-            ))
+        let empty_module_data;
+        let module_data = match module_data_lock.as_ref() {
+            Some(module_data) => module_data,
+            None => {
+                empty_module_data = ModuleData::new();
+                &empty_module_data
+            }
+        };
+
+        let func = module_data.funcs.get(&addr).map(|func| func.as_ref());
+        if let Some(func) = func {
+            if addr == func.size_start {
+                return Some((
+                    (func.locals_start - func.size_start) as usize,
+                    func_header(module_data, func),
+                ));
+            } else if addr == func.locals_start {
+                return Some(((func.ops_start - func.locals_start) as usize, locals(func)));
+            } else if let Some(op) = func.unpatched_op(addr, data) {
+                return Some((op.size, operator_text(module_data, Some(func), addr, op)?));
+            }
         }
+
+        // Bytes that aren't an instruction of the parsed module (e.g. disassembled
+        // through the API, or patched in) are decoded on their own.
+        let op = parse_operator(data)?;
+        Some((op.size, operator_text(module_data, None, addr, &op)?))
     }
 }
@@ -0,0 +1,456 @@
+use binaryninja::architecture::{Intrinsic, IntrinsicId};
+use binaryninja::confidence::{Conf, MAX_CONFIDENCE};
+use binaryninja::rc::Ref;
+use binaryninja::types::{NameAndType, Type};
+use once_cell::sync::Lazy;
+use std::borrow::Cow;
+use wasmparser::{MemArg, Operator, ValType};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AtomicRmwOp {
+    Add,
+    Sub,
+    And,
+    Or,
+    Xor,
+    Xchg,
+    Cmpxchg,
+}
+
+// Operations with no LLIL equivalent, which are lifted as intrinsics named after the
+// instruction. Narrower forms of an instruction (e.g. `i32.atomic.rmw8.add_u`) are
+// lifted as the full-width intrinsic on extended operands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WebAssemblyIntrinsic {
+    // Memory
+    MemorySize,
+    MemoryGrow,
+    MemoryFill,
+    MemoryCopy,
+    MemoryInit,
+    DataDrop,
+
+    // Saturating truncation, by the result and operand types
+    TruncSat {
+        to: ValType,
+        from: ValType,
+        signed: bool,
+    },
+
+    // Threads
+    AtomicFence,
+    AtomicNotify,
+    AtomicWait {
+        ty: ValType,
+    },
+    AtomicRmw {
+        op: AtomicRmwOp,
+        ty: ValType,
+    },
+
+    // Tables
+    TableGet,
+    TableSet,
+    TableSize,
+    TableGrow,
+    TableFill,
+    TableCopy,
+    TableInit,
+    ElemDrop,
+
+    // Exceptions
+    Throw,
+    ThrowRef,
+    Rethrow,
+}
+
+const INT_TYPES: [ValType; 2] = [ValType::I32, ValType::I64];
+const FLOAT_TYPES: [ValType; 2] = [ValType::F32, ValType::F64];
+const RMW_OPS: [AtomicRmwOp; 7] = [
+    AtomicRmwOp::Add,
+    AtomicRmwOp::Sub,
+    AtomicRmwOp::And,
+    AtomicRmwOp::Or,
+    AtomicRmwOp::Xor,
+    AtomicRmwOp::Xchg,
+    AtomicRmwOp::Cmpxchg,
+];
+
+fn type_name(ty: ValType) -> &'static str {
+    match ty {
+        ValType::I32 => "i32",
+        ValType::I64 => "i64",
+        ValType::F32 => "f32",
+        ValType::F64 => "f64",
+        ValType::V128 => "v128",
+        ValType::Ref(_) => "ref",
+    }
+}
+
+// References are opaque, so they are typed as the handles an engine would use.
+pub(crate) fn value_type(ty: ValType) -> Ref<Type> {
+    match ty {
+        ValType::I32 => Type::int(4, true),
+        ValType::I64 => Type::int(8, true),
+        ValType::F32 => Type::float(4),
+        ValType::F64 => Type::float(8),
+        ValType::V128 => Type::int(16, false),
+        ValType::Ref(_) => Type::int(4, false),
+    }
+}
+
+static ALL: Lazy<Vec<WebAssemblyIntrinsic>> = Lazy::new(WebAssemblyIntrinsic::all);
+
+// How an instruction is lifted as an intrinsic: the intrinsic, the immediates that
+// are its first inputs, and the memory argument of an instruction whose first operand
+// is an address.
+pub(crate) struct IntrinsicCall {
+    pub intrinsic: WebAssemblyIntrinsic,
+    pub immediates: Vec<u32>,
+    pub memarg: Option<MemArg>,
+}
+
+// The position of `ty` in `types`, which it is one of.
+fn type_index(types: [ValType; 2], ty: ValType) -> u32 {
+    (ty == types[1]) as u32
+}
+
+// The operation of an atomic read-modify-write instruction, the type of its operands
+// and its memory argument.
+pub(crate) fn atomic_rmw(op: &Operator) -> Option<(AtomicRmwOp, ValType, MemArg)> {
+    use AtomicRmwOp::{Add, And, Cmpxchg, Or, Sub, Xchg, Xor};
+    use ValType::{I32, I64};
+    let rmw = match *op {
+        Operator::I32AtomicRmwAdd { memarg }
+        | Operator::I32AtomicRmw8AddU { memarg }
+        | Operator::I32AtomicRmw16AddU { memarg } => (Add, I32, memarg),
+        Operator::I64AtomicRmwAdd { memarg }
+        | Operator::I64AtomicRmw8AddU { memarg }
+        | Operator::I64AtomicRmw16AddU { memarg }
+        | Operator::I64AtomicRmw32AddU { memarg } => (Add, I64, memarg),
+        Operator::I32AtomicRmwSub { memarg }
+        | Operator::I32AtomicRmw8SubU { memarg }
+        | Operator::I32AtomicRmw16SubU { memarg } => (Sub, I32, memarg),
+        Operator::I64AtomicRmwSub { memarg }
+        | Operator::I64AtomicRmw8SubU { memarg }
+        | Operator::I64AtomicRmw16SubU { memarg }
+        | Operator::I64AtomicRmw32SubU { memarg } => (Sub, I64, memarg),
+        Operator::I32AtomicRmwAnd { memarg }
+        | Operator::I32AtomicRmw8AndU { memarg }
+        | Operator::I32AtomicRmw16AndU { memarg } => (And, I32, memarg),
+        Operator::I64AtomicRmwAnd { memarg }
+        | Operator::I64AtomicRmw8AndU { memarg }
+        | Operator::I64AtomicRmw16AndU { memarg }
+        | Operator::I64AtomicRmw32AndU { memarg } => (And, I64, memarg),
+        Operator::I32AtomicRmwOr { memarg }
+        | Operator::I32AtomicRmw8OrU { memarg }
+        | Operator::I32AtomicRmw16OrU { memarg } => (Or, I32, memarg),
+        Operator::I64AtomicRmwOr { memarg }
+        | Operator::I64AtomicRmw8OrU { memarg }
+        | Operator::I64AtomicRmw16OrU { memarg }
+        | Operator::I64AtomicRmw32OrU { memarg } => (Or, I64, memarg),
+        Operator::I32AtomicRmwXor { memarg }
+        | Operator::I32AtomicRmw8XorU { memarg }
+        | Operator::I32AtomicRmw16XorU { memarg } => (Xor, I32, memarg),
+        Operator::I64AtomicRmwXor { memarg }
+        | Operator::I64AtomicRmw8XorU { memarg }
+        | Operator::I64AtomicRmw16XorU { memarg }
+        | Operator::I64AtomicRmw32XorU { memarg } => (Xor, I64, memarg),
+        Operator::I32AtomicRmwXchg { memarg }
+        | Operator::I32AtomicRmw8XchgU { memarg }
+        | Operator::I32AtomicRmw16XchgU { memarg } => (Xchg, I32, memarg),
+        Operator::I64AtomicRmwXchg { memarg }
+        | Operator::I64AtomicRmw8XchgU { memarg }
+        | Operator::I64AtomicRmw16XchgU { memarg }
+        | Operator::I64AtomicRmw32XchgU { memarg } => (Xchg, I64, memarg),
+        Operator::I32AtomicRmwCmpxchg { memarg }
+        | Operator::I32AtomicRmw8CmpxchgU { memarg }
+        | Operator::I32AtomicRmw16CmpxchgU { memarg } => (Cmpxchg, I32, memarg),
+        Operator::I64AtomicRmwCmpxchg { memarg }
+        | Operator::I64AtomicRmw8CmpxchgU { memarg }
+        | Operator::I64AtomicRmw16CmpxchgU { memarg }
+        | Operator::I64AtomicRmw32CmpxchgU { memarg } => (Cmpxchg, I64, memarg),
+        _ => return None,
+    };
+    Some(rmw)
+}
+
+impl WebAssemblyIntrinsic {
+    pub(crate) fn all() -> Vec<Self> {
+        let mut all = vec![
+            Self::MemorySize,
+            Self::MemoryGrow,
+            Self::MemoryFill,
+            Self::MemoryCopy,
+            Self::MemoryInit,
+            Self::DataDrop,
+        ];
+        for to in INT_TYPES {
+            for from in FLOAT_TYPES {
+                for signed in [true, false] {
+                    all.push(Self::TruncSat { to, from, signed });
+                }
+            }
+        }
+        all.extend([Self::AtomicFence, Self::AtomicNotify]);
+        for ty in INT_TYPES {
+            all.push(Self::AtomicWait { ty });
+            for op in RMW_OPS {
+                all.push(Self::AtomicRmw { op, ty });
+            }
+        }
+        all.extend([
+            Self::TableGet,
+            Self::TableSet,
+            Self::TableSize,
+            Self::TableGrow,
+            Self::TableFill,
+            Self::TableCopy,
+            Self::TableInit,
+            Self::ElemDrop,
+            Self::Throw,
+            Self::ThrowRef,
+            Self::Rethrow,
+        ]);
+        all
+    }
+
+    pub(crate) fn from_id(id: IntrinsicId) -> Option<Self> {
+        ALL.get(id.0 as usize).copied()
+    }
+
+    // The intrinsic that `op` is lifted as, if it is lifted as one.
+    pub(crate) fn for_operator(op: &Operator) -> Option<IntrinsicCall> {
+        use ValType::{F32, F64, I32, I64};
+        let call = |intrinsic, immediates: &[u32], memarg| IntrinsicCall {
+            intrinsic,
+            immediates: immediates.to_vec(),
+            memarg,
+        };
+        let trunc_sat = |to, from, signed| call(Self::TruncSat { to, from, signed }, &[], None);
+        let call = match *op {
+            Operator::MemorySize { .. } => call(Self::MemorySize, &[], None),
+            Operator::MemoryGrow { .. } => call(Self::MemoryGrow, &[], None),
+            Operator::MemoryFill { .. } => call(Self::MemoryFill, &[], None),
+            Operator::MemoryCopy { .. } => call(Self::MemoryCopy, &[], None),
+            Operator::MemoryInit { data_index, .. } => call(Self::MemoryInit, &[data_index], None),
+            Operator::DataDrop { data_index } => call(Self::DataDrop, &[data_index], None),
+
+            Operator::I32TruncSatF32S => trunc_sat(I32, F32, true),
+            Operator::I32TruncSatF32U => trunc_sat(I32, F32, false),
+            Operator::I32TruncSatF64S => trunc_sat(I32, F64, true),
+            Operator::I32TruncSatF64U => trunc_sat(I32, F64, false),
+            Operator::I64TruncSatF32S => trunc_sat(I64, F32, true),
+            Operator::I64TruncSatF32U => trunc_sat(I64, F32, false),
+            Operator::I64TruncSatF64S => trunc_sat(I64, F64, true),
+            Operator::I64TruncSatF64U => trunc_sat(I64, F64, false),
+
+            Operator::AtomicFence => call(Self::AtomicFence, &[], None),
+            Operator::MemoryAtomicNotify { memarg } => call(Self::AtomicNotify, &[], Some(memarg)),
+            Operator::MemoryAtomicWait32 { memarg } => {
+                call(Self::AtomicWait { ty: I32 }, &[], Some(memarg))
+            }
+            Operator::MemoryAtomicWait64 { memarg } => {
+                call(Self::AtomicWait { ty: I64 }, &[], Some(memarg))
+            }
+
+            Operator::TableGet { table } => call(Self::TableGet, &[table], None),
+            Operator::TableSet { table } => call(Self::TableSet, &[table], None),
+            Operator::TableSize { table } => call(Self::TableSize, &[table], None),
+            Operator::TableGrow { table } => call(Self::TableGrow, &[table], None),
+            Operator::TableFill { table } => call(Self::TableFill, &[table], None),
+            Operator::TableCopy {
+                dst_table,
+                src_table,
+            } => call(Self::TableCopy, &[dst_table, src_table], None),
+            Operator::TableInit { elem_index, table } => {
+                call(Self::TableInit, &[table, elem_index], None)
+            }
+            Operator::ElemDrop { elem_index } => call(Self::ElemDrop, &[elem_index], None),
+
+            _ => {
+                let (op, ty, memarg) = atomic_rmw(op)?;
+                call(Self::AtomicRmw { op, ty }, &[], Some(memarg))
+            }
+        };
+        Some(call)
+    }
+
+    // The named operands and the results of the intrinsic.
+    fn signature(&self) -> (Vec<(&'static str, ValType)>, Vec<ValType>) {
+        use ValType::{I32, I64};
+        let funcref = ValType::FUNCREF;
+        match *self {
+            Self::MemorySize => (vec![], vec![I32]),
+            Self::MemoryGrow => (vec![("delta", I32)], vec![I32]),
+            Self::MemoryFill => (vec![("dest", I32), ("value", I32), ("size", I32)], vec![]),
+            Self::MemoryCopy => (vec![("dest", I32), ("src", I32), ("size", I32)], vec![]),
+            Self::MemoryInit => (
+                vec![
+                    ("segment", I32),
+                    ("dest", I32),
+                    ("offset", I32),
+                    ("size", I32),
+                ],
+                vec![],
+            ),
+            Self::DataDrop => (vec![("segment", I32)], vec![]),
+            Self::TruncSat { to, from, .. } => (vec![("value", from)], vec![to]),
+            Self::AtomicFence => (vec![], vec![]),
+            Self::AtomicNotify => (vec![("addr", I32), ("count", I32)], vec![I32]),
+            Self::AtomicWait { ty } => (
+                vec![("addr", I32), ("expected", ty), ("timeout", I64)],
+                vec![I32],
+            ),
+            Self::AtomicRmw {
+                op: AtomicRmwOp::Cmpxchg,
+                ty,
+            } => (
+                vec![("addr", I32), ("expected", ty), ("replacement", ty)],
+                vec![ty],
+            ),
+            Self::AtomicRmw { ty, .. } => (vec![("addr", I32), ("value", ty)], vec![ty]),
+            Self::TableGet => (vec![("table", I32), ("index", I32)], vec![funcref]),
+            Self::TableSet => (
+                vec![("table", I32), ("index", I32), ("value", funcref)],
+                vec![],
+            ),
+            Self::TableSize => (vec![("table", I32)], vec![I32]),
+            Self::TableGrow => (
+                vec![("table", I32), ("value", funcref), ("delta", I32)],
+                vec![I32],
+            ),
+            Self::TableFill => (
+                vec![
+                    ("table", I32),
+                    ("index", I32),
+                    ("value", funcref),
+                    ("size", I32),
+                ],
+                vec![],
+            ),
+            Self::TableCopy => (
+                vec![
+                    ("dest_table", I32),
+                    ("src_table", I32),
+                    ("dest", I32),
+                    ("src", I32),
+                    ("size", I32),
+                ],
+                vec![],
+            ),
+            Self::TableInit => (
+                vec![
+                    ("table", I32),
+                    ("segment", I32),
+                    ("dest", I32),
+                    ("offset", I32),
+                    ("size", I32),
+                ],
+                vec![],
+            ),
+            Self::ElemDrop => (vec![("segment", I32)], vec![]),
+            Self::Throw => (vec![("tag", I32)], vec![]),
+            Self::ThrowRef => (vec![("exception", ValType::EXNREF)], vec![]),
+            Self::Rethrow => (vec![("depth", I32)], vec![]),
+        }
+    }
+}
+
+impl Intrinsic for WebAssemblyIntrinsic {
+    fn name(&self) -> Cow<'_, str> {
+        match *self {
+            Self::MemorySize => "memory.size".into(),
+            Self::MemoryGrow => "memory.grow".into(),
+            Self::MemoryFill => "memory.fill".into(),
+            Self::MemoryCopy => "memory.copy".into(),
+            Self::MemoryInit => "memory.init".into(),
+            Self::DataDrop => "data.drop".into(),
+            Self::TruncSat { to, from, signed } => format!(
+                "{}.trunc_sat_{}_{}",
+                type_name(to),
+                type_name(from),
+                if signed { "s" } else { "u" }
+            )
+            .into(),
+            Self::AtomicFence => "atomic.fence".into(),
+            Self::AtomicNotify => "memory.atomic.notify".into(),
+            Self::AtomicWait { ty } => match ty {
+                ValType::I64 => "memory.atomic.wait64".into(),
+                _ => "memory.atomic.wait32".into(),
+            },
+            Self::AtomicRmw { op, ty } => {
+                let op = match op {
+                    AtomicRmwOp::Add => "add",
+                    AtomicRmwOp::Sub => "sub",
+                    AtomicRmwOp::And => "and",
+                    AtomicRmwOp::Or => "or",
+                    AtomicRmwOp::Xor => "xor",
+                    AtomicRmwOp::Xchg => "xchg",
+                    AtomicRmwOp::Cmpxchg => "cmpxchg",
+                };
+                format!("{}.atomic.rmw.{op}", type_name(ty)).into()
+            }
+            Self::TableGet => "table.get".into(),
+            Self::TableSet => "table.set".into(),
+            Self::TableSize => "table.size".into(),
+            Self::TableGrow => "table.grow".into(),
+            Self::TableFill => "table.fill".into(),
+            Self::TableCopy => "table.copy".into(),
+            Self::TableInit => "table.init".into(),
+            Self::ElemDrop => "elem.drop".into(),
+            Self::Throw => "throw".into(),
+            Self::ThrowRef => "throw_ref".into(),
+            Self::Rethrow => "rethrow".into(),
+        }
+    }
+
+    // The intrinsic's position in `all`.
+    fn id(&self) -> IntrinsicId {
+        let id = match *self {
+            Self::MemorySize => 0,
+            Self::MemoryGrow => 1,
+            Self::MemoryFill => 2,
+            Self::MemoryCopy => 3,
+            Self::MemoryInit => 4,
+            Self::DataDrop => 5,
+            Self::TruncSat { to, from, signed } => {
+                6 + type_index(INT_TYPES, to) * 4
+                    + type_index(FLOAT_TYPES, from) * 2
+                    + !signed as u32
+            }
+            Self::AtomicFence => 14,
+            Self::AtomicNotify => 15,
+            Self::AtomicWait { ty } => 16 + type_index(INT_TYPES, ty) * 8,
+            Self::AtomicRmw { op, ty } => 16 + type_index(INT_TYPES, ty) * 8 + 1 + op as u32,
+            Self::TableGet => 32,
+            Self::TableSet => 33,
+            Self::TableSize => 34,
+            Self::TableGrow => 35,
+            Self::TableFill => 36,
+            Self::TableCopy => 37,
+            Self::TableInit => 38,
+            Self::ElemDrop => 39,
+            Self::Throw => 40,
+            Self::ThrowRef => 41,
+            Self::Rethrow => 42,
+        };
+        IntrinsicId(id)
+    }
+
+    fn inputs(&self) -> Vec<NameAndType> {
+        self.signature()
+            .0
+            .into_iter()
+            .map(|(name, ty)| NameAndType::new(name, Conf::new(value_type(ty), MAX_CONFIDENCE)))
+            .collect()
+    }
+
+    fn outputs(&self) -> Vec<Conf<Ref<Type>>> {
+        self.signature()
+            .1
+            .into_iter()
+            .map(|ty| Conf::new(value_type(ty), MAX_CONFIDENCE))
+            .collect()
+    }
+}
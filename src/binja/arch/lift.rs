@@ -0,0 +1,841 @@
+use crate::binja::arch::intrinsic::{IntrinsicCall, WebAssemblyIntrinsic};
+use crate::binja::arch::register::OperandStack;
+use crate::binja::arch::stack_effect::{address_type, local_type, stack_effect, StackEffect};
+use crate::binja::arch::{WebAssemblyArchitecture, WebAssemblyRegister};
+use crate::binja::parse::func_parse::parse_operator;
+use crate::binja::parse::module_data::{
+    BranchTargetAddr, FunctionData, ModuleData, OperatorData, MODULE_DATA,
+};
+use crate::binja::parse::pointers::memory_access;
+use crate::binja::parse::stack_state::{stack_layout, StackLayout, Unwind};
+use binaryninja::low_level_il::{
+    LowLevelILLabel, LowLevelILMutableFunction, LowLevelILRegisterKind, LowLevelILTempRegister,
+};
+use std::cmp::Ordering;
+use wasmparser::{FuncType, MemArg, Operator, ValType};
+
+// The temporaries that a branch keeps the values it takes along in while it unwinds
+// the operand stack, and the one it pops the values it discards into. The operands of
+// an instruction are in the temporaries from 0 up.
+const KEPT_TEMPS: u32 = 0x1000;
+const DISCARDED_TEMP: u32 = 0x2000;
+
+// The temporaries that the immediate inputs of an intrinsic are put in, and the one
+// its result is put in.
+const IMMEDIATE_TEMPS: u32 = 0x3000;
+const RESULT_TEMP: u32 = 0x4000;
+
+// The temporaries that the caller's own parameters are kept in across a call whose
+// arguments are passed in their registers.
+const SAVED_ARG_TEMPS: u32 = 0x5000;
+
+// A whole operand stack slot, for values whose type isn't known.
+const SLOT_SIZE: usize = 8;
+
+macro_rules! push {
+    ($il:expr, $size:expr, $value:expr) => {
+        $il.reg_stack_push($size, OperandStack, $value).append()
+    };
+}
+
+// The size of a value of type `ty` in IL. References are lifted as their index.
+pub(crate) fn value_size(ty: ValType) -> usize {
+    match ty {
+        ValType::I64 | ValType::F64 => 8,
+        ValType::V128 => 16,
+        ValType::I32 | ValType::F32 | ValType::Ref(_) => 4,
+    }
+}
+
+fn temp(index: u32) -> LowLevelILRegisterKind<WebAssemblyRegister> {
+    LowLevelILRegisterKind::Temp(LowLevelILTempRegister::new(index))
+}
+
+// Lifts the instructions of a function onto the operand stack, which is a register
+// stack, so that the decompiler resolves the pushes and pops to the values they carry.
+struct Lifter<'a> {
+    il: &'a LowLevelILMutableFunction,
+    module_data: &'a ModuleData,
+    func: Option<&'a FunctionData>,
+
+    // `None` for instructions that aren't where the function was parsed, i.e. patched
+    // ones, whose branch targets aren't known either.
+    layout: Option<&'a StackLayout>,
+    addr: u64,
+}
+
+impl Lifter<'_> {
+    // Pops operands of `types`, bottom first, into the temporaries from 0 up. They are
+    // popped top first before anything uses them, so they come off the stack in the
+    // order wasm pops them, whatever order the IL that uses them is evaluated in.
+    fn pop_operands(&self, types: &[ValType]) {
+        let il = self.il;
+        for (i, ty) in types.iter().enumerate().rev() {
+            let size = value_size(*ty);
+            il.set_reg(size, temp(i as u32), il.reg_stack_pop(size, OperandStack))
+                .append();
+        }
+    }
+
+    // Pushes values of `types` that aren't lifted.
+    fn push_unknown(&self, types: &[ValType]) {
+        for ty in types {
+            push!(self.il, value_size(*ty), self.il.unimplemented());
+        }
+    }
+
+    fn unwind(&self, unwind: &Unwind) {
+        let il = self.il;
+        for (i, ty) in unwind.keep.iter().enumerate().rev() {
+            let size = value_size(*ty);
+            let kept = temp(KEPT_TEMPS + i as u32);
+            il.set_reg(size, kept, il.reg_stack_pop(size, OperandStack))
+                .append();
+        }
+        for ty in unwind.discard.iter().rev() {
+            let size = value_size(*ty);
+            il.set_reg(
+                size,
+                temp(DISCARDED_TEMP),
+                il.reg_stack_pop(size, OperandStack),
+            )
+            .append();
+        }
+        for (i, ty) in unwind.keep.iter().enumerate() {
+            let size = value_size(*ty);
+            push!(il, size, il.reg(size, temp(KEPT_TEMPS + i as u32)));
+        }
+    }
+
+    // Returns the function's results, from the top of the operand stack, in the result
+    // registers. Results that have no register are dropped. Wasm has no return address
+    // to return to.
+    fn ret(&self) {
+        let il = self.il;
+        let results = self
+            .func
+            .and_then(|func| self.module_data.func_type(func.func_index))
+            .map_or(&[][..], FuncType::results);
+        for (i, ty) in results.iter().enumerate().rev() {
+            let size = value_size(*ty);
+            let reg = match WebAssemblyRegister::for_result(i as u32, *ty) {
+                Some(reg) => LowLevelILRegisterKind::Arch(reg),
+                None => temp(DISCARDED_TEMP),
+            };
+            il.set_reg(size, reg, il.reg_stack_pop(size, OperandStack))
+                .append();
+        }
+        il.ret(il.const_ptr(0)).append();
+    }
+
+    // Calls a function of type `ty` with `call`. The arguments, which `pop_operands`
+    // has put in the temporaries from 0 up, are passed in the argument registers, and
+    // the results are pushed from the result registers. The caller's own parameters
+    // are in the same argument registers, so the ones that are overwritten are kept
+    // across the call.
+    fn call(&self, ty: &FuncType, call: impl FnOnce()) {
+        let il = self.il;
+        let params = ty.params();
+        let saved: Vec<_> = self
+            .func
+            .and_then(|func| self.module_data.func_type(func.func_index))
+            .map(|caller| {
+                let n_params = caller.params().len() as u32;
+                caller.params()[..params.len().min(n_params as usize)]
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, ty)| {
+                        let reg = WebAssemblyRegister::for_local(n_params, i as u32, *ty)?;
+                        Some((reg, value_size(*ty)))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for (i, (reg, size)) in saved.iter().enumerate() {
+            let kept = temp(SAVED_ARG_TEMPS + i as u32);
+            il.set_reg(*size, kept, il.reg(*size, *reg)).append();
+        }
+        for (i, ty) in params.iter().enumerate() {
+            let size = value_size(*ty);
+            let n_params = params.len() as u32;
+            if let Some(reg) = WebAssemblyRegister::for_local(n_params, i as u32, *ty) {
+                il.set_reg(size, reg, il.reg(size, temp(i as u32))).append();
+            }
+        }
+        call();
+        for (i, (reg, size)) in saved.iter().enumerate() {
+            let kept = temp(SAVED_ARG_TEMPS + i as u32);
+            il.set_reg(*size, *reg, il.reg(*size, kept)).append();
+        }
+        for (i, ty) in ty.results().iter().enumerate() {
+            let size = value_size(*ty);
+            match WebAssemblyRegister::for_result(i as u32, *ty) {
+                Some(reg) => push!(il, size, il.reg(size, reg)),
+                None => push!(il, size, il.unimplemented()),
+            }
+        }
+    }
+
+    // Goes to the instruction at `target`, or returns if it's the end of the function.
+    fn go_to(&self, target: u64) {
+        let il = self.il;
+        if self.func.is_some_and(|func| target == func.end) {
+            self.ret();
+            return;
+        }
+        match il.label_for_address(target) {
+            Some(mut label) => il.goto(&mut label).append(),
+            None => il.jump(il.const_ptr(target)).append(),
+        }
+    }
+
+    // Branches to `target`, the block `depth` levels out, unwinding the operand stack
+    // on the way.
+    fn branch(&self, depth: u32, target: u64) {
+        let unwind = self
+            .layout
+            .and_then(|layout| layout.unwinds.get(&(self.addr, depth)));
+        if let Some(unwind) = unwind {
+            self.unwind(unwind);
+        }
+        self.go_to(target);
+    }
+
+    // Emits the `if` that `emit_if` makes of a true and a false label, followed by what
+    // each of them does: `taken` branches, and the other goes to `not_taken`, or else
+    // falls through to the IL that is lifted next.
+    fn branch_if(
+        &self,
+        emit_if: impl FnOnce(&mut LowLevelILLabel, &mut LowLevelILLabel),
+        taken: impl FnOnce(),
+        not_taken: Option<u64>,
+    ) {
+        let mut true_label = LowLevelILLabel::new();
+        let mut false_label = LowLevelILLabel::new();
+        emit_if(&mut true_label, &mut false_label);
+        self.il.mark_label(&mut true_label);
+        taken();
+        self.il.mark_label(&mut false_label);
+        if let Some(not_taken) = not_taken {
+            self.go_to(not_taken);
+        }
+    }
+
+    // The register and size of a local of the function.
+    fn local(&self, local_index: u32) -> Option<(WebAssemblyRegister, usize)> {
+        let func = self.func?;
+        let n_params = self.module_data.func_type(func.func_index)?.params().len();
+        let ty = local_type(self.module_data, func, local_index)?;
+        let reg = WebAssemblyRegister::for_local(n_params as u32, local_index, ty)?;
+        Some((reg, value_size(ty)))
+    }
+
+    fn lift(&self, op: &OperatorData) {
+        let il = self.il;
+        match (&op.op, &op.target) {
+            (
+                Operator::Nop
+                | Operator::Block { .. }
+                | Operator::Loop { .. }
+                | Operator::Try { .. }
+                | Operator::TryTable { .. }
+                | Operator::Catch { .. }
+                | Operator::CatchAll
+                | Operator::Delegate { .. },
+                _,
+            ) => il.nop().append(),
+            (Operator::End, Some(BranchTargetAddr::FunctionEnd)) | (Operator::Return, _) => {
+                self.ret()
+            }
+            (Operator::End, _) => il.nop().append(),
+            (Operator::Unreachable, _) => il.trap(0).append(),
+            (Operator::Else, Some(BranchTargetAddr::Unconditional(after))) => self.go_to(*after),
+            (
+                Operator::If { .. },
+                Some(BranchTargetAddr::Conditional {
+                    true_target,
+                    false_target,
+                }),
+            ) => {
+                self.pop_operands(&[ValType::I32]);
+                self.branch_if(
+                    |t, f| {
+                        il.if_expr(il.cmp_ne(4, il.reg(4, temp(0)), il.const_int(4, 0)), t, f)
+                            .append()
+                    },
+                    || self.go_to(*true_target),
+                    Some(*false_target),
+                );
+            }
+            (Operator::Br { relative_depth }, Some(BranchTargetAddr::Unconditional(target))) => {
+                self.branch(*relative_depth, *target)
+            }
+            (
+                Operator::BrIf { relative_depth },
+                Some(BranchTargetAddr::Conditional {
+                    true_target,
+                    false_target,
+                }),
+            ) => {
+                self.pop_operands(&[ValType::I32]);
+                self.branch_if(
+                    |t, f| {
+                        il.if_expr(il.cmp_ne(4, il.reg(4, temp(0)), il.const_int(4, 0)), t, f)
+                            .append()
+                    },
+                    || self.branch(*relative_depth, *true_target),
+                    Some(*false_target),
+                );
+            }
+            (
+                Operator::BrTable { targets },
+                Some(BranchTargetAddr::Table {
+                    targets: target_addrs,
+                    default_target,
+                }),
+            ) => {
+                self.pop_operands(&[ValType::I32]);
+                let depths = targets.targets().flatten();
+                for (i, (depth, target)) in depths.zip(target_addrs).enumerate() {
+                    self.branch_if(
+                        |t, f| {
+                            let index = il.const_int(4, i as u64);
+                            il.if_expr(il.cmp_e(4, il.reg(4, temp(0)), index), t, f)
+                                .append()
+                        },
+                        || self.branch(depth, *target),
+                        None,
+                    );
+                }
+                self.branch(targets.default(), *default_target);
+            }
+            (Operator::Throw { tag_index }, _) => {
+                let module_data = self.module_data;
+                let params = module_data
+                    .tags
+                    .get(*tag_index as usize)
+                    .and_then(|tag| module_data.types.get(tag.ty.func_type_idx as usize))
+                    .map_or(&[][..], |ty| ty.params());
+                let call = IntrinsicCall {
+                    intrinsic: WebAssemblyIntrinsic::Throw,
+                    immediates: vec![*tag_index],
+                    memarg: None,
+                };
+                self.lift_throw(&call, params);
+            }
+            (Operator::ThrowRef, _) => {
+                let call = IntrinsicCall {
+                    intrinsic: WebAssemblyIntrinsic::ThrowRef,
+                    immediates: Vec::new(),
+                    memarg: None,
+                };
+                self.lift_throw(&call, &[ValType::EXNREF]);
+            }
+            (Operator::Rethrow { relative_depth }, _) => {
+                let call = IntrinsicCall {
+                    intrinsic: WebAssemblyIntrinsic::Rethrow,
+                    immediates: vec![*relative_depth],
+                    memarg: None,
+                };
+                self.lift_throw(&call, &[]);
+            }
+            (Operator::Drop, _) => {
+                let size = self.operand_size();
+                il.set_reg(size, temp(0), il.reg_stack_pop(size, OperandStack))
+                    .append();
+            }
+            (Operator::Select, _) => self.lift_select(self.operand_size()),
+            (Operator::TypedSelect { ty }, _) => self.lift_select(value_size(*ty)),
+            (Operator::LocalGet { local_index }, _) => match self.local(*local_index) {
+                Some((reg, size)) => push!(il, size, il.reg(size, reg)),
+                None => self.lift_value_op(&op.op),
+            },
+            (Operator::LocalSet { local_index }, _) => match self.local(*local_index) {
+                Some((reg, size)) => il
+                    .set_reg(size, reg, il.reg_stack_pop(size, OperandStack))
+                    .append(),
+                None => self.lift_value_op(&op.op),
+            },
+            (Operator::LocalTee { local_index }, _) => match self.local(*local_index) {
+                Some((reg, size)) => {
+                    il.set_reg(size, reg, il.reg_stack_pop(size, OperandStack))
+                        .append();
+                    push!(il, size, il.reg(size, reg));
+                }
+                None => self.lift_value_op(&op.op),
+            },
+            (Operator::GlobalGet { global_index }, _) if self.is_stack_pointer(*global_index) => {
+                self.lift_stack_pointer_get(*global_index)
+            }
+            (Operator::GlobalSet { global_index }, _) if self.is_stack_pointer(*global_index) => {
+                self.lift_stack_pointer_set(*global_index)
+            }
+            (Operator::Call { function_index }, _) => {
+                let module_data = self.module_data;
+                let (Some(ty), Some(func_addr)) = (
+                    module_data.func_type(*function_index),
+                    module_data.func_addrs.get(*function_index as usize),
+                ) else {
+                    il.unimplemented().append();
+                    return;
+                };
+                self.pop_operands(ty.params());
+                self.call(ty, || il.call(il.const_ptr(*func_addr)).append());
+            }
+            (Operator::CallIndirect { type_index, .. }, _) => {
+                let module_data = self.module_data;
+                let Some(ty) = module_data.types.get(*type_index as usize) else {
+                    il.unimplemented().append();
+                    return;
+                };
+                self.pop_operands(&[ty.params(), &[ValType::I32]].concat());
+                let pinned = module_data
+                    .call_target_overrides
+                    .get(&self.addr)
+                    .and_then(|func_index| module_data.func_addrs.get(*func_index as usize));
+                self.call(ty, || match pinned {
+                    Some(func_addr) => il.call(il.const_ptr(*func_addr)).append(),
+                    None => il.call(il.unimplemented()).append(),
+                });
+            }
+            _ => self.lift_value_op(&op.op),
+        }
+    }
+
+    fn is_stack_pointer(&self, global_index: u32) -> bool {
+        self.module_data.stack_pointer_global == Some(global_index)
+    }
+
+    fn global_type(&self, global_index: u32) -> ValType {
+        let global = self.module_data.globals.get(global_index as usize);
+        global.map_or_else(
+            || address_type(self.module_data, 0),
+            |global| global.ty.content_type,
+        )
+    }
+
+    // The shadow stack pointer is lifted to `sp`, which holds where it points in the
+    // view rather than its value, so that binja sees the addresses of the accesses to
+    // a frame as offsets from `sp` and maps them to stack variables. `global.get` takes
+    // the memory base off again, and `global.set` adds it.
+    fn lift_stack_pointer_get(&self, global_index: u32) {
+        let il = self.il;
+        let addr_size = self.module_data.address_size;
+        let ty = self.global_type(global_index);
+        let size = value_size(ty);
+        let sp = il.reg(addr_size, WebAssemblyRegister::StackPointer);
+        let base = il.const_int(addr_size, self.module_data.memory_base);
+        if size < addr_size {
+            push!(il, size, il.low_part(size, il.sub(addr_size, sp, base)));
+        } else {
+            push!(il, size, il.sub(addr_size, sp, base));
+        }
+    }
+
+    fn lift_stack_pointer_set(&self, global_index: u32) {
+        let il = self.il;
+        let addr_size = self.module_data.address_size;
+        let ty = self.global_type(global_index);
+        let size = value_size(ty);
+        self.pop_operands(&[ty]);
+        let value = il.reg(size, temp(0));
+        let base = il.const_int(addr_size, self.module_data.memory_base);
+        let sp = WebAssemblyRegister::StackPointer;
+        if size < addr_size {
+            il.set_reg(
+                addr_size,
+                sp,
+                il.add(addr_size, il.zx(addr_size, value), base),
+            )
+            .append();
+        } else {
+            il.set_reg(addr_size, sp, il.add(addr_size, value, base))
+                .append();
+        }
+    }
+
+    // The size of the operand of `drop` or `select`, which depends on the stack.
+    fn operand_size(&self) -> usize {
+        self.layout
+            .and_then(|layout| layout.operand_types.get(&self.addr))
+            .map_or(SLOT_SIZE, |ty| value_size(*ty))
+    }
+
+    fn lift_select(&self, size: usize) {
+        let il = self.il;
+        for i in (0..3).rev() {
+            let size = if i == 2 { 4 } else { size };
+            il.set_reg(size, temp(i), il.reg_stack_pop(size, OperandStack))
+                .append();
+        }
+        let mut done = LowLevelILLabel::new();
+        self.branch_if(
+            |t, f| {
+                il.if_expr(il.cmp_ne(4, il.reg(4, temp(2)), il.const_int(4, 0)), t, f)
+                    .append()
+            },
+            || {
+                push!(il, size, il.reg(size, temp(0)));
+                il.goto(&mut done).append();
+            },
+            None,
+        );
+        push!(il, size, il.reg(size, temp(1)));
+        il.mark_label(&mut done);
+    }
+
+    // Sets temporary `index`, which holds an address of type `ty`, to where the address
+    // plus the offset of `memarg` is in the view, and returns its size. Only memory 0
+    // is mapped into the view, so addresses into other memories are left as they are.
+    fn view_address(&self, index: u32, ty: ValType, memarg: &MemArg) -> usize {
+        let il = self.il;
+        let size = value_size(ty);
+        if memarg.memory != 0 {
+            return size;
+        }
+        let addr_size = self.module_data.address_size;
+        let base = self.module_data.memory_base.wrapping_add(memarg.offset);
+        let offset = il.const_int(addr_size, base);
+        let addr = temp(index);
+        match size.cmp(&addr_size) {
+            Ordering::Less => il
+                .set_reg(
+                    addr_size,
+                    addr,
+                    il.add(addr_size, il.zx(addr_size, il.reg(size, addr)), offset),
+                )
+                .append(),
+            Ordering::Greater => il
+                .set_reg(
+                    addr_size,
+                    addr,
+                    il.add(
+                        addr_size,
+                        il.low_part(addr_size, il.reg(size, addr)),
+                        offset,
+                    ),
+                )
+                .append(),
+            Ordering::Equal => il
+                .set_reg(
+                    addr_size,
+                    addr,
+                    il.add(addr_size, il.reg(size, addr), offset),
+                )
+                .append(),
+        }
+        addr_size
+    }
+
+    // Lifts a load or store of `width` bytes, whose operands have been popped. Narrow
+    // loads extend the value to the width of the result, and narrow stores store its
+    // low bytes.
+    fn lift_memory_access(
+        &self,
+        op: &Operator,
+        memarg: &MemArg,
+        width: usize,
+        effect: &StackEffect,
+    ) {
+        let il = self.il;
+        let addr_size = self.view_address(0, effect.pops[0], memarg);
+        let addr = il.reg(addr_size, temp(0));
+        if let Some(result) = effect.pushes.first() {
+            let size = value_size(*result);
+            let signed = matches!(
+                op,
+                Operator::I32Load8S { .. }
+                    | Operator::I32Load16S { .. }
+                    | Operator::I64Load8S { .. }
+                    | Operator::I64Load16S { .. }
+                    | Operator::I64Load32S { .. }
+            );
+            if width == size {
+                push!(il, size, il.load(width, addr));
+            } else if signed {
+                push!(il, size, il.sx(size, il.load(width, addr)));
+            } else {
+                push!(il, size, il.zx(size, il.load(width, addr)));
+            }
+        } else {
+            let size = value_size(effect.pops[1]);
+            let value = il.reg(size, temp(1));
+            if width == size {
+                il.store(width, addr, value).append();
+            } else {
+                il.store(width, addr, il.low_part(width, value)).append();
+            }
+        }
+    }
+
+    // Lifts an instruction as the intrinsic `call` says, with the operands of `types`,
+    // which have been popped, as the inputs after its immediates, and pushes its result.
+    fn lift_intrinsic(&self, call: &IntrinsicCall, types: &[ValType], result: Option<ValType>) {
+        let il = self.il;
+        let mut inputs = Vec::new();
+        for (i, immediate) in call.immediates.iter().enumerate() {
+            let index = IMMEDIATE_TEMPS + i as u32;
+            il.set_reg(4, temp(index), il.const_int(4, *immediate as u64))
+                .append();
+            inputs.push((4, index));
+        }
+        for (i, ty) in types.iter().enumerate() {
+            let size = match (&call.memarg, i) {
+                (Some(memarg), 0) => self.view_address(0, *ty, memarg),
+                _ => value_size(*ty),
+            };
+            inputs.push((size, i as u32));
+        }
+        let outputs: Vec<_> = result.iter().map(|_| temp(RESULT_TEMP)).collect();
+        let inputs = inputs
+            .iter()
+            .map(|(size, index)| il.reg(*size, temp(*index)));
+        il.intrinsic(outputs, call.intrinsic, inputs).append();
+        if let Some(ty) = result {
+            let size = value_size(ty);
+            push!(il, size, il.reg(size, temp(RESULT_TEMP)));
+        }
+    }
+
+    // Lifts an instruction that throws, which doesn't return.
+    fn lift_throw(&self, call: &IntrinsicCall, types: &[ValType]) {
+        self.pop_operands(types);
+        self.lift_intrinsic(call, &[], None);
+        self.il.no_ret().append();
+    }
+
+    // Lifts an instruction that pops its operands and pushes at most one result. Ones
+    // that aren't lifted yet still take their operands off the stack and put unknown
+    // results on it, so that the rest of the stack stays where it is.
+    fn lift_value_op(&self, op: &Operator) {
+        let il = self.il;
+        let Some(effect) = stack_effect(self.module_data, self.func, op) else {
+            il.unimplemented().append();
+            return;
+        };
+        self.pop_operands(&effect.pops);
+        if let Some((memarg, width, _)) = memory_access(op) {
+            self.lift_memory_access(op, &memarg, width, &effect);
+            return;
+        }
+        if let Some(call) = WebAssemblyIntrinsic::for_operator(op) {
+            self.lift_intrinsic(&call, &effect.pops, effect.pushes.first().copied());
+            return;
+        }
+        let Some(result) = effect.pushes.first() else {
+            il.unimplemented().append();
+            return;
+        };
+        let size = value_size(*result);
+        let arg = |i: usize| il.reg(value_size(effect.pops[i]), temp(i as u32));
+        let operand_size = effect.pops.first().map_or(size, |ty| value_size(*ty));
+
+        match *op {
+            // Constants
+            Operator::I32Const { value } => {
+                push!(il, size, il.const_int(size, value as u32 as u64))
+            }
+            Operator::I64Const { value } => push!(il, size, il.const_int(size, value as u64)),
+            Operator::F32Const { value } => {
+                push!(
+                    il,
+                    size,
+                    il.float_const_single(f32::from_bits(value.bits()))
+                )
+            }
+            Operator::F64Const { value } => {
+                push!(
+                    il,
+                    size,
+                    il.float_const_double(f64::from_bits(value.bits()))
+                )
+            }
+
+            // Tests and comparisons, whose results binja types as bools
+            Operator::I32Eqz | Operator::I64Eqz => {
+                let zero = il.const_int(operand_size, 0);
+                push!(il, size, il.cmp_e(operand_size, arg(0), zero))
+            }
+            Operator::I32Eq | Operator::I64Eq => {
+                push!(il, size, il.cmp_e(operand_size, arg(0), arg(1)))
+            }
+            Operator::I32Ne | Operator::I64Ne => {
+                push!(il, size, il.cmp_ne(operand_size, arg(0), arg(1)))
+            }
+            Operator::I32LtS | Operator::I64LtS => {
+                push!(il, size, il.cmp_slt(operand_size, arg(0), arg(1)))
+            }
+            Operator::I32LtU | Operator::I64LtU => {
+                push!(il, size, il.cmp_ult(operand_size, arg(0), arg(1)))
+            }
+            Operator::I32GtS | Operator::I64GtS => {
+                push!(il, size, il.cmp_sgt(operand_size, arg(0), arg(1)))
+            }
+            Operator::I32GtU | Operator::I64GtU => {
+                push!(il, size, il.cmp_ugt(operand_size, arg(0), arg(1)))
+            }
+            Operator::I32LeS | Operator::I64LeS => {
+                push!(il, size, il.cmp_sle(operand_size, arg(0), arg(1)))
+            }
+            Operator::I32LeU | Operator::I64LeU => {
+                push!(il, size, il.cmp_ule(operand_size, arg(0), arg(1)))
+            }
+            Operator::I32GeS | Operator::I64GeS => {
+                push!(il, size, il.cmp_sge(operand_size, arg(0), arg(1)))
+            }
+            Operator::I32GeU | Operator::I64GeU => {
+                push!(il, size, il.cmp_uge(operand_size, arg(0), arg(1)))
+            }
+            Operator::F32Eq | Operator::F64Eq => {
+                push!(il, size, il.fcmp_e(operand_size, arg(0), arg(1)))
+            }
+            Operator::F32Ne | Operator::F64Ne => {
+                push!(il, size, il.fcmp_ne(operand_size, arg(0), arg(1)))
+            }
+            Operator::F32Lt | Operator::F64Lt => {
+                push!(il, size, il.fcmp_lt(operand_size, arg(0), arg(1)))
+            }
+            Operator::F32Gt | Operator::F64Gt => {
+                push!(il, size, il.fcmp_gt(operand_size, arg(0), arg(1)))
+            }
+            Operator::F32Le | Operator::F64Le => {
+                push!(il, size, il.fcmp_le(operand_size, arg(0), arg(1)))
+            }
+            Operator::F32Ge | Operator::F64Ge => {
+                push!(il, size, il.fcmp_ge(operand_size, arg(0), arg(1)))
+            }
+
+            // Integer arithmetic
+            Operator::I32Add | Operator::I64Add => push!(il, size, il.add(size, arg(0), arg(1))),
+            Operator::I32Sub | Operator::I64Sub => push!(il, size, il.sub(size, arg(0), arg(1))),
+            Operator::I32Mul | Operator::I64Mul => push!(il, size, il.mul(size, arg(0), arg(1))),
+            Operator::I32DivS | Operator::I64DivS => {
+                push!(il, size, il.divs(size, arg(0), arg(1)))
+            }
+            Operator::I32DivU | Operator::I64DivU => {
+                push!(il, size, il.divu(size, arg(0), arg(1)))
+            }
+            Operator::I32RemS | Operator::I64RemS => {
+                push!(il, size, il.mods(size, arg(0), arg(1)))
+            }
+            Operator::I32RemU | Operator::I64RemU => {
+                push!(il, size, il.modu(size, arg(0), arg(1)))
+            }
+            Operator::I32And | Operator::I64And => push!(il, size, il.and(size, arg(0), arg(1))),
+            Operator::I32Or | Operator::I64Or => push!(il, size, il.or(size, arg(0), arg(1))),
+            Operator::I32Xor | Operator::I64Xor => push!(il, size, il.xor(size, arg(0), arg(1))),
+            Operator::I32Shl | Operator::I64Shl => push!(il, size, il.lsl(size, arg(0), arg(1))),
+            Operator::I32ShrS | Operator::I64ShrS => {
+                push!(il, size, il.asr(size, arg(0), arg(1)))
+            }
+            Operator::I32ShrU | Operator::I64ShrU => {
+                push!(il, size, il.lsr(size, arg(0), arg(1)))
+            }
+            Operator::I32Rotl | Operator::I64Rotl => {
+                push!(il, size, il.rol(size, arg(0), arg(1)))
+            }
+            Operator::I32Rotr | Operator::I64Rotr => {
+                push!(il, size, il.ror(size, arg(0), arg(1)))
+            }
+            Operator::I32Extend8S | Operator::I64Extend8S => {
+                push!(il, size, il.sx(size, il.low_part(1, arg(0))))
+            }
+            Operator::I32Extend16S | Operator::I64Extend16S => {
+                push!(il, size, il.sx(size, il.low_part(2, arg(0))))
+            }
+            Operator::I64Extend32S => push!(il, size, il.sx(size, il.low_part(4, arg(0)))),
+
+            // Float arithmetic
+            Operator::F32Add | Operator::F64Add => push!(il, size, il.fadd(size, arg(0), arg(1))),
+            Operator::F32Sub | Operator::F64Sub => push!(il, size, il.fsub(size, arg(0), arg(1))),
+            Operator::F32Mul | Operator::F64Mul => push!(il, size, il.fmul(size, arg(0), arg(1))),
+            Operator::F32Div | Operator::F64Div => push!(il, size, il.fdiv(size, arg(0), arg(1))),
+            Operator::F32Abs | Operator::F64Abs => push!(il, size, il.fabs(size, arg(0))),
+            Operator::F32Neg | Operator::F64Neg => push!(il, size, il.fneg(size, arg(0))),
+            Operator::F32Sqrt | Operator::F64Sqrt => push!(il, size, il.fsqrt(size, arg(0))),
+            Operator::F32Ceil | Operator::F64Ceil => push!(il, size, il.ceil(size, arg(0))),
+            Operator::F32Floor | Operator::F64Floor => push!(il, size, il.floor(size, arg(0))),
+            Operator::F32Trunc | Operator::F64Trunc => push!(il, size, il.ftrunc(size, arg(0))),
+            Operator::F32Nearest | Operator::F64Nearest => {
+                push!(il, size, il.round_to_int(size, arg(0)))
+            }
+
+            // Conversions
+            Operator::I32WrapI64 => push!(il, size, il.low_part(size, arg(0))),
+            Operator::I64ExtendI32S => push!(il, size, il.sx(size, arg(0))),
+            Operator::I64ExtendI32U => push!(il, size, il.zx(size, arg(0))),
+            Operator::I32TruncF32S
+            | Operator::I32TruncF32U
+            | Operator::I32TruncF64S
+            | Operator::I32TruncF64U
+            | Operator::I64TruncF32S
+            | Operator::I64TruncF32U
+            | Operator::I64TruncF64S
+            | Operator::I64TruncF64U => push!(il, size, il.float_to_int(size, arg(0))),
+            Operator::F32ConvertI32S
+            | Operator::F32ConvertI32U
+            | Operator::F32ConvertI64S
+            | Operator::F32ConvertI64U
+            | Operator::F64ConvertI32S
+            | Operator::F64ConvertI32U
+            | Operator::F64ConvertI64S
+            | Operator::F64ConvertI64U => push!(il, size, il.int_to_float(size, arg(0))),
+            Operator::F32DemoteF64 | Operator::F64PromoteF32 => {
+                push!(il, size, il.float_conv(size, arg(0)))
+            }
+            // The bits stay the same, only how they're read changes.
+            Operator::I32ReinterpretF32
+            | Operator::I64ReinterpretF64
+            | Operator::F32ReinterpretI32
+            | Operator::F64ReinterpretI64 => push!(il, size, arg(0)),
+
+            _ => self.push_unknown(&effect.pushes),
+        }
+    }
+}
+
+impl WebAssemblyArchitecture {
+    pub(crate) fn _instruction_llil(
+        &self,
+        data: &[u8],
+        addr: u64,
+        il: &LowLevelILMutableFunction,
+    ) -> Option<(usize, bool)> {
+        let module_data_lock = MODULE_DATA.lock().unwrap();
+        let Some(module_data) = module_data_lock.as_ref() else {
+            let op = parse_operator(data)?;
+            il.unimplemented().append();
+            return Some((op.size, true));
+        };
+        let func = module_data.funcs.get(&addr).map(|func| func.as_ref());
+        let mut lifter = Lifter {
+            il,
+            module_data,
+            func,
+            layout: None,
+            addr,
+        };
+        if let Some(func) = func {
+            // The function header is shown as instructions, but does nothing.
+            if addr == func.size_start {
+                il.nop().append();
+                return Some(((func.locals_start - func.size_start) as usize, true));
+            } else if addr == func.locals_start {
+                il.nop().append();
+                return Some(((func.ops_start - func.locals_start) as usize, true));
+            } else if let Some(op) = func.unpatched_op(addr, data) {
+                let layout = func
+                    .stack_layout
+                    .get_or_init(|| stack_layout(module_data, func));
+                lifter.layout = Some(layout);
+                lifter.lift(op);
+                return Some((op.size, true));
+            }
+        }
+
+        let op = parse_operator(data)?;
+        lifter.lift(&op);
+        Some((op.size, true))
+    }
+}
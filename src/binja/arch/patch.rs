@@ -0,0 +1,344 @@
+use crate::binja::arch::stack_effect::stack_effect;
+use crate::binja::arch::WebAssemblyArchitecture;
+use crate::binja::parse::func_parse::parse_operator;
+use crate::binja::parse::module_data::{BranchTargetAddr, FunctionData, ModuleData, MODULE_DATA};
+use crate::binja::parse::stack_state::label_arity;
+use log::warn;
+use wasm_encoder::reencode::{Reencode, RoundtripReencoder};
+use wasm_encoder::Encode;
+use wasmparser::{BlockType, FuncType, Operator, ValType};
+
+const NOP: u8 = 0x01;
+const BLOCK: u8 = 0x02;
+const IF: u8 = 0x04;
+const BR: u8 = 0x0c;
+const BR_IF: u8 = 0x0d;
+const DROP: u8 = 0x1a;
+const I32_CONST: u8 = 0x41;
+const I64_CONST: u8 = 0x42;
+const I32_EQZ: u8 = 0x45;
+
+// The most bytes that the LEB128 of an `i32.const` and an `i64.const` can take.
+const MAX_I32_LEN: usize = 5;
+const MAX_I64_LEN: usize = 10;
+
+// Patches are made in place, so the replacement must fit in the bytes of the
+// instruction it replaces, and is padded out with `nop`s. Usually this leaves room for
+// little more than the original instruction, so a patch that adds an instruction
+// shrinks an immediate into fewer bytes, where it was encoded with more than needed
+// (as linkers do for relocated indices), or else isn't possible.
+//
+// A `br_if` whose target takes no values along always branches as a `br` with the same
+// depth. An `if` always runs its body as a `drop` and a `block`, and is inverted by an
+// `i32.eqz` before it, which only fit if its block type is a padded type index. An `if`
+// with an `else` can't always run its body, as the `else` would be left outside of an
+// `if`.
+
+// Pads `bytes` out to `len` with `nop`s, if they fit.
+fn fill(mut bytes: Vec<u8>, len: usize) -> Option<Vec<u8>> {
+    if bytes.len() > len {
+        return None;
+    }
+    bytes.resize(len, NOP);
+    Some(bytes)
+}
+
+// The low `7 * len` bits of `value` as a LEB128 of `len` bytes, with continuation bits
+// on all but the last, even where the rest is only padding.
+fn leb128(value: i64, len: usize) -> Vec<u8> {
+    let group = |i: usize| (value >> (7 * i)) as u8 & 0x7f;
+    let mut bytes = (0..len).map(|i| group(i) | 0x80).collect::<Vec<_>>();
+    if let Some(last) = bytes.last_mut() {
+        *last &= 0x7f;
+    }
+    bytes
+}
+
+// `value` as an unsigned LEB128 of exactly `len` bytes, if it fits.
+fn padded_unsigned(value: u32, len: usize) -> Option<Vec<u8>> {
+    if !(1..=5).contains(&len) || (len < 5 && value >> (7 * len) != 0) {
+        return None;
+    }
+    Some(leb128(value as i64, len))
+}
+
+// `value` as a signed LEB128 of exactly `len` bytes, and no more than `max_len`, if it
+// fits. The sign is carried into the bytes that only pad it out.
+fn padded_signed(value: i64, len: usize, max_len: usize) -> Option<Vec<u8>> {
+    if !(1..=max_len).contains(&len) {
+        return None;
+    }
+    let rest = value >> (7 * len - 1).min(63);
+    if rest != 0 && rest != -1 {
+        return None;
+    }
+    Some(leb128(value, len))
+}
+
+// The block type `blockty` encoded in exactly `len` bytes: as the empty type or a value
+// type, or else as the index of a function type with the same params and results,
+// padded out.
+fn block_type_bytes(module_data: &ModuleData, blockty: &BlockType, len: usize) -> Option<Vec<u8>> {
+    let (params, results): (&[ValType], &[ValType]) = match blockty {
+        BlockType::Empty => (&[], &[]),
+        BlockType::Type(ty) => (&[], std::slice::from_ref(ty)),
+        BlockType::FuncType(type_index) => {
+            let ty = module_data.types.get(*type_index as usize)?;
+            (ty.params(), ty.results())
+        }
+    };
+    if len == 1 && params.is_empty() {
+        let short = match results {
+            [] => Some(wasm_encoder::BlockType::Empty),
+            [ty] => RoundtripReencoder
+                .val_type(*ty)
+                .ok()
+                .map(wasm_encoder::BlockType::Result),
+            _ => None,
+        };
+        if let Some(short) = short {
+            let mut bytes = Vec::new();
+            short.encode(&mut bytes);
+            if bytes.len() == 1 {
+                return Some(bytes);
+            }
+        }
+    }
+    let type_index = match blockty {
+        BlockType::FuncType(type_index) => *type_index as usize,
+        _ => module_data
+            .types
+            .iter()
+            .position(|ty| ty.params() == params && ty.results() == results)?,
+    };
+    padded_signed(type_index as i64, len, 5)
+}
+
+// Whether the `if` at `addr` has an `else`, which is right before where it goes when
+// the condition is false.
+fn has_else(func: &FunctionData, addr: u64) -> bool {
+    let Some(BranchTargetAddr::Conditional { false_target, .. }) =
+        func.ops.get(&addr).and_then(|op| op.target.as_ref())
+    else {
+        return false;
+    };
+    matches!(
+        func.ops.range(..*false_target).next_back(),
+        Some((_, prev)) if matches!(prev.op, Operator::Else)
+    )
+}
+
+impl WebAssemblyArchitecture {
+    // Assembles `code` and pads it to the length of `data`, if it fits.
+    fn replacement(&self, data: &[u8], addr: u64, code: &str) -> Option<Vec<u8>> {
+        let op = parse_operator(data)?;
+        fill(self._assemble(code, addr).ok()?, op.size)
+    }
+
+    fn replace(&self, data: &mut [u8], addr: u64, code: &str) -> bool {
+        match self.replacement(data, addr, code) {
+            Some(bytes) => Self::write(data, &bytes),
+            None => false,
+        }
+    }
+
+    fn write(data: &mut [u8], bytes: &[u8]) -> bool {
+        data[..bytes.len()].copy_from_slice(bytes);
+        true
+    }
+
+    // The replacement that makes a `br_if` always branch, or an `if` always run its
+    // body.
+    fn always_branch_bytes(data: &[u8], addr: u64) -> Option<Vec<u8>> {
+        let op = parse_operator(data)?;
+        let module_data_lock = MODULE_DATA.lock().unwrap();
+        let module_data = module_data_lock.as_ref()?;
+        let func = module_data.funcs.get(&addr)?;
+        let func = func.as_ref();
+        func.unpatched_op(addr, data)?;
+        match op.op {
+            Operator::BrIf { relative_depth } => {
+                // A `br` to a block that takes nothing along discards the condition
+                // with the rest of the stack.
+                if label_arity(module_data, func, addr, relative_depth)? == 0 {
+                    return Some([&[BR], &data[1..op.size]].concat());
+                }
+                let depth = padded_unsigned(relative_depth, op.size.checked_sub(2)?)?;
+                Some([&[DROP, BR], depth.as_slice()].concat())
+            }
+            Operator::If { blockty } if !has_else(func, addr) => {
+                let blockty = block_type_bytes(module_data, &blockty, op.size.checked_sub(2)?)?;
+                Some([&[DROP, BLOCK], blockty.as_slice()].concat())
+            }
+            _ => None,
+        }
+    }
+
+    // The replacement that makes a `br_if` branch, or an `if` run its body, on the
+    // opposite condition.
+    fn invert_branch_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        let op = parse_operator(data)?;
+        match op.op {
+            Operator::BrIf { relative_depth } => {
+                let depth = padded_unsigned(relative_depth, op.size.checked_sub(2)?)?;
+                Some([&[I32_EQZ, BR_IF], depth.as_slice()].concat())
+            }
+            Operator::If { blockty } => {
+                let module_data_lock = MODULE_DATA.lock().unwrap();
+                let module_data = module_data_lock.as_ref()?;
+                let blockty = block_type_bytes(module_data, &blockty, op.size.checked_sub(2)?)?;
+                Some([&[I32_EQZ, IF], blockty.as_slice()].concat())
+            }
+            _ => None,
+        }
+    }
+
+    // The replacement that makes a `br_if` never branch, by dropping its condition.
+    fn never_branch_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        let op = parse_operator(data)?;
+        match op.op {
+            Operator::BrIf { .. } => fill(vec![DROP], op.size),
+            _ => None,
+        }
+    }
+
+    // The replacement that removes an instruction while keeping the operand stack as
+    // it would have been: its operands are dropped and its results replaced by zeros.
+    // When the results are the bottom operands (e.g. `i32.add`), dropping the operands
+    // above them is enough.
+    fn nop_code(data: &[u8], addr: u64) -> Result<String, &'static str> {
+        let op = parse_operator(data).ok_or("it couldn't be decoded")?.op;
+        if let Operator::BrIf { .. } = op {
+            return Ok("drop".into());
+        }
+
+        let module_data_lock = MODULE_DATA.lock().unwrap();
+        let module_data = module_data_lock.as_ref().ok_or("no module is loaded")?;
+        let func = module_data.funcs.get(&addr).map(|func| func.as_ref());
+        let effect = stack_effect(module_data, func, &op)
+            .ok_or("it changes control flow or its operand types aren't known")?;
+
+        let mut code = Vec::new();
+        if effect.pops.starts_with(&effect.pushes) {
+            code.resize(effect.pops.len() - effect.pushes.len(), "drop".to_string());
+        } else {
+            code.resize(effect.pops.len(), "drop".to_string());
+            for ty in &effect.pushes {
+                code.push(match ty {
+                    ValType::I32 => "i32.const 0".to_string(),
+                    ValType::I64 => "i64.const 0".to_string(),
+                    ValType::F32 => "f32.const 0".to_string(),
+                    ValType::F64 => "f64.const 0".to_string(),
+                    _ => return Err("one of its results has no constant"),
+                });
+            }
+        }
+        Ok(code.join("\n"))
+    }
+
+    // The replacement that skips a call and returns `value` in its place, dropping its
+    // arguments (and the table index of a `call_indirect`). Only calls that return
+    // nothing or a single integer can be skipped. The constant is padded out to the
+    // rest of the call, so that there is room for it wherever the call's index was
+    // encoded with more bytes than the drops.
+    fn skip_and_return_bytes(data: &[u8], value: Option<u64>) -> Option<Vec<u8>> {
+        let op = parse_operator(data)?;
+        let module_data_lock = MODULE_DATA.lock().unwrap();
+        let module_data = module_data_lock.as_ref()?;
+        let (ty, n_operands): (&FuncType, usize) = match op.op {
+            Operator::Call { function_index } => {
+                let ty = module_data.func_type(function_index)?;
+                (ty, ty.params().len())
+            }
+            Operator::CallIndirect { type_index, .. } => {
+                let ty = module_data.types.get(type_index as usize)?;
+                (ty, ty.params().len() + 1)
+            }
+            _ => return None,
+        };
+
+        let mut bytes = vec![DROP; n_operands];
+        let room = op.size.checked_sub(n_operands + 1);
+        match (ty.results(), value) {
+            ([], None) => {}
+            ([ValType::I32], Some(value)) => {
+                bytes.push(I32_CONST);
+                let len = room?.min(MAX_I32_LEN);
+                bytes.extend(padded_signed(value as i32 as i64, len, MAX_I32_LEN)?);
+            }
+            ([ValType::I64], Some(value)) => {
+                bytes.push(I64_CONST);
+                let len = room?.min(MAX_I64_LEN);
+                bytes.extend(padded_signed(value as i64, len, MAX_I64_LEN)?);
+            }
+            _ => return None,
+        }
+        fill(bytes, op.size)
+    }
+
+    // Calls that return nothing are skipped without a value to return.
+    fn skip_and_return_zero_bytes(data: &[u8]) -> Option<Vec<u8>> {
+        Self::skip_and_return_bytes(data, Some(0))
+            .or_else(|| Self::skip_and_return_bytes(data, None))
+    }
+
+    pub(crate) fn _is_never_branch_patch_available(&self, data: &[u8], _addr: u64) -> bool {
+        Self::never_branch_bytes(data).is_some()
+    }
+
+    pub(crate) fn _is_always_branch_patch_available(&self, data: &[u8], addr: u64) -> bool {
+        Self::always_branch_bytes(data, addr).is_some()
+    }
+
+    pub(crate) fn _is_invert_branch_patch_available(&self, data: &[u8], _addr: u64) -> bool {
+        Self::invert_branch_bytes(data).is_some()
+    }
+
+    pub(crate) fn _is_skip_and_return_zero_patch_available(&self, data: &[u8], _addr: u64) -> bool {
+        Self::skip_and_return_zero_bytes(data).is_some()
+    }
+
+    pub(crate) fn _is_skip_and_return_value_patch_available(
+        &self,
+        data: &[u8],
+        _addr: u64,
+    ) -> bool {
+        Self::skip_and_return_bytes(data, Some(0)).is_some()
+    }
+
+    // Refuses, rather than leaving the operand stack unbalanced, if the instruction
+    // can't be removed.
+    pub(crate) fn _convert_to_nop(&self, data: &mut [u8], addr: u64) -> bool {
+        let code = match Self::nop_code(data, addr) {
+            Ok(code) => code,
+            Err(reason) => {
+                warn!("Can't convert the instruction at {addr:#x} to a nop: {reason}");
+                return false;
+            }
+        };
+        if !self.replace(data, addr, &code) {
+            warn!(
+                "Can't convert the instruction at {addr:#x} to a nop: keeping the operand \
+                stack balanced takes more bytes than the instruction has"
+            );
+            return false;
+        }
+        true
+    }
+
+    pub(crate) fn _always_branch(&self, data: &mut [u8], addr: u64) -> bool {
+        Self::always_branch_bytes(data, addr).is_some_and(|bytes| Self::write(data, &bytes))
+    }
+
+    pub(crate) fn _invert_branch(&self, data: &mut [u8], _addr: u64) -> bool {
+        Self::invert_branch_bytes(data).is_some_and(|bytes| Self::write(data, &bytes))
+    }
+
+    pub(crate) fn _skip_and_return_value(&self, data: &mut [u8], _addr: u64, value: u64) -> bool {
+        let bytes = match value {
+            0 => Self::skip_and_return_zero_bytes(data),
+            _ => Self::skip_and_return_bytes(data, Some(value)),
+        };
+        bytes.is_some_and(|bytes| Self::write(data, &bytes))
+    }
+}
@@ -0,0 +1,280 @@
+use binaryninja::architecture::{
+    ImplicitRegisterExtend, Register, RegisterId, RegisterInfo, RegisterStack, RegisterStackId,
+    RegisterStackInfo,
+};
+use std::borrow::Cow;
+use wasmparser::ValType;
+
+// Parameters and declared locals beyond these many have no register, so they can't be
+// lifted. Engines reject functions with more than 1000 parameters.
+pub(crate) const MAX_PARAMS: u32 = 1000;
+pub(crate) const MAX_LOCALS: u32 = 1024;
+
+// Results that are returned in registers. The calling convention has a return register
+// and a high one, so further results of multi-value functions aren't lifted.
+pub(crate) const MAX_RESULTS: u32 = 2;
+
+// Operand stack slots, counted from the bottom of the function's operand stack. They
+// are the storage of the operand stack register stack, so IL refers to them by their
+// position relative to the top of the stack rather than directly.
+pub(crate) const MAX_STACK_SLOTS: u32 = 256;
+
+// Operand stack slots hold any value that is lifted: values are at most 64 bits wide
+// (v128 values aren't lifted), and narrower values are in the low bytes.
+const SLOT_SIZE: usize = 8;
+
+// Addresses are 32 or 64 bits wide, as the module's memory is, and the stack pointer
+// register is wide enough for either. IL uses it at the module's address size.
+const STACK_POINTER_SIZE: usize = 8;
+
+// The width of a parameter or local, which follows from its wasm type, so that each
+// has a register as wide as its values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LocalWidth {
+    Bits32,
+    Bits64,
+}
+
+impl LocalWidth {
+    // v128 locals aren't lifted, so they have no width.
+    pub(crate) fn of(ty: ValType) -> Option<Self> {
+        match ty {
+            ValType::I32 | ValType::F32 | ValType::Ref(_) => Some(Self::Bits32),
+            ValType::I64 | ValType::F64 => Some(Self::Bits64),
+            ValType::V128 => None,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::Bits32 => 4,
+            Self::Bits64 => 8,
+        }
+    }
+}
+
+// The registers wasm code is lifted to. Wasm has no registers, so these model the
+// function's locals, its operand stack, and the shadow stack pointer that compilers
+// keep in a global (`__stack_pointer`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WebAssemblyRegister {
+    // Wasm numbers the parameters and the declared locals together, parameters first.
+    // Here each are numbered from 0, so that `argN` is the Nth parameter of the
+    // function type and `locN` the Nth local declared in the body. The 64-bit
+    // registers are suffixed, e.g. `arg0_64`. Arguments are passed in the `argN`
+    // registers, so the 32-bit ones are the low halves of the 64-bit ones.
+    Arg(u32, LocalWidth),
+    Local(u32, LocalWidth),
+    StackSlot(u32),
+    StackPointer,
+
+    // The index of the top of the operand stack in its slots.
+    StackTop,
+
+    // The registers results are returned in, `retN` and `retN_64`, which are split
+    // into halves like the argument registers.
+    Result(u32, LocalWidth),
+}
+
+// Where each kind of register starts in the register IDs. Parameters and locals have
+// a register of each width, in the order of `WIDTHS`.
+const ARG_IDS: u32 = 0;
+const LOCAL_IDS: u32 = ARG_IDS + 2 * MAX_PARAMS;
+const STACK_SLOT_IDS: u32 = LOCAL_IDS + 2 * MAX_LOCALS;
+const STACK_POINTER_ID: u32 = STACK_SLOT_IDS + MAX_STACK_SLOTS;
+const STACK_TOP_ID: u32 = STACK_POINTER_ID + 1;
+const RESULT_IDS: u32 = STACK_TOP_ID + 1;
+const END_IDS: u32 = RESULT_IDS + 2 * MAX_RESULTS;
+
+const WIDTHS: [LocalWidth; 2] = [LocalWidth::Bits32, LocalWidth::Bits64];
+
+impl WebAssemblyRegister {
+    pub(crate) fn all() -> Vec<Self> {
+        let args = WIDTHS
+            .into_iter()
+            .flat_map(|width| (0..MAX_PARAMS).map(move |index| Self::Arg(index, width)));
+        let locals = WIDTHS
+            .into_iter()
+            .flat_map(|width| (0..MAX_LOCALS).map(move |index| Self::Local(index, width)));
+        let results = WIDTHS
+            .into_iter()
+            .flat_map(|width| (0..MAX_RESULTS).map(move |index| Self::Result(index, width)));
+        args.chain(locals)
+            .chain((0..MAX_STACK_SLOTS).map(Self::StackSlot))
+            .chain([Self::StackPointer, Self::StackTop])
+            .chain(results)
+            .collect()
+    }
+
+    pub(crate) fn from_id(id: RegisterId) -> Option<Self> {
+        let id = id.0;
+        let width = |index: u32, count: u32| WIDTHS[(index / count) as usize];
+        if id < LOCAL_IDS {
+            let index = id - ARG_IDS;
+            Some(Self::Arg(index % MAX_PARAMS, width(index, MAX_PARAMS)))
+        } else if id < STACK_SLOT_IDS {
+            let index = id - LOCAL_IDS;
+            Some(Self::Local(index % MAX_LOCALS, width(index, MAX_LOCALS)))
+        } else if id < STACK_POINTER_ID {
+            Some(Self::StackSlot(id - STACK_SLOT_IDS))
+        } else if id == STACK_POINTER_ID {
+            Some(Self::StackPointer)
+        } else if id == STACK_TOP_ID {
+            Some(Self::StackTop)
+        } else if id < END_IDS {
+            let index = id - RESULT_IDS;
+            Some(Self::Result(index % MAX_RESULTS, width(index, MAX_RESULTS)))
+        } else {
+            None
+        }
+    }
+
+    // The register of a wasm local of type `ty` of a function with `n_params`
+    // parameters.
+    pub(crate) fn for_local(n_params: u32, local_index: u32, ty: ValType) -> Option<Self> {
+        let width = LocalWidth::of(ty)?;
+        if local_index < n_params {
+            (local_index < MAX_PARAMS).then_some(Self::Arg(local_index, width))
+        } else {
+            let index = local_index - n_params;
+            (index < MAX_LOCALS).then_some(Self::Local(index, width))
+        }
+    }
+
+    // The register that the result at `result_index` of type `ty` is returned in.
+    pub(crate) fn for_result(result_index: u32, ty: ValType) -> Option<Self> {
+        let width = LocalWidth::of(ty)?;
+        (result_index < MAX_RESULTS).then_some(Self::Result(result_index, width))
+    }
+
+    // The register that a 32-bit argument or result register is the low half of.
+    fn parent(&self) -> Option<Self> {
+        match self {
+            Self::Arg(index, LocalWidth::Bits32) => Some(Self::Arg(*index, LocalWidth::Bits64)),
+            Self::Result(index, LocalWidth::Bits32) => {
+                Some(Self::Result(*index, LocalWidth::Bits64))
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_full_width(&self) -> bool {
+        self.parent().is_none()
+    }
+}
+
+impl Register for WebAssemblyRegister {
+    type InfoType = WebAssemblyRegisterInfo;
+
+    fn name(&self) -> Cow<'_, str> {
+        let suffix = |width: &LocalWidth| match width {
+            LocalWidth::Bits32 => "",
+            LocalWidth::Bits64 => "_64",
+        };
+        match self {
+            Self::Arg(index, width) => format!("arg{index}{}", suffix(width)).into(),
+            Self::Local(index, width) => format!("loc{index}{}", suffix(width)).into(),
+            Self::StackSlot(index) => format!("stack{index}").into(),
+            Self::StackPointer => "sp".into(),
+            Self::StackTop => "top".into(),
+            Self::Result(index, width) => format!("ret{index}{}", suffix(width)).into(),
+        }
+    }
+
+    fn info(&self) -> Self::InfoType {
+        let size = match self {
+            Self::Arg(_, width) | Self::Local(_, width) | Self::Result(_, width) => width.size(),
+            Self::StackSlot(_) => SLOT_SIZE,
+            Self::StackPointer => STACK_POINTER_SIZE,
+            Self::StackTop => 4,
+        };
+        WebAssemblyRegisterInfo {
+            size,
+            parent: self.parent(),
+        }
+    }
+
+    fn id(&self) -> RegisterId {
+        RegisterId(match self {
+            Self::Arg(index, width) => ARG_IDS + *width as u32 * MAX_PARAMS + index,
+            Self::Local(index, width) => LOCAL_IDS + *width as u32 * MAX_LOCALS + index,
+            Self::StackSlot(index) => STACK_SLOT_IDS + index,
+            Self::StackPointer => STACK_POINTER_ID,
+            Self::StackTop => STACK_TOP_ID,
+            Self::Result(index, width) => RESULT_IDS + *width as u32 * MAX_RESULTS + index,
+        })
+    }
+}
+
+// Registers are full-width registers of their own, except for the 32-bit argument and
+// result registers, which are zero-extended into the 64-bit ones when written.
+pub struct WebAssemblyRegisterInfo {
+    size: usize,
+    parent: Option<WebAssemblyRegister>,
+}
+
+impl RegisterInfo for WebAssemblyRegisterInfo {
+    type RegType = WebAssemblyRegister;
+
+    fn parent(&self) -> Option<Self::RegType> {
+        self.parent
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn offset(&self) -> usize {
+        0
+    }
+
+    fn implicit_extend(&self) -> ImplicitRegisterExtend {
+        match self.parent {
+            Some(_) => ImplicitRegisterExtend::ZeroExtendToFullWidth,
+            None => ImplicitRegisterExtend::NoExtend,
+        }
+    }
+}
+
+// The operand stack, as a register stack like x87's, so that the pushes and pops of
+// lifted instructions are resolved to slots and optimized away by the decompiler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OperandStack;
+
+impl RegisterStack for OperandStack {
+    type InfoType = OperandStackInfo;
+    type RegType = WebAssemblyRegister;
+    type RegInfoType = WebAssemblyRegisterInfo;
+
+    fn name(&self) -> Cow<'_, str> {
+        "opstack".into()
+    }
+
+    fn info(&self) -> Self::InfoType {
+        OperandStackInfo
+    }
+
+    fn id(&self) -> RegisterStackId {
+        RegisterStackId(0)
+    }
+}
+
+pub struct OperandStackInfo;
+
+impl RegisterStackInfo for OperandStackInfo {
+    type RegStackType = OperandStack;
+    type RegType = WebAssemblyRegister;
+    type RegInfoType = WebAssemblyRegisterInfo;
+
+    fn storage_regs(&self) -> (Self::RegType, usize) {
+        (WebAssemblyRegister::StackSlot(0), MAX_STACK_SLOTS as usize)
+    }
+
+    fn top_relative_regs(&self) -> Option<(Self::RegType, usize)> {
+        None
+    }
+
+    fn stack_top_reg(&self) -> Self::RegType {
+        WebAssemblyRegister::StackTop
+    }
+}
@@ -0,0 +1,141 @@
+use crate::binja::parse::module_data::MODULE_DATA;
+use binaryninja::architecture::CoreArchitecture;
+use binaryninja::binary_view::BinaryView;
+use binaryninja::relocation::{
+    CoreRelocationHandler, CustomRelocationHandlerHandle, Relocation, RelocationHandler,
+    RelocationInfo, RelocationType as CoreRelocationType,
+};
+use wasmparser::RelocationType;
+
+// How the value of a relocation is encoded in place of its placeholder.
+enum Encoding {
+    // A varuint padded to the full width of the placeholder, as LLVM emits it so that
+    // the linker can rewrite it without moving code.
+    PaddedUleb,
+    PaddedSleb,
+    Fixed,
+}
+
+fn encoding(ty: RelocationType) -> Encoding {
+    use RelocationType::*;
+    match ty {
+        FunctionIndexLeb | MemoryAddrLeb | MemoryAddrLeb64 | TypeIndexLeb | GlobalIndexLeb
+        | EventIndexLeb | TableNumberLeb => Encoding::PaddedUleb,
+        TableIndexSleb | TableIndexSleb64 | TableIndexRelSleb | TableIndexRelSleb64
+        | MemoryAddrSleb | MemoryAddrSleb64 | MemoryAddrRelSleb | MemoryAddrRelSleb64
+        | MemoryAddrTlsSleb | MemoryAddrTlsSleb64 => Encoding::PaddedSleb,
+        _ => Encoding::Fixed,
+    }
+}
+
+// Describes a relocation of the given type that patches the bytes at `addr`.
+pub(crate) fn relocation_info(ty: RelocationType, addr: u64) -> RelocationInfo {
+    let mut info = RelocationInfo::new();
+    info.type_ = CoreRelocationType::StandardRelocationType;
+    info.native_type = ty as u64;
+    info.size = ty.extent();
+    info.has_sign = matches!(encoding(ty), Encoding::PaddedSleb);
+    info.address = addr;
+    info
+}
+
+fn write_padded_leb(dest: &mut [u8], value: u64, signed: bool) {
+    let mut value = value;
+    let n_bytes = dest.len();
+    for (i, byte) in dest.iter_mut().enumerate() {
+        *byte = (value & 0x7f) as u8;
+        if i + 1 < n_bytes {
+            *byte |= 0x80;
+        }
+        value = if signed {
+            ((value as i64) >> 7) as u64
+        } else {
+            value >> 7
+        };
+    }
+}
+
+// Applies the relocations of wasm object files (the `reloc.*` custom sections), so
+// that placeholders read as the indices and addresses that the linker would write.
+pub struct WebAssemblyRelocationHandler {
+    handle: CustomRelocationHandlerHandle<Self>,
+    core_handler: CoreRelocationHandler,
+}
+
+impl WebAssemblyRelocationHandler {
+    pub fn new(
+        handle: CustomRelocationHandlerHandle<Self>,
+        core_handler: CoreRelocationHandler,
+    ) -> Self {
+        Self {
+            handle,
+            core_handler,
+        }
+    }
+}
+
+impl AsRef<CoreRelocationHandler> for WebAssemblyRelocationHandler {
+    fn as_ref(&self) -> &CoreRelocationHandler {
+        &self.core_handler
+    }
+}
+
+impl RelocationHandler for WebAssemblyRelocationHandler {
+    type Handle = CustomRelocationHandlerHandle<Self>;
+
+    fn get_relocation_info(
+        &self,
+        _bv: &BinaryView,
+        _arch: &CoreArchitecture,
+        info: &mut [RelocationInfo],
+    ) -> bool {
+        for info in info {
+            let Ok(ty) = RelocationType::try_from(info.native_type as u8) else {
+                return false;
+            };
+            *info = relocation_info(ty, info.address);
+        }
+        true
+    }
+
+    fn apply_relocation(
+        &self,
+        _bv: &BinaryView,
+        _arch: &CoreArchitecture,
+        reloc: &Relocation,
+        dest: &mut [u8],
+    ) -> bool {
+        // Binja may read the view while the module is still being parsed, in which
+        // case the placeholder is left as it is.
+        let Ok(module_data_lock) = MODULE_DATA.try_lock() else {
+            return false;
+        };
+        let Some(module_data) = module_data_lock.as_ref() else {
+            return false;
+        };
+        let Some(data) = module_data.relocations.get(&reloc.address()) else {
+            return false;
+        };
+        // Values that are only known after linking keep their placeholders.
+        let Some(value) = module_data.relocation_value(data) else {
+            return true;
+        };
+
+        let Some(dest) = dest.get_mut(..data.ty.extent()) else {
+            return false;
+        };
+        match encoding(data.ty) {
+            Encoding::PaddedUleb => write_padded_leb(dest, value, false),
+            Encoding::PaddedSleb => write_padded_leb(dest, value, true),
+            Encoding::Fixed => {
+                let n_bytes = dest.len();
+                dest.copy_from_slice(&value.to_le_bytes()[..n_bytes]);
+            }
+        }
+        true
+    }
+
+    fn handle(&self) -> Self::Handle {
+        self.handle
+    }
+}
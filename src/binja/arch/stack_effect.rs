@@ -0,0 +1,383 @@
+use crate::binja::arch::intrinsic::{atomic_rmw, AtomicRmwOp};
+use crate::binja::parse::module_data::{FunctionData, ModuleData};
+use wasmparser::{Operator, ValType};
+
+// The operands an instruction pops from the operand stack and the values it pushes,
+// both bottom first.
+#[derive(Debug)]
+pub(crate) struct StackEffect {
+    pub pops: Vec<ValType>,
+    pub pushes: Vec<ValType>,
+}
+
+impl StackEffect {
+    fn new(pops: &[ValType], pushes: &[ValType]) -> Self {
+        Self {
+            pops: pops.to_vec(),
+            pushes: pushes.to_vec(),
+        }
+    }
+}
+
+// The type of addresses into a memory, which is i64 for a 64-bit memory.
+pub(crate) fn address_type(module_data: &ModuleData, memory_index: u32) -> ValType {
+    match module_data.memories.get(memory_index as usize) {
+        Some(memory) if memory.ty.memory64 => ValType::I64,
+        _ => ValType::I32,
+    }
+}
+
+// The type of indices into a table, which is i64 for a 64-bit table, and the type of
+// its elements.
+fn table_types(module_data: &ModuleData, table_index: u32) -> Option<(ValType, ValType)> {
+    let table = module_data.tables.get(table_index as usize)?;
+    let index_ty = if table.ty.table64 {
+        ValType::I64
+    } else {
+        ValType::I32
+    };
+    Some((index_ty, ValType::Ref(table.ty.element_type)))
+}
+
+// The type of a parameter or local of the function.
+pub(crate) fn local_type(
+    module_data: &ModuleData,
+    func: &FunctionData,
+    local_index: u32,
+) -> Option<ValType> {
+    let params = module_data.func_type(func.func_index)?.params();
+    if let Some(ty) = params.get(local_index as usize) {
+        return Some(*ty);
+    }
+    let mut index = params.len() as u64;
+    for (count, ty) in &func.locals {
+        index += *count as u64;
+        if (local_index as u64) < index {
+            return Some(*ty);
+        }
+    }
+    None
+}
+
+// The stack effect of an instruction, if it has a fixed one that is known here.
+// Control instructions and instructions whose operand types depend on the stack
+// (`drop`, untyped `select`) have none. `func` is needed for the types of locals.
+pub(crate) fn stack_effect(
+    module_data: &ModuleData,
+    func: Option<&FunctionData>,
+    op: &Operator,
+) -> Option<StackEffect> {
+    use ValType::{F32, F64, I32, I64};
+
+    let effect = match *op {
+        Operator::Nop => StackEffect::new(&[], &[]),
+        Operator::TypedSelect { ty } => StackEffect::new(&[ty, ty, I32], &[ty]),
+
+        // Calls
+        Operator::Call { function_index } => {
+            let ty = module_data.func_type(function_index)?;
+            StackEffect::new(ty.params(), ty.results())
+        }
+        Operator::CallIndirect { type_index, .. } => {
+            let ty = module_data.types.get(type_index as usize)?;
+            let mut effect = StackEffect::new(ty.params(), ty.results());
+            effect.pops.push(I32);
+            effect
+        }
+
+        // Variables
+        Operator::LocalGet { local_index } => {
+            StackEffect::new(&[], &[local_type(module_data, func?, local_index)?])
+        }
+        Operator::LocalSet { local_index } => {
+            StackEffect::new(&[local_type(module_data, func?, local_index)?], &[])
+        }
+        Operator::LocalTee { local_index } => {
+            let ty = local_type(module_data, func?, local_index)?;
+            StackEffect::new(&[ty], &[ty])
+        }
+        Operator::GlobalGet { global_index } => {
+            let global = module_data.globals.get(global_index as usize)?;
+            StackEffect::new(&[], &[global.ty.content_type])
+        }
+        Operator::GlobalSet { global_index } => {
+            let global = module_data.globals.get(global_index as usize)?;
+            StackEffect::new(&[global.ty.content_type], &[])
+        }
+
+        // Memory
+        Operator::I32Load { memarg }
+        | Operator::I32Load8S { memarg }
+        | Operator::I32Load8U { memarg }
+        | Operator::I32Load16S { memarg }
+        | Operator::I32Load16U { memarg } => {
+            StackEffect::new(&[address_type(module_data, memarg.memory)], &[I32])
+        }
+        Operator::I64Load { memarg }
+        | Operator::I64Load8S { memarg }
+        | Operator::I64Load8U { memarg }
+        | Operator::I64Load16S { memarg }
+        | Operator::I64Load16U { memarg }
+        | Operator::I64Load32S { memarg }
+        | Operator::I64Load32U { memarg } => {
+            StackEffect::new(&[address_type(module_data, memarg.memory)], &[I64])
+        }
+        Operator::F32Load { memarg } => {
+            StackEffect::new(&[address_type(module_data, memarg.memory)], &[F32])
+        }
+        Operator::F64Load { memarg } => {
+            StackEffect::new(&[address_type(module_data, memarg.memory)], &[F64])
+        }
+        Operator::I32Store { memarg }
+        | Operator::I32Store8 { memarg }
+        | Operator::I32Store16 { memarg } => {
+            StackEffect::new(&[address_type(module_data, memarg.memory), I32], &[])
+        }
+        Operator::I64Store { memarg }
+        | Operator::I64Store8 { memarg }
+        | Operator::I64Store16 { memarg }
+        | Operator::I64Store32 { memarg } => {
+            StackEffect::new(&[address_type(module_data, memarg.memory), I64], &[])
+        }
+        Operator::F32Store { memarg } => {
+            StackEffect::new(&[address_type(module_data, memarg.memory), F32], &[])
+        }
+        Operator::F64Store { memarg } => {
+            StackEffect::new(&[address_type(module_data, memarg.memory), F64], &[])
+        }
+        Operator::MemorySize { mem } => StackEffect::new(&[], &[address_type(module_data, mem)]),
+        Operator::MemoryGrow { mem } => {
+            let ty = address_type(module_data, mem);
+            StackEffect::new(&[ty], &[ty])
+        }
+        Operator::MemoryFill { mem } => {
+            let ty = address_type(module_data, mem);
+            StackEffect::new(&[ty, I32, ty], &[])
+        }
+        Operator::MemoryCopy { dst_mem, src_mem } => {
+            let dst_ty = address_type(module_data, dst_mem);
+            let src_ty = address_type(module_data, src_mem);
+            // The length is only 64-bit if both memories are.
+            let len_ty = if dst_ty == I64 && src_ty == I64 {
+                I64
+            } else {
+                I32
+            };
+            StackEffect::new(&[dst_ty, src_ty, len_ty], &[])
+        }
+        Operator::MemoryInit { mem, .. } => {
+            StackEffect::new(&[address_type(module_data, mem), I32, I32], &[])
+        }
+        Operator::DataDrop { .. } => StackEffect::new(&[], &[]),
+
+        // Threads
+        Operator::AtomicFence => StackEffect::new(&[], &[]),
+        Operator::MemoryAtomicNotify { memarg } => {
+            StackEffect::new(&[address_type(module_data, memarg.memory), I32], &[I32])
+        }
+        Operator::MemoryAtomicWait32 { memarg } => StackEffect::new(
+            &[address_type(module_data, memarg.memory), I32, I64],
+            &[I32],
+        ),
+        Operator::MemoryAtomicWait64 { memarg } => StackEffect::new(
+            &[address_type(module_data, memarg.memory), I64, I64],
+            &[I32],
+        ),
+
+        // Tables
+        Operator::TableGet { table } => {
+            let (index_ty, elem_ty) = table_types(module_data, table)?;
+            StackEffect::new(&[index_ty], &[elem_ty])
+        }
+        Operator::TableSet { table } => {
+            let (index_ty, elem_ty) = table_types(module_data, table)?;
+            StackEffect::new(&[index_ty, elem_ty], &[])
+        }
+        Operator::TableSize { table } => {
+            let (index_ty, _) = table_types(module_data, table)?;
+            StackEffect::new(&[], &[index_ty])
+        }
+        Operator::TableGrow { table } => {
+            let (index_ty, elem_ty) = table_types(module_data, table)?;
+            StackEffect::new(&[elem_ty, index_ty], &[index_ty])
+        }
+        Operator::TableFill { table } => {
+            let (index_ty, elem_ty) = table_types(module_data, table)?;
+            StackEffect::new(&[index_ty, elem_ty, index_ty], &[])
+        }
+        Operator::TableCopy {
+            dst_table,
+            src_table,
+        } => {
+            let (dst_ty, _) = table_types(module_data, dst_table)?;
+            let (src_ty, _) = table_types(module_data, src_table)?;
+            // As with memories, the length is only 64-bit if both tables are.
+            let len_ty = if dst_ty == I64 && src_ty == I64 {
+                I64
+            } else {
+                I32
+            };
+            StackEffect::new(&[dst_ty, src_ty, len_ty], &[])
+        }
+        Operator::TableInit { table, .. } => {
+            let (index_ty, _) = table_types(module_data, table)?;
+            StackEffect::new(&[index_ty, I32, I32], &[])
+        }
+        Operator::ElemDrop { .. } => StackEffect::new(&[], &[]),
+
+        // Constants
+        Operator::I32Const { .. } => StackEffect::new(&[], &[I32]),
+        Operator::I64Const { .. } => StackEffect::new(&[], &[I64]),
+        Operator::F32Const { .. } => StackEffect::new(&[], &[F32]),
+        Operator::F64Const { .. } => StackEffect::new(&[], &[F64]),
+
+        // Tests and comparisons
+        Operator::I32Eqz => StackEffect::new(&[I32], &[I32]),
+        Operator::I64Eqz => StackEffect::new(&[I64], &[I32]),
+        Operator::I32Eq
+        | Operator::I32Ne
+        | Operator::I32LtS
+        | Operator::I32LtU
+        | Operator::I32GtS
+        | Operator::I32GtU
+        | Operator::I32LeS
+        | Operator::I32LeU
+        | Operator::I32GeS
+        | Operator::I32GeU => StackEffect::new(&[I32, I32], &[I32]),
+        Operator::I64Eq
+        | Operator::I64Ne
+        | Operator::I64LtS
+        | Operator::I64LtU
+        | Operator::I64GtS
+        | Operator::I64GtU
+        | Operator::I64LeS
+        | Operator::I64LeU
+        | Operator::I64GeS
+        | Operator::I64GeU => StackEffect::new(&[I64, I64], &[I32]),
+        Operator::F32Eq
+        | Operator::F32Ne
+        | Operator::F32Lt
+        | Operator::F32Gt
+        | Operator::F32Le
+        | Operator::F32Ge => StackEffect::new(&[F32, F32], &[I32]),
+        Operator::F64Eq
+        | Operator::F64Ne
+        | Operator::F64Lt
+        | Operator::F64Gt
+        | Operator::F64Le
+        | Operator::F64Ge => StackEffect::new(&[F64, F64], &[I32]),
+
+        // Arithmetic
+        Operator::I32Clz
+        | Operator::I32Ctz
+        | Operator::I32Popcnt
+        | Operator::I32Extend8S
+        | Operator::I32Extend16S => StackEffect::new(&[I32], &[I32]),
+        Operator::I32Add
+        | Operator::I32Sub
+        | Operator::I32Mul
+        | Operator::I32DivS
+        | Operator::I32DivU
+        | Operator::I32RemS
+        | Operator::I32RemU
+        | Operator::I32And
+        | Operator::I32Or
+        | Operator::I32Xor
+        | Operator::I32Shl
+        | Operator::I32ShrS
+        | Operator::I32ShrU
+        | Operator::I32Rotl
+        | Operator::I32Rotr => StackEffect::new(&[I32, I32], &[I32]),
+        Operator::I64Clz
+        | Operator::I64Ctz
+        | Operator::I64Popcnt
+        | Operator::I64Extend8S
+        | Operator::I64Extend16S
+        | Operator::I64Extend32S => StackEffect::new(&[I64], &[I64]),
+        Operator::I64Add
+        | Operator::I64Sub
+        | Operator::I64Mul
+        | Operator::I64DivS
+        | Operator::I64DivU
+        | Operator::I64RemS
+        | Operator::I64RemU
+        | Operator::I64And
+        | Operator::I64Or
+        | Operator::I64Xor
+        | Operator::I64Shl
+        | Operator::I64ShrS
+        | Operator::I64ShrU
+        | Operator::I64Rotl
+        | Operator::I64Rotr => StackEffect::new(&[I64, I64], &[I64]),
+        Operator::F32Abs
+        | Operator::F32Neg
+        | Operator::F32Ceil
+        | Operator::F32Floor
+        | Operator::F32Trunc
+        | Operator::F32Nearest
+        | Operator::F32Sqrt => StackEffect::new(&[F32], &[F32]),
+        Operator::F32Add
+        | Operator::F32Sub
+        | Operator::F32Mul
+        | Operator::F32Div
+        | Operator::F32Min
+        | Operator::F32Max
+        | Operator::F32Copysign => StackEffect::new(&[F32, F32], &[F32]),
+        Operator::F64Abs
+        | Operator::F64Neg
+        | Operator::F64Ceil
+        | Operator::F64Floor
+        | Operator::F64Trunc
+        | Operator::F64Nearest
+        | Operator::F64Sqrt => StackEffect::new(&[F64], &[F64]),
+        Operator::F64Add
+        | Operator::F64Sub
+        | Operator::F64Mul
+        | Operator::F64Div
+        | Operator::F64Min
+        | Operator::F64Max
+        | Operator::F64Copysign => StackEffect::new(&[F64, F64], &[F64]),
+
+        // Conversions
+        Operator::I32WrapI64 => StackEffect::new(&[I64], &[I32]),
+        Operator::I32TruncF32S
+        | Operator::I32TruncF32U
+        | Operator::I32TruncSatF32S
+        | Operator::I32TruncSatF32U
+        | Operator::I32ReinterpretF32 => StackEffect::new(&[F32], &[I32]),
+        Operator::I32TruncF64S
+        | Operator::I32TruncF64U
+        | Operator::I32TruncSatF64S
+        | Operator::I32TruncSatF64U => StackEffect::new(&[F64], &[I32]),
+        Operator::I64ExtendI32S | Operator::I64ExtendI32U => StackEffect::new(&[I32], &[I64]),
+        Operator::I64TruncF32S
+        | Operator::I64TruncF32U
+        | Operator::I64TruncSatF32S
+        | Operator::I64TruncSatF32U => StackEffect::new(&[F32], &[I64]),
+        Operator::I64TruncF64S
+        | Operator::I64TruncF64U
+        | Operator::I64TruncSatF64S
+        | Operator::I64TruncSatF64U
+        | Operator::I64ReinterpretF64 => StackEffect::new(&[F64], &[I64]),
+        Operator::F32ConvertI32S | Operator::F32ConvertI32U | Operator::F32ReinterpretI32 => {
+            StackEffect::new(&[I32], &[F32])
+        }
+        Operator::F32ConvertI64S | Operator::F32ConvertI64U => StackEffect::new(&[I64], &[F32]),
+        Operator::F32DemoteF64 => StackEffect::new(&[F64], &[F32]),
+        Operator::F64ConvertI32S | Operator::F64ConvertI32U => StackEffect::new(&[I32], &[F64]),
+        Operator::F64ConvertI64S | Operator::F64ConvertI64U | Operator::F64ReinterpretI64 => {
+            StackEffect::new(&[I64], &[F64])
+        }
+        Operator::F64PromoteF32 => StackEffect::new(&[F32], &[F64]),
+
+        _ => {
+            let (rmw_op, ty, memarg) = atomic_rmw(op)?;
+            let addr_ty = address_type(module_data, memarg.memory);
+            match rmw_op {
+                AtomicRmwOp::Cmpxchg => StackEffect::new(&[addr_ty, ty, ty], &[ty]),
+                _ => StackEffect::new(&[addr_ty, ty], &[ty]),
+            }
+        }
+    };
+    Some(effect)
+}
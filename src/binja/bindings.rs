@@ -0,0 +1,252 @@
+use crate::binja::parse::module_data::ModuleData;
+use wasmparser::{FuncType, ValType};
+
+// A name as an identifier in JS or Rust: characters other than letters, digits and `_`
+// are replaced with `_`, and a leading digit gets a `_` in front.
+fn identifier(name: &str) -> String {
+    let mut ident = name
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect::<String>();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+// Names for the parameters of a function: its local names, as identifiers.
+fn param_names(module_data: &ModuleData, func_index: u32, ty: &FuncType) -> Vec<String> {
+    (0..ty.params().len() as u32)
+        .map(|local_index| identifier(&module_data.local_name(func_index, local_index)))
+        .collect()
+}
+
+// The signature in WAT, e.g. `(param i32 i32) (result i32)`.
+fn wat_signature(ty: &FuncType) -> String {
+    let params = ty.params().iter().map(|ty| ty.to_string());
+    let results = ty.results().iter().map(|ty| ty.to_string());
+    let mut signature = Vec::new();
+    if !ty.params().is_empty() {
+        signature.push(format!("(param {})", params.collect::<Vec<_>>().join(" ")));
+    }
+    if !ty.results().is_empty() {
+        signature.push(format!(
+            "(result {})",
+            results.collect::<Vec<_>>().join(" ")
+        ));
+    }
+    signature.join(" ")
+}
+
+// The exported functions, by export name, with their types.
+fn exports(module_data: &ModuleData) -> Vec<(u32, &str, &FuncType)> {
+    module_data
+        .func_exports
+        .iter()
+        .filter_map(|(func_index, name)| {
+            Some((
+                *func_index,
+                name.as_str(),
+                module_data.func_type(*func_index)?,
+            ))
+        })
+        .collect()
+}
+
+// Imports other than functions can't be stubbed, since only the names of imported
+// functions are kept; note how many there are for the user to fill in.
+fn other_imports(module_data: &ModuleData) -> Option<String> {
+    let counts = [
+        (
+            "tables",
+            module_data.tables.iter().filter(|t| t.imported).count(),
+        ),
+        (
+            "memories",
+            module_data.memories.iter().filter(|m| m.imported).count(),
+        ),
+        (
+            "globals",
+            module_data.globals.iter().filter(|g| g.imported).count(),
+        ),
+        (
+            "tags",
+            module_data.tags.iter().filter(|t| t.imported).count(),
+        ),
+    ];
+    let counts = counts
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(kind, count)| format!("{count} {kind}"))
+        .collect::<Vec<_>>();
+    (!counts.is_empty()).then(|| {
+        format!(
+            "The module also imports {}, which have to be added by hand.",
+            counts.join(", ")
+        )
+    })
+}
+
+fn js_zero(ty: &ValType) -> &'static str {
+    match ty {
+        ValType::I64 => "0n",
+        ValType::I32 | ValType::F32 | ValType::F64 => "0",
+        _ => "null",
+    }
+}
+
+// A Node.js script that instantiates the module with stubs for its imported functions,
+// which log their calls, and wraps each exported function.
+pub(crate) fn js_bindings(module_data: &ModuleData) -> String {
+    let mut lines = vec![
+        "import { readFile } from \"node:fs/promises\";".to_string(),
+        String::new(),
+        "const imports = {};".to_string(),
+        "function stub(module, name, result) {".to_string(),
+        "  imports[module] ??= {};".to_string(),
+        "  imports[module][name] = (...args) => {".to_string(),
+        "    console.log(`${module}.${name}`, args);".to_string(),
+        "    return result;".to_string(),
+        "  };".to_string(),
+        "}".to_string(),
+        String::new(),
+    ];
+    for (func_index, import) in module_data.func_imports.iter().enumerate() {
+        let Some(ty) = module_data.func_type(func_index as u32) else {
+            continue;
+        };
+        let result = match ty.results() {
+            [] => "undefined".to_string(),
+            [ty] => js_zero(ty).to_string(),
+            results => format!(
+                "[{}]",
+                results.iter().map(js_zero).collect::<Vec<_>>().join(", ")
+            ),
+        };
+        lines.push(format!("// {}", wat_signature(ty)));
+        lines.push(format!(
+            "stub({:?}, {:?}, {result});",
+            import.module, import.name
+        ));
+    }
+    if let Some(note) = other_imports(module_data) {
+        lines.push(format!("// {note}"));
+    }
+    lines.extend([
+        String::new(),
+        "const bytes = await readFile(new URL(\"module.wasm\", import.meta.url));".to_string(),
+        "const { instance } = await WebAssembly.instantiate(bytes, imports);".to_string(),
+    ]);
+    for (func_index, name, ty) in exports(module_data) {
+        let params = param_names(module_data, func_index, ty).join(", ");
+        lines.push(String::new());
+        lines.push(format!("// {}", wat_signature(ty)));
+        lines.push(format!("export function {}({params}) {{", identifier(name)));
+        lines.push(format!("  return instance.exports[{name:?}]({params});"));
+        lines.push("}".to_string());
+    }
+    lines.join("\n") + "\n"
+}
+
+fn rust_type(ty: &ValType) -> Option<&'static str> {
+    match ty {
+        ValType::I32 => Some("i32"),
+        ValType::I64 => Some("i64"),
+        ValType::F32 => Some("f32"),
+        ValType::F64 => Some("f64"),
+        ValType::V128 => Some("V128"),
+        ValType::Ref(_) => None,
+    }
+}
+
+// Rust types for the values of a signature: the type itself for one, or else a tuple.
+fn rust_types(types: &[ValType]) -> Option<String> {
+    let types = types.iter().map(rust_type).collect::<Option<Vec<_>>>()?;
+    Some(match types.as_slice() {
+        [ty] => ty.to_string(),
+        types => format!(
+            "({})",
+            types.iter().map(|ty| format!("{ty},")).collect::<String>()
+        ),
+    })
+}
+
+// A wasmtime program that instantiates the module with stubs for its imported
+// functions, which log their calls, and gets a typed handle to each exported function.
+// Functions that pass references are left out, since their types vary between wasmtime
+// versions.
+pub(crate) fn rust_bindings(module_data: &ModuleData) -> String {
+    let mut lines = vec![
+        "use wasmtime::*;".to_string(),
+        String::new(),
+        "fn main() -> Result<()> {".to_string(),
+        "    let engine = Engine::default();".to_string(),
+        "    let module = Module::from_file(&engine, \"module.wasm\")?;".to_string(),
+        "    let mut linker = Linker::new(&engine);".to_string(),
+    ];
+    for (func_index, import) in module_data.func_imports.iter().enumerate() {
+        let func_index = func_index as u32;
+        let Some(ty) = module_data.func_type(func_index) else {
+            continue;
+        };
+        lines.push(String::new());
+        lines.push(format!("    // {}", wat_signature(ty)));
+        let (Some(_), Some(results)) = (rust_types(ty.params()), rust_types(ty.results())) else {
+            lines.push(format!(
+                "    // {}.{} passes references and isn't stubbed.",
+                import.module, import.name
+            ));
+            continue;
+        };
+        let names = (0..ty.params().len())
+            .map(|i| format!("arg{i}"))
+            .collect::<Vec<_>>();
+        let params = names
+            .iter()
+            .zip(ty.params())
+            .map(|(name, ty)| format!(", {name}: {}", rust_type(ty).unwrap_or_default()))
+            .collect::<String>();
+        lines.push(format!(
+            "    linker.func_wrap({:?}, {:?}, |_caller: Caller<'_, ()>{params}| -> {results} {{",
+            import.module, import.name
+        ));
+        let format = names.iter().map(|_| "{:?}").collect::<Vec<_>>().join(", ");
+        let args = names
+            .iter()
+            .map(|name| format!(", {name}"))
+            .collect::<String>();
+        lines.push(format!(
+            "        println!(\"{}.{}({format})\"{args});",
+            import.module.escape_debug(),
+            import.name.escape_debug()
+        ));
+        lines.push("        Default::default()".to_string());
+        lines.push("    })?;".to_string());
+    }
+    if let Some(note) = other_imports(module_data) {
+        lines.push(format!("    // {note}"));
+    }
+    lines.extend([
+        String::new(),
+        "    let mut store = Store::new(&engine, ());".to_string(),
+        "    let instance = linker.instantiate(&mut store, &module)?;".to_string(),
+    ]);
+    for (_, name, ty) in exports(module_data) {
+        lines.push(String::new());
+        lines.push(format!("    // {}", wat_signature(ty)));
+        let (Some(params), Some(results)) = (rust_types(ty.params()), rust_types(ty.results()))
+        else {
+            lines.push(format!("    // {name} passes references and isn't bound."));
+            continue;
+        };
+        lines.push(format!(
+            "    let {} = instance.get_typed_func::<{params}, {results}>(&mut store, {name:?})?;",
+            identifier(name)
+        ));
+    }
+    lines.extend([String::new(), "    Ok(())".to_string(), "}".to_string()]);
+    lines.join("\n") + "\n"
+}
@@ -0,0 +1,103 @@
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::workflow::call_targets;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use wasmparser::Operator;
+
+#[derive(Serialize)]
+struct Node {
+    index: u32,
+    name: String,
+    address: u64,
+    imported: bool,
+}
+
+// The calls from one function to another: direct calls, or the indirect calls and calls
+// through trampolines that were resolved to the callee.
+#[derive(Serialize)]
+struct Edge {
+    caller: u32,
+    callee: u32,
+    indirect: bool,
+    call_sites: Vec<u64>,
+}
+
+// The functions of the module, by function index, and the calls between them.
+#[derive(Serialize)]
+pub(crate) struct CallGraph {
+    functions: Vec<Node>,
+    calls: Vec<Edge>,
+}
+
+impl CallGraph {
+    pub(crate) fn new(module_data: &ModuleData) -> Self {
+        let functions = module_data
+            .func_addrs
+            .iter()
+            .enumerate()
+            .map(|(func_index, addr)| Node {
+                index: func_index as u32,
+                name: module_data.func_name(func_index as u32),
+                address: *addr,
+                imported: func_index < module_data.func_imports.len(),
+            })
+            .collect();
+
+        let mut calls = BTreeMap::<(u32, u32, bool), Vec<u64>>::new();
+        for (_, func) in module_data.funcs.iter() {
+            let func = func.as_ref();
+            for (addr, op) in func.reachable_ops() {
+                if let Operator::Call { function_index } | Operator::ReturnCall { function_index } =
+                    op.op
+                {
+                    let key = (func.func_index, function_index, false);
+                    calls.entry(key).or_default().push(*addr);
+                }
+            }
+            let sites = module_data
+                .indirect_call_targets
+                .range(func.ops_start..func.end);
+            for (addr, _) in sites {
+                for callee in call_targets(module_data, *addr) {
+                    let key = (func.func_index, callee, true);
+                    calls.entry(key).or_default().push(*addr);
+                }
+            }
+        }
+        let calls = calls
+            .into_iter()
+            .map(|((caller, callee, indirect), call_sites)| Edge {
+                caller,
+                callee,
+                indirect,
+                call_sites,
+            })
+            .collect();
+        Self { functions, calls }
+    }
+
+    pub(crate) fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    // The graph in Graphviz's DOT language. Nodes are named by function index and
+    // labeled with the function names; imported functions are drawn as ellipses, and
+    // indirect calls as dashed edges.
+    pub(crate) fn to_dot(&self) -> String {
+        let mut lines = vec![
+            "digraph calls {".to_string(),
+            "  node [shape=box];".to_string(),
+        ];
+        for node in &self.functions {
+            let label = format!("{}\n({})", node.name, node.index);
+            let shape = if node.imported { ", shape=ellipse" } else { "" };
+            lines.push(format!("  f{} [label={label:?}{shape}];", node.index));
+        }
+        for edge in &self.calls {
+            let style = if edge.indirect { " [style=dashed]" } else { "" };
+            lines.push(format!("  f{} -> f{}{style};", edge.caller, edge.callee));
+        }
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,988 @@
+use crate::binja::arch::instruction_text;
+use crate::binja::bindings::{js_bindings, rust_bindings};
+use crate::binja::call_graph::CallGraph;
+use crate::binja::diff::{diff_modules, parse_other_module, OTHER_MODULE};
+use crate::binja::name_map::{apply_names, current_names, module_with_names, NameMap};
+use crate::binja::parse::embedded::find_embedded_modules;
+use crate::binja::parse::module_data::{CALL_TARGET_OVERRIDES_KEY, MODULE_DATA};
+use crate::binja::parse::stack_state::stack_state;
+use crate::binja::patched::{patched_module, replace_body};
+use crate::binja::report::module_summary;
+use crate::binja::segments::{hexdump, read_segment, segment_file_name, segment_label};
+use crate::binja::settings::VIEW_TYPE_NAME;
+use crate::binja::toolchain::generate_signatures;
+use crate::binja::view::WebAssemblyView;
+use crate::binja::wat::{func_wat, module_wat, range_wat, Names};
+use crate::binja::workflow::call_target_refs;
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+use binaryninja::command::{
+    register_command, register_command_for_address, register_command_for_function,
+    register_command_for_range, AddressCommand, Command, FunctionCommand, RangeCommand,
+};
+use binaryninja::function::Function;
+use binaryninja::interaction::{
+    get_choice_input, get_directory_name_input, get_integer_input, get_open_filename_input,
+    get_save_filename_input, get_text_line_input,
+};
+use binaryninja::metadata::Metadata;
+use binaryninja::rc::Ref;
+use log::{error, info};
+use std::collections::HashMap;
+use std::ops::Range;
+
+fn is_wasm_view(view: &BinaryView) -> bool {
+    view.view_type() == VIEW_TYPE_NAME && MODULE_DATA.lock().unwrap().is_some()
+}
+
+// Saves the fingerprints of the named functions of the module, so that the same
+// functions can be named in stripped modules.
+struct SaveSignatures;
+
+impl Command for SaveSignatures {
+    fn action(&self, _view: &BinaryView) {
+        let Some(path) =
+            get_save_filename_input("Save function signatures", "*.json", "signatures.json")
+        else {
+            return;
+        };
+        let library = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            generate_signatures(module_data)
+        };
+        let result = serde_json::to_vec_pretty(&library)
+            .map_err(|e| e.to_string())
+            .and_then(|data| std::fs::write(&path, data).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => info!(
+                "Saved {} function signatures to {}",
+                library.functions.len(),
+                path.display()
+            ),
+            Err(e) => error!(
+                "Failed to save function signatures to {}: {e}",
+                path.display()
+            ),
+        }
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Writes a function back out as WebAssembly text, with the names the plugin resolved,
+// to read or diff outside of the disassembly.
+struct ExportFunctionWat;
+
+impl FunctionCommand for ExportFunctionWat {
+    fn action(&self, view: &BinaryView, func: &Function) {
+        let Some(style) = get_choice_input("Style", "Export function as WAT", &["Flat", "Folded"])
+        else {
+            return;
+        };
+        let Some(destination) =
+            get_choice_input("Export to", "Export function as WAT", &["Report", "File"])
+        else {
+            return;
+        };
+        let (name, text) = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            let Some(func_data) = module_data.funcs.get(&func.start()) else {
+                error!("No WebAssembly function at {:#x}", func.start());
+                return;
+            };
+            let func_data = func_data.as_ref();
+            let names = Names::new(module_data);
+            (
+                module_data.func_name(func_data.func_index),
+                func_wat(module_data, &names, func_data, style == 1, false),
+            )
+        };
+
+        if destination == 0 {
+            view.show_plaintext_report(&name, &text);
+            return;
+        }
+        let Some(path) =
+            get_save_filename_input("Export function as WAT", "*.wat", &format!("{name}.wat"))
+        else {
+            return;
+        };
+        match std::fs::write(&path, text + "\n") {
+            Ok(()) => info!("Exported {name} to {}", path.display()),
+            Err(e) => error!("Failed to export {name} to {}: {e}", path.display()),
+        }
+    }
+
+    fn valid(&self, view: &BinaryView, _func: &Function) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Writes the whole module out as WebAssembly text, like wasm2wat, but with the names
+// the plugin resolved and what its analysis found commented in.
+struct ExportModuleWat;
+
+impl Command for ExportModuleWat {
+    fn action(&self, view: &BinaryView) {
+        let Some(style) = get_choice_input("Style", "Export module as WAT", &["Flat", "Folded"])
+        else {
+            return;
+        };
+        let Some(path) = get_save_filename_input("Export module as WAT", "*.wat", "module.wat")
+        else {
+            return;
+        };
+        let text = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            let names = Names::new(module_data);
+            module_wat(view, module_data, &names, style == 1)
+        };
+        match std::fs::write(&path, text + "\n") {
+            Ok(()) => info!("Exported the module to {}", path.display()),
+            Err(e) => error!("Failed to export the module to {}: {e}", path.display()),
+        }
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Shows an overview of the module to triage it by.
+struct ShowModuleSummary;
+
+impl Command for ShowModuleSummary {
+    fn action(&self, view: &BinaryView) {
+        let (html, plaintext) = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            module_summary(module_data)
+        };
+        view.show_html_report("Module Summary", &html, &plaintext);
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Shows the functions that the indirect call at the cursor can call, and pins it to
+// one of them, or to any other function, where the analysis can't narrow them down.
+// Pinned calls only get a reference to the function they are pinned to.
+struct SetCallTarget;
+
+impl AddressCommand for SetCallTarget {
+    fn action(&self, view: &BinaryView, addr: u64) {
+        let title = "Set indirect call target";
+        // The lock isn't held while the user chooses, since the disassembly needs it.
+        let (candidates, n_funcs, mut choices, prompt) = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            let Some(candidates) = module_data.indirect_call_targets.get(&addr).cloned() else {
+                error!("No indirect call at {addr:#x}");
+                return;
+            };
+            let choices = candidates
+                .iter()
+                .map(|func_index| {
+                    format!(
+                        "{} (function {func_index})",
+                        module_data.func_name(*func_index)
+                    )
+                })
+                .collect::<Vec<_>>();
+            let prompt = match module_data.call_target_overrides.get(&addr) {
+                Some(func_index) => format!(
+                    "Target of the call at {addr:#x}, pinned to {}",
+                    module_data.func_name(*func_index)
+                ),
+                None => format!("Target of the call at {addr:#x}"),
+            };
+            (candidates, module_data.func_addrs.len(), choices, prompt)
+        };
+        choices.insert(0, format!("Any of the {} candidates", candidates.len()));
+        choices.push("Other function...".to_string());
+        let choices = choices.iter().map(String::as_str).collect::<Vec<_>>();
+        let Some(choice) = get_choice_input(&prompt, title, &choices) else {
+            return;
+        };
+        let pinned = match choice {
+            0 => None,
+            _ if choice <= candidates.len() => Some(candidates[choice - 1]),
+            _ => {
+                let Some(func_index) = get_integer_input("Function index", title) else {
+                    return;
+                };
+                if !(0..n_funcs as i64).contains(&func_index) {
+                    error!("There is no function {func_index}");
+                    return;
+                }
+                Some(func_index as u32)
+            }
+        };
+
+        // The view is changed once the lock is released, since changing references
+        // sets off analysis that takes it.
+        let (old_refs, new_refs, func_start, overrides, pinned_name) = {
+            let mut module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_mut() else {
+                return;
+            };
+            let old_refs = call_target_refs(module_data, addr);
+            match pinned {
+                Some(func_index) => module_data.call_target_overrides.insert(addr, func_index),
+                None => module_data.call_target_overrides.remove(&addr),
+            };
+            let new_refs = call_target_refs(module_data, addr);
+            let func_start = module_data
+                .funcs
+                .get(&addr)
+                .map(|func| func.as_ref().size_start);
+            let overrides = module_data
+                .call_target_overrides
+                .iter()
+                .map(|(addr, func_index)| (format!("{addr:#x}"), *func_index as u64))
+                .collect::<Vec<_>>();
+            let pinned_name = pinned.map(|func_index| module_data.func_name(func_index));
+            (old_refs, new_refs, func_start, overrides, pinned_name)
+        };
+
+        let bn_func = view
+            .default_platform()
+            .zip(func_start)
+            .and_then(|(platform, start)| view.function_at(&platform, start));
+        if let Some(bn_func) = bn_func {
+            for target in old_refs {
+                bn_func.remove_auto_code_ref(addr, target, None);
+            }
+            for target in new_refs {
+                bn_func.add_auto_code_ref(addr, target, None);
+            }
+        }
+
+        let metadata = overrides
+            .iter()
+            .map(|(addr, func_index)| (addr.as_str(), Ref::<Metadata>::from(*func_index)))
+            .collect::<HashMap<_, _>>();
+        view.store_metadata(CALL_TARGET_OVERRIDES_KEY, metadata, false);
+        match pinned_name {
+            Some(name) => info!("Pinned the call at {addr:#x} to {name}"),
+            None => info!("Unpinned the call at {addr:#x}"),
+        }
+    }
+
+    fn valid(&self, view: &BinaryView, addr: u64) -> bool {
+        is_wasm_view(view)
+            && MODULE_DATA
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|module_data| module_data.indirect_call_targets.contains_key(&addr))
+    }
+}
+
+// Shows what is on the operand stack before the instruction at the cursor runs: the
+// type of each value and the instruction that pushed it.
+struct ShowOperandStack;
+
+impl AddressCommand for ShowOperandStack {
+    fn action(&self, view: &BinaryView, addr: u64) {
+        let text = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            let Some(func) = module_data.funcs.get(&addr) else {
+                return;
+            };
+            let func = func.as_ref();
+            let Some(op) = func.ops.get(&addr) else {
+                error!("No instruction starts at {addr:#x}");
+                return;
+            };
+            let insn = instruction_text(module_data, func, addr, op).unwrap_or_default();
+            let mut lines = vec![format!("Before {addr:#x}: {insn}"), String::new()];
+            match stack_state(module_data, func, addr) {
+                Err(reason) => lines.push(format!("The stack can't be followed: {reason}")),
+                Ok(stack) if stack.is_empty() => lines.push("The stack is empty".to_string()),
+                Ok(stack) => {
+                    lines.push("Top of stack first:".to_string());
+                    for (depth, value) in stack.iter().rev().enumerate() {
+                        let producer = value.producer.and_then(|producer| {
+                            let op = func.ops.get(&producer)?;
+                            let insn = instruction_text(module_data, func, producer, op)?;
+                            Some(format!("{producer:#x}: {insn}"))
+                        });
+                        let producer = producer.unwrap_or("parameter of the block".to_string());
+                        lines.push(format!(
+                            "  {depth:>3}  {:<9}{producer}",
+                            value.ty.to_string()
+                        ));
+                    }
+                }
+            }
+            lines.join("\n")
+        };
+        view.show_plaintext_report("Operand Stack", &text);
+    }
+
+    fn valid(&self, view: &BinaryView, addr: u64) -> bool {
+        is_wasm_view(view)
+            && MODULE_DATA
+                .lock()
+                .unwrap()
+                .as_ref()
+                .and_then(|module_data| module_data.funcs.get(&addr))
+                .is_some_and(|func| func.as_ref().ops.contains_key(&addr))
+    }
+}
+
+// Shows the selected instructions as WAT, with the names of what they refer to, for
+// pasting into notes or a test module. The Rust API has no clipboard, but the text
+// of a report can be copied.
+struct SelectionWat;
+
+impl RangeCommand for SelectionWat {
+    fn action(&self, view: &BinaryView, range: Range<u64>) {
+        let text = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            let Some(func) = module_data.funcs.get(&range.start) else {
+                return;
+            };
+            let names = Names::new(module_data);
+            range_wat(module_data, &names, func.as_ref(), range.clone())
+        };
+        let title = format!("WAT of {:#x}-{:#x}", range.start, range.end);
+        view.show_plaintext_report(&title, &text);
+    }
+
+    fn valid(&self, view: &BinaryView, range: Range<u64>) -> bool {
+        is_wasm_view(view)
+            && MODULE_DATA
+                .lock()
+                .unwrap()
+                .as_ref()
+                .and_then(|module_data| module_data.funcs.get(&range.start))
+                .is_some_and(|func| func.as_ref().ops.range(range).next().is_some())
+    }
+}
+
+// Saves the calls between the functions of the module, with the indirect calls that
+// were resolved, for Graphviz or other tools.
+struct ExportCallGraph;
+
+impl Command for ExportCallGraph {
+    fn action(&self, _view: &BinaryView) {
+        let formats = ["DOT", "JSON"];
+        let Some(format) = get_choice_input("Format", "Export call graph", &formats) else {
+            return;
+        };
+        let (extension, default_name) = match format {
+            0 => ("*.dot", "calls.dot"),
+            _ => ("*.json", "calls.json"),
+        };
+        let Some(path) = get_save_filename_input("Export call graph", extension, default_name)
+        else {
+            return;
+        };
+        let graph = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            CallGraph::new(module_data)
+        };
+        let text = match format {
+            0 => Ok(graph.to_dot()),
+            _ => graph.to_json(),
+        };
+        let result = text.and_then(|text| std::fs::write(&path, text).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => info!("Exported the call graph to {}", path.display()),
+            Err(e) => error!("Failed to export the call graph to {}: {e}", path.display()),
+        }
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Saves the names of all functions by function index, with the user's renames.
+struct ExportNameMap;
+
+impl Command for ExportNameMap {
+    fn action(&self, view: &BinaryView) {
+        let Some(path) = get_save_filename_input("Export function names", "*.json", "names.json")
+        else {
+            return;
+        };
+        let names = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            current_names(view, module_data)
+        };
+        let result = serde_json::to_vec_pretty(&names)
+            .map_err(|e| e.to_string())
+            .and_then(|data| std::fs::write(&path, data).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => info!(
+                "Exported {} function names to {}",
+                names.functions.len(),
+                path.display()
+            ),
+            Err(e) => error!("Failed to export function names to {}: {e}", path.display()),
+        }
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Names the functions after a file saved by `ExportNameMap`, e.g. from another session
+// on the same module or a related build of it.
+struct ImportNameMap;
+
+impl Command for ImportNameMap {
+    fn action(&self, view: &BinaryView) {
+        let Some(path) = get_open_filename_input("Import function names", "*.json") else {
+            return;
+        };
+        let names = std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| serde_json::from_slice::<NameMap>(&data).map_err(|e| e.to_string()));
+        let names = match names {
+            Ok(names) => names,
+            Err(e) => {
+                error!("Failed to read function names from {}: {e}", path.display());
+                return;
+            }
+        };
+        let symbols = {
+            let mut module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_mut() else {
+                return;
+            };
+            apply_names(view, module_data, &names)
+        };
+        for symbol in &symbols {
+            view.define_user_symbol(symbol);
+        }
+        info!(
+            "Renamed {} functions from {}",
+            symbols.len(),
+            path.display()
+        );
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Saves a copy of the module whose name section has the names of the functions and
+// globals as they are now, for debuggers, wasm-objdump and other tools to show.
+struct SaveModuleWithNames;
+
+impl Command for SaveModuleWithNames {
+    fn action(&self, view: &BinaryView) {
+        let Some(path) = get_save_filename_input("Save module with names", "*.wasm", "module.wasm")
+        else {
+            return;
+        };
+        let module = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            module_with_names(view, module_data)
+        };
+        let result =
+            module.and_then(|module| std::fs::write(&path, module).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => info!("Saved the module with its names to {}", path.display()),
+            Err(e) => error!("Failed to save the module to {}: {e}", path.display()),
+        }
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Replaces the body of a function with one written in WAT, e.g. an edited copy of what
+// Export Function as WAT wrote, which is easier than patching instructions in place.
+struct ReplaceBodyFromWat;
+
+impl FunctionCommand for ReplaceBodyFromWat {
+    fn action(&self, view: &BinaryView, func: &Function) {
+        let title = "Replace function body from WAT";
+        let Some(source) = get_choice_input("Source", title, &["Paste text", "File"]) else {
+            return;
+        };
+        let text = match source {
+            0 => get_text_line_input("Function or body as WAT", title),
+            _ => get_open_filename_input("Function or body as WAT", "*.wat").and_then(|path| {
+                std::fs::read_to_string(&path)
+                    .inspect_err(|e| error!("Failed to read {}: {e}", path.display()))
+                    .ok()
+            }),
+        };
+        let Some(text) = text else {
+            return;
+        };
+
+        // The new body is written to the view with the lock released, since writing
+        // starts analysis that takes it, and stored in the module data after.
+        let (name, result) = {
+            let mut module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_mut() else {
+                return;
+            };
+            let name = module_data
+                .funcs
+                .get(&func.start())
+                .map(|func_data| module_data.func_name(func_data.as_ref().func_index));
+            let result = replace_body(module_data, func.start(), &text);
+            (name.unwrap_or_default(), result)
+        };
+        let result = match result {
+            Ok(Some(body)) => body.write(view).map(|()| Some(body)),
+            result => result,
+        };
+        match result {
+            Ok(Some(body)) => {
+                if let Some(module_data) = MODULE_DATA.lock().unwrap().as_mut() {
+                    body.store(module_data);
+                }
+                func.reanalyze();
+                info!("Replaced the body of {name}");
+            }
+            Ok(None) => info!("Replaced the body of {name} in the patched module only"),
+            Err(e) => error!("Failed to replace the body of {name}: {e}"),
+        }
+    }
+
+    fn valid(&self, view: &BinaryView, _func: &Function) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Saves a copy of the module with the patches made to its functions, with the sizes of
+// the function bodies encoded again, so that runtimes can load it.
+struct SavePatchedModule;
+
+impl Command for SavePatchedModule {
+    fn action(&self, view: &BinaryView) {
+        let Some(path) = get_save_filename_input("Save patched module", "*.wasm", "patched.wasm")
+        else {
+            return;
+        };
+        let module = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            patched_module(view, module_data)
+        };
+        let result =
+            module.and_then(|module| std::fs::write(&path, module).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => info!("Saved the patched module to {}", path.display()),
+            Err(e) => error!("Failed to save the module to {}: {e}", path.display()),
+        }
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Shows a hexdump of a data segment, addressed by where it is loaded in memory.
+struct ShowDataSegment;
+
+impl Command for ShowDataSegment {
+    fn action(&self, view: &BinaryView) {
+        let labels = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            (0..module_data.data_segments.len())
+                .map(|data_index| segment_label(module_data, data_index))
+                .collect::<Vec<_>>()
+        };
+        if labels.is_empty() {
+            info!("The module has no data segments");
+            return;
+        }
+        let choices = labels.iter().map(String::as_str).collect::<Vec<_>>();
+        let Some(data_index) = get_choice_input("Data segment", "Show data segment", &choices)
+        else {
+            return;
+        };
+        let text = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            let segment = &module_data.data_segments[data_index];
+            let Some(data) = read_segment(view, segment) else {
+                error!("Failed to read data segment {data_index}");
+                return;
+            };
+            let base = segment.memory_range().map_or(0, |range| range.start);
+            hexdump(&data, base)
+        };
+        view.show_plaintext_report(&labels[data_index], &text);
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Saves each data segment to its own file in a directory, named by where it is loaded,
+// to get at embedded assets and payloads.
+struct ExtractDataSegments;
+
+impl Command for ExtractDataSegments {
+    fn action(&self, view: &BinaryView) {
+        let Some(dir) = get_directory_name_input("Extract data segments to", "data") else {
+            return;
+        };
+        let module_data_lock = MODULE_DATA.lock().unwrap();
+        let Some(module_data) = module_data_lock.as_ref() else {
+            return;
+        };
+        let mut n_saved = 0;
+        for (data_index, segment) in module_data.data_segments.iter().enumerate() {
+            let path = dir.join(segment_file_name(module_data, data_index));
+            let result = read_segment(view, segment)
+                .ok_or("failed to read the segment".to_string())
+                .and_then(|data| std::fs::write(&path, data).map_err(|e| e.to_string()));
+            match result {
+                Ok(()) => n_saved += 1,
+                Err(e) => error!(
+                    "Failed to save data segment {data_index} to {}: {e}",
+                    path.display()
+                ),
+            }
+        }
+        info!("Extracted {n_saved} data segments to {}", dir.display());
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Saves a host program that instantiates the module with logging stubs for its imports
+// and a wrapper for each export, to start running or fuzzing the module from.
+struct GenerateBindings;
+
+impl Command for GenerateBindings {
+    fn action(&self, _view: &BinaryView) {
+        let languages = ["JavaScript (Node.js)", "Rust (wasmtime)"];
+        let Some(language) = get_choice_input("Language", "Generate host bindings", &languages)
+        else {
+            return;
+        };
+        let (extension, default_name) = match language {
+            0 => ("*.mjs", "bindings.mjs"),
+            _ => ("*.rs", "main.rs"),
+        };
+        let Some(path) = get_save_filename_input("Generate host bindings", extension, default_name)
+        else {
+            return;
+        };
+        let text = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            match language {
+                0 => js_bindings(module_data),
+                _ => rust_bindings(module_data),
+            }
+        };
+        match std::fs::write(&path, text) {
+            Ok(()) => info!("Saved host bindings to {}", path.display()),
+            Err(e) => error!("Failed to save host bindings to {}: {e}", path.display()),
+        }
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Searches any file for embedded WebAssembly modules and saves the one the user picks,
+// to be opened on its own.
+struct FindEmbeddedModules;
+
+impl Command for FindEmbeddedModules {
+    fn action(&self, view: &BinaryView) {
+        // Search the file itself, rather than what the view maps of it.
+        let raw = view
+            .file()
+            .view_of_type("Raw")
+            .unwrap_or_else(|| view.to_owned());
+        let modules = find_embedded_modules(&raw);
+        if modules.is_empty() {
+            info!("No embedded WebAssembly modules found");
+            return;
+        }
+        let labels = modules
+            .iter()
+            .map(|module| module.label())
+            .collect::<Vec<_>>();
+        for label in &labels {
+            info!("Found embedded {label}");
+        }
+        let choices = labels.iter().map(String::as_str).collect::<Vec<_>>();
+        let Some(index) = get_choice_input("Module", "Embedded WebAssembly modules", &choices)
+        else {
+            return;
+        };
+        let range = modules[index].range.clone();
+        let default_name = format!("module_{:#x}.wasm", range.start);
+        let Some(path) = get_save_filename_input("Save embedded module", "*.wasm", &default_name)
+        else {
+            return;
+        };
+        let len = (range.end - range.start) as usize;
+        let mut data = Vec::new();
+        let result = if raw.read_into_vec(&mut data, range.start, len) == len {
+            std::fs::write(&path, data).map_err(|e| e.to_string())
+        } else {
+            Err("failed to read the module".to_string())
+        };
+        match result {
+            Ok(()) => info!("Saved the embedded {} to {}", labels[index], path.display()),
+            Err(e) => error!("Failed to save the module to {}: {e}", path.display()),
+        }
+    }
+
+    fn valid(&self, _view: &BinaryView) -> bool {
+        true
+    }
+}
+
+// Selects the file in the view as the other version of the module to compare with.
+// Only one WebAssembly view can be open, so the other version is opened in its own
+// tab as a raw view.
+struct SelectModuleForComparison;
+
+impl Command for SelectModuleForComparison {
+    fn action(&self, view: &BinaryView) {
+        let raw = view
+            .file()
+            .view_of_type("Raw")
+            .unwrap_or_else(|| view.to_owned());
+        info!("Selected {} to compare with", view.file().filename());
+        *OTHER_MODULE.lock().unwrap() = Some(raw);
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        !is_wasm_view(view)
+    }
+}
+
+// Compares the functions of the module with those of another version of it, to see
+// what a patch or an update changed.
+struct CompareWithModule;
+
+impl Command for CompareWithModule {
+    fn action(&self, view: &BinaryView) {
+        let Some(other) = OTHER_MODULE.lock().unwrap().clone() else {
+            return;
+        };
+        // The other module is parsed before the lock is taken, as it takes a while.
+        let (other_view, other_module_data) = match parse_other_module(&other) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                error!("Failed to compare with {}: {e}", other.file().filename());
+                return;
+            }
+        };
+        let (html, plaintext) = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return;
+            };
+            diff_modules(view, module_data, &other_view, &other_module_data)
+        };
+        view.show_html_report("Function Diff", &html, &plaintext);
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view) && OTHER_MODULE.lock().unwrap().is_some()
+    }
+}
+
+// Parses the module again from scratch, to apply changed settings or to finish a parse
+// that was cancelled, without reopening the file.
+struct ReparseModule;
+
+impl Command for ReparseModule {
+    fn action(&self, view: &BinaryView) {
+        if WebAssemblyView::reparse(view).is_err() {
+            error!("Failed to reparse the module");
+        }
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+// Moves linear memory to another address, to make room for other regions.
+struct RebaseMemory;
+
+impl Command for RebaseMemory {
+    fn action(&self, view: &BinaryView) {
+        let Some(base) = get_integer_input("New base address", "Rebase linear memory") else {
+            return;
+        };
+        let Ok(base) = u64::try_from(base) else {
+            error!("Invalid base address {base}");
+            return;
+        };
+        if WebAssemblyView::rebase_memory(view, base).is_err() {
+            error!("Failed to rebase linear memory");
+        }
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        is_wasm_view(view)
+    }
+}
+
+pub fn register_commands() {
+    register_command(
+        "WebAssembly\\Save Function Signatures...",
+        "Save fingerprints of the named functions, to name the same functions in stripped modules",
+        SaveSignatures,
+    );
+    register_command_for_function(
+        "WebAssembly\\Export Function as WAT...",
+        "Show or save the current function as WebAssembly text",
+        ExportFunctionWat,
+    );
+    register_command(
+        "WebAssembly\\Export Module as WAT...",
+        "Save the module as WebAssembly text, with resolved names and analysis comments",
+        ExportModuleWat,
+    );
+    register_command_for_address(
+        "WebAssembly\\Set Indirect Call Target...",
+        "Show the functions that the indirect call at the cursor can call, and pin it to one",
+        SetCallTarget,
+    );
+    register_command_for_address(
+        "WebAssembly\\Show Operand Stack",
+        "Show the types of the values on the operand stack at the cursor and what pushed them",
+        ShowOperandStack,
+    );
+    register_command_for_range(
+        "WebAssembly\\Show Selection as WAT",
+        "Show the selected instructions as WebAssembly text, with resolved names, to copy",
+        SelectionWat,
+    );
+    register_command(
+        "WebAssembly\\Export Call Graph...",
+        "Save the calls between functions, including resolved indirect calls, as DOT or JSON",
+        ExportCallGraph,
+    );
+    register_command(
+        "WebAssembly\\Export Function Names...",
+        "Save the names of the functions by function index as JSON",
+        ExportNameMap,
+    );
+    register_command(
+        "WebAssembly\\Import Function Names...",
+        "Rename functions from a JSON file of names by function index",
+        ImportNameMap,
+    );
+    register_command(
+        "WebAssembly\\Save Module with Names...",
+        "Save a copy of the module with the current function and global names in its name section",
+        SaveModuleWithNames,
+    );
+    register_command(
+        "WebAssembly\\Save Patched Module...",
+        "Save a copy of the module with the patched functions, which runtimes can load",
+        SavePatchedModule,
+    );
+    register_command_for_function(
+        "WebAssembly\\Replace Function Body from WAT...",
+        "Assemble a new body for the current function from WAT, with the identifiers Export Function as WAT uses",
+        ReplaceBodyFromWat,
+    );
+    register_command(
+        "WebAssembly\\Show Data Segment...",
+        "Show a hexdump of a data segment",
+        ShowDataSegment,
+    );
+    register_command(
+        "WebAssembly\\Extract Data Segments...",
+        "Save each data segment to a file named after where it is loaded in memory",
+        ExtractDataSegments,
+    );
+    register_command(
+        "WebAssembly\\Show Module Summary",
+        "Show the sections, imports, memories, toolchain and largest functions of the module",
+        ShowModuleSummary,
+    );
+    register_command(
+        "WebAssembly\\Generate Host Bindings...",
+        "Save a JavaScript or Rust program that instantiates the module with stubs for its imports",
+        GenerateBindings,
+    );
+    register_command(
+        "WebAssembly\\Find Embedded Modules...",
+        "Search the file for embedded WebAssembly modules and save one to open on its own",
+        FindEmbeddedModules,
+    );
+    register_command(
+        "WebAssembly\\Select for Comparison",
+        "Select the file as the other version of the module to compare the open module with",
+        SelectModuleForComparison,
+    );
+    register_command(
+        "WebAssembly\\Compare with Selected Module",
+        "Match the functions with those of the selected version of the module and show which changed",
+        CompareWithModule,
+    );
+    register_command(
+        "WebAssembly\\Reparse Module",
+        "Parse the module again with the current settings, e.g. after a cancelled parse",
+        ReparseModule,
+    );
+    register_command(
+        "WebAssembly\\Rebase Linear Memory...",
+        "Move linear memory to another address, with the symbols and data variables in it",
+        RebaseMemory,
+    );
+}
@@ -0,0 +1,332 @@
+use crate::binja::name_map::current_names;
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::report::{html, plaintext, row, Table};
+use crate::binja::settings::VIEW_TYPE_NAME;
+use crate::binja::toolchain::normalize;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryView;
+use binaryninja::custom_binary_view::{BinaryViewType, BinaryViewTypeExt, CustomBinaryView};
+use binaryninja::rc::Ref;
+use once_cell::sync::Lazy;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use wasmparser::{FuncType, Operator};
+
+// The view of the other version of the module, which the user selects in its own tab,
+// as only one WebAssembly view can be open. It is usually the raw view of the file,
+// since opening it as WebAssembly fails.
+pub(crate) static OTHER_MODULE: Lazy<Mutex<Option<Ref<BinaryView>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+// Functions with the same signature are matched by their bodies if they are at least
+// this similar.
+const MIN_SIMILARITY: f64 = 0.7;
+
+// Each function is only compared for similarity with this many of the functions of the
+// other module with the same signature, those closest to it in size and instructions,
+// so that modules with many functions of one signature don't take quadratic time.
+const MAX_SIMILARITY_CANDIDATES: usize = 32;
+
+// What a function is matched and compared by. Bodies are normalized like they are for
+// signature libraries, so that functions that only moved don't count as changed.
+struct FuncSummary {
+    index: u32,
+    name: String,
+
+    // Whether the name comes from the module or the user, rather than being the
+    // placeholder name of a function without one.
+    named: bool,
+
+    signature: String,
+    body: Vec<Vec<u8>>,
+}
+
+// How a function of one module was matched with one of the other.
+#[derive(Clone, Copy)]
+enum MatchKind {
+    Name,
+    Body,
+    Similarity(f64),
+}
+
+fn signature(ty: Option<&FuncType>) -> String {
+    let Some(ty) = ty else {
+        return "?".to_string();
+    };
+    let types = |types: &[wasmparser::ValType]| {
+        let types = types.iter().map(|ty| ty.to_string()).collect::<Vec<_>>();
+        format!("({})", types.join(", "))
+    };
+    format!("{} -> {}", types(ty.params()), types(ty.results()))
+}
+
+fn summary(module_data: &ModuleData, index: u32, name: String, ops: &[Operator]) -> FuncSummary {
+    let imported = (index as usize) < module_data.func_imports.len();
+    let name = match module_data.func_imports.get(index as usize) {
+        Some(import) => format!("{}.{}", import.module, import.name),
+        None => name,
+    };
+    FuncSummary {
+        index,
+        named: imported || name != format!("func_{index}"),
+        name,
+        signature: signature(module_data.func_type(index)),
+        body: ops.iter().map(|op| normalize(module_data, op)).collect(),
+    }
+}
+
+// The functions of the module in the view, with the user's renames.
+fn functions(view: &BinaryView, module_data: &ModuleData) -> Vec<FuncSummary> {
+    let mut bodies = BTreeMap::new();
+    for (_, func) in module_data.funcs.iter() {
+        let func = func.as_ref();
+        let ops = func
+            .ops
+            .values()
+            .map(|op| op.op.clone())
+            .collect::<Vec<_>>();
+        bodies.insert(func.func_index, ops);
+    }
+    current_names(view, module_data)
+        .functions
+        .into_iter()
+        .map(|(index, name)| {
+            let ops = bodies.get(&index).map_or(&[][..], Vec::as_slice);
+            summary(module_data, index, name, ops)
+        })
+        .collect()
+}
+
+// Parses the module of `other` with the same parser as the module in the view, into a
+// WebAssembly view of its own that is only read.
+pub(crate) fn parse_other_module(
+    other: &BinaryView,
+) -> Result<(Ref<BinaryView>, ModuleData), String> {
+    let view_type = BinaryViewType::by_name(VIEW_TYPE_NAME)
+        .map_err(|_| "The WebAssembly view type isn't registered".to_string())?;
+    let view = view_type
+        .parse(other)
+        .map_err(|_| "The other view doesn't hold a WebAssembly module".to_string())?;
+    let mut wasm_view = WebAssemblyView::new(&view, &true)
+        .map_err(|_| "Failed to create a view of the other module".to_string())?;
+    let mut module_data = ModuleData::new();
+    wasm_view
+        .parse_module_data(&mut module_data)
+        .map_err(|_| "Failed to parse the other module".to_string())?;
+    Ok((view, module_data))
+}
+
+// The normalized instructions of a body in order, for comparing them as a multiset.
+fn sorted_ops(body: &[Vec<u8>]) -> Vec<&[u8]> {
+    let mut ops = body.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    ops.sort_unstable();
+    ops
+}
+
+// How alike two bodies are, from 0 to 1: the share of their instructions that they
+// have in common, regardless of order. Takes the instructions of each sorted.
+fn similarity(a: &[&[u8]], b: &[&[u8]]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let (mut i, mut j, mut n_common) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                n_common += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    2.0 * n_common as f64 / (a.len() + b.len()) as f64
+}
+
+// Matches the functions of this module with those of the other: first by name, then
+// by identical bodies, then by the most similar body with the same signature. Returns
+// the matches, by index into `this`, as the index into `other` and how they matched.
+fn match_functions(
+    this: &[FuncSummary],
+    other: &[FuncSummary],
+) -> BTreeMap<usize, (usize, MatchKind)> {
+    let mut matches = BTreeMap::new();
+    let mut matched_other = vec![false; other.len()];
+
+    let other_by_name = other
+        .iter()
+        .enumerate()
+        .filter(|(_, func)| func.named)
+        .map(|(i, func)| (func.name.as_str(), i))
+        .collect::<HashMap<_, _>>();
+    for (i, func) in this.iter().enumerate().filter(|(_, func)| func.named) {
+        let j = other_by_name.get(func.name.as_str()).copied();
+        if let Some(j) = j.filter(|j| !matched_other[*j]) {
+            matches.insert(i, (j, MatchKind::Name));
+            matched_other[j] = true;
+        }
+    }
+
+    // Bodies that several functions share can't tell them apart.
+    let mut by_body = HashMap::<(&str, &[Vec<u8>]), (Vec<usize>, Vec<usize>)>::new();
+    for (i, func) in this.iter().enumerate() {
+        if !matches.contains_key(&i) && !func.body.is_empty() {
+            let key = (func.signature.as_str(), func.body.as_slice());
+            by_body.entry(key).or_default().0.push(i);
+        }
+    }
+    for (j, func) in other.iter().enumerate() {
+        if !matched_other[j] && !func.body.is_empty() {
+            let key = (func.signature.as_str(), func.body.as_slice());
+            by_body.entry(key).or_default().1.push(j);
+        }
+    }
+    for (this_funcs, other_funcs) in by_body.values() {
+        if let ([i], [j]) = (this_funcs.as_slice(), other_funcs.as_slice()) {
+            matches.insert(*i, (*j, MatchKind::Body));
+            matched_other[*j] = true;
+        }
+    }
+
+    // The rest of the other module's functions, by signature and then by size.
+    let mut by_signature = HashMap::<&str, Vec<(usize, Vec<&[u8]>)>>::new();
+    for (j, func) in other.iter().enumerate() {
+        if !matched_other[j] && !func.body.is_empty() {
+            let bucket = by_signature.entry(func.signature.as_str()).or_default();
+            bucket.push((j, sorted_ops(&func.body)));
+        }
+    }
+    // Bodies of the same size are ordered by their instructions, so that those next to
+    // each other tend to have more of them in common.
+    for bucket in by_signature.values_mut() {
+        bucket.sort_by(|(_, a), (_, b)| (a.len(), a).cmp(&(b.len(), b)));
+    }
+
+    let mut candidates = Vec::new();
+    for (i, func) in this.iter().enumerate() {
+        if matches.contains_key(&i) || func.body.is_empty() {
+            continue;
+        }
+        let Some(bucket) = by_signature.get(func.signature.as_str()) else {
+            continue;
+        };
+        let ops = sorted_ops(&func.body);
+        let closest = bucket
+            .partition_point(|(_, other_ops)| (other_ops.len(), other_ops) < (ops.len(), &ops));
+        let start = closest.saturating_sub(MAX_SIMILARITY_CANDIDATES / 2);
+        let end = (start + MAX_SIMILARITY_CANDIDATES).min(bucket.len());
+        for (j, other_ops) in &bucket[start..end] {
+            // Bodies of very different lengths can't be similar enough.
+            let (len, other_len) = (ops.len() as f64, other_ops.len() as f64);
+            if len.min(other_len) / len.max(other_len) < MIN_SIMILARITY {
+                continue;
+            }
+            let similarity = similarity(&ops, other_ops);
+            if similarity >= MIN_SIMILARITY {
+                candidates.push((similarity, i, *j));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+    for (similarity, i, j) in candidates {
+        if !matches.contains_key(&i) && !matched_other[j] {
+            matches.insert(i, (j, MatchKind::Similarity(similarity)));
+            matched_other[j] = true;
+        }
+    }
+    matches
+}
+
+// Compares the functions of the module in the view with those of another version of
+// it, as `parse_other_module` read it. Functions only in this module are reported as
+// added, and those only in the other as removed. Returns the report as HTML and as
+// plain text.
+pub(crate) fn diff_modules(
+    view: &BinaryView,
+    module_data: &ModuleData,
+    other_view: &BinaryView,
+    other_module_data: &ModuleData,
+) -> (String, String) {
+    let this = functions(view, module_data);
+    let other = functions(other_view, other_module_data);
+    let matches = match_functions(&this, &other);
+
+    let mut matched_other = vec![false; other.len()];
+    let mut changed = Vec::new();
+    let mut added = Vec::new();
+    let mut n_unchanged = 0;
+    for (i, func) in this.iter().enumerate() {
+        let address = module_data
+            .func_addrs
+            .get(func.index as usize)
+            .map_or(String::new(), |addr| format!("{addr:#x}"));
+        let Some(&(j, kind)) = matches.get(&i) else {
+            added.push(vec![func.name.clone(), address, func.signature.clone()]);
+            continue;
+        };
+        matched_other[j] = true;
+        let other_func = &other[j];
+        if func.body == other_func.body && func.signature == other_func.signature {
+            n_unchanged += 1;
+            continue;
+        }
+        let matched_by = match kind {
+            MatchKind::Name => "name".to_string(),
+            MatchKind::Body => "body".to_string(),
+            MatchKind::Similarity(similarity) => format!("similarity ({:.0}%)", similarity * 100.0),
+        };
+        changed.push(vec![
+            func.name.clone(),
+            other_func.name.clone(),
+            address,
+            format!(
+                "{:.0}%",
+                similarity(&sorted_ops(&func.body), &sorted_ops(&other_func.body)) * 100.0
+            ),
+            matched_by,
+        ]);
+    }
+    let removed = other
+        .iter()
+        .zip(matched_other)
+        .filter(|(_, matched)| !matched)
+        .map(|(func, _)| {
+            vec![
+                func.name.clone(),
+                func.index.to_string(),
+                func.signature.clone(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let tables = [
+        Table {
+            heading: "Summary",
+            columns: &[],
+            rows: vec![
+                row("Unchanged functions", n_unchanged),
+                row("Changed functions", changed.len()),
+                row("Added functions", added.len()),
+                row("Removed functions", removed.len()),
+            ],
+        },
+        Table {
+            heading: "Changed functions",
+            columns: &["Name", "Other name", "Address", "Similarity", "Matched by"],
+            rows: changed,
+        },
+        Table {
+            heading: "Added functions",
+            columns: &["Name", "Address", "Signature"],
+            rows: added,
+        },
+        Table {
+            heading: "Removed functions",
+            columns: &["Name", "Index in other module", "Signature"],
+            rows: removed,
+        },
+    ];
+    (html(&tables), plaintext(&tables))
+}
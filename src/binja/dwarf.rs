@@ -0,0 +1,225 @@
+use crate::binja::parse::module_data::{ModuleData, MODULE_DATA};
+use crate::binja::settings::VIEW_TYPE_NAME;
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+use binaryninja::debuginfo::{
+    CustomDebugInfoParser, DebugFunctionInfo, DebugInfo, DebugInfoParser,
+};
+use gimli::{AttributeValue, DebuggingInformationEntry, Dwarf, EndianSlice, LittleEndian, Unit};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::path::Path;
+use wasmparser::{Parser, Payload};
+
+type Reader<'a> = EndianSlice<'a, LittleEndian>;
+
+// How many declarations and abstract instances are followed to find the name of a
+// function.
+const MAX_NAME_DEPTH: usize = 4;
+
+// A function that DWARF describes, by its offset into the code section.
+struct DwarfFunction {
+    name: String,
+    raw_name: Option<String>,
+    offset: u64,
+}
+
+fn attr_string(
+    dwarf: &Dwarf<Reader>,
+    unit: &Unit<Reader>,
+    entry: &DebuggingInformationEntry<Reader>,
+    attr: gimli::DwAt,
+) -> Option<String> {
+    let value = entry.attr_value(attr).ok()??;
+    let string = dwarf.attr_string(unit, value).ok()?;
+    Some(string.to_string_lossy().into_owned())
+}
+
+// The name and linkage name of a function. Definitions of C++ methods outside their
+// class, and the concrete copies of inlined functions, only refer to the entry that
+// has the names.
+fn subprogram_names(
+    dwarf: &Dwarf<Reader>,
+    unit: &Unit<Reader>,
+    entry: &DebuggingInformationEntry<Reader>,
+    depth: usize,
+) -> (Option<String>, Option<String>) {
+    let name = attr_string(dwarf, unit, entry, gimli::DW_AT_name);
+    let raw_name = attr_string(dwarf, unit, entry, gimli::DW_AT_linkage_name)
+        .or_else(|| attr_string(dwarf, unit, entry, gimli::DW_AT_MIPS_linkage_name));
+    if name.is_some() || depth == MAX_NAME_DEPTH {
+        return (name, raw_name);
+    }
+    for attr in [gimli::DW_AT_specification, gimli::DW_AT_abstract_origin] {
+        if let Ok(Some(AttributeValue::UnitRef(offset))) = entry.attr_value(attr)
+            && let Ok(origin) = unit.entry(offset)
+        {
+            let (name, origin_raw_name) = subprogram_names(dwarf, unit, &origin, depth + 1);
+            return (name, raw_name.or(origin_raw_name));
+        }
+    }
+    (None, raw_name)
+}
+
+// The functions described by the DWARF in `sections`, by section name.
+fn dwarf_functions(sections: &HashMap<String, Vec<u8>>) -> gimli::Result<Vec<DwarfFunction>> {
+    let dwarf = Dwarf::load(|id| -> gimli::Result<Reader> {
+        let data = sections.get(id.name()).map_or(&[][..], Vec::as_slice);
+        Ok(EndianSlice::new(data, LittleEndian))
+    })?;
+    let mut functions = Vec::new();
+    let mut headers = dwarf.units();
+    while let Some(header) = headers.next()? {
+        let unit = dwarf.unit(header)?;
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs()? {
+            if entry.tag() != gimli::DW_TAG_subprogram {
+                continue;
+            }
+            let Some(low_pc) = entry.attr_value(gimli::DW_AT_low_pc)? else {
+                continue;
+            };
+            let Some(offset) = dwarf.attr_address(&unit, low_pc)? else {
+                continue;
+            };
+            let (name, raw_name) = subprogram_names(&dwarf, &unit, entry, 0);
+            if let Some(name) = name.or_else(|| raw_name.clone()) {
+                functions.push(DwarfFunction {
+                    name,
+                    raw_name,
+                    offset,
+                });
+            }
+        }
+    }
+    Ok(functions)
+}
+
+// The contents of the DWARF sections of the module, as they are in the file.
+fn read_dwarf_sections(view: &BinaryView, module_data: &ModuleData) -> HashMap<String, Vec<u8>> {
+    let Some(parent) = view.parent_view() else {
+        return HashMap::new();
+    };
+    module_data
+        .dwarf_sections
+        .iter()
+        .filter_map(|(name, range)| {
+            let len = (range.end - range.start) as usize;
+            let mut data = Vec::new();
+            (parent.read_into_vec(&mut data, range.start, len) == len).then(|| (name.clone(), data))
+        })
+        .collect()
+}
+
+// The DWARF sections of the split debug info file at `path`, a module with the same
+// code as this one, whose code offsets are the same.
+fn read_external_dwarf_sections(path: &Path) -> Result<HashMap<String, Vec<u8>>, String> {
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    let mut sections = HashMap::new();
+    for payload in Parser::new(0).parse_all(&data) {
+        if let Payload::CustomSection(reader) = payload.map_err(|e| e.to_string())?
+            && reader.name().starts_with(".debug_")
+        {
+            sections.insert(reader.name().to_string(), reader.data().to_vec());
+        }
+    }
+    Ok(sections)
+}
+
+// The functions described by the DWARF in `sections`, each with the address of the
+// function in the view that its code offset falls in. Functions that the linker
+// dropped are left at offsets outside the code section, such as 0 or -1, and are
+// skipped.
+fn placed_functions(
+    module_data: &ModuleData,
+    sections: &HashMap<String, Vec<u8>>,
+) -> gimli::Result<Vec<(u64, DwarfFunction)>> {
+    let Some(code_range) = &module_data.code_range else {
+        return Ok(Vec::new());
+    };
+    let functions = dwarf_functions(sections)?
+        .into_iter()
+        .filter_map(|function| {
+            let addr = code_range.start.checked_add(function.offset)?;
+            let func = module_data.funcs.get(&addr)?;
+            Some((func.as_ref().size_start, function))
+        })
+        .collect();
+    Ok(functions)
+}
+
+// Imports the functions that the DWARF in `.debug_*` custom sections describes, of the
+// module or of its split debug info file. Code addresses in wasm DWARF are offsets
+// into the code section, starting at its function count, rather than addresses in the
+// view, which binja's own DWARF importer takes them for, so this parser moves them to
+// the code section.
+struct WebAssemblyDwarfParser;
+
+impl CustomDebugInfoParser for WebAssemblyDwarfParser {
+    fn is_valid(&self, view: &BinaryView) -> bool {
+        view.view_type() == VIEW_TYPE_NAME
+            && MODULE_DATA
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|module_data| {
+                    !module_data.dwarf_sections.is_empty()
+                        || module_data.external_debug_path.is_some()
+                })
+    }
+
+    fn parse_info(
+        &self,
+        debug_info: &mut DebugInfo,
+        view: &BinaryView,
+        _debug_file: &BinaryView,
+        _progress: Box<dyn Fn(usize, usize) -> Result<(), ()>>,
+    ) -> bool {
+        let functions = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let Some(module_data) = module_data_lock.as_ref() else {
+                return false;
+            };
+            let mut sections = read_dwarf_sections(view, module_data);
+            if let Some(path) = &module_data.external_debug_path {
+                match read_external_dwarf_sections(path) {
+                    Ok(external) => {
+                        for (name, data) in external {
+                            sections.entry(name).or_insert(data);
+                        }
+                    }
+                    Err(e) => warn!("Failed to read {}: {e}", path.display()),
+                }
+            }
+            placed_functions(module_data, &sections)
+        };
+        let functions = match functions {
+            Ok(functions) => functions,
+            Err(e) => {
+                warn!("Failed to read the DWARF debug info: {e}");
+                return false;
+            }
+        };
+
+        let platform = view.default_platform();
+        let mut n_added = 0;
+        for (addr, function) in functions {
+            let info = DebugFunctionInfo::new(
+                Some(function.name.clone()),
+                Some(function.name),
+                function.raw_name,
+                None,
+                Some(addr),
+                platform.clone(),
+                Vec::new(),
+                Vec::new(),
+            );
+            n_added += debug_info.add_function(&info) as usize;
+        }
+        info!("Imported {n_added} functions from the DWARF debug info");
+        true
+    }
+}
+
+pub fn register_dwarf_parser() {
+    DebugInfoParser::register("WebAssembly DWARF", WebAssemblyDwarfParser);
+}
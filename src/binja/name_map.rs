@@ -0,0 +1,205 @@
+use crate::binja::parse::module_data::ModuleData;
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+use binaryninja::rc::Ref;
+use binaryninja::symbol::{Binding, Symbol, SymbolType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const NAME_SECTION: &str = "name";
+
+// Ids of the subsections of the name section.
+const FUNCTION_NAMES: u8 = 1;
+const LOCAL_NAMES: u8 = 2;
+const GLOBAL_NAMES: u8 = 7;
+const DATA_NAMES: u8 = 9;
+
+// Names of functions by function index, as saved to and loaded from a JSON file, to
+// carry names over between sessions and builds of the same module.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct NameMap {
+    pub functions: BTreeMap<u32, String>,
+}
+
+// The names of the functions as they are now, with the user's renames.
+pub(crate) fn current_names(view: &BinaryView, module_data: &ModuleData) -> NameMap {
+    let functions = module_data
+        .func_addrs
+        .iter()
+        .enumerate()
+        .map(|(func_index, addr)| {
+            let name = match view.symbol_by_address(*addr) {
+                Some(symbol) => symbol.short_name().to_string(),
+                None => module_data.func_name(func_index as u32),
+            };
+            (func_index as u32, name)
+        })
+        .collect();
+    NameMap { functions }
+}
+
+// The symbols that rename the functions in the map that the module has and that are
+// named otherwise. The caller defines them once the module data is unlocked.
+pub(crate) fn apply_names(
+    view: &BinaryView,
+    module_data: &mut ModuleData,
+    names: &NameMap,
+) -> Vec<Ref<Symbol>> {
+    let current = current_names(view, module_data);
+    let mut symbols = Vec::new();
+    for (func_index, name) in &names.functions {
+        let Some(&addr) = module_data.func_addrs.get(*func_index as usize) else {
+            continue;
+        };
+        if current.functions.get(func_index) == Some(name) || name.is_empty() {
+            continue;
+        }
+        let symbol = match module_data.func_imports.get(*func_index as usize) {
+            Some(import) => Symbol::builder(SymbolType::External, name, addr)
+                .full_name(&format!("{}::{name}", import.module))
+                .binding(Binding::Global)
+                .create(),
+            None if module_data.func_exports.contains_key(func_index) => {
+                Symbol::builder(SymbolType::Function, name, addr)
+                    .binding(Binding::Global)
+                    .create()
+            }
+            None => Symbol::builder(SymbolType::Function, name, addr)
+                .binding(Binding::Local)
+                .create(),
+        };
+        module_data.func_names.insert(*func_index, name.clone());
+        symbols.push(symbol);
+    }
+    symbols
+}
+
+fn write_u32(buf: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+fn write_name(buf: &mut Vec<u8>, name: &str) {
+    write_u32(buf, name.len() as u32);
+    buf.extend_from_slice(name.as_bytes());
+}
+
+fn write_name_map(buf: &mut Vec<u8>, names: &BTreeMap<u32, String>) {
+    write_u32(buf, names.len() as u32);
+    for (index, name) in names {
+        write_u32(buf, *index);
+        write_name(buf, name);
+    }
+}
+
+fn write_subsection(buf: &mut Vec<u8>, id: u8, contents: &[u8]) {
+    buf.push(id);
+    write_u32(buf, contents.len() as u32);
+    buf.extend_from_slice(contents);
+}
+
+fn read_u32(data: &[u8], pos: &mut usize) -> Option<u32> {
+    let mut result = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *data.get(*pos)?;
+        *pos += 1;
+        result |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Some(result);
+        }
+    }
+    None
+}
+
+// A name section with the names of functions and globals as they are now, with the
+// user's renames, and the names of locals and data segments that the module had.
+// Functions that only have the placeholder names the plugin gives them are left out.
+fn name_section(view: &BinaryView, module_data: &ModuleData) -> Vec<u8> {
+    let funcs = current_names(view, module_data)
+        .functions
+        .into_iter()
+        .filter(|(func_index, name)| *name != format!("func_{func_index}"))
+        .collect::<BTreeMap<_, _>>();
+    let mut globals = module_data.global_names.clone();
+    for (global_index, global) in module_data.globals.iter().enumerate() {
+        if let Some(symbol) = view.symbol_by_address(global.addr) {
+            globals.insert(global_index as u32, symbol.short_name().to_string());
+        }
+    }
+
+    let mut contents = Vec::new();
+    write_name(&mut contents, NAME_SECTION);
+    let mut subsection = Vec::new();
+    write_name_map(&mut subsection, &funcs);
+    write_subsection(&mut contents, FUNCTION_NAMES, &subsection);
+    if !module_data.local_names.is_empty() {
+        let mut subsection = Vec::new();
+        write_u32(&mut subsection, module_data.local_names.len() as u32);
+        for (func_index, names) in &module_data.local_names {
+            write_u32(&mut subsection, *func_index);
+            write_name_map(&mut subsection, names);
+        }
+        write_subsection(&mut contents, LOCAL_NAMES, &subsection);
+    }
+    for (id, names) in [
+        (GLOBAL_NAMES, &globals),
+        (DATA_NAMES, &module_data.data_names),
+    ] {
+        if !names.is_empty() {
+            let mut subsection = Vec::new();
+            write_name_map(&mut subsection, names);
+            write_subsection(&mut contents, id, &subsection);
+        }
+    }
+
+    let mut section = vec![0];
+    write_u32(&mut section, contents.len() as u32);
+    section.extend_from_slice(&contents);
+    section
+}
+
+// The module with its name section replaced by one with the current names, so that
+// other tools show the names given to functions and globals here.
+pub(crate) fn module_with_names(
+    view: &BinaryView,
+    module_data: &ModuleData,
+) -> Result<Vec<u8>, String> {
+    let parent = view
+        .parent_view()
+        .ok_or("The file contents are not available")?;
+    let range = &module_data.module_range;
+    let len = (range.end - range.start) as usize;
+    let mut data = Vec::new();
+    if parent.read_into_vec(&mut data, range.start, len) != len {
+        return Err("Failed to read the module".to_string());
+    }
+
+    // The module header, then each section but the old name section. The new one goes
+    // last, which is after the data section, as the name section should be.
+    let mut module = data.get(..8).ok_or("The module is truncated")?.to_vec();
+    let mut pos = 8;
+    while pos < data.len() {
+        let start = pos;
+        let id = data[pos];
+        pos += 1;
+        let size = read_u32(&data, &mut pos).ok_or("A section size is malformed")? as usize;
+        let contents = data.get(pos..pos + size).ok_or("A section is truncated")?;
+        pos += size;
+        if id == 0 {
+            let mut name_pos = 0;
+            let name_len = read_u32(contents, &mut name_pos).unwrap_or(0) as usize;
+            if contents.get(name_pos..name_pos + name_len) == Some(NAME_SECTION.as_bytes()) {
+                continue;
+            }
+        }
+        module.extend_from_slice(&data[start..pos]);
+    }
+    module.extend(name_section(view, module_data));
+    Ok(module)
+}
@@ -1,3 +1,18 @@
 pub mod module_data;
 pub mod func_parse;
 mod module_parse;
+pub(crate) mod data_parse;
+mod debug_info;
+mod instruction_tags;
+mod source_map;
+mod component;
+pub(crate) mod embedded;
+pub(crate) mod js_carrier;
+mod linking;
+pub(crate) mod local_types;
+pub(crate) mod pointers;
+mod reparse;
+pub(crate) mod shadow_stack;
+pub(crate) mod signature;
+pub(crate) mod stack_state;
+pub(crate) mod text;
@@ -0,0 +1,137 @@
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use binaryninja::interaction::get_choice_input;
+use binaryninja::section::SectionBuilder;
+use log::{info, warn};
+use std::ops::Range;
+use wasmparser::{Chunk, Parser, Payload};
+
+// Finds the core modules embedded in a component, including those in nested
+// components, in the order they appear in the file.
+fn find_core_modules(data: &[u8]) -> Result<Vec<Range<usize>>, ()> {
+    let mut modules = Vec::new();
+    let mut parser = Parser::new(0);
+    let mut stack = Vec::new();
+    let mut offset = 0;
+    loop {
+        let (payload, consumed) = match parser.parse(&data[offset..], true).map_err(|_| ())? {
+            Chunk::Parsed { consumed, payload } => (payload, consumed),
+            Chunk::NeedMoreData(_) => return Err(()),
+        };
+        offset += consumed;
+
+        match payload {
+            Payload::ModuleSection {
+                unchecked_range, ..
+            } => {
+                // The outer parser has already moved past the module, so skip its
+                // contents rather than parsing them.
+                if unchecked_range.end > data.len() {
+                    return Err(());
+                }
+                offset = unchecked_range.end;
+                modules.push(unchecked_range);
+            }
+            Payload::ComponentSection { parser: nested, .. } => {
+                stack.push(parser);
+                parser = nested;
+            }
+            Payload::End(_) => match stack.pop() {
+                Some(outer) => parser = outer,
+                None => break,
+            },
+            _ => {}
+        }
+    }
+    Ok(modules)
+}
+
+impl WebAssemblyView {
+    // Asks the user which of several modules to load, unless there is only one. The
+    // choice is stored under `metadata_key` and reused if this view is reopened from a
+    // database.
+    pub(crate) fn choose_module(
+        &mut self,
+        metadata_key: &str,
+        prompt: &str,
+        title: &str,
+        choices: &[String],
+    ) -> usize {
+        let stored_index = self
+            .query_metadata(metadata_key)
+            .and_then(|index| index.get_unsigned_integer())
+            .map(|index| index as usize)
+            .filter(|index| *index < choices.len());
+        let index = match (choices.len(), stored_index) {
+            (_, Some(index)) => index,
+            (1, None) => 0,
+            _ => {
+                let choices = choices.iter().map(String::as_str).collect::<Vec<_>>();
+                get_choice_input(prompt, title, &choices).unwrap_or(0)
+            }
+        };
+        self.store_metadata(metadata_key, index as u64, true);
+        index
+    }
+
+    // Components wrap one or more core modules, and only core modules contain code
+    // this plugin can analyze. Returns the range of the module to load: the whole
+    // file for a core module, or a core module picked by the user for a component.
+    // Files that aren't WebAssembly at all are searched for embedded modules.
+    pub(crate) fn select_core_module(&mut self, parent: &BinaryView) -> Result<Range<u64>, ()> {
+        let mut range = 0..parent.len();
+        let mut header = [0; 8];
+        if parent.read(&mut header, 0) != header.len() {
+            return Ok(range);
+        }
+        if !header.starts_with(b"\0asm") {
+            range = self.select_embedded_module(parent)?;
+            parent.read(&mut header, range.start);
+        }
+        if !Parser::is_component(&header) {
+            return Ok(range);
+        }
+
+        let len = (range.end - range.start) as usize;
+        let mut data = Vec::new();
+        if parent.read_into_vec(&mut data, range.start, len) != len {
+            return Err(());
+        }
+
+        let modules = find_core_modules(&data)?
+            .into_iter()
+            .map(|module| (range.start + module.start as u64)..(range.start + module.end as u64))
+            .collect::<Vec<_>>();
+        info!("File is a component with {} core modules", modules.len());
+        for (i, module) in modules.iter().enumerate() {
+            self.add_section(
+                SectionBuilder::new(format!(".module.{i}"), module.clone()).is_auto(true),
+            );
+        }
+        if modules.is_empty() {
+            warn!("Component does not contain any core modules");
+            return Err(());
+        }
+
+        let choices = modules
+            .iter()
+            .enumerate()
+            .map(|(i, module)| {
+                format!(
+                    "module {i} at {:#x} ({} bytes)",
+                    module.start,
+                    module.end - module.start
+                )
+            })
+            .collect::<Vec<_>>();
+        let index = self.choose_module(
+            "wasm.component.coreModule",
+            "Core module to analyze",
+            "WebAssembly Component",
+            &choices,
+        );
+
+        info!("Loading core module {index} of the component");
+        Ok(modules[index].clone())
+    }
+}
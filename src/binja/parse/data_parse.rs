@@ -0,0 +1,199 @@
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+use binaryninja::section::{SectionBuilder, Semantics};
+use binaryninja::segment::{SegmentBuilder, SegmentFlags};
+use binaryninja::symbol::{Symbol, SymbolType};
+use binaryninja::types::Type;
+use log::warn;
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+const MIN_STRING_LEN: usize = 4;
+
+// Decodes a single printable UTF-8 character, returning it along with its length.
+fn decode_printable(data: &[u8]) -> Option<(char, usize)> {
+    let len = match data.first()? {
+        0x00..=0x7f => 1,
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return None,
+    };
+    let c = std::str::from_utf8(data.get(..len)?).ok()?.chars().next()?;
+    (!c.is_control() || matches!(c, '\t' | '\n' | '\r')).then_some((c, len))
+}
+
+// Finds runs of printable text in `data`. Each range includes the terminating NUL
+// if there is one; Rust strings are not NUL-terminated, so a terminator is not
+// required.
+fn find_strings(data: &[u8]) -> Vec<Range<usize>> {
+    let mut strings = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let start = i;
+        let mut n_chars = 0;
+        let mut has_alnum = false;
+        while let Some((c, len)) = decode_printable(&data[i..]) {
+            has_alnum |= c.is_alphanumeric();
+            n_chars += 1;
+            i += len;
+        }
+
+        if n_chars >= MIN_STRING_LEN && has_alnum {
+            if data.get(i) == Some(&0) {
+                i += 1;
+            }
+            strings.push(start..i);
+        } else if i == start {
+            i += 1;
+        }
+    }
+    strings
+}
+
+// wasm-ld lays out read-only data below mutable data, and names the merged
+// segments after the input sections (".rodata", ".data", ".tdata", ...). Use the
+// names if the name section has them; otherwise, assume that the lowest segment of
+// a module with several segments holds read-only data.
+fn read_only_segments(module_data: &ModuleData) -> BTreeSet<usize> {
+    let mapped = module_data
+        .data_segments
+        .iter()
+        .enumerate()
+        .filter_map(|(i, segment)| Some((i, segment.memory_range()?)));
+
+    if !module_data.data_names.is_empty() {
+        return mapped
+            .filter(|(i, _)| {
+                module_data
+                    .data_names
+                    .get(&(*i as u32))
+                    .is_some_and(|name| name.starts_with(".rodata"))
+            })
+            .map(|(i, _)| i)
+            .collect();
+    }
+
+    let mapped = mapped.collect::<Vec<_>>();
+    if mapped.len() < 2 {
+        return BTreeSet::new();
+    }
+    mapped
+        .iter()
+        .min_by_key(|(_, range)| range.start)
+        .map(|(i, _)| *i)
+        .into_iter()
+        .collect()
+}
+
+// Linear memory as the data segments initialize it.
+pub(crate) struct DataImage {
+    pub(crate) segments: Vec<(Range<u64>, Vec<u8>)>,
+}
+
+impl DataImage {
+    pub(crate) fn new(parent: &BinaryView, module_data: &ModuleData) -> Self {
+        let mut segments = Vec::new();
+        for segment in &module_data.data_segments {
+            let Some(memory_range) = segment.memory_range() else {
+                continue;
+            };
+            let mut data = Vec::new();
+            let len = (segment.data_range.end - segment.data_range.start) as usize;
+            if parent.read_into_vec(&mut data, segment.data_range.start, len) == len {
+                segments.push((memory_range, data));
+            }
+        }
+        Self { segments }
+    }
+
+    pub(crate) fn read(&self, addr: u64, len: usize) -> Option<&[u8]> {
+        let (range, data) = self
+            .segments
+            .iter()
+            .find(|(range, _)| range.contains(&addr))?;
+        let start = (addr - range.start) as usize;
+        data.get(start..start + len)
+    }
+
+    pub(crate) fn read_u32(&self, addr: u64) -> Option<u32> {
+        Some(u32::from_le_bytes(self.read(addr, 4)?.try_into().ok()?))
+    }
+}
+
+impl WebAssemblyView {
+    // Maps active data segments into linear memory so that the constant addresses
+    // used by code can be followed.
+    pub(crate) fn map_data_segments(&mut self, module_data: &ModuleData) {
+        let read_only = read_only_segments(module_data);
+        for (i, segment) in module_data.data_segments.iter().enumerate() {
+            let Some(memory_range) = segment.memory_range() else {
+                continue;
+            };
+            if memory_range.is_empty() {
+                continue;
+            }
+            let range = (module_data.memory_base + memory_range.start)
+                ..(module_data.memory_base + memory_range.end);
+            let writable = !read_only.contains(&i);
+
+            self.add_segment(
+                SegmentBuilder::new(range.clone())
+                    .parent_backing(segment.data_range.clone())
+                    .flags(
+                        SegmentFlags::new()
+                            .contains_data(true)
+                            .contains_code(false)
+                            .readable(true)
+                            .writable(writable)
+                            .executable(false)
+                            .deny_write(!writable)
+                            .deny_execute(true),
+                    )
+                    .is_auto(true),
+            );
+            self.add_section(
+                SectionBuilder::new(format!(".data.{i}"), range.clone())
+                    .semantics(if writable {
+                        Semantics::ReadWriteData
+                    } else {
+                        Semantics::ReadOnlyData
+                    })
+                    .is_auto(true),
+            );
+
+            if let Some(name) = module_data.data_names.get(&(i as u32)) {
+                let symbol = Symbol::builder(SymbolType::Data, name, range.start).create();
+                self.define_auto_symbol(&symbol);
+            }
+        }
+    }
+
+    // Defines string data variables in the mapped data segments, so that the strings
+    // view and the decompiler's string rendering work.
+    pub(crate) fn define_data_strings(&mut self, parent: &BinaryView, module_data: &ModuleData) {
+        for segment in &module_data.data_segments {
+            let Some(memory_range) = segment.memory_range() else {
+                continue;
+            };
+
+            let mut data = Vec::new();
+            let len = (segment.data_range.end - segment.data_range.start) as usize;
+            let n_read = parent.read_into_vec(&mut data, segment.data_range.start, len);
+            if n_read != len {
+                warn!(
+                    "Failed to read data segment at {:#x}: expected {len} bytes, got {n_read}",
+                    segment.data_range.start
+                );
+                continue;
+            }
+
+            let base = module_data.memory_base + memory_range.start;
+            for string in find_strings(&data) {
+                let ty = Type::array(&Type::char(), string.len() as u64);
+                self.define_auto_data_var(base + string.start as u64, &ty);
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::interaction::get_open_filename_input;
+use log::{info, warn};
+use std::path::{Path, PathBuf};
+use wasmparser::BinaryReader;
+
+// Reads the single string that makes up the contents of sections such as
+// `external_debug_info` and `sourceMappingURL`.
+pub(crate) fn read_url_section(data: &[u8], offset: usize) -> Option<String> {
+    let mut reader = BinaryReader::new(data, offset);
+    let url = reader.read_string().ok()?;
+    reader.eof().then(|| url.to_string())
+}
+
+// Resolves a URL from a custom section to a local file, relative to the directory
+// of the module. Remote URLs cannot be resolved.
+pub(crate) fn resolve_local_url(module_path: &Path, url: &str) -> Option<PathBuf> {
+    if url.contains("://") && !url.starts_with("file://") {
+        return None;
+    }
+    let path = Path::new(url.strip_prefix("file://").unwrap_or(url));
+    let path = match module_path.parent() {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path.to_path_buf(),
+    };
+    path.is_file().then_some(path)
+}
+
+impl WebAssemblyView {
+    // Split-DWARF builds move the debug info into a separate wasm file named by the
+    // `external_debug_info` section. Find it, asking the user if it is not next to the
+    // module, for the DWARF parser to read its `.debug_*` sections.
+    pub(crate) fn locate_external_debug_info(&mut self, url: &str) -> Option<PathBuf> {
+        self.store_metadata("wasm.externalDebugInfo", url, true);
+
+        let module_path = PathBuf::from(self.file().filename());
+        let path = resolve_local_url(&module_path, url).or_else(|| {
+            info!("External debug info {url:?} was not found next to the module");
+            get_open_filename_input(&format!("Locate external debug info ({url})"), "*.wasm")
+        });
+        match &path {
+            Some(path) => info!("Reading external debug info from {}", path.display()),
+            None => warn!("External debug info {url:?} was not loaded"),
+        }
+        path
+    }
+}
@@ -0,0 +1,158 @@
+use crate::binja::view::WebAssemblyView;
+use crate::util::bin_util::BinaryReadable;
+use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use binaryninja::section::SectionBuilder;
+use log::{info, warn};
+use std::ops::Range;
+
+// Headers of core modules (version 1) and components (version 0xd, layer 1).
+const CORE_HEADER: &[u8; 8] = b"\0asm\x01\0\0\0";
+const COMPONENT_HEADER: &[u8; 8] = b"\0asm\x0d\0\x01\0";
+
+// How much of the file is searched for headers at a time.
+const SCAN_CHUNK_SIZE: usize = 1 << 20;
+
+// Ids of the known sections of core modules, in the order they must appear in.
+const CORE_SECTION_ORDER: &[u8] = &[1, 2, 3, 4, 5, 13, 6, 7, 8, 9, 12, 10, 11];
+const MAX_COMPONENT_SECTION_ID: u8 = 11;
+
+// A module found inside another file.
+pub(crate) struct EmbeddedModule {
+    pub range: Range<u64>,
+    pub is_component: bool,
+}
+
+impl EmbeddedModule {
+    pub(crate) fn label(&self) -> String {
+        let kind = if self.is_component {
+            "component"
+        } else {
+            "module"
+        };
+        format!(
+            "{kind} at {:#x} ({} bytes)",
+            self.range.start,
+            self.range.end - self.range.start
+        )
+    }
+}
+
+// Whether a custom section's contents start with a well-formed name.
+fn has_custom_name(view: &BinaryView, start: u64, size: u32) -> bool {
+    let Ok((name_len, n_bytes)) = view.read_u32_leb128(start) else {
+        return false;
+    };
+    if name_len as u64 + n_bytes as u64 > size as u64 {
+        return false;
+    }
+    let mut name = Vec::new();
+    let len = name_len as usize;
+    view.read_into_vec(&mut name, start + n_bytes as u64, len) == len
+        && std::str::from_utf8(&name).is_ok()
+}
+
+// The end of the module whose header is at `start`, found by following its section
+// headers for as long as they look like the sections of a module. Nothing in the
+// module says where it ends, so it ends where the sections stop making sense or the
+// view does. Returns `None` if there isn't a single section after the header, since
+// the header alone is too likely to just be a constant in the code around it.
+fn module_end(view: &BinaryView, start: u64, is_component: bool) -> Option<u64> {
+    let len = view.len();
+    let mut pos = start + CORE_HEADER.len() as u64;
+    let mut last_rank = None;
+    let mut n_sections = 0;
+    while pos < len {
+        let mut id = [0];
+        if view.read(&mut id, pos) != 1 {
+            break;
+        }
+        let id = id[0];
+        let rank = match (id, is_component) {
+            (0, _) => None,
+            (_, true) if id <= MAX_COMPONENT_SECTION_ID => None,
+            (_, true) => break,
+            (_, false) => match CORE_SECTION_ORDER.iter().position(|other| *other == id) {
+                Some(rank) if last_rank.is_none_or(|last| rank > last) => Some(rank),
+                _ => break,
+            },
+        };
+        let Ok((size, n_bytes)) = view.read_u32_leb128(pos + 1) else {
+            break;
+        };
+        let contents = pos + 1 + n_bytes as u64;
+        if contents + size as u64 > len || (id == 0 && !has_custom_name(view, contents, size)) {
+            break;
+        }
+        last_rank = rank.or(last_rank);
+        n_sections += 1;
+        pos = contents + size as u64;
+    }
+    (n_sections > 0).then_some(pos)
+}
+
+// Finds the modules and components embedded in the contents of `view`, e.g. in
+// Electron apps, packed loaders or firmware, in the order they appear. The core
+// modules in a component are part of it and aren't listed on their own.
+pub(crate) fn find_embedded_modules(view: &BinaryView) -> Vec<EmbeddedModule> {
+    let len = view.len();
+    let mut modules: Vec<EmbeddedModule> = Vec::new();
+    let mut buf = Vec::new();
+    let mut chunk_start = 0;
+    while chunk_start < len {
+        // Chunks overlap by less than a header, so that each header is in one chunk.
+        let chunk_len = SCAN_CHUNK_SIZE + CORE_HEADER.len() - 1;
+        buf.clear();
+        let n_read = view.read_into_vec(&mut buf, chunk_start, chunk_len);
+        if n_read < CORE_HEADER.len() {
+            break;
+        }
+        for (i, window) in buf[..n_read].windows(CORE_HEADER.len()).enumerate() {
+            let is_component = window == COMPONENT_HEADER;
+            if window != CORE_HEADER && !is_component {
+                continue;
+            }
+            let start = chunk_start + i as u64;
+            if modules.last().is_some_and(|last| start < last.range.end) {
+                continue;
+            }
+            if let Some(end) = module_end(view, start, is_component) {
+                modules.push(EmbeddedModule {
+                    range: start..end,
+                    is_component,
+                });
+            }
+        }
+        chunk_start += SCAN_CHUNK_SIZE as u64;
+    }
+    modules
+}
+
+impl WebAssemblyView {
+    // Files that aren't WebAssembly are loaded as one of the modules embedded in them.
+    // Returns the range of the module to load, picked by the user if there are several.
+    pub(crate) fn select_embedded_module(&mut self, parent: &BinaryView) -> Result<Range<u64>, ()> {
+        let modules = find_embedded_modules(parent);
+        info!("File contains {} embedded modules", modules.len());
+        for (i, module) in modules.iter().enumerate() {
+            let range = module.range.clone();
+            self.add_section(SectionBuilder::new(format!(".embedded.{i}"), range).is_auto(true));
+        }
+        if modules.is_empty() {
+            warn!("File does not contain any WebAssembly modules");
+            return Err(());
+        }
+        let labels = modules
+            .iter()
+            .map(EmbeddedModule::label)
+            .collect::<Vec<_>>();
+        let index = self.choose_module(
+            "wasm.embeddedModule",
+            "Embedded module to analyze",
+            "Embedded WebAssembly Modules",
+            &labels,
+        );
+
+        info!("Loading embedded {}", labels[index]);
+        Ok(modules[index].range.clone())
+    }
+}
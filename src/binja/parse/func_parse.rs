@@ -1,16 +1,25 @@
 use std::cell::OnceCell;
 use crate::binja::parse::module_data::{BranchTarget, BranchTargetAddr, FunctionData, OperatorData};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
 use std::pin::Pin;
-use wasmparser::{BinaryReader, FunctionBody, Operator};
+use std::sync::OnceLock;
+use wasmparser::{BinaryReader, Catch, FunctionBody, Operator, OperatorsReader};
 
 pub(crate) fn parse_func(
+    func_index: u32,
     size_start: u64,
     locals_start: u64,
     end: u64,
     raw: Pin<Box<[u8]>>,
 ) -> Result<FunctionData, ()> {
     let body = FunctionBody::new(BinaryReader::new(&raw, locals_start as usize));
+    let locals = body
+        .get_locals_reader()
+        .map_err(|_| ())?
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ())?;
     let mut ops_reader = body.get_operators_reader().map_err(|_| ())?;
     let ops_start = ops_reader.original_position() as u64;
 
@@ -63,7 +72,9 @@ pub(crate) fn parse_func(
 
     // Initial parsing phase.
     let mut ops = BTreeMap::new();
+    let mut block_starts = BTreeMap::new();
     let mut unpatched_branches: BTreeMap<u64, BranchTarget<LabelKind>> = BTreeMap::new();
+    let mut catch_labels: BTreeMap<u64, Vec<LabelKind>> = BTreeMap::new();
     while !ops_reader.eof() {
         let offset = ops_reader.original_position() as u64;
         let op = ops_reader.read().map_err(|_| ())?;
@@ -76,6 +87,22 @@ pub(crate) fn parse_func(
             Operator::Loop { .. } => {
                 push_block(&mut blocks, &mut block_stack, offset, BlockKind::Loop);
             }
+            Operator::Try { .. } => {
+                push_block(&mut blocks, &mut block_stack, offset, BlockKind::Normal);
+            }
+            Operator::TryTable { try_table } => {
+                // The labels of the catch clauses are outside of the `try_table` block.
+                let labels = try_table.catches.iter().map(|catch| {
+                    let (Catch::One { label, .. }
+                    | Catch::OneRef { label, .. }
+                    | Catch::All { label }
+                    | Catch::AllRef { label }) = *catch;
+                    let block_id = get_nth_block_id(&block_stack, label)?;
+                    Ok(LabelKind::Break(block_id))
+                }).collect::<Result<Vec<_>, _>>()?;
+                catch_labels.insert(offset, labels);
+                push_block(&mut blocks, &mut block_stack, offset, BlockKind::Normal);
+            }
             Operator::If { .. } => {
                 let block_id = push_block(&mut blocks, &mut block_stack, offset, BlockKind::If);
                 unpatched_branches.insert(offset, BranchTarget::Conditional{
@@ -127,8 +154,17 @@ pub(crate) fn parse_func(
 
                 if matches!(block.kind, BlockKind::Function) {
                     unpatched_branches.insert(offset, BranchTarget::FunctionEnd);
+                } else {
+                    block_starts.insert(offset, block.start);
                 }
             }
+            Operator::Delegate { .. } => {
+                // Ends a legacy `try` block in place of its `end`.
+                let block_id = block_stack.pop().ok_or(())?;
+                let block = blocks.get_mut(block_id).ok_or(())?;
+                block.after.set(next_offset).map_err(|_| ())?;
+                block_starts.insert(offset, block.start);
+            }
             _ => {}
         }
 
@@ -195,12 +231,90 @@ pub(crate) fn parse_func(
         ops.get_mut(offset).ok_or(())?.target = Some(branch);
     }
 
-    Ok(FunctionData::new(
+    // Find the instructions that no path from the start of the function reaches, such
+    // as the code after a `br` or `return` up to the end of its block. Branches only go
+    // forward, or back to the start of a loop that is entered from above, so a single
+    // pass in address order finds them.
+    let mut reached = BTreeSet::new();
+    for (offset, labels) in &catch_labels {
+        if ops.contains_key(offset) {
+            for label in labels {
+                reached.insert(patch_label(label)?);
+            }
+        }
+    }
+    let mut unreachable: Vec<Range<u64>> = Vec::new();
+    let mut falls_through = true;
+    let mut in_unreachable = false;
+    for (offset, op) in &ops {
+        // Legacy exception handlers are entered from the calls in their `try` blocks.
+        let is_handler = matches!(op.op, Operator::Catch { .. } | Operator::CatchAll);
+        if !(falls_through || is_handler || reached.contains(offset)) {
+            // `end`s are only markers, and don't start a range of their own.
+            let op_end = offset + op.size as u64;
+            match unreachable.last_mut() {
+                Some(range) if in_unreachable => range.end = op_end,
+                _ if matches!(op.op, Operator::End) => continue,
+                _ => unreachable.push(*offset..op_end),
+            }
+            in_unreachable = true;
+            continue;
+        }
+        in_unreachable = false;
+
+        falls_through = match &op.target {
+            Some(BranchTargetAddr::Unconditional(target)) => {
+                reached.insert(*target);
+                false
+            }
+            Some(BranchTargetAddr::Conditional { true_target, false_target }) => {
+                reached.insert(*true_target);
+                reached.insert(*false_target);
+                false
+            }
+            Some(BranchTargetAddr::Table { targets, default_target }) => {
+                reached.extend(targets);
+                reached.insert(*default_target);
+                false
+            }
+            Some(BranchTargetAddr::FunctionEnd) => false,
+            None => !matches!(
+                op.op,
+                Operator::Unreachable
+                    | Operator::Return
+                    | Operator::ReturnCall { .. }
+                    | Operator::ReturnCallIndirect { .. }
+                    | Operator::ReturnCallRef { .. }
+                    | Operator::Throw { .. }
+                    | Operator::ThrowRef
+                    | Operator::Rethrow { .. }
+            ),
+        };
+    }
+
+    Ok(FunctionData {
+        func_index,
         size_start,
         locals_start,
         ops_start,
         end,
+        locals,
         ops,
-        raw,
-    ))
+        block_starts,
+        unreachable,
+        stack_layout: OnceLock::new(),
+        _raw: raw,
+    })
+}
+
+// Decodes the operator at the start of `data` on its own, for bytes outside of the
+// parsed functions. Branch targets are unknown without the enclosing blocks.
+pub(crate) fn parse_operator(data: &[u8]) -> Option<OperatorData<'_>> {
+    let mut reader = OperatorsReader::new(BinaryReader::new(data, 0));
+    let op = reader.read().ok()?;
+    Some(OperatorData {
+        op,
+        size: reader.original_position(),
+        target: None,
+    })
 }
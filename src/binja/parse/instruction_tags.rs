@@ -0,0 +1,108 @@
+use crate::binja::arch::opcode_name;
+use crate::binja::parse::module_data::{ModuleData, OperatorData};
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use log::info;
+use std::collections::BTreeMap;
+use wasmparser::Operator;
+
+// Kinds of instructions that are tagged, as entry points for the analyst: the traps,
+// the calls whose callee isn't fixed, where memory grows, and the shared-memory code.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum TagKind {
+    Unreachable,
+    IndirectCall,
+    MemoryGrow,
+    Atomic,
+}
+
+const TAG_KINDS: [TagKind; 4] = [
+    TagKind::Unreachable,
+    TagKind::IndirectCall,
+    TagKind::MemoryGrow,
+    TagKind::Atomic,
+];
+
+// Names of the tag types that instructions are tagged with.
+pub(crate) fn tag_type_names() -> impl Iterator<Item = &'static str> {
+    TAG_KINDS.into_iter().map(TagKind::name)
+}
+
+impl TagKind {
+    fn name(self) -> &'static str {
+        match self {
+            TagKind::Unreachable => "Unreachable",
+            TagKind::IndirectCall => "Indirect Call",
+            TagKind::MemoryGrow => "Memory Growth",
+            TagKind::Atomic => "Atomic",
+        }
+    }
+
+    fn icon(self) -> &'static str {
+        match self {
+            TagKind::Unreachable => "⛔",
+            TagKind::IndirectCall => "🔀",
+            TagKind::MemoryGrow => "📈",
+            TagKind::Atomic => "⚛",
+        }
+    }
+}
+
+fn tag_kind(module_data: &ModuleData, op: &OperatorData) -> Option<TagKind> {
+    match op.op {
+        Operator::Unreachable => Some(TagKind::Unreachable),
+        Operator::CallIndirect { .. }
+        | Operator::ReturnCallIndirect { .. }
+        | Operator::CallRef { .. }
+        | Operator::ReturnCallRef { .. } => Some(TagKind::IndirectCall),
+        Operator::MemoryGrow { .. } => Some(TagKind::MemoryGrow),
+        // All of the atomic instructions have `atomic` in their names, e.g.
+        // `i32.atomic.rmw.add` and `memory.atomic.wait32`.
+        _ => opcode_name(module_data, op)
+            .filter(|name| name.contains("atomic"))
+            .map(|_| TagKind::Atomic),
+    }
+}
+
+impl WebAssemblyView {
+    // Tags the reachable instructions that are worth a look, so that the tags pane
+    // lists all of the sites of each kind.
+    pub(crate) fn tag_notable_instructions(&mut self, module_data: &ModuleData) {
+        let mut tags = BTreeMap::<TagKind, Vec<(u64, String)>>::new();
+        for (_, func) in module_data.funcs.iter() {
+            let func = func.as_ref();
+            let func_name = module_data.func_name(func.func_index);
+            for (addr, op) in func.reachable_ops() {
+                let Some(kind) = tag_kind(module_data, op) else {
+                    continue;
+                };
+                let data = match (kind, module_data.indirect_call_targets.get(addr)) {
+                    (TagKind::IndirectCall, Some(callees)) => {
+                        format!("in {func_name}, {} possible callees", callees.len())
+                    }
+                    (TagKind::Atomic, _) => {
+                        let name = opcode_name(module_data, op).unwrap_or_default();
+                        format!("{name} in {func_name}")
+                    }
+                    _ => format!("in {func_name}"),
+                };
+                tags.entry(kind).or_default().push((*addr, data));
+            }
+        }
+
+        for (kind, sites) in &tags {
+            let tag_type = match self.tag_type_by_name(kind.name()) {
+                Some(tag_type) => tag_type,
+                None => self.create_tag_type(kind.name(), kind.icon()),
+            };
+            for (addr, data) in sites {
+                self.add_tag(*addr, &tag_type, data, false);
+            }
+            info!(
+                "Tagged {} {} sites",
+                sites.len(),
+                kind.name().to_lowercase()
+            );
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+
+// Headers of core modules (version 1) and components (version 0xd, layer 1).
+const HEADERS: [&[u8; 8]; 2] = [b"\0asm\x01\0\0\0", b"\0asm\x0d\0\x01\0"];
+
+// What `\0asm` starts with in base64. Modules are usually encoded on their own, so
+// their encoding starts at the start of the string.
+const BASE64_MAGIC: &str = "AGFzbQ";
+
+// Scripts larger than this are not searched for modules.
+const MAX_SCRIPT_LEN: usize = 64 << 20;
+
+// A module carried inline by a script: where its literal starts in the file, how it
+// is written, and the module itself.
+pub(crate) struct CarriedModule {
+    pub offset: usize,
+    pub encoding: &'static str,
+    pub data: Vec<u8>,
+}
+
+fn base64_value(c: u8) -> Option<u32> {
+    match c {
+        b'A'..=b'Z' => Some((c - b'A') as u32),
+        b'a'..=b'z' => Some((c - b'a') as u32 + 26),
+        b'0'..=b'9' => Some((c - b'0') as u32 + 52),
+        // URL-safe base64 uses `-` and `_` instead.
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    }
+}
+
+// Decodes base64, up to the first character that isn't part of it. Padding is left
+// out, since the data ends where the characters do.
+fn decode_base64(text: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(text.len() / 4 * 3);
+    let mut bits = 0u32;
+    let mut n_bits = 0;
+    for value in text.iter().map_while(|c| base64_value(*c)) {
+        bits = (bits << 6) | value;
+        n_bits += 6;
+        if n_bits >= 8 {
+            n_bits -= 8;
+            data.push((bits >> n_bits) as u8);
+        }
+    }
+    data
+}
+
+// Reads an integer literal from 0 to 255, in decimal or hex, at `*pos`.
+fn read_byte_literal(text: &[u8], pos: &mut usize) -> Option<u8> {
+    let (radix, start) = match text.get(*pos..*pos + 2) {
+        Some(b"0x" | b"0X") => (16, *pos + 2),
+        _ => (10, *pos),
+    };
+    let len = text[start..]
+        .iter()
+        .take_while(|c| (**c as char).is_digit(radix))
+        .count();
+    let digits = std::str::from_utf8(&text[start..start + len]).ok()?;
+    let value = u8::from_str_radix(digits, radix).ok()?;
+    *pos = start + len;
+    Some(value)
+}
+
+// Decodes the array literal of bytes after the `[` at `start`, e.g. the argument of
+// `new Uint8Array([0, 97, 115, 109, ...])`. Gives up as soon as the array turns out
+// not to be a module.
+fn decode_array(text: &[u8], start: usize) -> Option<Vec<u8>> {
+    let skip_whitespace = |pos: &mut usize| {
+        while text.get(*pos).is_some_and(u8::is_ascii_whitespace) {
+            *pos += 1;
+        }
+    };
+    let mut data = Vec::new();
+    let mut pos = start + 1;
+    loop {
+        skip_whitespace(&mut pos);
+        if text.get(pos) == Some(&b']') {
+            break;
+        }
+        data.push(read_byte_literal(text, &mut pos)?);
+        if data.len() == HEADERS[0].len() && !HEADERS.iter().any(|header| data == header[..]) {
+            return None;
+        }
+        skip_whitespace(&mut pos);
+        match text.get(pos)? {
+            b',' => pos += 1,
+            b']' => break,
+            _ => return None,
+        }
+    }
+    (data.len() > HEADERS[0].len()).then_some(data)
+}
+
+// The module whose literal starts at `pos`, how it is written, and how long the
+// literal is, or at least how far to skip ahead.
+fn decode_literal(text: &[u8], pos: usize) -> Option<(&'static str, Vec<u8>, usize)> {
+    if text[pos..].starts_with(BASE64_MAGIC.as_bytes()) {
+        let len = text[pos..]
+            .iter()
+            .take_while(|c| base64_value(**c).is_some())
+            .count();
+        let data = decode_base64(&text[pos..pos + len]);
+        let is_module = HEADERS.iter().any(|header| data.starts_with(&header[..]));
+        return is_module.then_some(("base64 string", data, len));
+    }
+    if text[pos] == b'[' {
+        return Some(("byte array", decode_array(text, pos)?, 1));
+    }
+    None
+}
+
+// Finds the modules that a script carries inline, in the order they appear: base64
+// strings, as passed to `WebAssembly.instantiate` after `atob` or `Buffer.from` or in
+// `data:` URLs, and literal arrays of bytes.
+pub(crate) fn find_carried_modules(text: &[u8]) -> Vec<CarriedModule> {
+    let mut modules = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let Some((encoding, data, len)) = decode_literal(text, pos) else {
+            pos += 1;
+            continue;
+        };
+        modules.push(CarriedModule {
+            offset: pos,
+            encoding,
+            data,
+        });
+        pos += len;
+    }
+    modules
+}
+
+// Whether the array literal after the `[` at `start` begins with the bytes of `\0asm`.
+fn array_starts_module(text: &[u8], start: usize) -> bool {
+    let mut pos = start + 1;
+    for expected in b"\0asm" {
+        while text
+            .get(pos)
+            .is_some_and(|c| c.is_ascii_whitespace() || *c == b',')
+        {
+            pos += 1;
+        }
+        if read_byte_literal(text, &mut pos) != Some(*expected) {
+            return false;
+        }
+    }
+    true
+}
+
+// Whether `text` has what looks like the start of a module literal, which is much
+// cheaper to check for than decoding the modules.
+fn has_module_marker(text: &[u8]) -> bool {
+    let magic = BASE64_MAGIC.as_bytes();
+    text.windows(magic.len()).any(|window| window == magic)
+        || (0..text.len()).any(|pos| text[pos] == b'[' && array_starts_module(text, pos))
+}
+
+// Whether `data`, the start of a file, could be a script. Binary files have NUL bytes
+// early on, which scripts don't.
+pub(crate) fn may_be_script(data: &[u8]) -> bool {
+    !data.is_empty() && !data.contains(&0)
+}
+
+// Whether `data`, the start of a file that may be a script, shows a module literal,
+// as scripts that are mostly a module do.
+pub(crate) fn probe_carries_module(data: &[u8]) -> bool {
+    may_be_script(data) && has_module_marker(data)
+}
+
+fn read_script(view: &BinaryView) -> Option<Vec<u8>> {
+    let len = view.len() as usize;
+    let mut text = Vec::new();
+    if len > MAX_SCRIPT_LEN || view.read_into_vec(&mut text, 0, len) != len {
+        return None;
+    }
+    Some(text)
+}
+
+// Whether the script in `view` shows a module literal anywhere, if it isn't too large
+// to search. This takes a read of the whole file.
+pub(crate) fn script_carries_module(view: &BinaryView) -> bool {
+    read_script(view).is_some_and(|text| has_module_marker(&text))
+}
+
+// Finds the modules carried by the script in `view`, if it isn't too large to search.
+pub(crate) fn read_carried_modules(view: &BinaryView) -> Vec<CarriedModule> {
+    read_script(view)
+        .map(|text| find_carried_modules(&text))
+        .unwrap_or_default()
+}
@@ -0,0 +1,128 @@
+use crate::binja::arch::relocation_info;
+use crate::binja::parse::module_data::{ModuleData, RelocationData, SymbolData};
+use crate::binja::view::WebAssemblyView;
+use binaryninja::architecture::CoreArchitecture;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::symbol::{Symbol, SymbolType};
+use log::info;
+use wasmparser::{Linking, LinkingSectionReader, RelocSectionReader, SymbolInfo};
+
+// Object files (`clang -c`, `rustc --emit=obj`) carry a symbol table in the `linking`
+// section, which the relocations in the `reloc.*` sections refer to by index.
+pub(crate) fn read_linking_section(
+    reader: LinkingSectionReader,
+    module_data: &mut ModuleData,
+) -> Result<(), ()> {
+    for subsection in reader.subsections() {
+        let Linking::SymbolTable(symbols) = subsection.map_err(|_| ())? else {
+            continue;
+        };
+        for symbol in symbols {
+            let (symbol, name, names) = match symbol.map_err(|_| ())? {
+                SymbolInfo::Func { index, name, .. } => (
+                    SymbolData::Func { index },
+                    name.map(|name| (index, name)),
+                    &mut module_data.func_names,
+                ),
+                SymbolInfo::Global { index, name, .. } => (
+                    SymbolData::Global { index },
+                    name.map(|name| (index, name)),
+                    &mut module_data.global_names,
+                ),
+                SymbolInfo::Event { index, name, .. } => (
+                    SymbolData::Tag { index },
+                    name.map(|name| (index, name)),
+                    &mut module_data.tag_names,
+                ),
+                SymbolInfo::Data { name, symbol, .. } => {
+                    module_data.symbols.push(SymbolData::Data {
+                        name: name.to_string(),
+                        segment: symbol.map(|symbol| (symbol.index, symbol.offset as u64)),
+                    });
+                    continue;
+                }
+                SymbolInfo::Table { index, .. } => {
+                    module_data.symbols.push(SymbolData::Table { index });
+                    continue;
+                }
+                SymbolInfo::Section { section, .. } => {
+                    module_data.symbols.push(SymbolData::Section { section });
+                    continue;
+                }
+            };
+            if let Some((index, name)) = name {
+                names.entry(index).or_insert_with(|| name.to_string());
+            }
+            module_data.symbols.push(symbol);
+        }
+    }
+    Ok(())
+}
+
+// Relocation offsets are relative to the contents of the section they apply to, which
+// comes earlier in the module.
+pub(crate) fn read_reloc_section(
+    reader: RelocSectionReader,
+    module_data: &mut ModuleData,
+) -> Result<(), ()> {
+    let start = *module_data
+        .section_starts
+        .get(reader.section_index() as usize)
+        .ok_or(())?;
+    for entry in reader.entries() {
+        let entry = entry.map_err(|_| ())?;
+        module_data.relocations.insert(
+            start + entry.offset as u64,
+            RelocationData {
+                ty: entry.ty,
+                index: entry.index,
+                addend: entry.addend,
+            },
+        );
+    }
+    Ok(())
+}
+
+impl WebAssemblyView {
+    // Defines the relocations of an object file, so that binja shows the indices and
+    // addresses they resolve to instead of the placeholders, and references what they
+    // refer to.
+    pub(crate) fn define_relocations(&mut self, module_data: &ModuleData) -> Result<(), ()> {
+        if module_data.relocations.is_empty() {
+            return Ok(());
+        }
+        let arch = CoreArchitecture::by_name("wasm").ok_or(())?;
+        let mut n_defined = 0;
+        for (&addr, reloc) in &module_data.relocations {
+            let Some(target) = module_data.relocation_target(reloc) else {
+                continue;
+            };
+            self.define_relocation(&arch, &relocation_info(reloc.ty, addr), target, addr);
+            n_defined += 1;
+        }
+        info!(
+            "Defined {n_defined} of {} relocations",
+            module_data.relocations.len()
+        );
+        Ok(())
+    }
+
+    // Names the data in linear memory that the symbol table defines.
+    pub(crate) fn define_data_symbols(&mut self, module_data: &ModuleData) {
+        for symbol in &module_data.symbols {
+            let SymbolData::Data {
+                name,
+                segment: Some((segment_index, offset)),
+            } = symbol
+            else {
+                continue;
+            };
+            let Some(addr) = module_data.data_symbol_address(*segment_index, *offset) else {
+                continue;
+            };
+            let symbol =
+                Symbol::builder(SymbolType::Data, name, module_data.memory_base + addr).create();
+            self.define_auto_symbol(&symbol);
+        }
+    }
+}
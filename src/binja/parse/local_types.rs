@@ -0,0 +1,219 @@
+use crate::binja::arch::stack_effect::{local_type, stack_effect};
+use crate::binja::arch::WebAssemblyRegister;
+use crate::binja::parse::module_data::{FunctionData, ModuleData};
+use crate::binja::parse::pointers::infer_pointer_locals;
+use crate::binja::parse::signature::is_comparison;
+use binaryninja::architecture::Register;
+use binaryninja::rc::Ref;
+use binaryninja::types::Type;
+use binaryninja::variable::{Variable, VariableSourceType};
+use std::collections::BTreeMap;
+use wasmparser::{Operator, ValType};
+
+// Where a value on the operand stack came from, as far as the types of locals go.
+#[derive(Clone, Copy)]
+enum Value {
+    Local(u32),
+    // The result of a comparison, or the constant 0 or 1.
+    Bool,
+    Other,
+}
+
+// How a local is used.
+#[derive(Default)]
+struct LocalUses {
+    n_gets: usize,
+    // Gets whose value is only tested: by `if`, `br_if`, `select` and `i32.eqz`.
+    n_tests: usize,
+    n_sets: usize,
+    n_bool_sets: usize,
+    // Uses by operators that treat their operands as signed or unsigned.
+    n_signed: usize,
+    n_unsigned: usize,
+}
+
+// Whether an operator treats its integer operands as signed or unsigned, if it makes a
+// difference.
+fn signedness(op: &Operator) -> Option<bool> {
+    match op {
+        Operator::I32LtS
+        | Operator::I32GtS
+        | Operator::I32LeS
+        | Operator::I32GeS
+        | Operator::I64LtS
+        | Operator::I64GtS
+        | Operator::I64LeS
+        | Operator::I64GeS
+        | Operator::I32DivS
+        | Operator::I32RemS
+        | Operator::I32ShrS
+        | Operator::I64DivS
+        | Operator::I64RemS
+        | Operator::I64ShrS
+        | Operator::I64ExtendI32S
+        | Operator::F32ConvertI32S
+        | Operator::F32ConvertI64S
+        | Operator::F64ConvertI32S
+        | Operator::F64ConvertI64S => Some(true),
+        Operator::I32LtU
+        | Operator::I32GtU
+        | Operator::I32LeU
+        | Operator::I32GeU
+        | Operator::I64LtU
+        | Operator::I64GtU
+        | Operator::I64LeU
+        | Operator::I64GeU
+        | Operator::I32DivU
+        | Operator::I32RemU
+        | Operator::I32ShrU
+        | Operator::I64DivU
+        | Operator::I64RemU
+        | Operator::I64ShrU
+        | Operator::I64ExtendI32U
+        | Operator::F32ConvertI32U
+        | Operator::F32ConvertI64U
+        | Operator::F64ConvertI32U
+        | Operator::F64ConvertI64U => Some(false),
+        _ => None,
+    }
+}
+
+// Follows the operand stack through straight-line code, like `find_accesses`, to find
+// how the value of each local is used and where the values set to it come from.
+fn find_uses(module_data: &ModuleData, func: &FunctionData) -> BTreeMap<u32, LocalUses> {
+    let mut uses = BTreeMap::<u32, LocalUses>::new();
+    let mut stack: Vec<Value> = Vec::new();
+    for (addr, op) in &func.ops {
+        if !func.is_reachable(*addr) {
+            stack.clear();
+            continue;
+        }
+        let op = &op.op;
+        match *op {
+            Operator::LocalGet { local_index } => {
+                uses.entry(local_index).or_default().n_gets += 1;
+                stack.push(Value::Local(local_index));
+                continue;
+            }
+            Operator::LocalSet { local_index } | Operator::LocalTee { local_index } => {
+                let value = stack.pop().unwrap_or(Value::Other);
+                let local = uses.entry(local_index).or_default();
+                local.n_sets += 1;
+                if let Value::Bool = value {
+                    local.n_bool_sets += 1;
+                }
+                if let Operator::LocalTee { .. } = op {
+                    stack.push(value);
+                }
+                continue;
+            }
+            Operator::I32Const { value: 0 | 1 } => {
+                stack.push(Value::Bool);
+                continue;
+            }
+            Operator::If { .. } | Operator::BrIf { .. } | Operator::I32Eqz => {
+                if let Some(Value::Local(local_index)) = stack.last() {
+                    uses.entry(*local_index).or_default().n_tests += 1;
+                }
+            }
+            Operator::Select | Operator::TypedSelect { .. } => {
+                if let Some(Value::Local(local_index)) = stack.last() {
+                    uses.entry(*local_index).or_default().n_tests += 1;
+                }
+                stack.clear();
+                continue;
+            }
+            _ => {}
+        }
+
+        let Some(effect) = stack_effect(module_data, Some(func), op) else {
+            stack.clear();
+            continue;
+        };
+        let n_pops = effect.pops.len().min(stack.len());
+        let signed = signedness(op);
+        for value in stack.drain(stack.len() - n_pops..) {
+            let (Value::Local(local_index), Some(signed)) = (value, signed) else {
+                continue;
+            };
+            let local = uses.entry(local_index).or_default();
+            match signed {
+                true => local.n_signed += 1,
+                false => local.n_unsigned += 1,
+            }
+        }
+        let pushed = match is_comparison(op) {
+            true => Value::Bool,
+            false => Value::Other,
+        };
+        stack.extend(effect.pushes.iter().map(|_| pushed));
+    }
+    uses
+}
+
+// Infers types for a function's parameters and locals from how they are used, where
+// they say more than the wasm types do: pointers, which are used as addresses; bools,
+// which are only tested and only set to 0, 1 and the results of comparisons; and
+// unsigned integers, which are mostly compared, divided and shifted as unsigned.
+// Returns the types by local index.
+pub(crate) fn infer_local_types(
+    module_data: &ModuleData,
+    func: &FunctionData,
+) -> BTreeMap<u32, Ref<Type>> {
+    let n_params = module_data
+        .func_type(func.func_index)
+        .map_or(0, |ty| ty.params().len() as u32);
+    let mut types = infer_pointer_locals(module_data, func);
+    for (local_index, uses) in find_uses(module_data, func) {
+        if types.contains_key(&local_index) {
+            continue;
+        }
+        let width = match local_type(module_data, func, local_index) {
+            Some(ValType::I32) => 4,
+            Some(ValType::I64) => 8,
+            _ => continue,
+        };
+        let is_bool = width == 4
+            && uses.n_gets > 0
+            && uses.n_tests == uses.n_gets
+            && uses.n_bool_sets == uses.n_sets
+            && (uses.n_sets > 0 || local_index < n_params);
+        if is_bool {
+            types.insert(local_index, Type::bool());
+        } else if uses.n_unsigned > uses.n_signed {
+            types.insert(local_index, Type::int(width, false));
+        }
+    }
+    types
+}
+
+// The variables of the locals of a function that aren't parameters, with the types
+// that their uses show and their names. Parameters are typed with the function.
+pub(crate) fn local_vars(
+    module_data: &ModuleData,
+    func: &FunctionData,
+) -> Vec<(Variable, Ref<Type>, String)> {
+    let Some(ty) = module_data.func_type(func.func_index) else {
+        return Vec::new();
+    };
+    let n_params = ty.params().len() as u32;
+    let mut vars = Vec::new();
+    for (local_index, ty) in infer_local_types(module_data, func) {
+        if local_index < n_params {
+            continue;
+        }
+        let reg = local_type(module_data, func, local_index)
+            .and_then(|wasm_ty| WebAssemblyRegister::for_local(n_params, local_index, wasm_ty));
+        let Some(reg) = reg else {
+            continue;
+        };
+        let var = Variable::new(
+            VariableSourceType::RegisterVariableSourceType,
+            0,
+            reg.id().0 as i64,
+        );
+        let name = module_data.local_name(func.func_index, local_index);
+        vars.push((var, ty, name));
+    }
+    vars
+}
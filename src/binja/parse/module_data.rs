@@ -1,10 +1,19 @@
+use crate::binja::arch::WebAssemblyRegister;
+use crate::binja::parse::stack_state::StackLayout;
+use crate::binja::settings::{ConstantDisplay, LoadSettings};
 use crate::util::arc_identity::ArcIdentity;
+use binaryninja::architecture::Register;
 use once_cell::sync::Lazy;
 use rangemap::RangeMap;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
 use std::pin::Pin;
-use std::sync::Mutex;
-use wasmparser::Operator;
+use std::sync::{Mutex, OnceLock};
+use wasmparser::{
+    FuncType, GlobalType, MemoryType, Operator, RelocationType, TableType, TagType, ValType,
+};
 
 // Unfortunately, due to limitations of the binja rust API, we need to store module data
 // in a global static variable...
@@ -35,6 +44,8 @@ pub struct OperatorData<'a> {
 
 #[derive(Debug)]
 pub struct FunctionData {
+    pub func_index: u32,
+
     // Address of the size:u32 field in the function header.
     pub size_start: u64,
 
@@ -47,6 +58,10 @@ pub struct FunctionData {
     // Address of the end of the function (exclusive).
     pub end: u64,
 
+    // Locals declared in the function header after the parameters, as runs of
+    // (count, type) in declaration order.
+    pub locals: Vec<(u32, ValType)>,
+
     // NB: Unfortunately `Operator` references the raw function bytes, so we need to store
     // the entire function body in memory.
     //
@@ -56,32 +71,396 @@ pub struct FunctionData {
     //
     // `ops` and `ops_raw` must be declared in this order to ensure correct drop order.
     pub ops: BTreeMap<u64, OperatorData<'static>>,
+
+    // Address of the instruction that opens the block each `end` or `delegate`
+    // closes, by the address of the `end`. The function's final `end` is not included.
+    pub block_starts: BTreeMap<u64, u64>,
+
+    // Ranges of instructions that no path from the start of the function reaches, such
+    // as code after a `br` or `return` that nothing branches to.
+    pub unreachable: Vec<Range<u64>>,
+
+    // What the lifter needs to know about the operand stack, worked out the first time
+    // the function is lifted.
+    pub stack_layout: OnceLock<StackLayout>,
+
     pub _raw: Pin<Box<[u8]>>,
 }
 
 impl FunctionData {
-    pub fn new(
-        size_start: u64,
-        locals_start: u64,
-        ops_start: u64,
-        end: u64,
-        ops: BTreeMap<u64, OperatorData<'static>>,
-        raw: Pin<Box<[u8]>>,
-    ) -> Self {
-        Self {
-            size_start,
-            locals_start,
-            ops_start,
-            end,
-            ops,
-            _raw: raw,
+    pub fn is_reachable(&self, addr: u64) -> bool {
+        !self.unreachable.iter().any(|range| range.contains(&addr))
+    }
+
+    // The operators that some path from the start of the function reaches.
+    pub fn reachable_ops(&self) -> impl Iterator<Item = (&u64, &OperatorData<'static>)> {
+        self.ops.iter().filter(|(addr, _)| self.is_reachable(**addr))
+    }
+
+    // The operator at `addr`, unless it has been patched since the module was parsed,
+    // in which case `data` (the bytes now at `addr`) no longer hold it.
+    pub fn unpatched_op(&self, addr: u64, data: &[u8]) -> Option<&OperatorData<'static>> {
+        let op = self.ops.get(&addr)?;
+        let start = (addr - self.locals_start) as usize;
+        let raw = self._raw.get(start..start + op.size)?;
+        (data.get(..op.size)? == raw).then_some(op)
+    }
+}
+
+#[derive(Debug)]
+pub enum ElementMode {
+    // Copied into a table at instantiation time. `offset` is `None` if the offset
+    // expression is not a constant (e.g. `global.get` of an imported global).
+    Active { table_index: u32, offset: Option<u64> },
+
+    // Only copied into a table by `table.init`.
+    Passive,
+
+    // Only forward-declares functions for `ref.func`.
+    Declared,
+}
+
+#[derive(Debug)]
+pub struct ElementSegment {
+    pub mode: ElementMode,
+
+    // Function index of each item, or `None` for null references and items that
+    // are not a constant `ref.func`.
+    pub items: Vec<Option<u32>>,
+}
+
+#[derive(Debug)]
+pub struct TableData {
+    pub ty: TableType,
+
+    // Address of the table's entry in the table or import section.
+    pub addr: u64,
+
+    // Whether the table comes from the import section rather than the table section.
+    pub imported: bool,
+}
+
+impl fmt::Display for TableData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, {} min", self.ty.element_type, self.ty.initial)?;
+        if let Some(maximum) = self.ty.maximum {
+            write!(f, ", {maximum} max")?;
+        }
+        if self.ty.table64 {
+            write!(f, ", 64-bit")?;
+        }
+        if self.ty.shared {
+            write!(f, ", shared")?;
+        }
+        if self.imported {
+            write!(f, " (imported)")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct MemoryData {
+    pub ty: MemoryType,
+
+    // Address of the memory's entry in the memory or import section.
+    pub addr: u64,
+
+    // Whether the memory comes from the import section rather than the memory section.
+    pub imported: bool,
+}
+
+impl fmt::Display for MemoryData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} pages min", self.ty.initial)?;
+        if let Some(maximum) = self.ty.maximum {
+            write!(f, ", {maximum} max")?;
+        }
+        if let Some(page_size_log2) = self.ty.page_size_log2 {
+            write!(f, ", {}-byte pages", 1u64 << page_size_log2)?;
+        }
+        if self.ty.memory64 {
+            write!(f, ", 64-bit")?;
+        }
+        if self.ty.shared {
+            write!(f, ", shared")?;
+        }
+        if self.imported {
+            write!(f, " (imported)")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct TagData {
+    pub ty: TagType,
+
+    // Address of the tag's entry in the tag or import section.
+    pub addr: u64,
+
+    // Whether the tag comes from the import section rather than the tag section.
+    pub imported: bool,
+}
+
+#[derive(Debug)]
+pub struct GlobalData {
+    pub ty: GlobalType,
+
+    // Address of the global's entry in the global or import section.
+    pub addr: u64,
+
+    // Value of the global's initializer, if it is an integer constant.
+    pub init: Option<u64>,
+
+    // Whether the global comes from the import section rather than the global section.
+    pub imported: bool,
+}
+
+#[derive(Debug)]
+pub enum DataMode {
+    // Copied into a memory at instantiation time. `offset` is `None` if the offset
+    // expression is not a constant.
+    Active { memory_index: u32, offset: Option<u64> },
+
+    // Only copied into a memory by `memory.init`.
+    Passive,
+}
+
+#[derive(Debug)]
+pub struct DataSegment {
+    pub mode: DataMode,
+
+    // Range of the segment's bytes in the file.
+    pub data_range: Range<u64>,
+}
+
+impl DataSegment {
+    // Range of linear memory 0 that this segment initializes, relative to the start
+    // of linear memory.
+    pub fn memory_range(&self) -> Option<Range<u64>> {
+        match self.mode {
+            DataMode::Active {
+                memory_index: 0,
+                offset: Some(offset),
+            } => Some(offset..(offset + (self.data_range.end - self.data_range.start))),
+            _ => None,
         }
     }
 }
 
+// The frame a function allocates on the shadow stack.
+#[derive(Debug)]
+pub struct StackFrame {
+    // Address of the `global.get` of the stack pointer that starts the prologue.
+    pub prologue: u64,
+
+    // The local that holds the address of the frame, which is the stack pointer after
+    // the frame was allocated.
+    pub local_index: u32,
+
+    pub size: u64,
+}
+
+// A function that only passes its arguments on to another function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trampoline {
+    // Calls the function with this index, like the wrappers that Binaryen's fpcast-emu
+    // and i64 legalization passes add.
+    Direct(u32),
+
+    // Calls the function at the table index in its first argument with the rest of its
+    // arguments, like Emscripten's `dynCall_*` functions and `invoke_*` imports.
+    Table { table_index: u32 },
+}
+
+// A symbol from the symbol table in the `linking` section of an object file. The names
+// of function, global and tag symbols go with the other names of those.
+#[derive(Debug)]
+pub enum SymbolData {
+    Func { index: u32 },
+
+    // `segment` is the data segment index and offset of a defined symbol, and `None`
+    // for an undefined one.
+    Data { name: String, segment: Option<(u32, u64)> },
+
+    Global { index: u32 },
+    Tag { index: u32 },
+    Table { index: u32 },
+    Section { section: u32 },
+}
+
+// An entry of a `reloc.*` section of an object file.
+#[derive(Debug)]
+pub struct RelocationData {
+    pub ty: RelocationType,
+
+    // Index of the symbol the relocation refers to, or the type index for type
+    // index relocations.
+    pub index: u32,
+
+    pub addend: i64,
+}
+
+#[derive(Debug)]
+pub struct FuncImport {
+    pub module: String,
+    pub name: String,
+}
+
+// Imported functions have no code in the module, so each gets a stub of this size in
+// an external section after the file contents, which calls to it can target.
+pub const EXTERN_STUB_SIZE: u64 = 4;
+pub const EXTERN_ALIGN: u64 = 0x1000;
+
+// Linear memory is mapped into the view above the file contents, so that addresses
+// in linear memory do not collide with file offsets. The base is rounded to a
+// multiple of this value.
+pub const DEFAULT_MEMORY_BASE: u64 = 0x1000_0000;
+
+// Metadata key under which the callees the user pinned indirect calls to are stored, as
+// a map from call site address to function index.
+pub const CALL_TARGET_OVERRIDES_KEY: &str = "wasm.callTargetOverrides";
+
+// Metadata key under which the address that the user moved linear memory to is stored.
+// It takes precedence over the memory base in the settings.
+pub const MEMORY_BASE_KEY: &str = "wasm.memoryBase";
+
 pub struct ModuleData {
     pub funcs: RangeMap<u64, ArcIdentity<FunctionData>>,
-    pub func_addrs: Vec<u64>
+    pub func_addrs: Vec<u64>,
+
+    // Module and name of each imported function. Imported functions come first in
+    // the function index space, so these are indexed by function index.
+    pub func_imports: Vec<FuncImport>,
+
+    // Address of the stub of the first imported function.
+    pub extern_base: u64,
+
+    // Export names of functions, by function index.
+    pub func_exports: BTreeMap<u32, String>,
+
+    // Type index of each function (including imported functions), parallel to
+    // `func_addrs`.
+    pub func_types: Vec<u32>,
+
+    // Signatures from the type section, by type index. Non-function types (from the
+    // GC proposal) are stored as empty signatures so that indices stay aligned.
+    pub types: Vec<FuncType>,
+
+    pub elements: Vec<ElementSegment>,
+    pub tables: Vec<TableData>,
+    pub memories: Vec<MemoryData>,
+    pub globals: Vec<GlobalData>,
+
+    // Exception tags from the exception-handling proposal.
+    pub tags: Vec<TagData>,
+
+    pub data_segments: Vec<DataSegment>,
+
+    // Range of the code section's contents (starting at the function count).
+    pub code_range: Option<Range<u64>>,
+
+    // Names of the custom sections, in the order they appear.
+    pub custom_sections: Vec<String>,
+
+    // Source languages named by the producers section, e.g. "C11" or "Rust".
+    pub languages: Vec<String>,
+
+    // Fields of the producers section, e.g. "processed-by: rustc 1.78.0, wasm-opt 116".
+    pub producers: Vec<String>,
+
+    // Range of the module in the file: all of it, or one core module of a component.
+    pub module_range: Range<u64>,
+
+    // Name and range of each section, in the order they appear in the file. Custom
+    // sections are named by their own names.
+    pub sections: Vec<(String, Range<u64>)>,
+
+    // Names of the `.debug_*` custom sections that carry DWARF debug info, and the
+    // ranges of their contents.
+    pub dwarf_sections: Vec<(String, Range<u64>)>,
+
+    // URL of the split debug info file named by the `external_debug_info` section.
+    pub external_debug_info: Option<String>,
+
+    // The split debug info file, once it has been found, for the DWARF parser to read.
+    pub external_debug_path: Option<PathBuf>,
+
+    // URL of the source map named by the `sourceMappingURL` section.
+    pub source_mapping_url: Option<String>,
+
+    // Index of the function named by the start section.
+    pub start_func: Option<u32>,
+
+    // Function names from the name section, by function index.
+    pub func_names: BTreeMap<u32, String>,
+
+    // Data segment names from the name section, by data segment index.
+    pub data_names: BTreeMap<u32, String>,
+
+    // Local names from the name section, by function index and then local index.
+    pub local_names: BTreeMap<u32, BTreeMap<u32, String>>,
+
+    // Names of globals, by global index: the import or export name, or else the name
+    // from the name section.
+    pub global_names: BTreeMap<u32, String>,
+
+    // Names of tags, by tag index: the import or export name, or else the name from
+    // the name section.
+    pub tag_names: BTreeMap<u32, String>,
+
+    // Index of the global that holds the shadow stack pointer, if there is one.
+    pub stack_pointer_global: Option<u32>,
+
+    // Frames allocated on the shadow stack, by function index.
+    pub stack_frames: BTreeMap<u32, StackFrame>,
+
+    // Address in the view at which linear memory 0 starts.
+    pub memory_base: u64,
+
+    // Width of addresses in the view, in bytes.
+    pub address_size: usize,
+
+    // Hints from the branch hint section, by function index: the offset of each hinted
+    // instruction from the start of the function body, and whether it is likely taken.
+    pub func_branch_hints: BTreeMap<u32, Vec<(u32, bool)>>,
+
+    // Whether each hinted `if` or `br_if` is likely taken, by instruction address.
+    pub branch_hints: BTreeMap<u64, bool>,
+
+    // Candidate callees of each `call_indirect`, and the functions that calls to
+    // trampolines go on to call, by call site address.
+    pub indirect_call_targets: BTreeMap<u64, Vec<u32>>,
+
+    // Functions that only pass their arguments on, by function index.
+    pub trampolines: BTreeMap<u32, Trampoline>,
+
+    // Callees that the user pinned indirect calls to, by call site address. These
+    // replace the candidates in `indirect_call_targets`.
+    pub call_target_overrides: BTreeMap<u64, u32>,
+
+    // Bodies that the user replaced functions with, assembled from WAT, by function
+    // index. These are saved in place of the bodies in the view, which only hold the
+    // new bodies padded out to the size of the old ones, if they fit at all.
+    pub replaced_bodies: BTreeMap<u32, Vec<u8>>,
+
+    // Options chosen by the user when opening the file.
+    pub load_settings: LoadSettings,
+
+    // How integer constants are shown in disassembly.
+    pub constant_display: ConstantDisplay,
+
+    // Address of the contents of each section, by section index. Relocation offsets
+    // are relative to these.
+    pub section_starts: Vec<u64>,
+
+    // Symbol table from the `linking` section of an object file.
+    pub symbols: Vec<SymbolData>,
+
+    // Relocations from the `reloc.*` sections of an object file, by the address of
+    // the bytes they patch.
+    pub relocations: BTreeMap<u64, RelocationData>,
 }
 
 impl ModuleData {
@@ -89,6 +468,225 @@ impl ModuleData {
         Self {
             funcs: RangeMap::new(),
             func_addrs: Vec::new(),
+            func_imports: Vec::new(),
+            extern_base: 0,
+            func_exports: BTreeMap::new(),
+            func_types: Vec::new(),
+            types: Vec::new(),
+            elements: Vec::new(),
+            tables: Vec::new(),
+            memories: Vec::new(),
+            globals: Vec::new(),
+            tags: Vec::new(),
+            data_segments: Vec::new(),
+            code_range: None,
+            custom_sections: Vec::new(),
+            languages: Vec::new(),
+            producers: Vec::new(),
+            module_range: 0..0,
+            sections: Vec::new(),
+            dwarf_sections: Vec::new(),
+            external_debug_info: None,
+            external_debug_path: None,
+            source_mapping_url: None,
+            start_func: None,
+            func_names: BTreeMap::new(),
+            data_names: BTreeMap::new(),
+            local_names: BTreeMap::new(),
+            global_names: BTreeMap::new(),
+            tag_names: BTreeMap::new(),
+            stack_pointer_global: None,
+            stack_frames: BTreeMap::new(),
+            memory_base: DEFAULT_MEMORY_BASE,
+            address_size: 4,
+            func_branch_hints: BTreeMap::new(),
+            branch_hints: BTreeMap::new(),
+            indirect_call_targets: BTreeMap::new(),
+            trampolines: BTreeMap::new(),
+            call_target_overrides: BTreeMap::new(),
+            replaced_bodies: BTreeMap::new(),
+            load_settings: LoadSettings::default(),
+            constant_display: ConstantDisplay::default(),
+            section_starts: Vec::new(),
+            symbols: Vec::new(),
+            relocations: BTreeMap::new(),
+        }
+    }
+
+    // Name of a function as its symbol is named: by its import or export name, or
+    // else its name from the name section.
+    pub fn func_name(&self, func_index: u32) -> String {
+        if let Some(import) = self.func_imports.get(func_index as usize) {
+            return import.name.clone();
+        }
+        match self
+            .func_exports
+            .get(&func_index)
+            .or_else(|| self.func_names.get(&func_index))
+        {
+            Some(name) => name.clone(),
+            None => format!("func_{func_index}"),
+        }
+    }
+
+    pub fn func_type(&self, func_index: u32) -> Option<&FuncType> {
+        self.types.get(*self.func_types.get(func_index as usize)? as usize)
+    }
+
+    // Whether the address in linear memory 0 is initialized by a data segment that is
+    // mapped into the view.
+    pub fn is_data_address(&self, addr: u64) -> bool {
+        self.load_settings.map_data_segments
+            && self.data_segments.iter().any(|segment| {
+                segment
+                    .memory_range()
+                    .is_some_and(|range| range.contains(&addr))
+            })
+    }
+
+    // Name of a local: its name from the name section, or else the name of the registers
+    // it is lifted to without their width, `argN` for the Nth parameter and `locN` for
+    // the Nth declared local.
+    pub fn local_name(&self, func_index: u32, local_index: u32) -> String {
+        if let Some(name) = self
+            .local_names
+            .get(&func_index)
+            .and_then(|names| names.get(&local_index))
+        {
+            return name.clone();
+        }
+        let n_params = self
+            .func_type(func_index)
+            .map_or(0, |ty| ty.params().len() as u32);
+        // The 32-bit registers have no suffix.
+        match WebAssemblyRegister::for_local(n_params, local_index, ValType::I32) {
+            Some(reg) => reg.name().into_owned(),
+            None => format!("local{local_index}"),
+        }
+    }
+
+    // Contents of a table after instantiation, by slot, as initialized by the active
+    // element segments with constant offsets.
+    pub fn table_contents(&self, table_index: u32) -> BTreeMap<u64, u32> {
+        let mut contents = BTreeMap::new();
+        for segment in &self.elements {
+            let ElementMode::Active {
+                table_index: segment_table,
+                offset: Some(offset),
+            } = segment.mode
+            else {
+                continue;
+            };
+            if segment_table != table_index {
+                continue;
+            }
+            for (i, item) in segment.items.iter().enumerate() {
+                let slot = offset + i as u64;
+                match item {
+                    Some(func_index) => contents.insert(slot, *func_index),
+                    None => contents.remove(&slot),
+                };
+            }
+        }
+        contents
+    }
+
+    // Functions that a `call_indirect` with the given type and table may call. This
+    // is every function that can end up in the table, either at instantiation time
+    // or through `table.init`, whose signature matches.
+    pub fn indirect_call_candidates(&self, type_index: u32, table_index: u32) -> Vec<u32> {
+        let Some(ty) = self.types.get(type_index as usize) else {
+            return Vec::new();
+        };
+
+        let mut funcs = self
+            .table_contents(table_index)
+            .into_values()
+            .collect::<BTreeSet<_>>();
+        for segment in &self.elements {
+            let may_reach_table = match segment.mode {
+                ElementMode::Active {
+                    table_index: segment_table,
+                    offset: None,
+                } => segment_table == table_index,
+                ElementMode::Passive => true,
+                _ => false,
+            };
+            if may_reach_table {
+                funcs.extend(segment.items.iter().flatten());
+            }
+        }
+
+        funcs
+            .into_iter()
+            .filter(|func_index| self.func_type(*func_index) == Some(ty))
+            .collect()
+    }
+
+    // Address of a defined data symbol in linear memory 0, relative to its start.
+    pub fn data_symbol_address(&self, segment_index: u32, offset: u64) -> Option<u64> {
+        let segment = self.data_segments.get(segment_index as usize)?;
+        Some(segment.memory_range()?.start + offset)
+    }
+
+    // The value that a relocation stands for, which the linker would write in place
+    // of the placeholder. `None` if it isn't known without linking, e.g. for
+    // undefined symbols, table slots, and addresses relative to other addresses.
+    pub fn relocation_value(&self, reloc: &RelocationData) -> Option<u64> {
+        use RelocationType::*;
+        if reloc.ty == TypeIndexLeb {
+            return Some(reloc.index as u64);
+        }
+        let symbol = self.symbols.get(reloc.index as usize)?;
+        let value = match (reloc.ty, symbol) {
+            (FunctionIndexLeb | FunctionIndexI32, SymbolData::Func { index, .. })
+            | (GlobalIndexLeb | GlobalIndexI32, SymbolData::Global { index, .. })
+            | (EventIndexLeb, SymbolData::Tag { index, .. })
+            | (TableNumberLeb, SymbolData::Table { index, .. }) => *index as u64,
+            (
+                MemoryAddrLeb | MemoryAddrSleb | MemoryAddrI32 | MemoryAddrLeb64
+                | MemoryAddrSleb64 | MemoryAddrI64,
+                SymbolData::Data {
+                    segment: Some((segment_index, offset)),
+                    ..
+                },
+            ) => self
+                .data_symbol_address(*segment_index, *offset)?
+                .wrapping_add_signed(reloc.addend),
+            (FunctionOffsetI32 | FunctionOffsetI64, SymbolData::Func { index, .. })
+                if *index as usize >= self.func_imports.len() =>
+            {
+                let offset = self.func_addrs.get(*index as usize)? - self.code_range.as_ref()?.start;
+                offset.wrapping_add_signed(reloc.addend)
+            }
+            (SectionOffsetI32, SymbolData::Section { .. }) => reloc.addend as u64,
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    // Address in the view of what a relocation refers to.
+    pub fn relocation_target(&self, reloc: &RelocationData) -> Option<u64> {
+        if reloc.ty == RelocationType::TypeIndexLeb {
+            return None;
+        }
+        match self.symbols.get(reloc.index as usize)? {
+            SymbolData::Func { index, .. } => self.func_addrs.get(*index as usize).copied(),
+            SymbolData::Data {
+                segment: Some((segment_index, offset)),
+                ..
+            } => {
+                let addr = self.data_symbol_address(*segment_index, *offset)?;
+                Some(self.memory_base + addr.wrapping_add_signed(reloc.addend))
+            }
+            SymbolData::Data { segment: None, .. } => None,
+            SymbolData::Global { index, .. } => Some(self.globals.get(*index as usize)?.addr),
+            SymbolData::Tag { index, .. } => Some(self.tags.get(*index as usize)?.addr),
+            SymbolData::Table { index, .. } => Some(self.tables.get(*index as usize)?.addr),
+            SymbolData::Section { section } => {
+                let start = *self.section_starts.get(*section as usize)?;
+                Some(start.wrapping_add_signed(reloc.addend))
+            }
         }
     }
 }
@@ -1,21 +1,98 @@
+use crate::binja::parse::debug_info::read_url_section;
 use crate::binja::parse::func_parse::parse_func;
-use crate::binja::parse::module_data::ModuleData;
+use crate::binja::parse::linking::{read_linking_section, read_reloc_section};
+use crate::binja::parse::module_data::{
+    DataMode, DataSegment, ElementMode, ElementSegment, FuncImport, GlobalData, MemoryData,
+    ModuleData, TableData, TagData, CALL_TARGET_OVERRIDES_KEY, DEFAULT_MEMORY_BASE, EXTERN_ALIGN,
+    EXTERN_STUB_SIZE, MEMORY_BASE_KEY,
+};
+use crate::binja::settings::{ConstantDisplay, LoadSettings};
 use crate::binja::view::WebAssemblyView;
 use crate::util::arc_identity::ArcIdentity;
 use crate::util::bin_util::BinaryReadable;
+use binaryninja::background_task::BackgroundTask;
 use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use binaryninja::function::Function;
+use binaryninja::metadata::Metadata;
+use binaryninja::rc::Ref;
 use binaryninja::section::{SectionBuilder, Semantics};
 use binaryninja::segment::{SegmentBuilder, SegmentFlags};
-use binaryninja::symbol::{Symbol, SymbolType};
+use binaryninja::symbol::{Binding, Symbol, SymbolType};
 use log::{info, warn};
 use std::cmp::min;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ops::Range;
 use std::pin::Pin;
 use wasmparser::{
-    Chunk, ExportSectionReader, ExternalKind, ImportSectionReader, Parser, Payload, TypeRef,
+    BinaryReader, BranchHintSectionReader, Chunk, CompositeInnerType, ConstExpr,
+    CustomSectionReader, DataKind, DataSectionReader, ElementItems, ElementKind,
+    ElementSectionReader, ExportSectionReader, ExternalKind, FuncType, FunctionSectionReader,
+    GlobalSectionReader, ImportSectionReader, KnownCustom, MemorySectionReader, Name,
+    NameSectionReader, Operator, Parser, Payload, ProducersSectionReader, TableSectionReader,
+    TagSectionReader, TypeRef, TypeSectionReader, ValType,
 };
 
+pub(crate) const EXPORT_TAG: &str = "Exported Function";
+pub(crate) const IMPORT_TAG: &str = "Imported Function";
+
+// How many functions are parsed between updates of the progress text.
+const PROGRESS_INTERVAL: u32 = 1000;
+
+// The tag type that binja lists as bookmarks.
+pub(crate) const BOOKMARK_TAG: &str = "Bookmarks";
+
+// A signature as function header comments show it, e.g. `(i32, i32) -> i32`.
+fn signature_text(ty: &FuncType) -> String {
+    let types = |types: &[ValType]| types.iter().map(|ty| ty.to_string()).collect::<Vec<_>>();
+    let params = format!("({})", types(ty.params()).join(", "));
+    match ty.results() {
+        [] => params,
+        [result] => format!("{params} -> {result}"),
+        results => format!("{params} -> ({})", types(results).join(", ")),
+    }
+}
+
+// Evaluates a constant expression that consists of a single integer constant.
+fn eval_const_int(expr: &ConstExpr) -> Option<u64> {
+    let mut reader = expr.get_operators_reader();
+    let value = match reader.read().ok()? {
+        Operator::I32Const { value } => value as u32 as u64,
+        Operator::I64Const { value } => value as u64,
+        _ => return None,
+    };
+    matches!(reader.read().ok()?, Operator::End).then_some(value)
+}
+
+// Evaluates a constant expression that consists of a single `ref.func`.
+fn eval_const_func_ref(expr: &ConstExpr) -> Option<u32> {
+    let mut reader = expr.get_operators_reader();
+    let function_index = match reader.read().ok()? {
+        Operator::RefFunc { function_index } => function_index,
+        _ => return None,
+    };
+    matches!(reader.read().ok()?, Operator::End).then_some(function_index)
+}
+
+// Name of a section by its id, as in the spec.
+fn section_name(id: u8) -> &'static str {
+    match id {
+        1 => "type",
+        2 => "import",
+        3 => "function",
+        4 => "table",
+        5 => "memory",
+        6 => "global",
+        7 => "export",
+        8 => "start",
+        9 => "element",
+        10 => "code",
+        11 => "data",
+        12 => "datacount",
+        13 => "tag",
+        _ => "unknown",
+    }
+}
+
 impl WebAssemblyView {
     fn add_wasm_section(
         &mut self,
@@ -42,38 +119,660 @@ impl WebAssemblyView {
         );
     }
 
+    fn handle_custom_section(&mut self, reader: CustomSectionReader, module_data: &mut ModuleData) {
+        module_data.custom_sections.push(reader.name().to_string());
+        if reader.name().starts_with(".debug_") {
+            // DWARF sections keep the prefix of custom sections, since binja's DWARF
+            // importer would take the code offsets in them for addresses in the view.
+            // The plugin's own parser imports them instead. Only the section contents
+            // are covered, so that they show as raw DWARF.
+            self.add_wasm_section(
+                reader.data_offset()..reader.range().end,
+                format!(".custom.{}", reader.name()),
+                |sb| {
+                    sb.flags(
+                        SegmentFlags::new()
+                            .contains_data(true)
+                            .contains_code(false)
+                            .readable(true)
+                            .writable(false)
+                            .executable(false)
+                            .deny_write(true)
+                            .deny_execute(true),
+                    )
+                },
+                |sb| sb.semantics(Semantics::ReadOnlyData),
+            );
+            module_data.dwarf_sections.push((
+                reader.name().to_string(),
+                (reader.data_offset() as u64)..(reader.range().end as u64),
+            ));
+            return;
+        }
+
+        self.add_wasm_section_default(reader.range(), format!(".custom.{}", reader.name()));
+
+        // Custom sections are optional, so a malformed one should not prevent the rest
+        // of the module from loading.
+        if reader.name() == "external_debug_info" {
+            module_data.external_debug_info = read_url_section(reader.data(), reader.data_offset());
+            if module_data.external_debug_info.is_none() {
+                warn!("Failed to parse the external_debug_info section");
+            }
+        } else if reader.name() == "sourceMappingURL" {
+            module_data.source_mapping_url = read_url_section(reader.data(), reader.data_offset());
+            if module_data.source_mapping_url.is_none() {
+                warn!("Failed to parse the sourceMappingURL section");
+            }
+        } else if reader.name() == "build_id" {
+            let addr = reader.range().start as u64;
+            if self
+                .handle_build_id_section(reader.data(), reader.data_offset(), addr)
+                .is_err()
+            {
+                warn!("Failed to parse the build_id section");
+            }
+        } else if reader.name() == "emscripten_metadata" {
+            let addr = reader.range().start as u64;
+            if self
+                .handle_emscripten_metadata_section(reader.data(), reader.data_offset(), addr)
+                .is_err()
+            {
+                warn!("Failed to parse the emscripten_metadata section");
+            }
+        } else if let KnownCustom::Name(reader) = reader.as_known() {
+            if !module_data.load_settings.use_name_section {
+                return;
+            }
+            if self.handle_name_section(reader, module_data).is_err() {
+                warn!("Failed to parse the name section");
+            }
+        } else if let KnownCustom::BranchHints(hints) = reader.as_known() {
+            if self.handle_branch_hint_section(hints, module_data).is_err() {
+                warn!("Failed to parse the branch hint section");
+            }
+        } else if let KnownCustom::Producers(producers) = reader.as_known() {
+            let addr = reader.range().start as u64;
+            if self.handle_producers_section(producers, addr, module_data).is_err() {
+                warn!("Failed to parse the producers section");
+            }
+        } else if let KnownCustom::Linking(linking) = reader.as_known() {
+            if read_linking_section(linking, module_data).is_err() {
+                warn!("Failed to parse the linking section");
+            }
+        } else if let KnownCustom::Reloc(reloc) = reader.as_known() {
+            if read_reloc_section(reloc, module_data).is_err() {
+                warn!("Failed to parse the {} section", reader.name());
+            }
+        }
+    }
+
+    fn handle_name_section(
+        &mut self,
+        reader: NameSectionReader,
+        module_data: &mut ModuleData,
+    ) -> Result<(), ()> {
+        for name in reader {
+            let name = name.map_err(|_| ())?;
+            if let Name::Local(funcs) = name {
+                for func in funcs {
+                    let func = func.map_err(|_| ())?;
+                    let map = module_data.local_names.entry(func.index).or_default();
+                    for naming in func.names {
+                        let naming = naming.map_err(|_| ())?;
+                        map.insert(naming.index, naming.name.to_string());
+                    }
+                }
+                continue;
+            }
+            let (names, map) = match name {
+                Name::Function(names) => (names, &mut module_data.func_names),
+                Name::Data(names) => (names, &mut module_data.data_names),
+                Name::Global(names) => (names, &mut module_data.global_names),
+                Name::Tag(names) => (names, &mut module_data.tag_names),
+                _ => continue,
+            };
+            for naming in names {
+                let naming = naming.map_err(|_| ())?;
+                map.entry(naming.index)
+                    .or_insert_with(|| naming.name.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    // The producers section names the source languages and the tools that built the
+    // module (e.g. "language: Rust; processed-by: rustc 1.78.0, wasm-opt 116"), which
+    // says a lot about what the code will look like.
+    fn handle_producers_section(
+        &mut self,
+        reader: ProducersSectionReader,
+        addr: u64,
+        module_data: &mut ModuleData,
+    ) -> Result<(), ()> {
+        let mut summary = Vec::new();
+        let mut metadata: HashMap<&str, Ref<Metadata>> = HashMap::new();
+        for field in reader {
+            let field = field.map_err(|_| ())?;
+            let mut values = Vec::new();
+            let mut entries = Vec::new();
+            for value in field.values {
+                let value = value.map_err(|_| ())?;
+                if field.name == "language" {
+                    module_data.languages.push(value.name.to_string());
+                }
+                values.push(
+                    format!("{} {}", value.name, value.version)
+                        .trim()
+                        .to_string(),
+                );
+
+                let mut entry: HashMap<&str, Ref<Metadata>> = HashMap::new();
+                entry.insert("name", value.name.into());
+                entry.insert("version", value.version.into());
+                entries.push(Ref::<Metadata>::from(entry));
+            }
+            summary.push(format!("{}: {}", field.name, values.join(", ")));
+            metadata.insert(field.name, entries.into());
+        }
+
+        module_data.producers.extend(summary.iter().cloned());
+        let summary = summary.join("; ");
+        info!("Module producers: {summary}");
+        self.set_comment_at(addr, &format!("producers: {summary}"));
+        self.store_metadata("wasm.producers", metadata, true);
+        Ok(())
+    }
+
+    // The branch hint section comes before the code section, so the hints can only be
+    // placed once the function bodies have been parsed.
+    fn handle_branch_hint_section(
+        &mut self,
+        reader: BranchHintSectionReader,
+        module_data: &mut ModuleData,
+    ) -> Result<(), ()> {
+        for func in reader {
+            let func = func.map_err(|_| ())?;
+            let hints = func
+                .hints
+                .into_iter()
+                .map(|hint| hint.map(|hint| (hint.func_offset, hint.taken)))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| ())?;
+            module_data.func_branch_hints.insert(func.func, hints);
+        }
+        Ok(())
+    }
+
+    fn resolve_branch_hints(&self, module_data: &mut ModuleData) {
+        let mut branch_hints = BTreeMap::new();
+        for (func_index, hints) in &module_data.func_branch_hints {
+            let Some(addr) = module_data.func_addrs.get(*func_index as usize) else {
+                continue;
+            };
+            let Some(func) = module_data.funcs.get(addr) else {
+                continue;
+            };
+            let func = func.as_ref();
+            for (offset, taken) in hints {
+                let addr = func.locals_start + *offset as u64;
+                match func.ops.get(&addr).map(|op| &op.op) {
+                    Some(Operator::If { .. } | Operator::BrIf { .. }) => {
+                        branch_hints.insert(addr, *taken);
+                    }
+                    _ => warn!("Branch hint at {addr:#x} does not refer to a branch"),
+                }
+            }
+        }
+        if !branch_hints.is_empty() {
+            info!("Module has {} branch hints", branch_hints.len());
+        }
+        module_data.branch_hints = branch_hints;
+    }
+
+    // The build id identifies the build that produced the module, so samples can be
+    // correlated and matched with debug info from the same build.
+    fn handle_build_id_section(&mut self, data: &[u8], offset: usize, addr: u64) -> Result<(), ()> {
+        let mut reader = BinaryReader::new(data, offset);
+        let len = reader.read_var_u32().map_err(|_| ())?;
+        let build_id = reader.read_bytes(len as usize).map_err(|_| ())?;
+        if !reader.eof() {
+            return Err(());
+        }
+
+        let build_id = build_id
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>();
+        info!("Module build id: {build_id}");
+        self.set_comment_at(addr, &format!("build id: {build_id}"));
+        self.store_metadata("wasm.buildId", build_id.as_str(), true);
+        Ok(())
+    }
+
+    // Older Emscripten releases (before 2.0.0) record the ABI version and memory layout
+    // the module was built with. Later versions of the section append fields, so decode
+    // as many as are present.
+    fn handle_emscripten_metadata_section(
+        &mut self,
+        data: &[u8],
+        offset: usize,
+        addr: u64,
+    ) -> Result<(), ()> {
+        const FIELDS: &[&str] = &[
+            "metadataMajor",
+            "metadataMinor",
+            "abiMajor",
+            "abiMinor",
+            "backend",
+            "memorySize",
+            "tableSize",
+            "globalBase",
+            "dynamicBase",
+            "dynamictopPtr",
+            "tempDoublePtr",
+            "standaloneWasm",
+        ];
+
+        let mut reader = BinaryReader::new(data, offset);
+        let mut values = Vec::new();
+        for field in FIELDS {
+            if reader.eof() {
+                break;
+            }
+            values.push((*field, reader.read_var_u32().map_err(|_| ())?));
+        }
+        if values.len() < 4 {
+            return Err(());
+        }
+
+        let summary = values
+            .iter()
+            .map(|(field, value)| format!("{field}={value}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        info!("Emscripten metadata: {summary}");
+        self.set_comment_at(addr, &format!("emscripten_metadata: {summary}"));
+
+        let mut metadata: HashMap<&str, Ref<Metadata>> = HashMap::new();
+        for (field, value) in values {
+            metadata.insert(field, (value as u64).into());
+        }
+        self.store_metadata("wasm.emscriptenMetadata", metadata, true);
+        Ok(())
+    }
+
+    fn handle_type_section(
+        &mut self,
+        reader: TypeSectionReader,
+        types: &mut Vec<FuncType>,
+    ) -> Result<(), ()> {
+        self.add_wasm_section_default(reader.range(), ".type");
+        for rec_group in reader {
+            for sub_type in rec_group.map_err(|_| ())?.into_types() {
+                types.push(match sub_type.composite_type.inner {
+                    CompositeInnerType::Func(func_type) => func_type,
+                    _ => FuncType::new([], []),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_function_section(
+        &mut self,
+        reader: FunctionSectionReader,
+        func_types: &mut Vec<u32>,
+    ) -> Result<(), ()> {
+        self.add_wasm_section_default(reader.range(), ".function");
+        for type_index in reader {
+            func_types.push(type_index.map_err(|_| ())?);
+        }
+        Ok(())
+    }
+
     fn handle_import_section(
         &mut self,
         reader: ImportSectionReader,
+        module_data: &mut ModuleData,
         func_index: &mut u32,
-        func_addrs: &mut Vec<u64>,
     ) -> Result<(), ()> {
         self.add_wasm_section_default(reader.range(), ".import");
-        for import in reader {
-            let import = import.map_err(|_| ())?;
-            if matches!(import.ty, TypeRef::Func(_)) {
-                *func_index += 1;
-                func_addrs.push(0);
+        for import in reader.into_iter_with_offsets() {
+            let (offset, import) = import.map_err(|_| ())?;
+            let addr = offset as u64;
+            match import.ty {
+                TypeRef::Func(type_index) => {
+                    let stub = module_data.extern_base + *func_index as u64 * EXTERN_STUB_SIZE;
+                    *func_index += 1;
+                    module_data.func_addrs.push(stub);
+                    module_data.func_types.push(type_index);
+                    module_data.func_imports.push(FuncImport {
+                        module: import.module.to_string(),
+                        name: import.name.to_string(),
+                    });
+                }
+                TypeRef::Table(ty) => module_data.tables.push(TableData {
+                    ty,
+                    addr,
+                    imported: true,
+                }),
+                TypeRef::Memory(ty) => module_data.memories.push(MemoryData {
+                    ty,
+                    addr,
+                    imported: true,
+                }),
+                TypeRef::Global(ty) => {
+                    let global_index = module_data.globals.len() as u32;
+                    module_data
+                        .global_names
+                        .insert(global_index, import.name.to_string());
+                    module_data.globals.push(GlobalData {
+                        ty,
+                        addr,
+                        init: None,
+                        imported: true,
+                    });
+                }
+                TypeRef::Tag(ty) => {
+                    let tag_index = module_data.tags.len() as u32;
+                    module_data
+                        .tag_names
+                        .insert(tag_index, import.name.to_string());
+                    module_data.tags.push(TagData {
+                        ty,
+                        addr,
+                        imported: true,
+                    });
+                }
             }
         }
+        self.add_extern_section(module_data);
+        Ok(())
+    }
+
+    // Map the stubs of imported functions in an external section, and name them after
+    // the imports.
+    fn add_extern_section(&mut self, module_data: &ModuleData) {
+        if module_data.func_imports.is_empty() {
+            return;
+        }
+        let start = module_data.extern_base;
+        let end = start + module_data.func_imports.len() as u64 * EXTERN_STUB_SIZE;
+        self.add_segment(
+            SegmentBuilder::new(start..end)
+                .flags(
+                    SegmentFlags::new()
+                        .contains_data(false)
+                        .contains_code(false)
+                        .readable(true)
+                        .writable(false)
+                        .executable(false),
+                )
+                .is_auto(true),
+        );
+        self.add_section(
+            SectionBuilder::new(".extern", start..end)
+                .semantics(Semantics::External)
+                .is_auto(true),
+        );
+
+        for (import, addr) in module_data.func_imports.iter().zip(&module_data.func_addrs) {
+            let symbol = Symbol::builder(SymbolType::External, &import.name, *addr)
+                .full_name(&format!("{}::{}", import.module, import.name))
+                .binding(Binding::Global)
+                .create();
+            self.define_auto_symbol(&symbol);
+        }
+    }
+
+    fn handle_table_section(
+        &mut self,
+        reader: TableSectionReader,
+        tables: &mut Vec<TableData>,
+    ) -> Result<(), ()> {
+        let range = reader.range();
+        self.add_wasm_section_default(range.clone(), ".table");
+
+        let entries = reader
+            .into_iter_with_offsets()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ())?;
+        for (i, (start, table)) in entries.iter().enumerate() {
+            let end = entries.get(i + 1).map_or(range.end, |(next, _)| *next);
+            let table = TableData {
+                ty: table.ty,
+                addr: *start as u64,
+                imported: false,
+            };
+
+            // Give each table its own section so the indirect-call surface of each
+            // table is visible at a glance.
+            let table_index = tables.len();
+            self.add_section(
+                SectionBuilder::new(
+                    format!(".table.{table_index}"),
+                    (*start as u64)..(end as u64),
+                )
+                .is_auto(true),
+            );
+            self.set_comment_at(*start as u64, &format!("table {table_index}: {table}"));
+            tables.push(table);
+        }
+        Ok(())
+    }
+
+    fn handle_memory_section(
+        &mut self,
+        reader: MemorySectionReader,
+        memories: &mut Vec<MemoryData>,
+    ) -> Result<(), ()> {
+        self.add_wasm_section_default(reader.range(), ".memory");
+        for memory in reader.into_iter_with_offsets() {
+            let (offset, ty) = memory.map_err(|_| ())?;
+            memories.push(MemoryData {
+                ty,
+                addr: offset as u64,
+                imported: false,
+            });
+        }
+        Ok(())
+    }
+
+    // Surface the memory limits, since they are one of the first things an analyst
+    // wants to know about a module.
+    fn describe_memories(&mut self, memories: &[MemoryData]) {
+        let mut metadata = Vec::new();
+        for (i, memory) in memories.iter().enumerate() {
+            self.set_comment_at(memory.addr, &format!("memory {i}: {memory}"));
+
+            let mut entry: HashMap<&str, Ref<Metadata>> = HashMap::new();
+            entry.insert("initial", memory.ty.initial.into());
+            if let Some(maximum) = memory.ty.maximum {
+                entry.insert("maximum", maximum.into());
+            }
+            entry.insert("shared", memory.ty.shared.into());
+            entry.insert("memory64", memory.ty.memory64.into());
+            entry.insert("imported", memory.imported.into());
+            metadata.push(Ref::<Metadata>::from(entry));
+        }
+        self.store_metadata("wasm.memories", metadata, true);
+    }
+
+    fn handle_tag_section(
+        &mut self,
+        reader: TagSectionReader,
+        tags: &mut Vec<TagData>,
+    ) -> Result<(), ()> {
+        self.add_wasm_section_default(reader.range(), ".tag");
+        for tag in reader.into_iter_with_offsets() {
+            let (offset, ty) = tag.map_err(|_| ())?;
+            tags.push(TagData {
+                ty,
+                addr: offset as u64,
+                imported: false,
+            });
+        }
+        Ok(())
+    }
+
+    // Name each tag and describe the payload its exceptions carry, so that `throw`
+    // and `catch` instructions, which refer to tags, can be followed to them.
+    fn describe_tags(&mut self, module_data: &ModuleData) {
+        for (i, tag) in module_data.tags.iter().enumerate() {
+            let tag_index = i as u32;
+            let imported = if tag.imported { " (imported)" } else { "" };
+            match module_data.types.get(tag.ty.func_type_idx as usize) {
+                Some(ty) => self.set_comment_at(tag.addr, &format!("tag {i}: {ty}{imported}")),
+                None => self.set_comment_at(tag.addr, &format!("tag {i}{imported}")),
+            }
+            if let Some(name) = module_data.tag_names.get(&tag_index) {
+                let symbol = Symbol::builder(SymbolType::Data, name, tag.addr).create();
+                self.define_auto_symbol(&symbol);
+            }
+        }
+    }
+
+    fn handle_global_section(
+        &mut self,
+        reader: GlobalSectionReader,
+        globals: &mut Vec<GlobalData>,
+    ) -> Result<(), ()> {
+        self.add_wasm_section_default(reader.range(), ".global");
+        for global in reader.into_iter_with_offsets() {
+            let (offset, global) = global.map_err(|_| ())?;
+            globals.push(GlobalData {
+                ty: global.ty,
+                addr: offset as u64,
+                init: eval_const_int(&global.init_expr),
+                imported: false,
+            });
+        }
         Ok(())
     }
 
     fn handle_export_section(
         &mut self,
         reader: ExportSectionReader,
+        module_data: &mut ModuleData,
         func_exports: &mut BTreeMap<u32, String>,
     ) {
         self.add_wasm_section_default(reader.range(), ".export");
         for export in reader {
-            if let Ok(export) = export {
-                if export.kind == ExternalKind::Func {
-                    func_exports.insert(export.index, export.name.to_string());
+            let Ok(export) = export else {
+                continue;
+            };
+            let name = export.name;
+            match export.kind {
+                ExternalKind::Func => {
+                    func_exports.insert(export.index, name.to_string());
+                }
+                ExternalKind::Global => {
+                    module_data
+                        .global_names
+                        .entry(export.index)
+                        .or_insert_with(|| name.to_string());
+                    let Some(global) = module_data.globals.get(export.index as usize) else {
+                        continue;
+                    };
+                    let symbol = Symbol::builder(SymbolType::Data, name, global.addr).create();
+                    self.define_auto_symbol(&symbol);
+
+                    // Globals such as `__heap_base` and `__data_end` hold addresses
+                    // in linear memory, which are more useful landmarks than the
+                    // globals themselves.
+                    if let (Some(init), false) = (global.init, global.ty.mutable) {
+                        let addr = module_data.memory_base + init;
+                        let symbol = Symbol::builder(SymbolType::Data, name, addr).create();
+                        self.define_auto_symbol(&symbol);
+                    }
                 }
+                ExternalKind::Memory if export.index == 0 => {
+                    let addr = module_data.memory_base;
+                    let symbol = Symbol::builder(SymbolType::Data, name, addr).create();
+                    self.define_auto_symbol(&symbol);
+                }
+                ExternalKind::Table => {
+                    let Some(table) = module_data.tables.get(export.index as usize) else {
+                        continue;
+                    };
+                    let symbol = Symbol::builder(SymbolType::Data, name, table.addr).create();
+                    self.define_auto_symbol(&symbol);
+                }
+                ExternalKind::Tag => {
+                    module_data
+                        .tag_names
+                        .entry(export.index)
+                        .or_insert_with(|| name.to_string());
+                }
+                _ => {}
             }
         }
     }
 
+    fn handle_element_section(
+        &mut self,
+        reader: ElementSectionReader,
+        elements: &mut Vec<ElementSegment>,
+    ) -> Result<(), ()> {
+        self.add_wasm_section_default(reader.range(), ".element");
+        for element in reader {
+            let element = element.map_err(|_| ())?;
+            let mode = match element.kind {
+                ElementKind::Active {
+                    table_index,
+                    offset_expr,
+                } => ElementMode::Active {
+                    table_index: table_index.unwrap_or(0),
+                    offset: eval_const_int(&offset_expr),
+                },
+                ElementKind::Passive => ElementMode::Passive,
+                ElementKind::Declared => ElementMode::Declared,
+            };
+            let items = match element.items {
+                ElementItems::Functions(reader) => reader
+                    .into_iter()
+                    .map(|index| index.map(Some).map_err(|_| ()))
+                    .collect::<Result<Vec<_>, _>>()?,
+                ElementItems::Expressions(_, reader) => reader
+                    .into_iter()
+                    .map(|expr| expr.map(|expr| eval_const_func_ref(&expr)).map_err(|_| ()))
+                    .collect::<Result<Vec<_>, _>>()?,
+            };
+            elements.push(ElementSegment { mode, items });
+        }
+        Ok(())
+    }
+
+    fn handle_data_section(
+        &mut self,
+        reader: DataSectionReader,
+        data_segments: &mut Vec<DataSegment>,
+    ) -> Result<(), ()> {
+        self.add_wasm_section_default(reader.range(), ".data");
+        for data in reader {
+            let data = data.map_err(|_| ())?;
+            let mode = match data.kind {
+                DataKind::Active {
+                    memory_index,
+                    offset_expr,
+                } => DataMode::Active {
+                    memory_index,
+                    offset: eval_const_int(&offset_expr),
+                },
+                DataKind::Passive => DataMode::Passive,
+            };
+
+            // The segment's bytes are at the end of the segment entry.
+            let data_start = (data.range.end - data.data.len()) as u64;
+            data_segments.push(DataSegment {
+                mode,
+                data_range: data_start..(data.range.end as u64),
+            });
+        }
+        Ok(())
+    }
+
     fn handle_code_section_start(&mut self, _count: u32, range: Range<usize>, _size: u32) {
         self.add_wasm_section(
             range,
@@ -127,52 +826,403 @@ impl WebAssemblyView {
 
         module_data.funcs.insert(
             size_start..end,
-            ArcIdentity::new(parse_func(size_start, locals_start, end, raw).map_err(|_| ())?),
+            ArcIdentity::new(
+                parse_func(func_index, size_start, locals_start, end, raw).map_err(|_| ())?,
+            ),
         );
-        self.add_auto_function(size_start).ok_or(())?;
+        // Bodies past the limit are left as code, for the user to create functions for.
+        let body_index = func_index - module_data.func_imports.len() as u32;
+        let settings = &module_data.load_settings;
+        if settings.create_functions && settings.max_functions.is_none_or(|max| body_index < max) {
+            self.add_auto_function(size_start).ok_or(())?;
+        }
 
         if let Some(name) = func_exports.get(&func_index) {
-            let symbol = Symbol::builder(SymbolType::Function, name.as_str(), size_start).create();
+            let symbol = Symbol::builder(SymbolType::Function, name.as_str(), size_start)
+                .binding(Binding::Global)
+                .create();
             self.define_auto_symbol(&symbol);
         }
         Ok(())
     }
 
+    // The name section usually comes after the code section, so functions are named
+    // once the whole module has been parsed. Exported names take precedence.
+    fn define_func_names(&mut self, module_data: &ModuleData) {
+        for (func_index, name) in &module_data.func_names {
+            let imported = (*func_index as usize) < module_data.func_imports.len();
+            if imported || module_data.func_exports.contains_key(func_index) {
+                continue;
+            }
+            match module_data.func_addrs.get(*func_index as usize) {
+                Some(&addr) if addr != 0 => {
+                    let symbol = Symbol::builder(SymbolType::Function, name, addr)
+                        .binding(Binding::Local)
+                        .create();
+                    self.define_auto_symbol(&symbol);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Tags the functions that make up the module's interface: the exports it is entered
+    // through and the imports it calls out to. Internal functions are left untagged, and
+    // their symbols are local where the others' are global.
+    fn tag_interface_functions(&mut self, module_data: &ModuleData) {
+        let import_tag = match self.tag_type_by_name(IMPORT_TAG) {
+            Some(tag_type) => tag_type,
+            None => self.create_tag_type(IMPORT_TAG, "📥"),
+        };
+        for (import, addr) in module_data.func_imports.iter().zip(&module_data.func_addrs) {
+            let data = format!("{}::{}", import.module, import.name);
+            self.add_tag(*addr, &import_tag, &data, false);
+        }
+
+        let export_tag = match self.tag_type_by_name(EXPORT_TAG) {
+            Some(tag_type) => tag_type,
+            None => self.create_tag_type(EXPORT_TAG, "📤"),
+        };
+        for (func_index, name) in &module_data.func_exports {
+            if let Some(&addr) = module_data.func_addrs.get(*func_index as usize) {
+                self.add_tag(addr, &export_tag, name, false);
+            }
+        }
+    }
+
+    fn add_func_refs(
+        &self,
+        bn_func: &Function,
+        module_data: &ModuleData,
+        from_addr: u64,
+        funcs: impl IntoIterator<Item = u32>,
+    ) {
+        for func_index in funcs {
+            if let Some(&target) = module_data.func_addrs.get(func_index as usize) {
+                bn_func.add_auto_code_ref(from_addr, target, None);
+            }
+        }
+    }
+
+    // `table.init` copies an element segment into a table at runtime, which is how
+    // dynamically-linked modules populate their dispatch tables. Add references from
+    // each `table.init` to the functions in its segment so they can be followed.
+    fn add_table_init_refs(&self, module_data: &ModuleData) -> Result<(), ()> {
+        let platform = self.default_platform().ok_or(())?;
+        for (_, func) in module_data.funcs.iter() {
+            let func = func.as_ref();
+            let Some(bn_func) = self.function_at(&platform, func.size_start) else {
+                continue;
+            };
+            for (addr, op) in &func.ops {
+                let Operator::TableInit { elem_index, .. } = op.op else {
+                    continue;
+                };
+                let Some(segment) = module_data.elements.get(elem_index as usize) else {
+                    continue;
+                };
+                let funcs = segment.items.iter().flatten().copied();
+                self.add_func_refs(&bn_func, module_data, *addr, funcs);
+            }
+        }
+        Ok(())
+    }
+
+    // A `call_indirect` can only call functions in its table whose signature matches
+    // its type, so resolve the candidate callees of each call site. The workflow adds
+    // references to them.
+    fn resolve_indirect_calls(&self, module_data: &mut ModuleData) {
+        let mut candidates_cache = BTreeMap::new();
+        let mut targets = BTreeMap::new();
+        for (_, func) in module_data.funcs.iter() {
+            let func = func.as_ref();
+            for (addr, op) in &func.ops {
+                let Operator::CallIndirect {
+                    type_index,
+                    table_index,
+                } = op.op
+                else {
+                    continue;
+                };
+                let candidates: &Vec<u32> = candidates_cache
+                    .entry((type_index, table_index))
+                    .or_insert_with(|| {
+                        module_data.indirect_call_candidates(type_index, table_index)
+                    });
+                targets.insert(*addr, candidates.clone());
+            }
+        }
+        module_data.indirect_call_targets = targets;
+    }
+
+    // Restore the callees the user pinned indirect calls to, if this view was reopened
+    // from a database.
+    fn load_call_target_overrides(&self, module_data: &mut ModuleData) {
+        let Some(overrides) = self
+            .query_metadata(CALL_TARGET_OVERRIDES_KEY)
+            .and_then(|overrides| overrides.get_value_store())
+        else {
+            return;
+        };
+        for (addr, func_index) in overrides {
+            let addr = u64::from_str_radix(addr.trim_start_matches("0x"), 16).ok();
+            let func_index = func_index.get_unsigned_integer();
+            let (Some(addr), Some(func_index)) = (addr, func_index) else {
+                continue;
+            };
+            if module_data.indirect_call_targets.contains_key(&addr)
+                && (func_index as usize) < module_data.func_addrs.len()
+            {
+                module_data
+                    .call_target_overrides
+                    .insert(addr, func_index as u32);
+            }
+        }
+    }
+
+    // Comment the code that nothing reaches, such as padding after a `return` or the
+    // leftovers of optimizations, so that it isn't mistaken for part of the function.
+    fn annotate_unreachable_code(&mut self, module_data: &ModuleData) {
+        let mut n_ranges = 0;
+        for (_, func) in module_data.funcs.iter() {
+            for range in &func.as_ref().unreachable {
+                let comment = format!("Unreachable code ({} bytes)", range.end - range.start);
+                self.set_comment_at(range.start, &comment);
+                n_ranges += 1;
+            }
+        }
+        if n_ranges > 0 {
+            info!("Found {n_ranges} ranges of unreachable code");
+        }
+    }
+
+    fn add_start_function(&mut self, module_data: &ModuleData) {
+        let Some(start_func) = module_data.start_func else {
+            return;
+        };
+        let imported = (start_func as usize) < module_data.func_imports.len();
+        match module_data.func_addrs.get(start_func as usize) {
+            Some(&addr) if !imported => {
+                self.set_entry_point(addr);
+                self.add_entry_point(addr);
+            }
+            _ => warn!("Start function {start_func} is not defined in the module"),
+        }
+    }
+
+    // Comments each function with the facts about it that the module states, e.g.
+    // `func[212] type[9] (i32, i32) -> i32, exported as "compress"`, so that they show
+    // in the decompiler and in reports. Comments the user has written are kept.
+    fn comment_functions(&self, module_data: &ModuleData) {
+        let Some(platform) = self.default_platform() else {
+            return;
+        };
+        for (_, func) in module_data.funcs.iter() {
+            let func = func.as_ref();
+            let Some(bn_func) = self.function_at(&platform, func.size_start) else {
+                continue;
+            };
+            if !bn_func.comment().is_empty() {
+                continue;
+            }
+            let mut comment = format!("func[{}]", func.func_index);
+            if let Some(type_index) = module_data.func_types.get(func.func_index as usize) {
+                comment.push_str(&format!(" type[{type_index}]"));
+            }
+            if let Some(ty) = module_data.func_type(func.func_index) {
+                comment.push_str(&format!(" {}", signature_text(ty)));
+            }
+            if let Some(name) = module_data.func_exports.get(&func.func_index) {
+                comment.push_str(&format!(", exported as {name:?}"));
+            }
+            comment.push_str(&format!(
+                ", {}-byte body in the code section",
+                func.end - func.size_start
+            ));
+            bn_func.set_comment(&comment);
+        }
+    }
+
+    // Bookmarks where execution of the module can begin: the start function, which runs
+    // when the module is instantiated, and the exported functions, by export name.
+    fn bookmark_entry_points(&mut self, module_data: &ModuleData) {
+        let n_imported = module_data.func_imports.len();
+        let start = module_data
+            .start_func
+            .map(|func_index| (func_index, "start function".to_string()));
+        let exports = module_data
+            .func_exports
+            .iter()
+            .map(|(func_index, name)| (*func_index, format!("export {name}")));
+        let entry_points = start
+            .into_iter()
+            .chain(exports)
+            .filter(|(func_index, _)| *func_index as usize >= n_imported)
+            .filter_map(|(func_index, data)| {
+                Some((*module_data.func_addrs.get(func_index as usize)?, data))
+            })
+            .collect::<Vec<_>>();
+        if entry_points.is_empty() {
+            return;
+        }
+
+        let tag_type = match self.tag_type_by_name(BOOKMARK_TAG) {
+            Some(tag_type) => tag_type,
+            None => self.create_tag_type(BOOKMARK_TAG, "📌"),
+        };
+        for (addr, data) in &entry_points {
+            self.add_tag(*addr, &tag_type, data, false);
+        }
+        info!("Bookmarked {} entry points", entry_points.len());
+    }
+
+    // Code addresses in wasm DWARF are offsets from the start of the code section
+    // rather than file offsets. The plugin's DWARF parser moves the functions it imports
+    // to the code section, and the offset is logged to match up the rest by hand.
+    fn describe_dwarf(&self, module_data: &ModuleData) {
+        let Some(code_range) = &module_data.code_range else {
+            return;
+        };
+        if !module_data.dwarf_sections.is_empty() {
+            info!(
+                "Module has DWARF debug info; its code addresses are relative to the code \
+                 section at {:#x}",
+                code_range.start
+            );
+        }
+    }
+
+    // Addresses are 64-bit if linear memory is (memory64), or if the view reaches past
+    // 4 GiB, e.g. because linear memory is mapped high.
+    fn choose_address_size(&mut self, module_data: &mut ModuleData) {
+        module_data.address_size = match module_data.load_settings.address_size {
+            Some(address_size) => address_size,
+            None => {
+                let memory64 = module_data.memories.iter().any(|memory| memory.ty.memory64);
+                let extern_end = module_data.extern_base
+                    + module_data.func_imports.len() as u64 * EXTERN_STUB_SIZE;
+                let data_end = module_data
+                    .data_segments
+                    .iter()
+                    .filter_map(|segment| segment.memory_range())
+                    .map(|range| module_data.memory_base + range.end)
+                    .max()
+                    .unwrap_or(module_data.memory_base);
+                if memory64 || extern_end.max(data_end) > u32::MAX as u64 + 1 {
+                    8
+                } else {
+                    4
+                }
+            }
+        };
+        self.set_address_size(module_data.address_size);
+        info!("Using {}-bit addresses", module_data.address_size * 8);
+    }
+
+    // Parses the module with its progress shown in a background task, since large
+    // modules take long enough to look hung otherwise.
     pub(crate) fn parse_module(&mut self, module_data: &mut ModuleData) -> Result<(), ()> {
+        let task = BackgroundTask::new("Parsing WebAssembly module", true);
+        let result = self.parse_module_with_progress(module_data, &task, true);
+        task.finish();
+        result
+    }
+
+    // Parses the sections of the module into `module_data` and leaves it at that, e.g.
+    // to compare another module with the one that is open. No functions are created,
+    // since the architecture would analyze them with the module data of the module
+    // that is open, and none of the analysis of the module is done.
+    pub(crate) fn parse_module_data(&mut self, module_data: &mut ModuleData) -> Result<(), ()> {
+        let task = BackgroundTask::new("Parsing WebAssembly module", true);
+        let result = self.parse_module_with_progress(module_data, &task, false);
+        task.finish();
+        result
+    }
+
+    fn parse_module_with_progress(
+        &mut self,
+        module_data: &mut ModuleData,
+        task: &BackgroundTask,
+        analyze: bool,
+    ) -> Result<(), ()> {
         let parent = self.parent_view().ok_or(())?;
+        module_data.load_settings = LoadSettings::for_view(self.as_ref());
+        module_data.load_settings.create_functions &= analyze;
+        info!("Loading with {:?}", module_data.load_settings);
+        module_data.constant_display = ConstantDisplay::for_view(self.as_ref());
+        if analyze {
+            self.select_workflow();
+        }
+        // Each import takes at least `EXTERN_STUB_SIZE` bytes of the file, so the stubs
+        // of imported functions end before `extern_base` + the file length.
+        module_data.extern_base = parent.len().next_multiple_of(EXTERN_ALIGN);
+        let rebased = self
+            .query_metadata(MEMORY_BASE_KEY)
+            .and_then(|base| base.get_unsigned_integer());
+        let memory_base = rebased.or(module_data.load_settings.memory_base);
+        module_data.memory_base = memory_base.unwrap_or_else(|| {
+            (module_data.extern_base + parent.len())
+                .max(1)
+                .next_multiple_of(DEFAULT_MEMORY_BASE)
+        });
+        let module_range = self.select_core_module(&parent)?;
+        module_data.module_range = module_range.clone();
 
         const BUF_SIZE: usize = 1024;
         let mut buf = Vec::new();
-        let mut i = 0u64;
+        let mut i = module_range.start;
         let mut eof = false;
 
-        let mut parser = Parser::new(0);
+        let mut parser = Parser::new(module_range.start);
         let mut func_exports = BTreeMap::new();
         let mut func_index = 0u32;
+        let mut cancelled = false;
         loop {
             let (payload, consumed) = match parser.parse(&buf, eof).map_err(|_| ())? {
                 Chunk::NeedMoreData(hint) => {
                     assert!(!eof);
-                    let n_read = parent.read_into_vec(&mut buf, i, min(hint as usize, BUF_SIZE));
+                    let len = min(hint as usize, BUF_SIZE).min((module_range.end - i) as usize);
+                    let n_read = parent.read_into_vec(&mut buf, i, len);
                     i += n_read as u64;
                     eof = n_read == 0;
                     continue;
                 }
                 Chunk::Parsed { consumed, payload } => (payload, consumed),
             };
+            if let Some((id, range)) = payload.as_section() {
+                module_data.section_starts.push(range.start as u64);
+                let name = match &payload {
+                    Payload::CustomSection(reader) => reader.name().to_string(),
+                    _ => section_name(id).to_string(),
+                };
+                module_data.sections.push((name, (range.start as u64)..(range.end as u64)));
+            }
 
             if let Payload::CodeSectionStart { count, range, size } = payload {
                 // Parse the code section ourselves since we don't actually use the
                 // result of the `wasmparser` code section parser.
                 self.handle_code_section_start(count, range.clone(), size);
                 parser.skip_section();
+                module_data.code_range = Some((range.start as u64)..(range.end as u64));
 
                 let mut addr = range.start as u64;
                 let (count_2, n_bytes) = parent.read_u32_leb128(addr)?;
                 assert_eq!(count, count_2);
                 addr += n_bytes as u64;
 
-                for _ in 0..count {
+                for n_parsed in 0..count {
+                    // The functions after a cancellation are left out, so the rest of
+                    // the module still loads, with fewer functions.
+                    if task.is_cancelled() {
+                        warn!("Parsing was cancelled after {n_parsed} of {count} functions");
+                        cancelled = true;
+                        break;
+                    }
+                    if n_parsed % PROGRESS_INTERVAL == 0 {
+                        task.set_progress_text(&format!(
+                            "Parsing WebAssembly module: parsed {n_parsed}/{count} functions"
+                        ));
+                    }
                     let size_start = addr;
                     let (size, n_bytes) = parent.read_u32_leb128(addr)?;
                     addr += n_bytes as u64;
@@ -194,7 +1244,12 @@ impl WebAssemblyView {
                     module_data.func_addrs.push(size_start);
                 }
 
-                if addr != range.end as u64 {
+                let settings = &module_data.load_settings;
+                let max_functions = settings.max_functions.filter(|max| *max < count);
+                if let (true, Some(max)) = (settings.create_functions, max_functions) {
+                    info!("Created functions for the first {max} of {count} bodies");
+                }
+                if !cancelled && addr != range.end as u64 {
                     warn!(
                         "Code section start address {addr:#x} does not match range end {}",
                         range.end
@@ -206,38 +1261,43 @@ impl WebAssemblyView {
                 buf.clear();
             } else {
                 match payload {
-                    Payload::CustomSection(reader) => self.add_wasm_section_default(
-                        reader.range(),
-                        format!(".custom.{}", reader.name()),
-                    ),
+                    Payload::CustomSection(reader) => {
+                        self.handle_custom_section(reader, module_data)
+                    }
                     Payload::TypeSection(reader) => {
-                        self.add_wasm_section_default(reader.range(), ".type")
+                        self.handle_type_section(reader, &mut module_data.types)?
+                    }
+                    Payload::ImportSection(reader) => {
+                        self.handle_import_section(reader, module_data, &mut func_index)?;
+                        self.select_platform(module_data);
                     }
-                    Payload::ImportSection(reader) => self.handle_import_section(
-                        reader,
-                        &mut func_index,
-                        &mut module_data.func_addrs,
-                    )?,
                     Payload::FunctionSection(reader) => {
-                        self.add_wasm_section_default(reader.range(), ".function")
+                        self.handle_function_section(reader, &mut module_data.func_types)?
                     }
                     Payload::TableSection(reader) => {
-                        self.add_wasm_section_default(reader.range(), ".table")
+                        self.handle_table_section(reader, &mut module_data.tables)?
                     }
                     Payload::MemorySection(reader) => {
-                        self.add_wasm_section_default(reader.range(), ".memory")
+                        self.handle_memory_section(reader, &mut module_data.memories)?
                     }
                     Payload::GlobalSection(reader) => {
-                        self.add_wasm_section_default(reader.range(), ".global")
+                        self.handle_global_section(reader, &mut module_data.globals)?
+                    }
+                    Payload::TagSection(reader) => {
+                        self.handle_tag_section(reader, &mut module_data.tags)?
                     }
                     Payload::ExportSection(reader) => {
-                        self.handle_export_section(reader, &mut func_exports)
+                        self.handle_export_section(reader, module_data, &mut func_exports)
+                    }
+                    Payload::StartSection { func, range } => {
+                        self.add_wasm_section_default(range, ".start");
+                        module_data.start_func = Some(func);
                     }
                     Payload::ElementSection(reader) => {
-                        self.add_wasm_section_default(reader.range(), ".element")
+                        self.handle_element_section(reader, &mut module_data.elements)?
                     }
                     Payload::DataSection(reader) => {
-                        self.add_wasm_section_default(reader.range(), ".data")
+                        self.handle_data_section(reader, &mut module_data.data_segments)?
                     }
 
                     Payload::End(_) => break,
@@ -250,6 +1310,70 @@ impl WebAssemblyView {
             }
         }
 
+        module_data.func_exports = func_exports;
+        if !analyze {
+            return Ok(());
+        }
+        task.set_progress_text("Analyzing WebAssembly module");
+        self.choose_address_size(module_data);
+        self.define_func_names(module_data);
+        self.tag_interface_functions(module_data);
+        self.apply_signature_libraries(module_data);
+        self.add_start_function(module_data);
+        self.bookmark_entry_points(module_data);
+        self.describe_memories(&module_data.memories);
+        self.describe_tags(module_data);
+        self.resolve_branch_hints(module_data);
+        self.describe_dwarf(module_data);
+        // A reopened database already has the debug info and source comments, so
+        // don't ask the user to locate the files again.
+        if !self.file().is_database_backed() {
+            if let Some(url) = module_data.external_debug_info.clone() {
+                module_data.external_debug_path = self.locate_external_debug_info(&url);
+            }
+            if let Some(url) = &module_data.source_mapping_url {
+                self.load_source_map(url);
+            }
+        }
+        self.add_table_init_refs(module_data)?;
+        self.comment_functions(module_data);
+        // The passes through the instructions of every function and the contents of
+        // every segment are the slow ones, so a cancelled parse skips them and leaves
+        // the functions and segments that were parsed as they are.
+        let cancelled = cancelled || task.is_cancelled();
+        if !cancelled {
+            self.resolve_indirect_calls(module_data);
+            self.annotate_unreachable_code(module_data);
+            self.annotate_virtual_calls(&parent, module_data);
+            self.resolve_trampolines(module_data);
+            self.load_call_target_overrides(module_data);
+            self.tag_notable_instructions(module_data);
+            self.define_results_structs(module_data);
+            self.find_stack_frames(module_data);
+        }
+        if module_data.load_settings.map_data_segments {
+            self.map_data_segments(module_data);
+            if !cancelled {
+                self.define_data_strings(&parent, module_data);
+                self.define_data_symbols(module_data);
+            }
+        }
+        self.define_relocations(module_data)?;
+        if cancelled || task.is_cancelled() {
+            warn!("Analysis was cancelled; the module is only partially analyzed");
+            return Ok(());
+        }
+        self.annotate_allocators(module_data);
+        self.annotate_memory_idioms(module_data);
+        self.annotate_bindgen(module_data);
+        self.annotate_emscripten(module_data);
+        self.annotate_go(module_data);
+        self.annotate_cosmwasm(module_data);
+        self.annotate_ink(module_data);
+        self.annotate_near(module_data);
+        self.apply_type_libraries(module_data);
+        self.annotate_wasi_calls(&parent, module_data);
+
         Ok(())
     }
 }
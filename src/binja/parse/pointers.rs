@@ -0,0 +1,247 @@
+use crate::binja::arch::stack_effect::{address_type, local_type, stack_effect};
+use crate::binja::parse::module_data::{FunctionData, ModuleData};
+use binaryninja::rc::Ref;
+use binaryninja::types::{PointerBaseType, Type, TypeBuilder};
+use std::collections::BTreeMap;
+use wasmparser::{MemArg, Operator, ValType};
+
+// Where a value on the operand stack came from, as far as it matters for addresses.
+#[derive(Clone, Copy)]
+enum Origin {
+    // A local, plus a constant offset.
+    Local(u32, u64),
+    Const(u64),
+    Unknown,
+}
+
+// The memory access of a load or store, and the width and kind of the value accessed.
+pub(crate) fn memory_access(op: &Operator) -> Option<(MemArg, usize, bool)> {
+    let access = match *op {
+        Operator::I32Load8S { memarg }
+        | Operator::I32Load8U { memarg }
+        | Operator::I64Load8S { memarg }
+        | Operator::I64Load8U { memarg }
+        | Operator::I32Store8 { memarg }
+        | Operator::I64Store8 { memarg } => (memarg, 1, false),
+        Operator::I32Load16S { memarg }
+        | Operator::I32Load16U { memarg }
+        | Operator::I64Load16S { memarg }
+        | Operator::I64Load16U { memarg }
+        | Operator::I32Store16 { memarg }
+        | Operator::I64Store16 { memarg } => (memarg, 2, false),
+        Operator::I32Load { memarg }
+        | Operator::I64Load32S { memarg }
+        | Operator::I64Load32U { memarg }
+        | Operator::I32Store { memarg }
+        | Operator::I64Store32 { memarg } => (memarg, 4, false),
+        Operator::I64Load { memarg } | Operator::I64Store { memarg } => (memarg, 8, false),
+        Operator::F32Load { memarg } | Operator::F32Store { memarg } => (memarg, 4, true),
+        Operator::F64Load { memarg } | Operator::F64Store { memarg } => (memarg, 8, true),
+        _ => return None,
+    };
+    Some(access)
+}
+
+// What a function's loads and stores reveal about its values.
+#[derive(Default)]
+pub(crate) struct Accesses {
+    // The width and kind of the values accessed through each local, by local index.
+    // `None` if they are accessed as different types.
+    pub(crate) pointer_locals: BTreeMap<u32, Option<(usize, bool)>>,
+
+    // The width and kind of the values accessed at each offset from each local, by
+    // local index and offset. Of different accesses at the same offset, the widest is
+    // kept.
+    pub(crate) local_offsets: BTreeMap<(u32, u64), (usize, bool)>,
+
+    // Constant addresses in linear memory, by the address of the instruction.
+    pub(crate) const_addrs: Vec<(u64, u64)>,
+
+    // Constants, including sums of constants, by the address of the instruction that
+    // produces them.
+    pub(crate) constants: Vec<(u64, u64)>,
+
+    // The arguments of direct calls, by the address of the call: the function index,
+    // and the value of each argument that is a constant.
+    pub(crate) calls: Vec<(u64, u32, Vec<Option<u64>>)>,
+}
+
+// Follows the operand stack through straight-line code to find the address operand
+// of each load and store. At block boundaries and other instructions whose effect on
+// the stack isn't simple, what is known about the stack is dropped.
+pub(crate) fn find_accesses(module_data: &ModuleData, func: &FunctionData) -> Accesses {
+    let mut accesses = Accesses::default();
+    let mut stack: Vec<Origin> = Vec::new();
+    for (addr, op) in &func.ops {
+        if !func.is_reachable(*addr) {
+            stack.clear();
+            continue;
+        }
+        let op = &op.op;
+        if let Some((memarg, width, float)) = memory_access(op) {
+            let Some(effect) = stack_effect(module_data, Some(func), op) else {
+                stack.clear();
+                continue;
+            };
+            let address = stack
+                .len()
+                .checked_sub(effect.pops.len())
+                .map_or(Origin::Unknown, |i| stack[i]);
+            // Only memory 0 is mapped into the view.
+            let address = match memarg.memory {
+                0 => address,
+                _ => Origin::Unknown,
+            };
+            match address {
+                Origin::Local(local_index, offset) => {
+                    let offset = offset.wrapping_add(memarg.offset);
+                    accesses
+                        .local_offsets
+                        .entry((local_index, offset))
+                        .and_modify(|access| {
+                            if width > access.0 {
+                                *access = (width, float);
+                            }
+                        })
+                        .or_insert((width, float));
+                    accesses
+                        .pointer_locals
+                        .entry(local_index)
+                        .and_modify(|access| {
+                            if *access != Some((width, float)) {
+                                *access = None;
+                            }
+                        })
+                        .or_insert(Some((width, float)));
+                }
+                Origin::Const(value) => {
+                    let target = value.wrapping_add(memarg.offset);
+                    accesses.const_addrs.push((*addr, target));
+                }
+                Origin::Unknown => {}
+            }
+        }
+
+        if let Operator::Call { function_index } = *op
+            && let Some(ty) = module_data.func_type(function_index)
+        {
+            let n_params = ty.params().len();
+            let args = (0..n_params)
+                .map(|i| {
+                    let j = stack.len().checked_sub(n_params - i)?;
+                    match stack[j] {
+                        Origin::Const(value) => Some(value),
+                        _ => None,
+                    }
+                })
+                .collect();
+            accesses.calls.push((*addr, function_index, args));
+        }
+
+        let origin = match *op {
+            Operator::LocalGet { local_index } => Origin::Local(local_index, 0),
+            Operator::I32Const { value } => Origin::Const(value as u32 as u64),
+            Operator::I64Const { value } => Origin::Const(value as u64),
+            Operator::I32Add | Operator::I64Add => {
+                let b = stack.pop().unwrap_or(Origin::Unknown);
+                let a = stack.pop().unwrap_or(Origin::Unknown);
+                match (a, b) {
+                    (Origin::Local(local_index, offset), Origin::Const(value))
+                    | (Origin::Const(value), Origin::Local(local_index, offset)) => {
+                        Origin::Local(local_index, offset.wrapping_add(value))
+                    }
+                    (Origin::Const(a), Origin::Const(b)) => {
+                        let sum = match op {
+                            Operator::I32Add => (a as u32).wrapping_add(b as u32) as u64,
+                            _ => a.wrapping_add(b),
+                        };
+                        Origin::Const(sum)
+                    }
+                    _ => Origin::Unknown,
+                }
+            }
+            Operator::LocalTee { .. } => continue,
+            Operator::Drop => {
+                stack.pop();
+                continue;
+            }
+            _ => {
+                let Some(effect) = stack_effect(module_data, Some(func), op) else {
+                    stack.clear();
+                    continue;
+                };
+                let n_pops = effect.pops.len().min(stack.len());
+                stack.truncate(stack.len() - n_pops);
+                stack.extend(effect.pushes.iter().map(|_| Origin::Unknown));
+                continue;
+            }
+        };
+        if let Origin::Const(value) = origin {
+            accesses.constants.push((*addr, value));
+        }
+        stack.push(origin);
+    }
+    accesses
+}
+
+fn pointee_type(access: Option<(usize, bool)>) -> Ref<Type> {
+    match access {
+        Some((width, true)) => Type::float(width),
+        Some((width, false)) => Type::int(width, false),
+        None => Type::void(),
+    }
+}
+
+// A pointer to `pointee` in linear memory. Pointers are offsets into memory, so they
+// are based on where memory is mapped in the view, and binja follows them there.
+pub(crate) fn memory_pointer(module_data: &ModuleData, pointee: &Type) -> Ref<Type> {
+    let width = if address_type(module_data, 0) == ValType::I64 {
+        8
+    } else {
+        4
+    };
+    let builder = TypeBuilder::pointer_of_width(pointee, width, false, false, None);
+    builder.set_pointer_base(
+        PointerBaseType::RelativeToConstantPointerBaseType,
+        module_data.memory_base as i64,
+    );
+    builder.finalize()
+}
+
+// Finds the parameters and locals of a function that are used as addresses of loads
+// and stores, which are pointers into linear memory, and returns their types by local
+// index.
+pub(crate) fn infer_pointer_locals(
+    module_data: &ModuleData,
+    func: &FunctionData,
+) -> BTreeMap<u32, Ref<Type>> {
+    let accesses = find_accesses(module_data, func);
+    let ptr_type = address_type(module_data, 0);
+    accesses
+        .pointer_locals
+        .iter()
+        .filter(|(local_index, _)| local_type(module_data, func, **local_index) == Some(ptr_type))
+        .map(|(local_index, access)| {
+            let pointee = pointee_type(*access);
+            (*local_index, memory_pointer(module_data, &pointee))
+        })
+        .collect()
+}
+
+// The instructions of a function that reference data, with the addresses of the data:
+// the data they access at constant addresses, and the data that constants point into,
+// which is how strings and tables are found in wasm: their addresses are materialized
+// with `i32.const`, either directly or as a sum.
+pub(crate) fn data_refs(module_data: &ModuleData, func: &FunctionData) -> Vec<(u64, u64)> {
+    let accesses = find_accesses(module_data, func);
+    let pointers = accesses
+        .constants
+        .iter()
+        .filter(|(_, value)| module_data.is_data_address(*value));
+    accesses
+        .const_addrs
+        .iter()
+        .chain(pointers)
+        .map(|(addr, target)| (*addr, module_data.memory_base + target))
+        .collect()
+}
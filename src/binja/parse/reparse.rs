@@ -0,0 +1,219 @@
+use crate::binja::parse::instruction_tags::tag_type_names;
+use crate::binja::parse::module_data::{
+    ModuleData, EXTERN_STUB_SIZE, MEMORY_BASE_KEY, MODULE_DATA,
+};
+use crate::binja::parse::module_parse::{BOOKMARK_TAG, EXPORT_TAG, IMPORT_TAG};
+use crate::binja::parse::pointers::data_refs;
+use crate::binja::toolchain::TAG_TYPES;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+use binaryninja::custom_binary_view::CustomBinaryView;
+use binaryninja::symbol::Symbol;
+use log::{error, info};
+use std::ops::Range;
+
+// The addresses that linear memory 0 takes up when it is mapped at `base`: 4 GiB for a
+// 32-bit memory, or the rest of the address space for a 64-bit one.
+fn memory_range(module_data: &ModuleData, base: u64) -> Range<u64> {
+    let memory64 = module_data
+        .memories
+        .first()
+        .is_some_and(|memory| memory.ty.memory64);
+    let size = if memory64 { u64::MAX - base } else { 1 << 32 };
+    base..base.saturating_add(size)
+}
+
+// The references from functions to data that the workflow adds, by the start of the
+// function they are from.
+fn all_data_refs(module_data: &ModuleData) -> Vec<(u64, Vec<(u64, u64)>)> {
+    module_data
+        .funcs
+        .iter()
+        .map(|(_, func)| {
+            let func = func.as_ref();
+            (func.size_start, data_refs(module_data, func))
+        })
+        .collect()
+}
+
+// Removes the references from `all_data_refs`, so they don't outlive the addresses they
+// point to.
+fn remove_data_refs(view: &BinaryView, refs: &[(u64, Vec<(u64, u64)>)]) {
+    let Some(platform) = view.default_platform() else {
+        return;
+    };
+    for (func_start, refs) in refs {
+        if let Some(bn_func) = view.function_at(&platform, *func_start) {
+            for (addr, target) in refs {
+                bn_func.remove_auto_code_ref(*addr, *target, None);
+            }
+        }
+    }
+}
+
+// Where parsing the module put things in the view: the module in the file, the stubs
+// of imported functions and linear memory. Also the functions it may have bookmarked.
+struct ParsedRanges {
+    module: Range<u64>,
+    others: Vec<Range<u64>>,
+    func_addrs: Vec<u64>,
+}
+
+impl ParsedRanges {
+    fn new(module_data: &ModuleData) -> Self {
+        let extern_end =
+            module_data.extern_base + module_data.func_imports.len() as u64 * EXTERN_STUB_SIZE;
+        Self {
+            module: module_data.module_range.clone(),
+            others: vec![
+                module_data.extern_base..extern_end,
+                memory_range(module_data, module_data.memory_base),
+            ],
+            func_addrs: module_data.func_addrs.clone(),
+        }
+    }
+
+    fn contains(&self, addr: u64) -> bool {
+        self.module.contains(&addr) || self.others.iter().any(|range| range.contains(&addr))
+    }
+}
+
+impl WebAssemblyView {
+    // Removes what parsing the module added to the view, other than functions, so that
+    // the module can be parsed again without leaving stale symbols and segments behind,
+    // e.g. those of a different memory base. Functions are kept, since the bodies are
+    // still where they were in the file, and so are the segments of the module itself,
+    // which parsing adds again the same before it gets to the functions. The segments
+    // of imports and linear memory are removed, since they may move.
+    fn clear_module(&mut self, parsed: &ParsedRanges) {
+        for symbol in &self.symbols() {
+            if symbol.auto_defined() && parsed.contains(symbol.address()) {
+                self.undefine_auto_symbol(symbol);
+            }
+        }
+        for data_var in &self.data_variables() {
+            if data_var.auto_discovered() && parsed.contains(data_var.address()) {
+                self.undefine_auto_data_var(data_var.address(), None);
+            }
+        }
+        for section in &self.sections() {
+            if section.auto_defined() && parsed.contains(section.start()) {
+                self.remove_auto_section(section.name());
+            }
+        }
+        for segment in &self.segments() {
+            let range = segment.address_range();
+            let in_others = parsed.others.iter().any(|other| other.contains(&range.start));
+            if segment.auto_defined() && in_others {
+                self.remove_auto_segment(range);
+            }
+        }
+        // Bookmarks share their tag type with the user's, so only the ones parsing
+        // added are removed.
+        if let Some(bookmarks) = self.tag_type_by_name(BOOKMARK_TAG) {
+            for addr in &parsed.func_addrs {
+                self.remove_auto_data_tags_of_type(*addr, &bookmarks);
+            }
+        }
+        let tag_types = [EXPORT_TAG, IMPORT_TAG]
+            .into_iter()
+            .chain(tag_type_names())
+            .chain(TAG_TYPES);
+        for name in tag_types {
+            if let Some(tag_type) = self.tag_type_by_name(name) {
+                self.remove_tag_type(&tag_type);
+            }
+        }
+    }
+
+    // Parses the module of `view` again from scratch, e.g. to pick up changed settings
+    // or to finish a parse that was cancelled. The old module data is used until the
+    // new one is ready.
+    pub(crate) fn reparse(view: &BinaryView) -> Result<(), ()> {
+        let parsed = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            ParsedRanges::new(module_data_lock.as_ref().ok_or(())?)
+        };
+        let mut wasm_view = WebAssemblyView::new(view, &true)?;
+        wasm_view.clear_module(&parsed);
+        wasm_view.set_entry_point(0);
+        wasm_view.set_address_size(4);
+
+        let mut module_data = ModuleData::new();
+        let result = wasm_view.parse_module(&mut module_data);
+        *MODULE_DATA.lock().unwrap() = Some(module_data);
+        view.update_analysis();
+        if result.is_ok() {
+            info!("Reparsed the module");
+        }
+        result
+    }
+
+    // Moves linear memory to `new_base` by parsing the module again, e.g. to make room
+    // for other regions the user adds. The symbols and data variables the user defined
+    // in memory move with it, and the references to data are made again when functions
+    // are reanalyzed. The base is stored in the view, so it's kept when the view is
+    // reopened from a database.
+    pub(crate) fn rebase_memory(view: &BinaryView, new_base: u64) -> Result<(), ()> {
+        let (refs, old_range) = {
+            let module_data_lock = MODULE_DATA.lock().unwrap();
+            let module_data = module_data_lock.as_ref().ok_or(())?;
+            let extern_end =
+                module_data.extern_base + module_data.func_imports.len() as u64 * EXTERN_STUB_SIZE;
+            if new_base < extern_end {
+                error!(
+                    "Linear memory at {new_base:#x} would overlap the module or the imported \
+                     functions, which end at {extern_end:#x}"
+                );
+                return Err(());
+            }
+            (
+                all_data_refs(module_data),
+                memory_range(module_data, module_data.memory_base),
+            )
+        };
+        // The view is changed once the lock is released, and the module is parsed again
+        // after, which takes the lock again.
+        remove_data_refs(view, &refs);
+        let rebase = |addr: u64| (addr - old_range.start).checked_add(new_base);
+
+        let mut symbols = Vec::new();
+        for symbol in &view.symbols() {
+            if symbol.auto_defined() || !old_range.contains(&symbol.address()) {
+                continue;
+            }
+            view.undefine_user_symbol(symbol);
+            if let Some(addr) = rebase(symbol.address()) {
+                symbols.push(Symbol::builder(symbol.sym_type(), &symbol.raw_name(), addr).create());
+            }
+        }
+        let mut data_vars = Vec::new();
+        for data_var in &view.data_variables() {
+            if data_var.auto_discovered() || !old_range.contains(&data_var.address()) {
+                continue;
+            }
+            view.undefine_user_data_var(data_var.address());
+            if let Some(addr) = rebase(data_var.address()) {
+                data_vars.push((addr, data_var.ty()));
+            }
+        }
+
+        view.store_metadata(MEMORY_BASE_KEY, new_base, false);
+        Self::reparse(view)?;
+        for (addr, ty) in &data_vars {
+            view.define_user_data_var(*addr, ty);
+        }
+        for symbol in &symbols {
+            view.define_user_symbol(symbol);
+        }
+        view.reanalyze();
+        info!(
+            "Moved linear memory from {:#x} to {new_base:#x}, with {} symbols and {} data \
+             variables",
+            old_range.start,
+            symbols.len(),
+            data_vars.len()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use crate::binja::arch::stack_effect::address_type;
+use crate::binja::parse::module_data::{FunctionData, ModuleData, StackFrame};
+use crate::binja::parse::pointers::find_accesses;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::rc::Ref;
+use binaryninja::types::Type;
+use log::info;
+use std::collections::BTreeMap;
+use wasmparser::Operator;
+
+const STACK_POINTER_NAME: &str = "__stack_pointer";
+
+// Finds the prologue that allocates a frame on the shadow stack, which LLVM emits as
+//
+//     global.get __stack_pointer
+//     i32.const <size>
+//     i32.sub
+//     local.tee <frame>
+//
+// or without the `i32.const` and `i32.sub` if the frame is empty, and with `local.set`
+// if the stack pointer isn't written back.
+fn find_frame(func: &FunctionData, stack_pointer: u32) -> Option<StackFrame> {
+    let addrs = func.ops.keys().copied().collect::<Vec<_>>();
+    let ops = func.ops.values().map(|op| &op.op).collect::<Vec<_>>();
+    for (i, op) in ops.iter().enumerate() {
+        let Operator::GlobalGet { global_index } = op else {
+            continue;
+        };
+        if *global_index != stack_pointer {
+            continue;
+        }
+        let (size, rest) = match ops.get(i + 1..i + 3) {
+            Some([Operator::I32Const { value }, Operator::I32Sub]) => (*value as u32 as u64, i + 3),
+            Some([Operator::I64Const { value }, Operator::I64Sub]) => (*value as u64, i + 3),
+            _ => (0, i + 1),
+        };
+        match ops.get(rest) {
+            Some(Operator::LocalTee { local_index } | Operator::LocalSet { local_index }) => {
+                return Some(StackFrame {
+                    prologue: addrs[i],
+                    local_index: *local_index,
+                    size,
+                });
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+// The stack pointer is the global named `__stack_pointer`, or in stripped modules the
+// mutable global of the address type that the most functions allocate frames from.
+fn stack_pointer_global(module_data: &ModuleData) -> Option<u32> {
+    if let Some((index, _)) = module_data
+        .global_names
+        .iter()
+        .find(|(_, name)| *name == STACK_POINTER_NAME)
+    {
+        return Some(*index);
+    }
+
+    let ptr_type = address_type(module_data, 0);
+    let mut n_frames = BTreeMap::<u32, usize>::new();
+    for (_, func) in module_data.funcs.iter() {
+        let ops = func
+            .as_ref()
+            .ops
+            .values()
+            .map(|op| &op.op)
+            .collect::<Vec<_>>();
+        let global_index = ops.windows(3).find_map(|window| match window {
+            [Operator::GlobalGet { global_index }, Operator::I32Const { .. }, Operator::I32Sub]
+            | [Operator::GlobalGet { global_index }, Operator::I64Const { .. }, Operator::I64Sub] => {
+                Some(*global_index)
+            }
+            _ => None,
+        });
+        if let Some(global_index) = global_index {
+            *n_frames.entry(global_index).or_default() += 1;
+        }
+    }
+    n_frames
+        .into_iter()
+        .filter(|(index, _)| {
+            module_data
+                .globals
+                .get(*index as usize)
+                .is_some_and(|global| global.ty.mutable && global.ty.content_type == ptr_type)
+        })
+        .max_by_key(|(_, count)| *count)
+        .map(|(index, _)| index)
+}
+
+// The stack variables of the accesses to a function's frame on the shadow stack, by
+// their offset, with their types and names. Linear memory has no native stack, so
+// compilers keep one in memory and address it through the `__stack_pointer` global.
+// Offsets are relative to the value of the stack pointer on entry, as they are on
+// native architectures.
+//
+// Each variable extends to the next one, or to the end of the frame, so a buffer that
+// is accessed only at its start is shown as an array of its full size.
+pub(crate) fn frame_vars(
+    module_data: &ModuleData,
+    func: &FunctionData,
+) -> Vec<(i64, Ref<Type>, String)> {
+    let Some(frame) = module_data.stack_frames.get(&func.func_index) else {
+        return Vec::new();
+    };
+    let accesses = find_accesses(module_data, func);
+    let vars = accesses
+        .local_offsets
+        .iter()
+        .filter(|((local_index, offset), _)| {
+            *local_index == frame.local_index && *offset < frame.size
+        })
+        .map(|((_, offset), access)| (*offset, *access))
+        .collect::<Vec<_>>();
+    let mut frame_vars = Vec::new();
+    for (i, &(offset, (width, float))) in vars.iter().enumerate() {
+        let end = vars.get(i + 1).map_or(frame.size, |(next, _)| *next);
+        let element = match float {
+            true => Type::float(width),
+            false => Type::int(width, false),
+        };
+        let count = (end - offset) / width as u64;
+        let ty = match count {
+            0 | 1 => element,
+            _ => Type::array(&element, count),
+        };
+        let stack_offset = offset as i64 - frame.size as i64;
+        let name = format!("var_{:x}", -stack_offset);
+        frame_vars.push((stack_offset, ty, name));
+    }
+    frame_vars
+}
+
+impl WebAssemblyView {
+    // Finds the global that holds the shadow stack pointer, and names it if the module
+    // is stripped. Then recovers the size of each function's frame from its prologue.
+    pub(crate) fn find_stack_frames(&mut self, module_data: &mut ModuleData) {
+        let Some(stack_pointer) = stack_pointer_global(module_data) else {
+            return;
+        };
+        info!("Global {stack_pointer} is the shadow stack pointer");
+        module_data.stack_pointer_global = Some(stack_pointer);
+        module_data
+            .global_names
+            .entry(stack_pointer)
+            .or_insert_with(|| STACK_POINTER_NAME.to_string());
+
+        let mut frames = BTreeMap::new();
+        for (_, func) in module_data.funcs.iter() {
+            let func = func.as_ref();
+            let Some(frame) = find_frame(func, stack_pointer) else {
+                continue;
+            };
+            if frame.size > 0 {
+                self.set_comment_at(
+                    frame.prologue,
+                    &format!(
+                        "Allocates a {:#x}-byte frame on the shadow stack",
+                        frame.size
+                    ),
+                );
+            }
+            frames.insert(func.func_index, frame);
+        }
+        info!("Found {} shadow stack frames", frames.len());
+        module_data.stack_frames = frames;
+    }
+}
@@ -0,0 +1,166 @@
+use crate::binja::arch::intrinsic::value_type;
+use crate::binja::parse::local_types::infer_local_types;
+use crate::binja::parse::module_data::{FunctionData, ModuleData};
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::rc::Ref;
+use binaryninja::types::{FunctionParameter, MemberAccess, MemberScope, StructureBuilder, Type};
+use std::collections::{BTreeMap, BTreeSet};
+use wasmparser::{Operator, ValType};
+
+// What analysis of a function's code says about its parameters and results beyond
+// their wasm types.
+#[derive(Default)]
+struct SignatureHints {
+    // Types of the parameters that their uses show to be pointers, bools or unsigned,
+    // by parameter index.
+    param_types: BTreeMap<u32, Ref<Type>>,
+}
+
+impl SignatureHints {
+    fn is_empty(&self) -> bool {
+        self.param_types.is_empty()
+    }
+}
+
+// Tests and comparisons, which push 1 or 0.
+pub(crate) fn is_comparison(op: &Operator) -> bool {
+    matches!(
+        op,
+        Operator::I32Eqz
+            | Operator::I32Eq
+            | Operator::I32Ne
+            | Operator::I32LtS
+            | Operator::I32LtU
+            | Operator::I32GtS
+            | Operator::I32GtU
+            | Operator::I32LeS
+            | Operator::I32LeU
+            | Operator::I32GeS
+            | Operator::I32GeU
+            | Operator::I64Eqz
+            | Operator::I64Eq
+            | Operator::I64Ne
+            | Operator::I64LtS
+            | Operator::I64LtU
+            | Operator::I64GtS
+            | Operator::I64GtU
+            | Operator::I64LeS
+            | Operator::I64LeU
+            | Operator::I64GeS
+            | Operator::I64GeU
+            | Operator::F32Eq
+            | Operator::F32Ne
+            | Operator::F32Lt
+            | Operator::F32Gt
+            | Operator::F32Le
+            | Operator::F32Ge
+            | Operator::F64Eq
+            | Operator::F64Ne
+            | Operator::F64Lt
+            | Operator::F64Gt
+            | Operator::F64Le
+            | Operator::F64Ge
+    )
+}
+
+fn value_type_name(ty: ValType) -> &'static str {
+    match ty {
+        ValType::I32 => "i32",
+        ValType::I64 => "i64",
+        ValType::F32 => "f32",
+        ValType::F64 => "f64",
+        ValType::V128 => "v128",
+        ValType::Ref(_) => "ref",
+    }
+}
+
+// Functions with several results return them in a struct named after their types,
+// e.g. `i32_i64`, with the results as members `r0`, `r1`, ... in order.
+fn results_struct_name(results: &[ValType]) -> String {
+    results
+        .iter()
+        .map(|result| value_type_name(*result))
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn results_struct(results: &[ValType]) -> Ref<Type> {
+    let mut builder = StructureBuilder::new();
+    for (i, result) in results.iter().enumerate() {
+        builder.append(
+            &value_type(*result),
+            &format!("r{i}"),
+            MemberAccess::NoAccess,
+            MemberScope::NoScope,
+        );
+    }
+    Type::structure(&builder.finalize())
+}
+
+// The type of a function: its wasm signature, refined by `hints`.
+fn function_type(
+    module_data: &ModuleData,
+    func: &FunctionData,
+    hints: &SignatureHints,
+) -> Option<Ref<Type>> {
+    let ty = module_data.func_type(func.func_index)?;
+    let result = match ty.results() {
+        [] => Type::void(),
+        [result] => value_type(*result),
+        results => {
+            let structure = results_struct(results);
+            Type::named_type_from_type(&results_struct_name(results), &structure)
+        }
+    };
+    let params = ty
+        .params()
+        .iter()
+        .enumerate()
+        .map(|(i, param)| {
+            let local_index = i as u32;
+            let ty = match hints.param_types.get(&local_index) {
+                Some(ty) => ty.clone(),
+                None => value_type(*param),
+            };
+            let name = module_data.local_name(func.func_index, local_index);
+            FunctionParameter::new(ty, name, None)
+        })
+        .collect::<Vec<_>>();
+    Some(Type::function(&result, params, false))
+}
+
+// The wasm signature of a function, where analysis of its code shows more than the
+// wasm types do: parameters that are pointers, bools or unsigned. Functions with
+// several results are always typed, since binja would otherwise only see the first of
+// them. `None` if the function doesn't need typing.
+pub(crate) fn refine_signature(module_data: &ModuleData, func: &FunctionData) -> Option<Ref<Type>> {
+    let ty = module_data.func_type(func.func_index)?;
+    let n_params = ty.params().len();
+    let hints = SignatureHints {
+        param_types: infer_local_types(module_data, func)
+            .into_iter()
+            .filter(|(local_index, _)| (*local_index as usize) < n_params)
+            .collect(),
+    };
+    if hints.is_empty() && ty.results().len() <= 1 {
+        return None;
+    }
+    function_type(module_data, func, &hints)
+}
+
+impl WebAssemblyView {
+    // Defines the structs that functions with several results return.
+    pub(crate) fn define_results_structs(&mut self, module_data: &ModuleData) {
+        let results = module_data
+            .types
+            .iter()
+            .map(|ty| ty.results())
+            .filter(|results| results.len() > 1)
+            .collect::<BTreeSet<_>>();
+        for results in results {
+            let structure = results_struct(results);
+            self.define_auto_type(&results_struct_name(results), "wasm", &structure);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::binja::parse::debug_info::resolve_local_url;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::interaction::get_open_filename_input;
+use log::{info, warn};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+// Source map v3, as emitted by emscripten, AssemblyScript and wasm-pack. Only the
+// fields needed to map code offsets to source lines are read.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SourceMap {
+    version: u32,
+    #[serde(default)]
+    source_root: Option<String>,
+    sources: Vec<Option<String>>,
+    mappings: String,
+}
+
+// A source location that code at `addr` maps to. Lines are zero-based, as they are
+// in the source map.
+struct Mapping {
+    addr: u64,
+    source: usize,
+    line: u32,
+}
+
+fn decode_base64_digit(c: u8) -> Option<i64> {
+    match c {
+        b'A'..=b'Z' => Some((c - b'A') as i64),
+        b'a'..=b'z' => Some((c - b'a') as i64 + 26),
+        b'0'..=b'9' => Some((c - b'0') as i64 + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+// Decodes the base64 VLQ fields of a single mapping segment.
+fn decode_vlq_segment(segment: &str) -> Option<Vec<i64>> {
+    let mut fields = Vec::new();
+    let mut value = 0i64;
+    let mut shift = 0;
+    for c in segment.bytes() {
+        let digit = decode_base64_digit(c)?;
+        value += (digit & 0x1f) << shift;
+        if digit & 0x20 != 0 {
+            shift += 5;
+            if shift > 60 {
+                return None;
+            }
+            continue;
+        }
+
+        // The lowest bit of the value is its sign.
+        let magnitude = value >> 1;
+        fields.push(if value & 1 != 0 {
+            -magnitude
+        } else {
+            magnitude
+        });
+        value = 0;
+        shift = 0;
+    }
+    (shift == 0).then_some(fields)
+}
+
+// Decodes the `mappings` string. In wasm source maps, everything is on a single
+// "line", and the generated column is the byte offset of the instruction in the
+// module.
+fn decode_mappings(mappings: &str) -> Option<Vec<Mapping>> {
+    let mut result = Vec::new();
+    let (mut column, mut source, mut line) = (0i64, 0i64, 0i64);
+    for segment in mappings.split([';', ',']).filter(|s| !s.is_empty()) {
+        let fields = decode_vlq_segment(segment)?;
+        column += fields[0];
+        // Segments with a single field map code to no source location.
+        if fields.len() < 4 {
+            continue;
+        }
+        source += fields[1];
+        line += fields[2];
+        result.push(Mapping {
+            addr: u64::try_from(column).ok()?,
+            source: usize::try_from(source).ok()?,
+            line: u32::try_from(line).ok()?,
+        });
+    }
+    Some(result)
+}
+
+// The path of `source` under `source_root`, which the spec says is prefixed to it, but
+// which tools write both with and without a trailing `/`.
+fn source_path(source_root: &str, source: &str) -> String {
+    if source_root.is_empty() || source_root.ends_with('/') || source.starts_with('/') {
+        format!("{source_root}{source}")
+    } else {
+        format!("{source_root}/{source}")
+    }
+}
+
+impl WebAssemblyView {
+    // Find the source map named by the `sourceMappingURL` section (asking the user if
+    // it is not next to the module), and comment each instruction that starts a new
+    // source line with its file and line number.
+    pub(crate) fn load_source_map(&mut self, url: &str) {
+        self.store_metadata("wasm.sourceMappingURL", url, true);
+
+        let module_path = PathBuf::from(self.file().filename());
+        let path = resolve_local_url(&module_path, url).or_else(|| {
+            info!("Source map {url:?} was not found next to the module");
+            get_open_filename_input(&format!("Locate source map ({url})"), "*.map")
+        });
+        let Some(path) = path else {
+            warn!("Source map {url:?} was not loaded");
+            return;
+        };
+
+        let source_map = match std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| serde_json::from_slice::<SourceMap>(&data).map_err(|e| e.to_string()))
+        {
+            Ok(source_map) if source_map.version == 3 => source_map,
+            Ok(source_map) => {
+                warn!("Unsupported source map version {}", source_map.version);
+                return;
+            }
+            Err(e) => {
+                warn!("Failed to read source map {}: {e}", path.display());
+                return;
+            }
+        };
+        let Some(mappings) = decode_mappings(&source_map.mappings) else {
+            warn!(
+                "Failed to decode the mappings of source map {}",
+                path.display()
+            );
+            return;
+        };
+
+        info!(
+            "Loaded {} mappings from source map {}",
+            mappings.len(),
+            path.display()
+        );
+        let source_root = source_map.source_root.as_deref().unwrap_or_default();
+        let mut prev: Option<&Mapping> = None;
+        for mapping in &mappings {
+            // Consecutive instructions usually map to the same line; only comment the
+            // first of them.
+            if prev.is_some_and(|prev| (prev.source, prev.line) == (mapping.source, mapping.line)) {
+                continue;
+            }
+            prev = Some(mapping);
+
+            let Some(Some(source)) = source_map.sources.get(mapping.source) else {
+                continue;
+            };
+            self.set_comment_at(
+                mapping.addr,
+                &format!("{}:{}", source_path(source_root, source), mapping.line + 1),
+            );
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use crate::binja::arch::opcode_name;
+use crate::binja::arch::stack_effect::stack_effect;
+use crate::binja::parse::module_data::{FunctionData, ModuleData};
+use log::warn;
+use std::collections::BTreeMap;
+use wasmparser::{BlockType, Operator, ValType};
+
+// A value on the operand stack, and the address of the instruction that pushed it, or
+// `None` for the parameters of the block it is in.
+#[derive(Clone, Copy)]
+pub(crate) struct StackValue {
+    pub ty: ValType,
+    pub producer: Option<u64>,
+}
+
+// A block that the operand stack is in: how high the stack was outside of it, and the
+// types of its parameters and results.
+struct Frame {
+    height: usize,
+    params: Vec<ValType>,
+    results: Vec<ValType>,
+    is_loop: bool,
+}
+
+impl Frame {
+    // The values that a branch to the block takes along: the parameters of a loop,
+    // which it goes back to the start of, or else the results.
+    fn label_types(&self) -> &[ValType] {
+        if self.is_loop {
+            &self.params
+        } else {
+            &self.results
+        }
+    }
+}
+
+// What a branch does to the operand stack on its way to its target: it takes the
+// values the target's block expects along, and discards the ones below them down to
+// where the block started. Both are bottom first.
+#[derive(Debug)]
+pub(crate) struct Unwind {
+    pub keep: Vec<ValType>,
+    pub discard: Vec<ValType>,
+}
+
+// What the lifter needs to know about the operand stack of a function's instructions
+// that the instructions themselves don't say.
+#[derive(Debug, Default)]
+pub(crate) struct StackLayout {
+    // The type of the operands of `drop` and untyped `select`, by their address.
+    pub operand_types: BTreeMap<u64, ValType>,
+
+    // How each branch that leaves values behind unwinds the stack, by the address of
+    // the branch and the relative depth of its target.
+    pub unwinds: BTreeMap<(u64, u32), Unwind>,
+}
+
+fn block_types(module_data: &ModuleData, blockty: &BlockType) -> (Vec<ValType>, Vec<ValType>) {
+    match *blockty {
+        BlockType::Empty => (Vec::new(), Vec::new()),
+        BlockType::Type(ty) => (Vec::new(), vec![ty]),
+        BlockType::FuncType(type_index) => match module_data.types.get(type_index as usize) {
+            Some(ty) => (ty.params().to_vec(), ty.results().to_vec()),
+            None => (Vec::new(), Vec::new()),
+        },
+    }
+}
+
+fn tag_params(module_data: &ModuleData, tag_index: u32) -> Vec<ValType> {
+    let ty = module_data
+        .tags
+        .get(tag_index as usize)
+        .and_then(|tag| module_data.types.get(tag.ty.func_type_idx as usize));
+    ty.map_or_else(Vec::new, |ty| ty.params().to_vec())
+}
+
+fn pop(stack: &mut Vec<StackValue>, frame: Option<&Frame>, n: usize) {
+    let floor = frame.map_or(0, |frame| frame.height);
+    stack.truncate(stack.len().saturating_sub(n).max(floor));
+}
+
+fn push(stack: &mut Vec<StackValue>, types: &[ValType], producer: Option<u64>) {
+    stack.extend(types.iter().map(|ty| StackValue { ty: *ty, producer }));
+}
+
+// Follows the types of the values that the instructions of `func` before `end` pop and
+// push, and calls `visit` with each reachable instruction and the stack and blocks
+// right before it runs. Returns the stack at `end`, or fails with why it can't be
+// known: an instruction before it has a stack effect that isn't known.
+fn walk_stack(
+    module_data: &ModuleData,
+    func: &FunctionData,
+    end: u64,
+    mut visit: impl FnMut(u64, &Operator, &[StackValue], &[Frame]),
+) -> Result<Vec<StackValue>, String> {
+    let mut stack: Vec<StackValue> = Vec::new();
+    let mut frames: Vec<Frame> = Vec::new();
+    for (op_addr, op_data) in func.ops.range(func.ops_start..end) {
+        let (op_addr, op) = (*op_addr, &op_data.op);
+        // Unreachable code is skipped, but not the blocks it is in.
+        let is_structure = matches!(
+            op,
+            Operator::Block { .. }
+                | Operator::Loop { .. }
+                | Operator::If { .. }
+                | Operator::Try { .. }
+                | Operator::TryTable { .. }
+                | Operator::Else
+                | Operator::Catch { .. }
+                | Operator::CatchAll
+                | Operator::End
+                | Operator::Delegate { .. }
+        );
+        let reachable = func.is_reachable(op_addr);
+        if !is_structure && !reachable {
+            continue;
+        }
+        if reachable {
+            visit(op_addr, op, &stack, &frames);
+        }
+        match op {
+            Operator::Block { blockty }
+            | Operator::Loop { blockty }
+            | Operator::If { blockty }
+            | Operator::Try { blockty } => {
+                if let Operator::If { .. } = op {
+                    pop(&mut stack, frames.last(), 1);
+                }
+                let (params, results) = block_types(module_data, blockty);
+                pop(&mut stack, frames.last(), params.len());
+                frames.push(Frame {
+                    height: stack.len(),
+                    params: params.clone(),
+                    results,
+                    is_loop: matches!(op, Operator::Loop { .. }),
+                });
+                push(&mut stack, &params, None);
+            }
+            Operator::TryTable { try_table } => {
+                let (params, results) = block_types(module_data, &try_table.ty);
+                pop(&mut stack, frames.last(), params.len());
+                frames.push(Frame {
+                    height: stack.len(),
+                    params: params.clone(),
+                    results,
+                    is_loop: false,
+                });
+                push(&mut stack, &params, None);
+            }
+            // The other arm starts from the block's parameters again, and a handler
+            // from what was thrown.
+            Operator::Else => {
+                if let Some(frame) = frames.last() {
+                    stack.truncate(frame.height);
+                    push(&mut stack, &frame.params, None);
+                }
+            }
+            Operator::Catch { tag_index } => {
+                if let Some(frame) = frames.last() {
+                    stack.truncate(frame.height);
+                    push(&mut stack, &tag_params(module_data, *tag_index), None);
+                }
+            }
+            Operator::CatchAll => {
+                if let Some(frame) = frames.last() {
+                    stack.truncate(frame.height);
+                }
+            }
+            Operator::End | Operator::Delegate { .. } => {
+                if let Some(frame) = frames.pop() {
+                    stack.truncate(frame.height);
+                    push(&mut stack, &frame.results, Some(op_addr));
+                }
+            }
+            Operator::BrIf { .. } => pop(&mut stack, frames.last(), 1),
+            Operator::Drop => pop(&mut stack, frames.last(), 1),
+            Operator::Select => {
+                let ty = stack.len().checked_sub(2).map(|i| stack[i].ty);
+                pop(&mut stack, frames.last(), 3);
+                if let Some(ty) = ty {
+                    push(&mut stack, &[ty], Some(op_addr));
+                }
+            }
+            // After an instruction that doesn't fall through, the rest of the block is
+            // unreachable, so there is nothing more to follow until it ends.
+            Operator::Br { .. }
+            | Operator::BrTable { .. }
+            | Operator::Return
+            | Operator::Unreachable
+            | Operator::Throw { .. }
+            | Operator::ThrowRef
+            | Operator::Rethrow { .. }
+            | Operator::ReturnCall { .. }
+            | Operator::ReturnCallIndirect { .. }
+            | Operator::ReturnCallRef { .. } => {
+                let floor = frames.last().map_or(0, |frame| frame.height);
+                stack.truncate(floor);
+            }
+            _ => {
+                let Some(effect) = stack_effect(module_data, Some(func), op) else {
+                    let name = opcode_name(module_data, op_data).unwrap_or(format!("{op:?}"));
+                    return Err(format!(
+                        "the stack effect of {name} at {op_addr:#x} isn't known"
+                    ));
+                };
+                pop(&mut stack, frames.last(), effect.pops.len());
+                push(&mut stack, &effect.pushes, Some(op_addr));
+            }
+        }
+    }
+    Ok(stack)
+}
+
+// The operand stack right before the instruction at `addr` runs, bottom first. Blocks
+// only see the values above where they start, but the values below are shown too.
+// Fails with why the stack can't be known: the instruction is unreachable, or an
+// instruction before it has a stack effect that isn't known.
+pub(crate) fn stack_state(
+    module_data: &ModuleData,
+    func: &FunctionData,
+    addr: u64,
+) -> Result<Vec<StackValue>, String> {
+    if !func.is_reachable(addr) {
+        return Err("the instruction is unreachable".to_string());
+    }
+    walk_stack(module_data, func, addr, |_, _, _, _| {})
+}
+
+// The number of values that a branch at `addr` to the block `depth` levels out takes
+// along, where the function's own block takes its results. Fails if the instruction
+// is unreachable, or the stack before it can't be followed.
+pub(crate) fn label_arity(
+    module_data: &ModuleData,
+    func: &FunctionData,
+    addr: u64,
+    depth: u32,
+) -> Option<usize> {
+    let mut arity = None;
+    walk_stack(module_data, func, addr + 1, |op_addr, _, _, frames| {
+        if op_addr != addr {
+            return;
+        }
+        arity = match frames.len().checked_sub(depth as usize + 1) {
+            Some(i) => Some(frames[i].label_types().len()),
+            None if depth as usize == frames.len() => module_data
+                .func_type(func.func_index)
+                .map(|ty| ty.results().len()),
+            None => None,
+        };
+    })
+    .ok()?;
+    arity
+}
+
+// How a branch to the block `depth` levels out unwinds `stack`, which is the stack
+// once the branch has popped its own operand, if it leaves anything behind. Branches
+// out of the function are returns, which don't need to unwind.
+fn unwind(stack: &[StackValue], frames: &[Frame], depth: u32) -> Option<Unwind> {
+    let frame = frames.get(frames.len().checked_sub(depth as usize + 1)?)?;
+    let keep_start = stack.len().checked_sub(frame.label_types().len())?;
+    let discard = stack.get(frame.height..keep_start)?;
+    if discard.is_empty() {
+        return None;
+    }
+    Some(Unwind {
+        keep: stack[keep_start..].iter().map(|value| value.ty).collect(),
+        discard: discard.iter().map(|value| value.ty).collect(),
+    })
+}
+
+// Follows the operand stack through the whole function, as far as it can be followed,
+// for the operand types and unwinds that the lifter needs.
+pub(crate) fn stack_layout(module_data: &ModuleData, func: &FunctionData) -> StackLayout {
+    let mut layout = StackLayout::default();
+    let result = walk_stack(module_data, func, func.end, |addr, op, stack, frames| {
+        let top = |n: usize| stack.len().checked_sub(n).map(|i| stack[i].ty);
+        match op {
+            Operator::Drop => {
+                if let Some(ty) = top(1) {
+                    layout.operand_types.insert(addr, ty);
+                }
+            }
+            Operator::Select => {
+                if let Some(ty) = top(2) {
+                    layout.operand_types.insert(addr, ty);
+                }
+            }
+            Operator::Br { relative_depth } => {
+                if let Some(unwind) = unwind(stack, frames, *relative_depth) {
+                    layout.unwinds.insert((addr, *relative_depth), unwind);
+                }
+            }
+            Operator::BrIf { relative_depth } => {
+                let stack = &stack[..stack.len().saturating_sub(1)];
+                if let Some(unwind) = unwind(stack, frames, *relative_depth) {
+                    layout.unwinds.insert((addr, *relative_depth), unwind);
+                }
+            }
+            Operator::BrTable { targets } => {
+                let stack = &stack[..stack.len().saturating_sub(1)];
+                let depths = targets.targets().flatten().chain([targets.default()]);
+                for depth in depths {
+                    if let Some(unwind) = unwind(stack, frames, depth) {
+                        layout.unwinds.insert((addr, depth), unwind);
+                    }
+                }
+            }
+            _ => {}
+        }
+    });
+    if let Err(e) = result {
+        warn!("Stopped following the operand stack of function {}: {e}", func.func_index);
+    }
+    layout
+}
@@ -0,0 +1,142 @@
+// Module fields that may appear at the top level of a text file without an enclosing
+// `(module ...)`.
+const MODULE_KEYWORDS: &[&str] = &[
+    "module",
+    "component",
+    "func",
+    "type",
+    "rec",
+    "import",
+    "export",
+    "table",
+    "memory",
+    "global",
+    "tag",
+    "elem",
+    "data",
+    "start",
+];
+
+// Skips whitespace, `;;` line comments and (possibly nested) `(; ;)` block comments.
+fn skip_trivia(text: &str) -> &str {
+    let mut text = text;
+    loop {
+        let trimmed = text.trim_start();
+        if let Some(rest) = trimmed.strip_prefix(";;") {
+            text = rest.find('\n').map_or("", |i| &rest[i..]);
+        } else if trimmed.starts_with("(;") {
+            let mut depth = 0;
+            let mut i = 0;
+            let bytes = trimmed.as_bytes();
+            while i < bytes.len() {
+                match &bytes[i..(i + 2).min(bytes.len())] {
+                    b"(;" => depth += 1,
+                    b";)" => depth -= 1,
+                    _ => {
+                        i += 1;
+                        continue;
+                    }
+                }
+                i += 2;
+                if depth == 0 {
+                    break;
+                }
+            }
+            text = &trimmed[i..];
+        } else {
+            return trimmed;
+        }
+    }
+}
+
+// Returns the keyword of the first top-level form, e.g. "module" for `(module ...)`.
+fn first_keyword(text: &str) -> Option<&str> {
+    let rest = skip_trivia(text).strip_prefix('(')?;
+    let rest = skip_trivia(rest);
+    let end = rest
+        .find(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == ';')
+        .unwrap_or(rest.len());
+    Some(&rest[..end])
+}
+
+// Whether `data`, the start of a file, looks like a module in the WebAssembly text
+// format (.wat), or a script of modules and assertions (.wast). Other s-expression
+// formats can look the same, and only fail once the view assembles the file.
+pub(crate) fn starts_like_text_module(data: &[u8]) -> bool {
+    // `data` may end in the middle of a character.
+    let valid_len = match std::str::from_utf8(data) {
+        Ok(text) => text.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => return false,
+    };
+    let Ok(text) = std::str::from_utf8(&data[..valid_len]) else {
+        return false;
+    };
+    first_keyword(text)
+        .is_some_and(|keyword| MODULE_KEYWORDS.contains(&keyword) || keyword.starts_with("assert_"))
+}
+
+// Finds the first top-level `(module ...)` form of a .wast script, which also holds
+// assertions and commands that are not part of any module.
+fn first_module_form(text: &str) -> Option<&str> {
+    let mut rest = skip_trivia(text);
+    while !rest.is_empty() {
+        let len = form_len(rest)?;
+        if first_keyword(rest) == Some("module") {
+            return Some(&rest[..len]);
+        }
+        rest = skip_trivia(&rest[len..]);
+    }
+    None
+}
+
+// Length of the parenthesized form at the start of `text`, skipping over strings and
+// comments.
+fn form_len(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b';' if bytes.get(i + 1) == Some(&b';') => {
+                i = text[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                continue;
+            }
+            b'(' if bytes.get(i + 1) == Some(&b';') => {
+                let skipped = text.len() - i - skip_trivia(&text[i..]).len();
+                i += skipped.max(1);
+                continue;
+            }
+            b'(' => depth += 1,
+            b')' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// Assembles a text module into the binary format. For .wast scripts, the first
+// module in the script is assembled. Returns `None` if the file is not a text module,
+// which is only known once it fails to assemble.
+pub(crate) fn assemble_text_module(data: &[u8]) -> Option<Vec<u8>> {
+    let text = std::str::from_utf8(data).ok()?;
+    if let Ok(binary) = wat::parse_str(text) {
+        return Some(binary);
+    }
+
+    // Scripts also hold assertions and commands, which are not valid in a module.
+    let module = first_module_form(text).filter(|module| module.len() < text.trim().len())?;
+    wat::parse_str(module).ok()
+}
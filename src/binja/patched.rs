@@ -0,0 +1,228 @@
+use crate::binja::parse::func_parse::{parse_func, parse_operator};
+use crate::binja::parse::module_data::{FunctionData, ModuleData};
+use crate::binja::wat::{context_wat, Names};
+use crate::util::arc_identity::ArcIdentity;
+use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use log::{info, warn};
+use std::pin::Pin;
+use wasm_encoder::{CodeSection, Module, RawSection};
+use wasmparser::{BinaryReader, Operator, Parser, Payload};
+
+const CODE_SECTION_ID: u8 = 10;
+const NOP: u8 = 0x01;
+const END: u8 = 0x0b;
+
+// Prefixes of the custom sections that refer to code by its offset in the code
+// section, which are wrong once a function body changes size: DWARF, relocations and
+// branch hints.
+const CODE_OFFSET_SECTIONS: [&str; 3] = [".debug_", "reloc.", "metadata.code."];
+
+// The body of `func` as it is in `data`, the module at `base` as it is now, with the
+// `nop`s that patches padded their instructions out with removed. Also returns whether
+// any were patched. Patched code is decoded from where it starts, as a patch may span
+// several of the original instructions, until it is back at an original instruction
+// that is unchanged. Only `nop`s where the original code had none are padding.
+fn patched_body(func: &FunctionData, data: &[u8], base: u64) -> Result<(Vec<u8>, bool), String> {
+    let offset = |addr: u64| (addr - base) as usize;
+    let mut body = data[offset(func.locals_start)..offset(func.ops_start)].to_vec();
+    let mut patched = false;
+    let mut addr = func.ops_start;
+    while addr < func.end {
+        let current = &data[offset(addr)..offset(func.end)];
+        if let Some(op) = func.unpatched_op(addr, current) {
+            body.extend_from_slice(&current[..op.size]);
+            addr += op.size as u64;
+            continue;
+        }
+        let op = parse_operator(current)
+            .ok_or_else(|| format!("The patched instruction at {addr:#x} is malformed"))?;
+        let original_nop = func
+            .ops
+            .get(&addr)
+            .is_some_and(|original| matches!(original.op, Operator::Nop));
+        if original_nop || !matches!(op.op, Operator::Nop) {
+            body.extend_from_slice(&current[..op.size]);
+        }
+        addr += op.size as u64;
+        patched = true;
+    }
+    Ok((body, patched))
+}
+
+// Rebuilds the module from the view, with the function bodies as they have been
+// patched. Patches are made in place and padded with `nop`s, so the padding is removed
+// and the sizes of the bodies and the code section are encoded again. Custom sections
+// that refer to code offsets are left out if any body changed size.
+pub(crate) fn patched_module(
+    view: &BinaryView,
+    module_data: &ModuleData,
+) -> Result<Vec<u8>, String> {
+    let parent = view
+        .parent_view()
+        .ok_or("The file contents are not available")?;
+    let range = &module_data.module_range;
+    let len = (range.end - range.start) as usize;
+    let mut data = Vec::new();
+    if parent.read_into_vec(&mut data, range.start, len) != len {
+        return Err("Failed to read the module".to_string());
+    }
+
+    let mut code = CodeSection::new();
+    let mut n_patched = 0;
+    let mut resized = false;
+    for (_, func) in module_data.funcs.iter() {
+        let func = func.as_ref();
+        let (body, patched) = match module_data.replaced_bodies.get(&func.func_index) {
+            Some(body) => (body.clone(), true),
+            None => patched_body(func, &data, range.start)?,
+        };
+        n_patched += patched as usize;
+        resized |= body.len() as u64 != func.end - func.locals_start;
+        code.raw(&body);
+    }
+
+    let mut module = Module::new();
+    let sections = data.get(8..).ok_or("The module is truncated")?;
+    let mut reader = BinaryReader::new(sections, 8);
+    let mut dropped = Vec::new();
+    while !reader.eof() {
+        let id = reader.read_u8().map_err(|e| e.to_string())?;
+        let size = reader.read_var_u32().map_err(|e| e.to_string())?;
+        let contents = reader
+            .read_bytes(size as usize)
+            .map_err(|e| e.to_string())?;
+        match id {
+            CODE_SECTION_ID => {
+                let count = BinaryReader::new(contents, 0)
+                    .read_var_u32()
+                    .map_err(|e| e.to_string())?;
+                if count != code.len() {
+                    return Err(format!(
+                        "Only {} of {count} functions were parsed; reparse the module first",
+                        code.len()
+                    ));
+                }
+                module.section(&code);
+            }
+            0 => {
+                let name = BinaryReader::new(contents, 0)
+                    .read_string()
+                    .map_err(|e| e.to_string())?;
+                if resized && CODE_OFFSET_SECTIONS.iter().any(|p| name.starts_with(p)) {
+                    dropped.push(name.to_string());
+                    continue;
+                }
+                module.section(&RawSection { id, data: contents });
+            }
+            _ => {
+                module.section(&RawSection { id, data: contents });
+            }
+        }
+    }
+
+    info!("Rebuilt the module with {n_patched} patched functions");
+    if !dropped.is_empty() {
+        warn!(
+            "Left out sections that refer to code offsets: {}",
+            dropped.join(", ")
+        );
+    }
+    Ok(module.finish())
+}
+
+// Assembles `text`, as `context_wat` takes it, into a function body.
+fn assemble_body(module_data: &ModuleData, func_index: u32, text: &str) -> Result<Vec<u8>, String> {
+    let names = Names::new(module_data);
+    let wat = context_wat(module_data, &names, func_index, text);
+    let binary = wat::parse_str(&wat).map_err(|e| e.to_string())?;
+    for payload in Parser::new(0).parse_all(&binary) {
+        if let Payload::CodeSectionEntry(body) = payload.map_err(|e| e.to_string())? {
+            return Ok(binary[body.range()].to_vec());
+        }
+    }
+    Err("The text does not define a function".to_string())
+}
+
+// A new body that fits in place of the old one of a function, padded out to its size,
+// with the function as parsed from it. It is written to the view, and then stored in
+// the module data, with the lock on it released in between since writing starts
+// analysis that takes it.
+pub(crate) struct InPlaceBody {
+    locals_start: u64,
+    bytes: Vec<u8>,
+    func: FunctionData,
+}
+
+impl InPlaceBody {
+    pub(crate) fn write(&self, view: &BinaryView) -> Result<(), String> {
+        if BinaryViewBase::write(view, self.locals_start, &self.bytes) != self.bytes.len() {
+            return Err("Failed to write the new body".to_string());
+        }
+        Ok(())
+    }
+
+    // Replaces the parsed function, and drops any larger body queued for the patched
+    // module, so that later patches to the function are saved with it.
+    pub(crate) fn store(self, module_data: &mut ModuleData) {
+        let func = self.func;
+        module_data.replaced_bodies.remove(&func.func_index);
+        module_data
+            .funcs
+            .insert(func.size_start..func.end, ArcIdentity::new(func));
+    }
+}
+
+// Assembles a new body from `text` for the function at `addr`. A body that fits in
+// place of the old one is padded out with `nop`s before its final `end`, and returned
+// to be written to the view. A larger body can only be saved with the patched module,
+// so it is queued for that instead, and `None` is returned.
+pub(crate) fn replace_body(
+    module_data: &mut ModuleData,
+    addr: u64,
+    text: &str,
+) -> Result<Option<InPlaceBody>, String> {
+    let func = module_data
+        .funcs
+        .get(&addr)
+        .ok_or_else(|| format!("No WebAssembly function at {addr:#x}"))?
+        .clone();
+    let func = func.as_ref();
+    let body = assemble_body(module_data, func.func_index, text)?;
+    let (ops, end) = body.split_at(body.len() - 1);
+    if end != [END] {
+        return Err("The new body does not end with `end`".to_string());
+    }
+
+    let old_len = (func.end - func.locals_start) as usize;
+    if body.len() > old_len {
+        let raw = Pin::new(body.clone().into_boxed_slice());
+        parse_func(func.func_index, 0, 0, body.len() as u64, raw)
+            .map_err(|_| "Failed to parse the new body".to_string())?;
+        warn!(
+            "The new body of {} is {} bytes larger than the old one, so it is only used \
+             when saving the patched module",
+            module_data.func_name(func.func_index),
+            body.len() - old_len
+        );
+        module_data.replaced_bodies.insert(func.func_index, body);
+        return Ok(None);
+    }
+
+    let mut padded = ops.to_vec();
+    padded.resize(old_len - 1, NOP);
+    padded.push(END);
+    let raw = Pin::new(padded.clone().into_boxed_slice());
+    let new_func = parse_func(
+        func.func_index,
+        func.size_start,
+        func.locals_start,
+        func.end,
+        raw,
+    )
+    .map_err(|_| "Failed to parse the new body".to_string())?;
+    Ok(Some(InPlaceBody {
+        locals_start: func.locals_start,
+        bytes: padded,
+        func: new_func,
+    }))
+}
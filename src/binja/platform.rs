@@ -0,0 +1,81 @@
+use crate::binja::arch::{
+    WebAssemblyArchitecture, WebAssemblyCallingConvention, CALLING_CONVENTION_NAME,
+};
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::toolchain::is_emscripten_module;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::calling_convention::register_calling_convention;
+use binaryninja::platform::Platform;
+use binaryninja::rc::Ref;
+use log::{info, warn};
+
+const OS_NAME: &str = "wasm";
+
+// The environments a module can be built for, which are told apart by the modules it
+// imports from. Type libraries and calling conventions specific to an environment
+// attach to its platform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebAssemblyPlatform {
+    Bare,
+    Wasi,
+    Emscripten,
+}
+
+impl WebAssemblyPlatform {
+    const ALL: [Self; 3] = [Self::Bare, Self::Wasi, Self::Emscripten];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bare => "wasm-bare",
+            Self::Wasi => "wasm-wasi",
+            Self::Emscripten => "wasm-emscripten",
+        }
+    }
+
+    pub fn platform(&self) -> Option<Ref<Platform>> {
+        Platform::by_name(self.name())
+    }
+
+    // Emscripten modules import WASI functions too, so they are recognized by the
+    // runtime functions in `env` first.
+    pub(crate) fn for_module(module_data: &ModuleData) -> Self {
+        if is_emscripten_module(module_data) {
+            Self::Emscripten
+        } else if module_data
+            .func_imports
+            .iter()
+            .any(|import| import.module.starts_with("wasi_") || import.module.starts_with("wasi:"))
+        {
+            Self::Wasi
+        } else {
+            Self::Bare
+        }
+    }
+}
+
+// Every platform calls functions the same way, so they share the calling convention.
+pub fn register_platforms(arch: &WebAssemblyArchitecture) {
+    let cc =
+        register_calling_convention(arch, CALLING_CONVENTION_NAME, WebAssemblyCallingConvention);
+    for platform in WebAssemblyPlatform::ALL {
+        let bn_platform = Platform::new(arch, platform.name());
+        bn_platform.register_calling_convention(&cc);
+        bn_platform.set_default_calling_convention(&cc);
+        bn_platform.register_os(OS_NAME);
+    }
+}
+
+impl WebAssemblyView {
+    // Picks the platform from the imports. This has to happen before the functions are
+    // created, since they take the default platform.
+    pub(crate) fn select_platform(&mut self, module_data: &ModuleData) {
+        let platform = WebAssemblyPlatform::for_module(module_data);
+        let Some(bn_platform) = platform.platform() else {
+            warn!("Platform {} is not registered", platform.name());
+            return;
+        };
+        info!("Using platform {}", platform.name());
+        self.set_default_platform(&bn_platform);
+    }
+}
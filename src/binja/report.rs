@@ -0,0 +1,217 @@
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::toolchain::detect_toolchains;
+use std::collections::BTreeMap;
+
+// How many of the largest functions the summary lists.
+const N_LARGEST_FUNCS: usize = 10;
+
+// A table of a report: a heading, the column headers if it has any, and the rows.
+pub(crate) struct Table {
+    pub heading: &'static str,
+    pub columns: &'static [&'static str],
+    pub rows: Vec<Vec<String>>,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub(crate) fn html(tables: &[Table]) -> String {
+    let mut html = String::from("<html><body>");
+    for table in tables {
+        html.push_str(&format!("<h2>{}</h2><table>", escape(table.heading)));
+        if !table.columns.is_empty() {
+            html.push_str("<tr>");
+            for column in table.columns {
+                html.push_str(&format!("<th align=\"left\">{}</th>", escape(column)));
+            }
+            html.push_str("</tr>");
+        }
+        for row in &table.rows {
+            html.push_str("<tr>");
+            for cell in row {
+                html.push_str(&format!("<td>{}</td>", escape(cell)));
+            }
+            html.push_str("</tr>");
+        }
+        html.push_str("</table>");
+    }
+    html.push_str("</body></html>");
+    html
+}
+
+pub(crate) fn plaintext(tables: &[Table]) -> String {
+    let mut lines = Vec::new();
+    for table in tables {
+        lines.push(table.heading.to_string());
+        if !table.columns.is_empty() {
+            lines.push(format!("  {}", table.columns.join(" | ")));
+        }
+        for row in &table.rows {
+            lines.push(format!("  {}", row.join(" | ")));
+        }
+        lines.push(String::new());
+    }
+    lines.join("\n")
+}
+
+pub(crate) fn row(name: &str, value: impl ToString) -> Vec<String> {
+    vec![name.to_string(), value.to_string()]
+}
+
+fn overview(module_data: &ModuleData) -> Table {
+    let n_imported = module_data.func_imports.len();
+    let mut rows = vec![
+        row("Types", module_data.types.len()),
+        row("Imported functions", n_imported),
+        row(
+            "Defined functions",
+            module_data.func_addrs.len() - n_imported,
+        ),
+        row("Exported functions", module_data.func_exports.len()),
+    ];
+    if let Some(start_func) = module_data.start_func {
+        rows.push(row("Start function", module_data.func_name(start_func)));
+    }
+    let toolchains = detect_toolchains(module_data);
+    if !toolchains.is_empty() {
+        rows.push(row("Detected toolchains", toolchains.join(", ")));
+    }
+    for producer in &module_data.producers {
+        rows.push(row("Producers", producer));
+    }
+    rows.push(row(
+        "DWARF debug info",
+        if module_data.dwarf_sections.is_empty() {
+            "no"
+        } else {
+            "yes"
+        },
+    ));
+    if let Some(url) = &module_data.external_debug_info {
+        rows.push(row("External debug info", url));
+    }
+    if let Some(url) = &module_data.source_mapping_url {
+        rows.push(row("Source map", url));
+    }
+    Table {
+        heading: "Overview",
+        columns: &[],
+        rows,
+    }
+}
+
+fn sections(module_data: &ModuleData) -> Table {
+    let rows = module_data
+        .sections
+        .iter()
+        .map(|(name, range)| {
+            vec![
+                name.clone(),
+                format!("{:#x}", range.start),
+                (range.end - range.start).to_string(),
+            ]
+        })
+        .collect();
+    Table {
+        heading: "Sections",
+        columns: &["Name", "Offset", "Size"],
+        rows,
+    }
+}
+
+fn imports(module_data: &ModuleData) -> Table {
+    let mut by_module = BTreeMap::<&str, usize>::new();
+    for import in &module_data.func_imports {
+        *by_module.entry(&import.module).or_default() += 1;
+    }
+    let mut rows = by_module
+        .into_iter()
+        .map(|(module, count)| row(&format!("Functions from \"{module}\""), count))
+        .collect::<Vec<_>>();
+    let counts = [
+        (
+            "Tables",
+            module_data.tables.iter().filter(|t| t.imported).count(),
+        ),
+        (
+            "Memories",
+            module_data.memories.iter().filter(|m| m.imported).count(),
+        ),
+        (
+            "Globals",
+            module_data.globals.iter().filter(|g| g.imported).count(),
+        ),
+        (
+            "Tags",
+            module_data.tags.iter().filter(|t| t.imported).count(),
+        ),
+    ];
+    for (kind, count) in counts {
+        if count > 0 {
+            rows.push(row(kind, count));
+        }
+    }
+    Table {
+        heading: "Imports",
+        columns: &[],
+        rows,
+    }
+}
+
+fn limits(module_data: &ModuleData) -> Table {
+    let memories = module_data.memories.iter().enumerate();
+    let tables = module_data.tables.iter().enumerate();
+    let rows = memories
+        .map(|(i, memory)| row(&format!("Memory {i}"), memory))
+        .chain(tables.map(|(i, table)| row(&format!("Table {i}"), table)))
+        .collect();
+    Table {
+        heading: "Memories and tables",
+        columns: &[],
+        rows,
+    }
+}
+
+fn largest_funcs(module_data: &ModuleData) -> Table {
+    let mut funcs = module_data
+        .funcs
+        .iter()
+        .map(|(_, func)| func.as_ref())
+        .collect::<Vec<_>>();
+    funcs.sort_by_key(|func| std::cmp::Reverse(func.end - func.size_start));
+    let rows = funcs
+        .iter()
+        .take(N_LARGEST_FUNCS)
+        .map(|func| {
+            vec![
+                module_data.func_name(func.func_index),
+                format!("{:#x}", func.size_start),
+                (func.end - func.size_start).to_string(),
+                func.ops.len().to_string(),
+            ]
+        })
+        .collect();
+    Table {
+        heading: "Largest functions",
+        columns: &["Name", "Address", "Size", "Instructions"],
+        rows,
+    }
+}
+
+// A summary of the module to triage it by: what it is made of, what it imports and
+// exports, what it was built with, and where most of its code is. Returns the summary
+// as HTML and as plain text.
+pub(crate) fn module_summary(module_data: &ModuleData) -> (String, String) {
+    let tables = [
+        overview(module_data),
+        sections(module_data),
+        imports(module_data),
+        limits(module_data),
+        largest_funcs(module_data),
+    ];
+    (html(&tables), plaintext(&tables))
+}
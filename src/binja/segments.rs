@@ -0,0 +1,91 @@
+use crate::binja::parse::module_data::{DataMode, DataSegment, ModuleData};
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+
+// Segments longer than this are cut off in hexdumps; the rest can be extracted.
+const MAX_HEXDUMP_LEN: usize = 0x10000;
+
+const HEXDUMP_LINE_LEN: usize = 16;
+
+// Describes a segment to choose it by, e.g. `1 .rodata: 0x400 bytes at 0x10000`.
+pub(crate) fn segment_label(module_data: &ModuleData, data_index: usize) -> String {
+    let segment = &module_data.data_segments[data_index];
+    let len = segment.data_range.end - segment.data_range.start;
+    let name = match module_data.data_names.get(&(data_index as u32)) {
+        Some(name) => format!(" {name}"),
+        None => String::new(),
+    };
+    let place = match segment.mode {
+        DataMode::Active {
+            memory_index,
+            offset: Some(offset),
+        } => format!("at {offset:#x} in memory {memory_index}"),
+        DataMode::Active { memory_index, .. } => format!("in memory {memory_index}"),
+        DataMode::Passive => "passive".to_string(),
+    };
+    format!("{data_index}{name}: {len:#x} bytes {place}")
+}
+
+pub(crate) fn read_segment(view: &BinaryView, segment: &DataSegment) -> Option<Vec<u8>> {
+    let len = (segment.data_range.end - segment.data_range.start) as usize;
+    let mut data = Vec::new();
+    (view.read_into_vec(&mut data, segment.data_range.start, len) == len).then_some(data)
+}
+
+// A file name for a segment, with where in memory it is loaded if it is known, e.g.
+// `data_1_.rodata_mem0_0x10000.bin`.
+pub(crate) fn segment_file_name(module_data: &ModuleData, data_index: usize) -> String {
+    let segment = &module_data.data_segments[data_index];
+    let mut name = format!("data_{data_index}");
+    if let Some(data_name) = module_data.data_names.get(&(data_index as u32)) {
+        let data_name = data_name
+            .chars()
+            .map(|c| match c.is_ascii_alphanumeric() || "._-".contains(c) {
+                true => c,
+                false => '_',
+            })
+            .collect::<String>();
+        name.push_str(&format!("_{data_name}"));
+    }
+    match segment.mode {
+        DataMode::Active {
+            memory_index,
+            offset: Some(offset),
+        } => name.push_str(&format!("_mem{memory_index}_{offset:#x}")),
+        DataMode::Active { memory_index, .. } => name.push_str(&format!("_mem{memory_index}")),
+        DataMode::Passive => name.push_str("_passive"),
+    }
+    name.push_str(".bin");
+    name
+}
+
+// A hexdump of `data`, with each line prefixed by its address from `base`.
+pub(crate) fn hexdump(data: &[u8], base: u64) -> String {
+    let mut lines = Vec::new();
+    for (i, chunk) in data
+        .chunks(HEXDUMP_LINE_LEN)
+        .take(MAX_HEXDUMP_LEN / HEXDUMP_LINE_LEN)
+        .enumerate()
+    {
+        let addr = base + (i * HEXDUMP_LINE_LEN) as u64;
+        let hex = chunk
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let text = chunk
+            .iter()
+            .map(|byte| match byte {
+                0x20..=0x7e => *byte as char,
+                _ => '.',
+            })
+            .collect::<String>();
+        lines.push(format!("{addr:010x}  {hex:<47}  |{text}|"));
+    }
+    if data.len() > MAX_HEXDUMP_LEN {
+        lines.push(format!(
+            "... {:#x} more bytes; extract the segment to see them",
+            data.len() - MAX_HEXDUMP_LEN
+        ));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,294 @@
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+use binaryninja::settings::{QueryOptions, Settings};
+
+pub const VIEW_TYPE_NAME: &str = "wasm";
+
+const MEMORY_BASE: &str = "loader.wasm.memoryBase";
+const MAP_DATA_SEGMENTS: &str = "loader.wasm.mapDataSegments";
+const CREATE_FUNCTIONS: &str = "loader.wasm.createFunctions";
+const USE_NAME_SECTION: &str = "loader.wasm.useNameSection";
+const GO_ANALYSIS: &str = "loader.wasm.goAnalysis";
+const ADDRESS_SIZE: &str = "loader.wasm.addressSize";
+
+const SETTINGS_GROUP: &str = "wasm";
+const CONSTANT_DISPLAY: &str = "wasm.constantDisplay";
+const SIGNATURE_LIBRARIES: &str = "wasm.signatureLibraries";
+const DETECT_EMBEDDED_MODULES: &str = "wasm.detectEmbeddedModules";
+const DEFAULT_MEMORY_BASE: &str = "wasm.memoryBase";
+const DEFAULT_MAP_DATA_SEGMENTS: &str = "wasm.mapDataSegments";
+const DEFAULT_USE_NAME_SECTION: &str = "wasm.useNameSection";
+const CREATE_AUTO_FUNCTIONS: &str = "wasm.createAutoFunctions";
+const MAX_FUNCTIONS_TO_LIFT: &str = "wasm.maxFunctionsToLift";
+
+// Adds the WebAssembly options to the settings that apply to every view, which can be
+// overridden per view.
+pub fn register_settings(settings: &Settings) {
+    settings.register_group(SETTINGS_GROUP, "WebAssembly");
+    settings.register_setting_json(
+        CONSTANT_DISPLAY,
+        r#"{
+            "title": "Integer Constant Display",
+            "type": "string",
+            "default": "hex",
+            "enum": ["hex", "decimal", "char"],
+            "enumDescriptions": [
+                "Show integer constants in hexadecimal.",
+                "Show integer constants as signed decimal numbers.",
+                "Show integer constants that are printable ASCII as character literals, and others in hexadecimal."
+            ],
+            "description": "How i32.const and i64.const operands are shown in disassembly. Takes effect when the file is next opened."
+        }"#,
+    );
+    settings.register_setting_json(
+        SIGNATURE_LIBRARIES,
+        r#"{
+            "title": "Function Signature Libraries",
+            "type": "array",
+            "elementType": "string",
+            "default": [],
+            "description": "Paths of function signature libraries, as saved by the Save Function Signatures command. Functions without names are named after the functions in these libraries whose bodies they match."
+        }"#,
+    );
+    settings.register_setting_json(
+        DETECT_EMBEDDED_MODULES,
+        r#"{
+            "title": "Detect Embedded Modules",
+            "type": "boolean",
+            "default": false,
+            "description": "Offer to open files that are not WebAssembly but contain WebAssembly modules, such as Electron apps, packed loaders and firmware, as one of the modules in them, and scripts that carry a module further in than their start. Finding the modules takes a scan of the whole file."
+        }"#,
+    );
+    settings.register_setting_json(
+        DEFAULT_MEMORY_BASE,
+        r#"{
+            "title": "Linear Memory Base",
+            "type": "number",
+            "default": 0,
+            "description": "Address at which linear memory is mapped into the view, for files opened without options. If 0, linear memory is mapped above the file contents."
+        }"#,
+    );
+    settings.register_setting_json(
+        DEFAULT_MAP_DATA_SEGMENTS,
+        r#"{
+            "title": "Map Data Segments",
+            "type": "boolean",
+            "default": true,
+            "description": "Map active data segments into linear memory and define the strings in them, for files opened without options."
+        }"#,
+    );
+    settings.register_setting_json(
+        DEFAULT_USE_NAME_SECTION,
+        r#"{
+            "title": "Use Name Section",
+            "type": "boolean",
+            "default": true,
+            "description": "Name functions and data segments after the entries in the name custom section, for files opened without options."
+        }"#,
+    );
+    settings.register_setting_json(
+        CREATE_AUTO_FUNCTIONS,
+        r#"{
+            "title": "Create Auto Functions",
+            "type": "boolean",
+            "default": true,
+            "description": "Create a function for each body in the code section, for files opened without options. Bodies without functions are still parsed and named, and functions can be created for them by hand."
+        }"#,
+    );
+    settings.register_setting_json(
+        MAX_FUNCTIONS_TO_LIFT,
+        r#"{
+            "title": "Maximum Functions to Lift",
+            "type": "number",
+            "default": 0,
+            "minValue": 0,
+            "description": "Create functions, which are lifted and analyzed, for at most this many of the first bodies in the code section. If 0, there is no limit. Takes effect when the file is next opened."
+        }"#,
+    );
+}
+
+// Paths of the function signature libraries to match functions against.
+pub fn signature_libraries(view: &BinaryView) -> Vec<String> {
+    let settings = Settings::new();
+    let mut options = QueryOptions::new_with_view(view);
+    settings.get_string_list_with_opts(SIGNATURE_LIBRARIES, &mut options)
+}
+
+// Whether to look for modules embedded in files that are not WebAssembly.
+pub fn detect_embedded_modules(view: &BinaryView) -> bool {
+    let settings = Settings::new();
+    let mut options = QueryOptions::new_with_view(view);
+    settings.get_bool_with_opts(DETECT_EMBEDDED_MODULES, &mut options)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ConstantDisplay {
+    #[default]
+    Hex,
+    Decimal,
+    Char,
+}
+
+impl ConstantDisplay {
+    pub fn for_view(view: &BinaryView) -> Self {
+        let settings = Settings::new();
+        let mut options = QueryOptions::new_with_view(view);
+        match settings
+            .get_string_with_opts(CONSTANT_DISPLAY, &mut options)
+            .as_str()
+        {
+            "decimal" => Self::Decimal,
+            "char" => Self::Char,
+            _ => Self::Hex,
+        }
+    }
+}
+
+// Adds the WebAssembly options to the settings shown when a file is opened with
+// options.
+pub fn register_load_settings(settings: &Settings) {
+    settings.register_setting_json(
+        MEMORY_BASE,
+        r#"{
+            "title": "Linear Memory Base",
+            "type": "number",
+            "default": 0,
+            "description": "Address at which linear memory is mapped into the view. If 0, linear memory is mapped above the file contents.",
+            "ignore": ["SettingsProjectScope", "SettingsUserScope"]
+        }"#,
+    );
+    settings.register_setting_json(
+        MAP_DATA_SEGMENTS,
+        r#"{
+            "title": "Map Data Segments",
+            "type": "boolean",
+            "default": true,
+            "description": "Map active data segments into linear memory and define the strings in them.",
+            "ignore": ["SettingsProjectScope", "SettingsUserScope"]
+        }"#,
+    );
+    settings.register_setting_json(
+        CREATE_FUNCTIONS,
+        r#"{
+            "title": "Create Functions",
+            "type": "boolean",
+            "default": true,
+            "description": "Create a function for each body in the code section.",
+            "ignore": ["SettingsProjectScope", "SettingsUserScope"]
+        }"#,
+    );
+    settings.register_setting_json(
+        USE_NAME_SECTION,
+        r#"{
+            "title": "Use Name Section",
+            "type": "boolean",
+            "default": true,
+            "description": "Name functions and data segments after the entries in the name custom section.",
+            "ignore": ["SettingsProjectScope", "SettingsUserScope"]
+        }"#,
+    );
+    settings.register_setting_json(
+        GO_ANALYSIS,
+        r#"{
+            "title": "Go Analysis",
+            "type": "boolean",
+            "default": true,
+            "description": "If the module was built by the Go compiler, name the globals it uses as registers and describe the arguments its imports read from the Go stack.",
+            "ignore": ["SettingsProjectScope", "SettingsUserScope"]
+        }"#,
+    );
+    settings.register_setting_json(
+        ADDRESS_SIZE,
+        r#"{
+            "title": "Address Size",
+            "type": "string",
+            "default": "auto",
+            "enum": ["auto", "32", "64"],
+            "enumDescriptions": [
+                "Use 64-bit addresses if the module has a 64-bit memory or the view does not fit in 4 GiB.",
+                "Use 32-bit addresses.",
+                "Use 64-bit addresses."
+            ],
+            "description": "Width of addresses and pointers in the view.",
+            "ignore": ["SettingsProjectScope", "SettingsUserScope"]
+        }"#,
+    );
+}
+
+#[derive(Debug)]
+pub struct LoadSettings {
+    // Address of linear memory 0, or `None` to place it above the file contents.
+    pub memory_base: Option<u64>,
+    pub map_data_segments: bool,
+    pub create_functions: bool,
+    pub use_name_section: bool,
+    pub go_analysis: bool,
+
+    // How many of the bodies in the code section get functions, or `None` for all.
+    pub max_functions: Option<u32>,
+
+    // Width of addresses in bytes, or `None` to choose it from the module.
+    pub address_size: Option<usize>,
+}
+
+impl Default for LoadSettings {
+    fn default() -> Self {
+        Self {
+            memory_base: None,
+            map_data_segments: true,
+            create_functions: true,
+            use_name_section: true,
+            go_analysis: true,
+            max_functions: None,
+            address_size: None,
+        }
+    }
+}
+
+impl LoadSettings {
+    // Reads the load settings the user chose for `view`, falling back to the settings
+    // in the `wasm` group if the file was opened without options.
+    pub fn for_view(view: &BinaryView) -> Self {
+        let defaults = Self::from_settings(view);
+        let Ok(settings) = view.load_settings(VIEW_TYPE_NAME) else {
+            return defaults;
+        };
+        if !settings.contains(MEMORY_BASE) {
+            return defaults;
+        }
+
+        let mut options = QueryOptions::new_with_view(view);
+        let memory_base = settings.get_integer_with_opts(MEMORY_BASE, &mut options);
+        Self {
+            memory_base: (memory_base != 0).then_some(memory_base),
+            map_data_segments: settings.get_bool_with_opts(MAP_DATA_SEGMENTS, &mut options),
+            create_functions: settings.get_bool_with_opts(CREATE_FUNCTIONS, &mut options),
+            use_name_section: settings.get_bool_with_opts(USE_NAME_SECTION, &mut options),
+            go_analysis: settings.get_bool_with_opts(GO_ANALYSIS, &mut options),
+            max_functions: defaults.max_functions,
+            address_size: match settings
+                .get_string_with_opts(ADDRESS_SIZE, &mut options)
+                .as_str()
+            {
+                "32" => Some(4),
+                "64" => Some(8),
+                _ => None,
+            },
+        }
+    }
+
+    // Reads the settings in the `wasm` group that stand in for the load settings.
+    fn from_settings(view: &BinaryView) -> Self {
+        let settings = Settings::new();
+        let mut options = QueryOptions::new_with_view(view);
+        let memory_base = settings.get_integer_with_opts(DEFAULT_MEMORY_BASE, &mut options);
+        let max_functions = settings.get_integer_with_opts(MAX_FUNCTIONS_TO_LIFT, &mut options);
+        Self {
+            memory_base: (memory_base != 0).then_some(memory_base),
+            map_data_segments: settings.get_bool_with_opts(DEFAULT_MAP_DATA_SEGMENTS, &mut options),
+            create_functions: settings.get_bool_with_opts(CREATE_AUTO_FUNCTIONS, &mut options),
+            use_name_section: settings.get_bool_with_opts(DEFAULT_USE_NAME_SECTION, &mut options),
+            max_functions: (max_functions != 0).then(|| max_functions.min(u32::MAX as u64) as u32),
+            ..Self::default()
+        }
+    }
+}
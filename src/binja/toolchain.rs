@@ -0,0 +1,81 @@
+// Recognizers for the toolchains and runtimes that modules are commonly built with.
+// Each one annotates the module with what it knows about the toolchain's conventions.
+mod allocator;
+mod bindgen;
+mod cosmwasm;
+mod cxx;
+mod emscripten;
+mod fingerprint;
+mod go;
+mod ink;
+mod mem_idioms;
+mod near;
+mod trampoline;
+mod wasi;
+
+pub(crate) use emscripten::is_emscripten_module;
+pub(crate) use fingerprint::{generate_signatures, normalize};
+
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::typelib::wasi::PREVIEW1;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::rc::Ref;
+use binaryninja::tags::TagType;
+
+// Tag types that the recognizers above create.
+pub(crate) const TAG_TYPES: [&str; 4] = [
+    cosmwasm::ENTRY_POINT_TAG,
+    ink::ENTRY_POINT_TAG,
+    near::METHOD_TAG,
+    trampoline::TRAMPOLINE_TAG,
+];
+
+// The icon of the tags that smart contract recognizers mark entry points with.
+const CONTRACT_TAG_ICON: &str = "⛓";
+
+impl WebAssemblyView {
+    // The tag type `name` that a smart contract recognizer marks entry points with,
+    // created if the view doesn't have it yet.
+    pub(crate) fn contract_tag_type(&self, name: &str) -> Ref<TagType> {
+        match self.tag_type_by_name(name) {
+            Some(tag_type) => tag_type,
+            None => self.create_tag_type(name, CONTRACT_TAG_ICON),
+        }
+    }
+}
+
+// Names of the toolchains and runtimes that the module looks like it was built with or
+// for, by the same checks that decide which of the recognizers above apply.
+pub(crate) fn detect_toolchains(module_data: &ModuleData) -> Vec<String> {
+    let mut toolchains = Vec::new();
+    if allocator::is_rust_module(module_data) {
+        toolchains.push("Rust".to_string());
+    }
+    if bindgen::is_bindgen_module(module_data) {
+        toolchains.push("wasm-bindgen".to_string());
+    }
+    if emscripten::is_emscripten_module(module_data) {
+        toolchains.push("Emscripten".to_string());
+    }
+    if let Some(target) = go::detect_go(module_data) {
+        toolchains.push(format!("Go (GOOS={})", target.goos()));
+    }
+    if cosmwasm::is_cosmwasm_module(module_data) {
+        toolchains.push("CosmWasm".to_string());
+    }
+    if ink::is_ink_module(module_data) {
+        toolchains.push("ink!".to_string());
+    }
+    if near::is_near_module(module_data) {
+        toolchains.push("NEAR".to_string());
+    }
+    let has_wasi = module_data
+        .func_imports
+        .iter()
+        .any(|import| PREVIEW1.modules.contains(&import.module.as_str()));
+    if has_wasi {
+        toolchains.push("WASI".to_string());
+    }
+    toolchains
+}
@@ -0,0 +1,286 @@
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::parse::pointers::memory_access;
+use crate::binja::typelib::alloc::ALLOC;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::metadata::Metadata;
+use binaryninja::rc::Ref;
+use binaryninja::symbol::{Symbol, SymbolType};
+use log::info;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use wasmparser::{Operator, ValType};
+
+// Names the allocator functions go by, and the function of `ALLOC` each one is.
+// dlmalloc is built with `USE_DL_PREFIX` by Emscripten, and Rust's default allocator
+// implements the `__rust_*` shims as `__rdl_*`.
+const NAMES: &[(&str, &str)] = &[
+    ("malloc", "malloc"),
+    ("free", "free"),
+    ("calloc", "calloc"),
+    ("realloc", "realloc"),
+    ("dlmalloc", "malloc"),
+    ("dlfree", "free"),
+    ("dlcalloc", "calloc"),
+    ("dlrealloc", "realloc"),
+    ("emscripten_builtin_malloc", "malloc"),
+    ("emscripten_builtin_free", "free"),
+    ("__rust_alloc", "__rust_alloc"),
+    ("__rust_dealloc", "__rust_dealloc"),
+    ("__rust_realloc", "__rust_realloc"),
+    ("__rust_alloc_zeroed", "__rust_alloc_zeroed"),
+    ("__rdl_alloc", "__rust_alloc"),
+    ("__rdl_dealloc", "__rust_dealloc"),
+    ("__rdl_realloc", "__rust_realloc"),
+    ("__rdl_alloc_zeroed", "__rust_alloc_zeroed"),
+];
+
+const I32: ValType = ValType::I32;
+
+// A function is only taken to be the allocator if it is called from at least this many
+// places, and the deallocator if it is called alongside the allocator from at least
+// this many, so that a function that merely has the right shape isn't.
+const MIN_ALLOC_CALLERS: usize = 3;
+const MIN_PAIRED_CALLERS: usize = 2;
+
+// Calls between the functions defined in the module, and which of them grow and access
+// memory.
+struct CallGraph {
+    callees: BTreeMap<u32, BTreeSet<u32>>,
+    n_callers: BTreeMap<u32, usize>,
+
+    // Functions that execute `memory.grow`, or call one that does.
+    grows_memory: BTreeSet<u32>,
+
+    // Functions that load or store, or call one that does. Allocators keep their
+    // bookkeeping in memory.
+    accesses_memory: BTreeSet<u32>,
+}
+
+// Adds the functions that call one in `funcs` to it, until there are no more.
+fn add_callers(callees: &BTreeMap<u32, BTreeSet<u32>>, funcs: &mut BTreeSet<u32>) {
+    loop {
+        let callers = callees
+            .iter()
+            .filter(|(caller, calls)| {
+                !funcs.contains(*caller) && calls.iter().any(|callee| funcs.contains(callee))
+            })
+            .map(|(caller, _)| *caller)
+            .collect::<Vec<_>>();
+        if callers.is_empty() {
+            break;
+        }
+        funcs.extend(callers);
+    }
+}
+
+impl CallGraph {
+    fn new(module_data: &ModuleData) -> Self {
+        let mut callees = BTreeMap::<u32, BTreeSet<u32>>::new();
+        let mut grows_memory = BTreeSet::new();
+        let mut accesses_memory = BTreeSet::new();
+        for (_, func) in module_data.funcs.iter() {
+            let func = func.as_ref();
+            let calls = callees.entry(func.func_index).or_default();
+            for (_, op) in func.reachable_ops() {
+                match op.op {
+                    Operator::Call { function_index } | Operator::ReturnCall { function_index } => {
+                        calls.insert(function_index);
+                    }
+                    Operator::MemoryGrow { .. } => {
+                        grows_memory.insert(func.func_index);
+                    }
+                    ref op if memory_access(op).is_some() => {
+                        accesses_memory.insert(func.func_index);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut n_callers = BTreeMap::<u32, usize>::new();
+        for calls in callees.values() {
+            for callee in calls {
+                *n_callers.entry(*callee).or_default() += 1;
+            }
+        }
+
+        add_callers(&callees, &mut grows_memory);
+        add_callers(&callees, &mut accesses_memory);
+
+        Self {
+            callees,
+            n_callers,
+            grows_memory,
+            accesses_memory,
+        }
+    }
+
+    fn n_callers(&self, func_index: u32) -> usize {
+        self.n_callers.get(&func_index).copied().unwrap_or(0)
+    }
+
+    // Whether `func_index` could be the allocator: it grows memory, keeps its
+    // bookkeeping there and is called from enough places.
+    fn could_allocate(&self, func_index: u32) -> bool {
+        self.grows_memory.contains(&func_index)
+            && self.accesses_memory.contains(&func_index)
+            && self.n_callers(func_index) >= MIN_ALLOC_CALLERS
+    }
+
+    fn calls(&self, caller: u32, callee: u32) -> bool {
+        self.callees
+            .get(&caller)
+            .is_some_and(|calls| calls.contains(&callee))
+    }
+
+    // Of the functions with the given type that satisfy `filter`, the one called from
+    // the most places.
+    fn most_called(
+        &self,
+        module_data: &ModuleData,
+        params: &[ValType],
+        results: &[ValType],
+        filter: impl Fn(u32) -> bool,
+    ) -> Option<u32> {
+        self.callees
+            .keys()
+            .copied()
+            .filter(|func_index| {
+                module_data
+                    .func_type(*func_index)
+                    .is_some_and(|ty| ty.params() == params && ty.results() == results)
+            })
+            .filter(|func_index| filter(*func_index))
+            .max_by_key(|func_index| self.n_callers(*func_index))
+    }
+
+    // The deallocator that goes with `alloc`: of the functions with the given type that
+    // access memory without growing it, the one called most often alongside it, if it
+    // is called alongside it often enough.
+    fn paired_with(&self, module_data: &ModuleData, alloc: u32, params: &[ValType]) -> Option<u32> {
+        let mut n_pairs = BTreeMap::<u32, usize>::new();
+        for calls in self.callees.values() {
+            if !calls.contains(&alloc) {
+                continue;
+            }
+            for callee in calls {
+                let matches = module_data
+                    .func_type(*callee)
+                    .is_some_and(|ty| ty.params() == params && ty.results().is_empty());
+                let frees =
+                    self.accesses_memory.contains(callee) && !self.grows_memory.contains(callee);
+                if matches && frees {
+                    *n_pairs.entry(*callee).or_default() += 1;
+                }
+            }
+        }
+        n_pairs
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .filter(|(_, count)| *count >= MIN_PAIRED_CALLERS)
+            .map(|(func_index, _)| func_index)
+    }
+}
+
+pub(crate) fn is_rust_module(module_data: &ModuleData) -> bool {
+    module_data
+        .languages
+        .iter()
+        .any(|language| language == "Rust")
+        || module_data
+            .func_names
+            .values()
+            .any(|name| name.starts_with("_ZN") || name.starts_with("__rust_"))
+}
+
+// Finds the allocator functions by their names, or in stripped modules by how they
+// are used: the allocator is the most called function that can grow memory and
+// accesses it, the deallocator is the function that accesses memory most often called
+// alongside it, and the reallocator calls both.
+fn find_allocators(module_data: &ModuleData) -> BTreeMap<&'static str, u32> {
+    let n_imports = module_data.func_imports.len() as u32;
+    let mut found = BTreeMap::new();
+    for (func_index, name) in module_data
+        .func_names
+        .iter()
+        .chain(&module_data.func_exports)
+    {
+        if *func_index < n_imports {
+            continue;
+        }
+        if let Some((_, canonical)) = NAMES.iter().find(|(alias, _)| alias == name) {
+            found.entry(*canonical).or_insert(*func_index);
+        }
+    }
+
+    let graph = CallGraph::new(module_data);
+    let allocates = |func_index: u32| graph.could_allocate(func_index);
+    if !found.contains_key("malloc")
+        && let Some(malloc) = graph.most_called(module_data, &[I32], &[I32], allocates)
+    {
+        found.insert("malloc", malloc);
+    }
+    if let Some(&malloc) = found.get("malloc")
+        && !found.contains_key("free")
+        && let Some(free) = graph.paired_with(module_data, malloc, &[I32])
+    {
+        found.insert("free", free);
+    }
+    if let (Some(&malloc), Some(&free)) = (found.get("malloc"), found.get("free"))
+        && !found.contains_key("realloc")
+    {
+        let calls_both = |f: u32| f != malloc && graph.calls(f, malloc) && graph.calls(f, free);
+        if let Some(realloc) = graph.most_called(module_data, &[I32, I32], &[I32], calls_both) {
+            found.insert("realloc", realloc);
+        }
+    }
+
+    if !is_rust_module(module_data) {
+        return found;
+    }
+    if !found.contains_key("__rust_alloc")
+        && let Some(alloc) = graph.most_called(module_data, &[I32, I32], &[I32], allocates)
+    {
+        found.insert("__rust_alloc", alloc);
+    }
+    if let Some(&alloc) = found.get("__rust_alloc")
+        && !found.contains_key("__rust_dealloc")
+        && let Some(dealloc) = graph.paired_with(module_data, alloc, &[I32, I32, I32])
+    {
+        found.insert("__rust_dealloc", dealloc);
+    }
+    found
+}
+
+impl WebAssemblyView {
+    // Names and types the allocator functions, so that heap allocations can be told
+    // apart from other calls even in stripped modules.
+    pub(crate) fn annotate_allocators(&mut self, module_data: &ModuleData) {
+        let allocators = find_allocators(module_data);
+        if allocators.is_empty() {
+            return;
+        }
+
+        let mut metadata: HashMap<&str, Ref<Metadata>> = HashMap::new();
+        for (name, func_index) in &allocators {
+            let Some(&addr) = module_data.func_addrs.get(*func_index as usize) else {
+                continue;
+            };
+            let named = module_data.func_names.contains_key(func_index)
+                || module_data.func_exports.contains_key(func_index);
+            if !named {
+                let symbol = Symbol::builder(SymbolType::Function, name, addr).create();
+                self.define_auto_symbol(&symbol);
+            }
+            if let Some(prototype) = ALLOC.prototype(name) {
+                self.apply_function_prototype(&ALLOC, module_data, addr, prototype);
+            }
+            metadata.insert(name, addr.into());
+        }
+        info!(
+            "Recognized allocator functions: {}",
+            allocators.keys().copied().collect::<Vec<_>>().join(", ")
+        );
+        self.store_metadata("wasm.allocators", metadata, true);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::metadata::Metadata;
+use binaryninja::rc::Ref;
+use log::info;
+use std::collections::HashMap;
+use wasmparser::{AbstractHeapType, HeapType};
+
+// Holds the wasm-bindgen program description, before the module is processed by the
+// wasm-bindgen CLI.
+const UNSTABLE_SECTION: &str = "__wasm_bindgen_unstable";
+
+// Import modules of intrinsics and JS imports, before and after processing.
+const PLACEHOLDER_MODULE: &str = "__wbindgen_placeholder__";
+const EXTERNREF_XFORM_MODULE: &str = "__wbindgen_externref_xform__";
+const GLUE_MODULE_SUFFIX: &str = "_bg.js";
+
+#[derive(Clone, Copy)]
+enum Role {
+    // An import of a JS function, through the generated glue.
+    JsImport,
+    // An import of a wasm-bindgen intrinsic, such as `__wbindgen_throw`.
+    Intrinsic,
+    // An export that describes the type of an import or export to the CLI.
+    Descriptor,
+    // An export that supports the glue, such as `__wbindgen_malloc`.
+    Runtime,
+    // An export of a `#[wasm_bindgen]` function, which the glue wraps.
+    Shim,
+}
+
+impl Role {
+    fn describe(self, name: &str) -> String {
+        match self {
+            Role::JsImport => format!("wasm-bindgen: JS import `{}`", js_import_name(name)),
+            Role::Intrinsic => "wasm-bindgen: intrinsic".to_string(),
+            Role::Descriptor => "wasm-bindgen: type descriptor".to_string(),
+            Role::Runtime => "wasm-bindgen: glue runtime".to_string(),
+            Role::Shim => format!("wasm-bindgen: export shim, called from JS as `{name}`"),
+        }
+    }
+
+    fn metadata_key(self) -> &'static str {
+        match self {
+            Role::JsImport => "jsImports",
+            Role::Intrinsic => "intrinsics",
+            Role::Descriptor => "descriptors",
+            Role::Runtime => "runtime",
+            Role::Shim => "shims",
+        }
+    }
+}
+
+// JS imports are named `__wbg_<name>_<hash>`; recover `<name>`.
+fn js_import_name(name: &str) -> &str {
+    let name = name.strip_prefix("__wbg_").unwrap_or(name);
+    match name.rsplit_once('_') {
+        Some((js_name, hash))
+            if !js_name.is_empty() && hash.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            js_name
+        }
+        _ => name,
+    }
+}
+
+fn import_role(module: &str, name: &str) -> Option<Role> {
+    let bindgen_module = module == PLACEHOLDER_MODULE
+        || module == EXTERNREF_XFORM_MODULE
+        || module.ends_with(GLUE_MODULE_SUFFIX);
+    if name.starts_with("__wbindgen_") {
+        Some(Role::Intrinsic)
+    } else if name.starts_with("__wbg_") || bindgen_module {
+        Some(Role::JsImport)
+    } else {
+        None
+    }
+}
+
+fn export_role(name: &str) -> Role {
+    if name.starts_with("__wbindgen_describe") {
+        Role::Descriptor
+    } else if name.starts_with("__wbindgen_") || name.starts_with("__externref_") {
+        Role::Runtime
+    } else {
+        Role::Shim
+    }
+}
+
+pub(crate) fn is_bindgen_module(module_data: &ModuleData) -> bool {
+    module_data
+        .custom_sections
+        .iter()
+        .any(|name| name == UNSTABLE_SECTION)
+        || module_data
+            .func_imports
+            .iter()
+            .any(|import| import_role(&import.module, &import.name).is_some())
+        || module_data
+            .func_exports
+            .values()
+            .any(|name| name.starts_with("__wbindgen_"))
+}
+
+impl WebAssemblyView {
+    // wasm-bindgen wraps a Rust module in JS glue. Annotate the boundary with the
+    // glue: which imports are JS functions, and which exports are shims the glue
+    // calls, as opposed to the glue's own runtime.
+    pub(crate) fn annotate_bindgen(&mut self, module_data: &ModuleData) {
+        if !is_bindgen_module(module_data) {
+            return;
+        }
+        info!("Module was built with wasm-bindgen");
+
+        let mut roles = Vec::new();
+        for (import, addr) in module_data.func_imports.iter().zip(&module_data.func_addrs) {
+            if let Some(role) = import_role(&import.module, &import.name) {
+                roles.push((role, import.name.as_str(), *addr));
+            }
+        }
+        for (func_index, name) in &module_data.func_exports {
+            if let Some(&addr) = module_data.func_addrs.get(*func_index as usize) {
+                roles.push((export_role(name), name.as_str(), addr));
+            }
+        }
+
+        let mut metadata: HashMap<&str, Vec<Ref<Metadata>>> = HashMap::new();
+        for (role, name, addr) in roles {
+            self.set_comment_at(addr, &role.describe(name));
+            metadata
+                .entry(role.metadata_key())
+                .or_default()
+                .push(name.into());
+        }
+
+        // With reference types enabled, JS objects live in an externref table rather
+        // than in the heap of the glue.
+        for (i, table) in module_data.tables.iter().enumerate() {
+            if let HeapType::Abstract {
+                ty: AbstractHeapType::Extern,
+                ..
+            } = table.ty.element_type.heap_type()
+            {
+                self.set_comment_at(
+                    table.addr,
+                    &format!("table {i}: {table}; wasm-bindgen: JS objects held by the module"),
+                );
+            }
+        }
+
+        let metadata = metadata
+            .into_iter()
+            .map(|(key, names)| (key, names.into()))
+            .collect::<HashMap<&str, Ref<Metadata>>>();
+        self.store_metadata("wasm.bindgen", metadata, true);
+    }
+}
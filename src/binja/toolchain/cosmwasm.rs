@@ -0,0 +1,101 @@
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::typelib::cosmwasm::{ENV, EXPORTS};
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::metadata::Metadata;
+use binaryninja::rc::Ref;
+use log::info;
+use std::collections::HashMap;
+
+// Exports that carry the version of the VM interface a contract was built against.
+// `interface_version_*` replaced `cosmwasm_vm_version_*` in CosmWasm 0.14.
+const VERSION_PREFIXES: &[&str] = &["interface_version_", "cosmwasm_vm_version_"];
+
+// Exports through which a contract declares the chain capabilities it needs.
+const CAPABILITY_PREFIX: &str = "requires_";
+
+// Exports the VM calls to run a contract.
+const ENTRY_POINTS: &[&str] = &[
+    "instantiate",
+    "execute",
+    "query",
+    "migrate",
+    "sudo",
+    "reply",
+    "ibc_channel_open",
+    "ibc_channel_connect",
+    "ibc_channel_close",
+    "ibc_packet_receive",
+    "ibc_packet_ack",
+    "ibc_packet_timeout",
+];
+
+pub(crate) const ENTRY_POINT_TAG: &str = "CosmWasm Entry Point";
+
+fn interface_version(module_data: &ModuleData) -> Option<u64> {
+    module_data.func_exports.values().find_map(|name| {
+        VERSION_PREFIXES
+            .iter()
+            .find_map(|prefix| name.strip_prefix(prefix)?.parse().ok())
+    })
+}
+
+pub(crate) fn is_cosmwasm_module(module_data: &ModuleData) -> bool {
+    if interface_version(module_data).is_some() {
+        return true;
+    }
+    let exports = |name: &str| module_data.func_exports.values().any(|e| e == name);
+    exports("allocate")
+        && exports("deallocate")
+        && ["instantiate", "execute", "query"]
+            .iter()
+            .any(|name| exports(name))
+}
+
+impl WebAssemblyView {
+    // CosmWasm contracts exchange data with the VM through `Region`s, which describe
+    // buffers in linear memory. Type the VM's imports and the contract's exports in
+    // terms of them, and tag the entry points the VM can call.
+    pub(crate) fn annotate_cosmwasm(&mut self, module_data: &ModuleData) {
+        if !is_cosmwasm_module(module_data) {
+            return;
+        }
+        let version = interface_version(module_data);
+        match version {
+            Some(version) => info!("Module is a CosmWasm contract (interface version {version})"),
+            None => info!("Module is a CosmWasm contract"),
+        }
+        self.apply_type_library(&ENV, module_data);
+
+        let tag_type = self.contract_tag_type(ENTRY_POINT_TAG);
+        let mut entry_points = Vec::new();
+        let mut capabilities = Vec::new();
+        for (index, name) in &module_data.func_exports {
+            let Some(&addr) = module_data.func_addrs.get(*index as usize) else {
+                continue;
+            };
+            if let Some(capability) = name.strip_prefix(CAPABILITY_PREFIX) {
+                capabilities.push(Ref::<Metadata>::from(capability));
+                continue;
+            }
+            if (*index as usize) < module_data.func_imports.len() {
+                continue;
+            }
+            if let Some(prototype) = EXPORTS.prototype(name) {
+                self.apply_function_prototype(&EXPORTS, module_data, addr, prototype);
+            }
+            if ENTRY_POINTS.contains(&name.as_str()) {
+                self.add_tag(addr, &tag_type, name, false);
+                entry_points.push(Ref::<Metadata>::from(name.as_str()));
+            }
+        }
+
+        let mut metadata: HashMap<&str, Ref<Metadata>> = HashMap::new();
+        if let Some(version) = version {
+            metadata.insert("interfaceVersion", version.into());
+        }
+        metadata.insert("entryPoints", entry_points.into());
+        metadata.insert("capabilities", capabilities.into());
+        self.store_metadata("wasm.cosmwasm", metadata, true);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::binja::parse::data_parse::DataImage;
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+use binaryninja::symbol::{Symbol, SymbolType};
+use binaryninja::types::Type;
+use log::info;
+use std::collections::BTreeMap;
+use wasmparser::Operator;
+
+// Type names longer than this are not taken for names.
+const MAX_TYPE_NAME_LEN: usize = 1024;
+
+// The mangled name of a type, as the `std::type_info` at `addr` holds it, e.g. `3Foo`
+// or `N2ns3FooE`.
+fn type_name(image: &DataImage, addr: u64) -> Option<String> {
+    let name_addr = image.read_u32(addr + 4)? as u64;
+    let mut name = String::new();
+    for i in 0..MAX_TYPE_NAME_LEN as u64 {
+        match *image.read(name_addr + i, 1)?.first()? {
+            0 => break,
+            c if c.is_ascii_alphanumeric() || c == b'_' => name.push(c as char),
+            _ => return None,
+        }
+    }
+    let mangled = name.starts_with(|c: char| c.is_ascii_digit() || c == 'N');
+    mangled.then_some(name)
+}
+
+// A virtual table of the Itanium C++ ABI: the offset to the top of the object, a
+// pointer to the class's `std::type_info`, and then the virtual functions, which in
+// wasm are indices into the function table. Objects point at the first function.
+struct VirtualTable {
+    addr: u64,
+    type_name: String,
+    funcs: Vec<u32>,
+}
+
+fn find_virtual_tables(image: &DataImage, module_data: &ModuleData) -> Vec<VirtualTable> {
+    let table = module_data.table_contents(0);
+    let mut vtables = Vec::new();
+    for (range, _) in &image.segments {
+        let mut addr = range.start.next_multiple_of(4) + 8;
+        while addr + 4 <= range.end {
+            let offset_to_top = image.read_u32(addr - 8).map(|value| value as i32);
+            let type_info = image.read_u32(addr - 4).filter(|addr| *addr != 0);
+            let type_name = match (offset_to_top, type_info) {
+                (Some(-0x10000..=0), Some(type_info)) => type_name(image, type_info as u64),
+                _ => None,
+            };
+            let Some(type_name) = type_name else {
+                addr += 4;
+                continue;
+            };
+
+            let funcs = (0..)
+                .map_while(|i| {
+                    let slot = image.read_u32(addr + 4 * i)?;
+                    table.get(&(slot as u64)).copied()
+                })
+                .collect::<Vec<_>>();
+            if funcs.is_empty() {
+                addr += 4;
+                continue;
+            }
+            let len = 4 * funcs.len() as u64;
+            vtables.push(VirtualTable {
+                addr,
+                type_name,
+                funcs,
+            });
+            addr += len;
+        }
+    }
+    vtables
+}
+
+// Finds virtual calls: a `call_indirect` whose table index is loaded from an object's
+// virtual table, as in
+//
+//     local.get 0       ;; this
+//     i32.load          ;; the virtual table pointer
+//     i32.load offset=8 ;; the function in slot 2
+//     call_indirect
+//
+// and returns the slot each one calls, by the address of the `call_indirect`.
+fn find_virtual_calls(module_data: &ModuleData) -> BTreeMap<u64, (u32, usize)> {
+    let mut calls = BTreeMap::new();
+    for (_, func) in module_data.funcs.iter() {
+        let ops = func.as_ref().ops.iter().collect::<Vec<_>>();
+        for window in ops.windows(3) {
+            let [(_, vtable), (_, slot), (addr, call)] = window else {
+                continue;
+            };
+            let Operator::CallIndirect {
+                type_index,
+                table_index: 0,
+            } = call.op
+            else {
+                continue;
+            };
+            let Operator::I32Load { memarg } = slot.op else {
+                continue;
+            };
+            let loads_vtable = matches!(
+                vtable.op,
+                Operator::I32Load { .. } | Operator::LocalGet { .. }
+            );
+            if loads_vtable && memarg.memory == 0 && memarg.offset % 4 == 0 {
+                calls.insert(**addr, (type_index, (memarg.offset / 4) as usize));
+            }
+        }
+    }
+    calls
+}
+
+impl WebAssemblyView {
+    // C++ built for wasm dispatches virtual calls through virtual tables in linear
+    // memory. Name the virtual tables after their classes, and narrow the candidate
+    // callees of each virtual call to the functions in the matching slot of a virtual
+    // table.
+    pub(crate) fn annotate_virtual_calls(
+        &mut self,
+        parent: &BinaryView,
+        module_data: &mut ModuleData,
+    ) {
+        let image = DataImage::new(parent, module_data);
+        let vtables = find_virtual_tables(&image, module_data);
+        if vtables.is_empty() {
+            return;
+        }
+
+        if module_data.load_settings.map_data_segments {
+            for vtable in &vtables {
+                let start = module_data.memory_base + vtable.addr - 8;
+                let name = format!("_ZTV{}", vtable.type_name);
+                let symbol = Symbol::builder(SymbolType::Data, &name, start).create();
+                self.define_auto_symbol(&symbol);
+                let ty = Type::array(&Type::int(4, false), vtable.funcs.len() as u64 + 2);
+                self.define_auto_data_var(start, &ty);
+            }
+        }
+
+        let mut n_resolved = 0;
+        for (addr, (type_index, slot)) in find_virtual_calls(module_data) {
+            let ty = module_data.types.get(type_index as usize);
+            let mut targets = vtables
+                .iter()
+                .filter_map(|vtable| vtable.funcs.get(slot).copied())
+                .filter(|func_index| module_data.func_type(*func_index) == ty)
+                .collect::<Vec<_>>();
+            targets.sort();
+            targets.dedup();
+            if !targets.is_empty() {
+                module_data.indirect_call_targets.insert(addr, targets);
+                n_resolved += 1;
+            }
+        }
+        info!(
+            "Found {} C++ virtual tables and resolved {n_resolved} virtual calls",
+            vtables.len()
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::typelib::emscripten::{ENV, SYSCALL_NUMBERS};
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::metadata::Metadata;
+use binaryninja::rc::Ref;
+use log::info;
+use std::collections::HashMap;
+use wasmparser::{FuncType, ValType};
+
+const ENV_MODULE: &str = "env";
+
+// Import name prefixes that only the Emscripten JS runtime provides.
+const RUNTIME_PREFIXES: &[&str] = &[
+    "emscripten_",
+    "_emscripten_",
+    "__syscall_",
+    "invoke_",
+    "_embind_",
+];
+
+pub(crate) fn is_emscripten_module(module_data: &ModuleData) -> bool {
+    module_data.func_imports.iter().any(|import| {
+        import.module == ENV_MODULE
+            && RUNTIME_PREFIXES
+                .iter()
+                .any(|prefix| import.name.starts_with(prefix))
+    })
+}
+
+fn value_type_name(ty: &ValType) -> &'static str {
+    match ty {
+        ValType::I32 => "i32",
+        ValType::I64 => "i64",
+        ValType::F32 => "f32",
+        ValType::F64 => "f64",
+        _ => "void*",
+    }
+}
+
+// `invoke_*` imports call a function in the indirect function table from JS, so that
+// C++ exceptions and longjmp can unwind through it. The first argument is the table
+// index and the rest are passed to the function.
+fn invoke_prototype(name: &str, ty: &FuncType) -> String {
+    let result = ty.results().first().map_or("void", value_type_name);
+    let params = ty
+        .params()
+        .iter()
+        .enumerate()
+        .map(|(i, param)| match i {
+            0 => format!("{} func_index", value_type_name(param)),
+            _ => format!("{} arg{}", value_type_name(param), i - 1),
+        })
+        .collect::<Vec<_>>();
+    format!("{result} {name}({})", params.join(", "))
+}
+
+impl WebAssemblyView {
+    // Emscripten modules call into a JS runtime for libc, syscalls and exception
+    // handling. Type the runtime's imports, and describe what the syscall and invoke
+    // trampolines do.
+    pub(crate) fn annotate_emscripten(&mut self, module_data: &ModuleData) {
+        if !is_emscripten_module(module_data) {
+            return;
+        }
+        info!("Module was built with Emscripten");
+        self.apply_type_library(&ENV, module_data);
+
+        let mut syscalls = Vec::new();
+        let mut invokes = Vec::new();
+        for (i, (import, addr)) in module_data
+            .func_imports
+            .iter()
+            .zip(&module_data.func_addrs)
+            .enumerate()
+        {
+            if import.module != ENV_MODULE {
+                continue;
+            }
+            if let Some(syscall) = import.name.strip_prefix("__syscall_") {
+                let comment = match SYSCALL_NUMBERS.iter().find(|(name, _)| *name == syscall) {
+                    Some((_, number)) => {
+                        format!("Emscripten syscall {syscall} (SYS_{syscall} = {number})")
+                    }
+                    None => format!("Emscripten syscall {syscall}"),
+                };
+                self.set_comment_at(*addr, &comment);
+                syscalls.push(Ref::<Metadata>::from(syscall));
+            } else if import.name.starts_with("invoke_") {
+                let Some(ty) = module_data.func_type(i as u32) else {
+                    continue;
+                };
+                self.apply_prototype(
+                    &ENV,
+                    module_data,
+                    *addr,
+                    &invoke_prototype(&import.name, ty),
+                );
+                self.set_comment_at(
+                    *addr,
+                    "Emscripten invoke: calls the function at table index `func_index` with the remaining arguments, catching exceptions and longjmps",
+                );
+                invokes.push(Ref::<Metadata>::from(import.name.as_str()));
+            }
+        }
+
+        let mut metadata: HashMap<&str, Ref<Metadata>> = HashMap::new();
+        metadata.insert("syscalls", syscalls.into());
+        metadata.insert("invokes", invokes.into());
+        self.store_metadata("wasm.emscripten", metadata, true);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::binja::parse::module_data::{FunctionData, ModuleData};
+use crate::binja::settings::signature_libraries;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::symbol::{Symbol, SymbolType};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use wasm_encoder::reencode::{Error, Reencode};
+use wasm_encoder::Encode;
+use wasmparser::{Operator, ValType};
+
+// Functions shorter than this are too generic to tell apart by their bodies.
+const MIN_FINGERPRINT_OPS: usize = 8;
+
+// Names of functions by the fingerprints of their bodies, as saved to and loaded from
+// a JSON file.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct SignatureLibrary {
+    pub functions: BTreeMap<String, String>,
+}
+
+// Whether a constant is an address in a data segment. Unlike what the view shows, this
+// doesn't depend on whether the segments are mapped.
+fn is_data_address(module_data: &ModuleData, value: u64) -> bool {
+    module_data.data_segments.iter().any(|segment| {
+        segment
+            .memory_range()
+            .is_some_and(|range| range.contains(&value))
+    })
+}
+
+// Follows a constant that is an address of data, which is encoded as zero. No operator
+// is encoded with a byte after the zero of a constant.
+const DATA_ADDRESS_MARKER: u8 = 0xff;
+
+// Encodes operators with the indices that differ between modules that link the same
+// code zeroed: those of functions, globals and types.
+struct Normalizer;
+
+impl Reencode for Normalizer {
+    type Error = Infallible;
+
+    fn function_index(&mut self, _func: u32) -> Result<u32, Error> {
+        Ok(0)
+    }
+
+    fn global_index(&mut self, _global: u32) -> Result<u32, Error> {
+        Ok(0)
+    }
+
+    fn type_index(&mut self, _ty: u32) -> Result<u32, Error> {
+        Ok(0)
+    }
+}
+
+// Appends the encoding of a list of value types to `bytes`.
+fn encode_types(bytes: &mut Vec<u8>, types: &[ValType]) {
+    if let Ok(types) = Normalizer.val_types(types.to_vec()) {
+        types.encode(bytes);
+    }
+}
+
+// An operator in the binary format, with the operands that differ between modules that
+// link the same code normalized: function, global and type indices are zeroed, and
+// addresses of data are zeroed and marked. Indirect calls are followed by the types
+// they call, since those are the same where the indices aren't. The encoding is the
+// plugin's own rather than the operator's bytes, so that immediates are always encoded
+// the same, e.g. without padding.
+pub(crate) fn normalize(module_data: &ModuleData, op: &Operator) -> Vec<u8> {
+    let (op, is_data) = match *op {
+        Operator::I32Const { value } if is_data_address(module_data, value as u32 as u64) => {
+            (Operator::I32Const { value: 0 }, true)
+        }
+        Operator::I64Const { value } if is_data_address(module_data, value as u64) => {
+            (Operator::I64Const { value: 0 }, true)
+        }
+        _ => (op.clone(), false),
+    };
+    let mut bytes = Vec::new();
+    // Re-encoding only fails on operators that can't be read, and these already were.
+    if let Ok(instruction) = Normalizer.instruction(op.clone()) {
+        instruction.encode(&mut bytes);
+    }
+    if is_data {
+        bytes.push(DATA_ADDRESS_MARKER);
+    }
+    if let Operator::CallIndirect { type_index, .. }
+    | Operator::ReturnCallIndirect { type_index, .. } = op
+        && let Some(ty) = module_data.types.get(type_index as usize)
+    {
+        encode_types(&mut bytes, ty.params());
+        encode_types(&mut bytes, ty.results());
+    }
+    bytes
+}
+
+// A hash of a function's type and normalized body, which is the same for the same
+// routine linked into different modules. FNV-1a, since it has to be stable across
+// builds of the plugin.
+fn fingerprint(module_data: &ModuleData, func: &FunctionData) -> Option<String> {
+    if func.ops.len() < MIN_FINGERPRINT_OPS {
+        return None;
+    }
+    let ty = module_data.func_type(func.func_index)?;
+    let mut hash: u64 = 0xcbf29ce484222325;
+    // Each part is prefixed with its length, so that parts can't run into each other.
+    let mut update = |bytes: &[u8]| {
+        for byte in (bytes.len() as u32).to_le_bytes().iter().chain(bytes) {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    };
+    let mut signature = Vec::new();
+    encode_types(&mut signature, ty.params());
+    encode_types(&mut signature, ty.results());
+    update(&signature);
+    for op in func.ops.values() {
+        update(&normalize(module_data, &op.op));
+    }
+    Some(format!("{hash:016x}"))
+}
+
+// Fingerprints the named functions defined in a module, to match them in stripped
+// modules. Fingerprints shared by functions with different names are left out, since
+// they can't tell them apart.
+pub(crate) fn generate_signatures(module_data: &ModuleData) -> SignatureLibrary {
+    let mut functions = BTreeMap::<String, Option<String>>::new();
+    for (_, func) in module_data.funcs.iter() {
+        let func = func.as_ref();
+        let Some(name) = module_data.func_names.get(&func.func_index) else {
+            continue;
+        };
+        let Some(fingerprint) = fingerprint(module_data, func) else {
+            continue;
+        };
+        functions
+            .entry(fingerprint)
+            .and_modify(|existing| {
+                if existing.as_ref() != Some(name) {
+                    *existing = None;
+                }
+            })
+            .or_insert_with(|| Some(name.clone()));
+    }
+    SignatureLibrary {
+        functions: functions
+            .into_iter()
+            .filter_map(|(fingerprint, name)| Some((fingerprint, name?)))
+            .collect(),
+    }
+}
+
+fn load_library(path: &str) -> Option<SignatureLibrary> {
+    let library = std::fs::read(path)
+        .map_err(|e| e.to_string())
+        .and_then(|data| serde_json::from_slice(&data).map_err(|e| e.to_string()));
+    match library {
+        Ok(library) => Some(library),
+        Err(e) => {
+            warn!("Failed to read signature library {path}: {e}");
+            None
+        }
+    }
+}
+
+impl WebAssemblyView {
+    // Names the functions of a stripped module after the functions in the signature
+    // libraries whose bodies they match, such as the libc and compiler-rt routines
+    // that are statically linked into most modules.
+    pub(crate) fn apply_signature_libraries(&mut self, module_data: &mut ModuleData) {
+        let mut functions = BTreeMap::new();
+        for path in signature_libraries(self.as_ref()) {
+            if let Some(library) = load_library(&path) {
+                functions.extend(library.functions);
+            }
+        }
+        if functions.is_empty() {
+            return;
+        }
+
+        let mut names = Vec::new();
+        for (_, func) in module_data.funcs.iter() {
+            let func = func.as_ref();
+            if module_data.func_names.contains_key(&func.func_index)
+                || module_data.func_exports.contains_key(&func.func_index)
+            {
+                continue;
+            }
+            let name = fingerprint(module_data, func).and_then(|f| functions.get(&f));
+            if let Some(name) = name {
+                names.push((func.func_index, func.size_start, name.clone()));
+            }
+        }
+        for (func_index, addr, name) in &names {
+            let symbol = Symbol::builder(SymbolType::Function, name, *addr).create();
+            self.define_auto_symbol(&symbol);
+            module_data.func_names.insert(*func_index, name.clone());
+        }
+        info!("Named {} functions from signature libraries", names.len());
+    }
+}
@@ -0,0 +1,236 @@
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::parse::pointers::memory_pointer;
+use crate::binja::typelib::go::{JS, JS_SIGNATURES};
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::metadata::Metadata;
+use binaryninja::rc::Ref;
+use binaryninja::symbol::{Symbol, SymbolType};
+use binaryninja::types::{FunctionParameter, MemberAccess, MemberScope, StructureBuilder, Type};
+use log::info;
+use std::collections::HashMap;
+use wasmparser::ValType;
+
+// Custom section the Go linker writes into every module.
+const BUILD_ID_SECTION: &str = "go:buildid";
+
+// Exports through which `wasm_exec.js` drives a GOOS=js module.
+const JS_EXPORTS: &[&str] = &["run", "resume", "getsp"];
+
+// The Go compiler passes arguments and results on a stack in linear memory rather
+// than on the wasm stack, and keeps its registers in the first globals.
+const REGISTER_GLOBALS: &[&str] = &["SP", "CTXT", "g", "RET0", "RET1", "RET2", "RET3", "PAUSE"];
+
+// Where the arguments of the `wasm_exec.js` imports start on the Go stack, after the
+// return address.
+const FRAME_ARGS_OFFSET: u64 = 8;
+
+#[derive(Clone, Copy)]
+pub(crate) enum GoTarget {
+    Js,
+    Wasip1,
+}
+
+impl GoTarget {
+    pub(crate) fn goos(self) -> &'static str {
+        match self {
+            GoTarget::Js => "js",
+            GoTarget::Wasip1 => "wasip1",
+        }
+    }
+}
+
+// A Go type as it is laid out on the Go stack of GOARCH=wasm, where ints and
+// pointers are 64-bit: its type in the view, and its size. Values are aligned to their
+// size, up to 8 bytes.
+fn go_type(view: &WebAssemblyView, ty: &str) -> Option<(Ref<Type>, u64)> {
+    let words = |name: &str, fields: &[&str]| {
+        let mut builder = StructureBuilder::new();
+        for field in fields {
+            builder.append(
+                &Type::int(8, false),
+                field,
+                MemberAccess::NoAccess,
+                MemberScope::NoScope,
+            );
+        }
+        let structure = Type::structure(&builder.finalize());
+        view.define_auto_type(name, "Go", &structure);
+        Type::named_type_from_type(name, &structure)
+    };
+    let go_type = match ty {
+        "bool" => (Type::bool(), 1),
+        "int32" => (Type::int(4, true), 4),
+        "int64" => (Type::int(8, true), 8),
+        "int" => (Type::named_int(8, true, "int"), 8),
+        "uintptr" | "unsafe.Pointer" => (Type::named_int(8, false, "uintptr"), 8),
+        "ref" => (Type::named_int(8, false, "ref"), 8),
+        "string" => (words("string", &["ptr", "len"]), 16),
+        _ if ty.starts_with("[]") => (words("slice", &["ptr", "len", "cap"]), 24),
+        _ => return None,
+    };
+    Some(go_type)
+}
+
+// Splits the parameters or results of a Go signature, e.g. `v ref, i int`, into
+// (name, type) pairs. Unnamed results are named `r0`, `r1`, ... in order.
+fn go_fields(list: &str) -> Vec<(String, &str)> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .enumerate()
+        .map(|(i, field)| match field.split_once(' ') {
+            Some((name, ty)) => (name.to_string(), ty.trim()),
+            None => (format!("r{i}"), field),
+        })
+        .collect()
+}
+
+// The frame on the Go stack that the import `name` with the Go `signature` reads its
+// arguments from and writes its results to, starting at the stack pointer. Arguments
+// follow the return address, each aligned to its size, and the results follow them
+// at the next multiple of 8 bytes.
+fn go_frame(view: &WebAssemblyView, name: &str, signature: &str) -> Option<Ref<Type>> {
+    let (params, results) = signature.strip_prefix("func(")?.split_once(')')?;
+    let results = results.trim();
+    let results = results
+        .strip_prefix('(')
+        .and_then(|results| results.strip_suffix(')'))
+        .unwrap_or(results);
+
+    let mut builder = StructureBuilder::new();
+    builder.insert(
+        &Type::int(8, false),
+        "return_addr",
+        0,
+        false,
+        MemberAccess::NoAccess,
+        MemberScope::NoScope,
+    );
+    let mut offset = FRAME_ARGS_OFFSET;
+    for (i, list) in [params, results].into_iter().enumerate() {
+        if i == 1 {
+            offset = offset.next_multiple_of(8);
+        }
+        for (field, ty) in go_fields(list) {
+            let (ty, size) = go_type(view, ty)?;
+            offset = offset.next_multiple_of(size.min(8));
+            builder.insert(
+                &ty,
+                &field,
+                offset,
+                false,
+                MemberAccess::NoAccess,
+                MemberScope::NoScope,
+            );
+            offset += size;
+        }
+    }
+    let frame_name = format!(
+        "{}_frame",
+        name.replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+    );
+    let structure = Type::structure(&builder.finalize());
+    view.define_auto_type(&frame_name, "Go", &structure);
+    Some(Type::named_type_from_type(&frame_name, &structure))
+}
+
+pub(crate) fn detect_go(module_data: &ModuleData) -> Option<GoTarget> {
+    let js_imports = module_data
+        .func_imports
+        .iter()
+        .any(|import| JS.modules.contains(&import.module.as_str()));
+    let js_exports = JS_EXPORTS.iter().all(|name| {
+        module_data
+            .func_exports
+            .values()
+            .any(|export| export == name)
+    });
+    if js_imports || js_exports {
+        return Some(GoTarget::Js);
+    }
+
+    let build_id = module_data
+        .custom_sections
+        .iter()
+        .any(|name| name == BUILD_ID_SECTION);
+    let runtime = module_data
+        .func_names
+        .values()
+        .any(|name| name == "runtime.rt0_go");
+    (build_id || runtime).then_some(GoTarget::Wasip1)
+}
+
+impl WebAssemblyView {
+    // Go modules are large, and their code is hard to follow without knowing the Go
+    // calling convention. Name the globals the compiler uses as registers, and type
+    // and describe the imports of GOOS=js modules.
+    pub(crate) fn annotate_go(&mut self, module_data: &ModuleData) {
+        if !module_data.load_settings.go_analysis {
+            return;
+        }
+        let Some(target) = detect_go(module_data) else {
+            return;
+        };
+        info!(
+            "Module was built by the Go compiler for GOOS={}",
+            target.goos()
+        );
+
+        let has_registers = module_data.globals.len() >= REGISTER_GLOBALS.len()
+            && module_data.globals[0].ty.mutable
+            && module_data.globals[0].ty.content_type == ValType::I32;
+        if has_registers {
+            for (global, name) in module_data.globals.iter().zip(REGISTER_GLOBALS) {
+                let symbol = Symbol::builder(SymbolType::Data, name, global.addr).create();
+                self.define_auto_symbol(&symbol);
+            }
+            if let Some(sp) = module_data.globals.first() {
+                self.set_comment_at(
+                    sp.addr,
+                    "Go stack pointer; arguments and results are passed on the Go stack from SP+8",
+                );
+            }
+        }
+
+        for (import, addr) in module_data.func_imports.iter().zip(&module_data.func_addrs) {
+            if !JS.modules.contains(&import.module.as_str()) {
+                continue;
+            }
+            let signature = JS_SIGNATURES
+                .iter()
+                .find(|(name, _)| *name == import.name)
+                .map(|(_, signature)| *signature);
+            let frame = signature.and_then(|signature| go_frame(self, &import.name, signature));
+            match frame {
+                // The stack pointer points to the frame, so its fields show up as the
+                // arguments and results of calls.
+                Some(frame) => {
+                    let sp = FunctionParameter::new(
+                        memory_pointer(module_data, &frame),
+                        "sp".to_string(),
+                        None,
+                    );
+                    let ty = Type::function(&Type::void(), vec![sp], false);
+                    self.define_auto_data_var(*addr, &ty);
+                }
+                None => {
+                    self.apply_prototype(
+                        &JS,
+                        module_data,
+                        *addr,
+                        &format!("void {}(i32 sp)", import.name),
+                    );
+                }
+            }
+            if let Some(signature) = signature {
+                self.set_comment_at(*addr, &format!("Go: {signature}"));
+            }
+        }
+
+        let mut metadata: HashMap<&str, Ref<Metadata>> = HashMap::new();
+        metadata.insert("goos", target.goos().into());
+        metadata.insert("registerGlobals", has_registers.into());
+        self.store_metadata("wasm.go", metadata, true);
+    }
+}
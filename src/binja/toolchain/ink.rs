@@ -0,0 +1,156 @@
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::typelib::pallet_contracts::{SEAL0, SEAL1, SEAL2};
+use crate::binja::typelib::TypeLibrary;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::metadata::Metadata;
+use binaryninja::rc::Ref;
+use log::info;
+use std::collections::{BTreeSet, HashMap};
+use wasmparser::Operator;
+
+const LIBRARIES: &[&TypeLibrary] = &[&SEAL0, &SEAL1, &SEAL2];
+
+// Exports the contracts pallet calls to run a contract.
+const ENTRY_POINTS: &[&str] = &["deploy", "call"];
+
+pub(crate) const ENTRY_POINT_TAG: &str = "ink! Entry Point";
+
+// ink! inlines aggressively, so the dispatch on the message selector is usually in
+// an entry point or a function it calls, but can be a few calls down.
+const MAX_DISPATCH_DEPTH: usize = 3;
+
+// Selectors are the first 4 bytes of a hash, so comparisons against small constants
+// are more likely to be something else.
+const MIN_SELECTOR: u32 = 0x100;
+
+pub(crate) fn is_ink_module(module_data: &ModuleData) -> bool {
+    module_data.func_imports.iter().any(|import| {
+        LIBRARIES
+            .iter()
+            .any(|library| library.modules.contains(&import.module.as_str()))
+    })
+}
+
+fn host_prototype(module: &str, name: &str) -> Option<(&'static TypeLibrary, &'static str)> {
+    let library = *LIBRARIES
+        .iter()
+        .find(|library| library.modules.contains(&module))?;
+    let prototype = library
+        .prototype(name)
+        .or_else(|| library.prototype(name.strip_prefix("seal_")?))?;
+    Some((library, prototype))
+}
+
+fn is_input_import(module_data: &ModuleData, func_index: u32) -> bool {
+    module_data
+        .func_imports
+        .get(func_index as usize)
+        .is_some_and(|import| {
+            host_prototype(&import.module, &import.name).is_some()
+                && (import.name == "input" || import.name == "seal_input")
+        })
+}
+
+// Finds the constants compared against the message selector, starting from the
+// entry point `func_index`. Only functions that read the input are searched for
+// comparisons, as the selector is its first 4 bytes.
+fn find_selectors(module_data: &ModuleData, func_index: u32) -> Vec<(u64, u32)> {
+    let mut selectors = Vec::new();
+    let mut visited = BTreeSet::new();
+    let mut frontier = vec![func_index];
+    for _ in 0..MAX_DISPATCH_DEPTH {
+        let mut next = Vec::new();
+        for func_index in frontier {
+            if (func_index as usize) < module_data.func_imports.len() || !visited.insert(func_index)
+            {
+                continue;
+            }
+            let Some(addr) = module_data.func_addrs.get(func_index as usize) else {
+                continue;
+            };
+            let Some(func) = module_data.funcs.get(addr) else {
+                continue;
+            };
+            let func = func.as_ref();
+
+            let mut reads_input = false;
+            let mut comparisons = Vec::new();
+            let mut prev_const = None;
+            for (addr, op) in &func.ops {
+                match op.op {
+                    Operator::Call { function_index } => {
+                        reads_input |= is_input_import(module_data, function_index);
+                        next.push(function_index);
+                    }
+                    Operator::I32Eq | Operator::I32Ne => {
+                        if let Some((const_addr, value)) = prev_const {
+                            comparisons.push((const_addr, value));
+                        }
+                    }
+                    _ => {}
+                }
+                prev_const = match op.op {
+                    Operator::I32Const { value } if value as u32 >= MIN_SELECTOR => {
+                        Some((*addr, value as u32))
+                    }
+                    _ => None,
+                };
+            }
+            if reads_input {
+                selectors.extend(comparisons);
+            }
+        }
+        frontier = next;
+    }
+    selectors
+}
+
+impl WebAssemblyView {
+    // ink! contracts run on Substrate's contracts pallet. Type its host functions, tag
+    // the entry points, and mark the constants that messages are dispatched on, so
+    // the routing of each message to its handler can be followed.
+    pub(crate) fn annotate_ink(&mut self, module_data: &ModuleData) {
+        if !is_ink_module(module_data) {
+            return;
+        }
+        info!("Module is a pallet-contracts (ink!) contract");
+
+        let mut n_applied = 0;
+        for (import, addr) in module_data.func_imports.iter().zip(&module_data.func_addrs) {
+            if let Some((library, prototype)) = host_prototype(&import.module, &import.name)
+                && self.apply_prototype(library, module_data, *addr, prototype)
+            {
+                n_applied += 1;
+            }
+        }
+        info!("Applied {n_applied} pallet-contracts prototypes to imports");
+
+        let tag_type = self.contract_tag_type(ENTRY_POINT_TAG);
+        let mut metadata: HashMap<&str, Ref<Metadata>> = HashMap::new();
+        for (func_index, name) in &module_data.func_exports {
+            if !ENTRY_POINTS.contains(&name.as_str())
+                || (*func_index as usize) < module_data.func_imports.len()
+            {
+                continue;
+            }
+            let Some(&addr) = module_data.func_addrs.get(*func_index as usize) else {
+                continue;
+            };
+            self.add_tag(addr, &tag_type, name, false);
+
+            let mut selectors = Vec::new();
+            for (const_addr, value) in find_selectors(module_data, *func_index) {
+                // The selector bytes are loaded as a little-endian i32.
+                let selector = format!("0x{:08x}", value.swap_bytes());
+                self.set_comment_at(const_addr, &format!("ink! {name} selector {selector}"));
+                selectors.push(Ref::<Metadata>::from(selector.as_str()));
+            }
+            if !selectors.is_empty() {
+                info!("Found {} selectors dispatched by {name}", selectors.len());
+            }
+            metadata.insert(name.as_str(), selectors.into());
+        }
+        self.store_metadata("wasm.ink", metadata, true);
+    }
+}
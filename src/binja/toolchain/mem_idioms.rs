@@ -0,0 +1,203 @@
+use crate::binja::parse::module_data::{FunctionData, ModuleData};
+use crate::binja::typelib::string::STRING;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::symbol::{Symbol, SymbolType};
+use log::info;
+use std::collections::BTreeMap;
+use wasmparser::{Operator, ValType};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Idiom {
+    Copy,
+    Fill,
+}
+
+impl Idiom {
+    fn func_name(&self) -> &'static str {
+        match self {
+            Self::Copy => "memcpy",
+            Self::Fill => "memset",
+        }
+    }
+
+    fn loop_comment(&self) -> &'static str {
+        match self {
+            Self::Copy => "Byte copy loop (memcpy)",
+            Self::Fill => "Byte fill loop (memset)",
+        }
+    }
+}
+
+// Operators that copy and fill loops are built from, besides their loads and stores.
+fn is_loop_op(op: &Operator) -> bool {
+    matches!(
+        op,
+        Operator::Loop { .. }
+            | Operator::Block { .. }
+            | Operator::End
+            | Operator::Br { .. }
+            | Operator::BrIf { .. }
+            | Operator::LocalGet { .. }
+            | Operator::LocalSet { .. }
+            | Operator::LocalTee { .. }
+            | Operator::I32Const { .. }
+            | Operator::I32Add
+            | Operator::I32Sub
+            | Operator::I32Eqz
+            | Operator::I32Eq
+            | Operator::I32Ne
+            | Operator::I32LtU
+            | Operator::I32GtU
+            | Operator::I32LeU
+            | Operator::I32GeU
+    )
+}
+
+// Recognizes a loop that moves one byte per iteration, as LLVM emits for copies and
+// fills of unknown length when it can't call `memcpy` or `memset`.
+fn loop_idiom<'a>(ops: impl Iterator<Item = &'a Operator<'a>>) -> Option<Idiom> {
+    let mut n_loads = 0;
+    let mut n_stores = 0;
+    for op in ops {
+        match op {
+            Operator::I32Load8U { .. } => n_loads += 1,
+            Operator::I32Store8 { .. } => n_stores += 1,
+            op if is_loop_op(op) => {}
+            _ => return None,
+        }
+    }
+    match (n_loads, n_stores) {
+        (1, 1) => Some(Idiom::Copy),
+        (0, 1) => Some(Idiom::Fill),
+        _ => None,
+    }
+}
+
+// The idiom a whole function implements, if it is `memcpy` or `memset`: a wrapper around
+// `memory.copy` or `memory.fill`, or a function that copies or fills in loops without
+// calling anything.
+fn func_idiom(module_data: &ModuleData, func: &FunctionData) -> Option<Idiom> {
+    let ty = module_data.func_type(func.func_index)?;
+    if ty.params() != [ValType::I32, ValType::I32, ValType::I32] {
+        return None;
+    }
+    let mut has_loop = false;
+    let mut has_load = false;
+    let mut has_store = false;
+    for (_, op) in func.reachable_ops() {
+        match op.op {
+            Operator::MemoryCopy { .. } => return Some(Idiom::Copy),
+            Operator::MemoryFill { .. } => return Some(Idiom::Fill),
+            Operator::Call { .. } | Operator::CallIndirect { .. } => return None,
+            Operator::Loop { .. } => has_loop = true,
+            Operator::I32Load { .. } | Operator::I64Load { .. } | Operator::I32Load8U { .. } => {
+                has_load = true
+            }
+            Operator::I32Store { .. } | Operator::I64Store { .. } | Operator::I32Store8 { .. } => {
+                has_store = true
+            }
+            _ => {}
+        }
+    }
+    match (has_loop, has_load, has_store) {
+        (true, true, true) => Some(Idiom::Copy),
+        (true, false, true) => Some(Idiom::Fill),
+        _ => None,
+    }
+}
+
+fn call_counts(module_data: &ModuleData) -> BTreeMap<u32, usize> {
+    let mut counts = BTreeMap::new();
+    for (_, func) in module_data.funcs.iter() {
+        for (_, op) in func.as_ref().reachable_ops() {
+            if let Operator::Call { function_index } = op.op {
+                *counts.entry(function_index).or_default() += 1;
+            }
+        }
+    }
+    counts
+}
+
+impl WebAssemblyView {
+    // Data-heavy code spends much of its length copying and filling memory. Name and
+    // type the `memcpy` and `memset` functions, and comment the loops that do the same
+    // inline, so that they read as the single operation they are.
+    pub(crate) fn annotate_memory_idioms(&mut self, module_data: &ModuleData) {
+        let mut funcs = BTreeMap::<u32, &str>::new();
+        for (func_index, name) in module_data
+            .func_names
+            .iter()
+            .chain(&module_data.func_exports)
+        {
+            if STRING.prototype(name).is_some() {
+                funcs.insert(*func_index, name);
+            }
+        }
+
+        // In stripped modules, the most called function that implements each idiom.
+        let named = |func_index: &u32| {
+            module_data.func_names.contains_key(func_index)
+                || module_data.func_exports.contains_key(func_index)
+        };
+        let counts = call_counts(module_data);
+        let mut idiom_funcs = BTreeMap::<Idiom, (u32, usize)>::new();
+        for (_, func) in module_data.funcs.iter() {
+            let func = func.as_ref();
+            if named(&func.func_index) {
+                continue;
+            }
+            let Some(idiom) = func_idiom(module_data, func) else {
+                continue;
+            };
+            if funcs.values().any(|name| *name == idiom.func_name()) {
+                continue;
+            }
+            let count = counts.get(&func.func_index).copied().unwrap_or(0);
+            let best = idiom_funcs.entry(idiom).or_insert((func.func_index, count));
+            if count > best.1 {
+                *best = (func.func_index, count);
+            }
+        }
+        for (idiom, (func_index, _)) in &idiom_funcs {
+            let Some(&addr) = module_data.func_addrs.get(*func_index as usize) else {
+                continue;
+            };
+            let symbol = Symbol::builder(SymbolType::Function, idiom.func_name(), addr).create();
+            self.define_auto_symbol(&symbol);
+            funcs.insert(*func_index, idiom.func_name());
+        }
+        for (func_index, name) in &funcs {
+            let Some(&addr) = module_data.func_addrs.get(*func_index as usize) else {
+                continue;
+            };
+            if let Some(prototype) = STRING.prototype(name) {
+                self.apply_function_prototype(&STRING, module_data, addr, prototype);
+            }
+        }
+
+        let mut n_loops = 0;
+        for (_, func) in module_data.funcs.iter() {
+            let func = func.as_ref();
+            if funcs.contains_key(&func.func_index) {
+                continue;
+            }
+            for (end, start) in &func.block_starts {
+                let Some(Operator::Loop { .. }) = func.ops.get(start).map(|op| &op.op) else {
+                    continue;
+                };
+                let body = func.ops.range(start..=end).map(|(_, op)| &op.op);
+                if let Some(idiom) = loop_idiom(body) {
+                    self.set_comment_at(*start, idiom.loop_comment());
+                    n_loops += 1;
+                }
+            }
+        }
+        if !funcs.is_empty() || n_loops > 0 {
+            info!(
+                "Recognized {} memory functions and {n_loops} copy or fill loops",
+                funcs.len()
+            );
+        }
+    }
+}
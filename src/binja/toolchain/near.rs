@@ -0,0 +1,62 @@
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::typelib::near::ENV;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::metadata::Metadata;
+use binaryninja::rc::Ref;
+use log::info;
+use std::collections::HashMap;
+
+const ENV_MODULE: &str = "env";
+
+// Host functions that only the NEAR runtime provides.
+const REGISTER_IMPORTS: &[&str] = &["read_register", "register_len"];
+
+pub(crate) const METHOD_TAG: &str = "NEAR Method";
+
+pub(crate) fn is_near_module(module_data: &ModuleData) -> bool {
+    module_data.func_imports.iter().any(|import| {
+        import.module == ENV_MODULE && REGISTER_IMPORTS.contains(&import.name.as_str())
+    })
+}
+
+impl WebAssemblyView {
+    // NEAR contracts receive their arguments and return their results through
+    // registers held by the runtime rather than as parameters. Type the runtime's
+    // host functions, and tag the exported methods, which take no parameters.
+    pub(crate) fn annotate_near(&mut self, module_data: &ModuleData) {
+        if !is_near_module(module_data) {
+            return;
+        }
+        info!("Module is a NEAR contract");
+        self.apply_type_library(&ENV, module_data);
+
+        let tag_type = self.contract_tag_type(METHOD_TAG);
+        let mut methods = Vec::new();
+        for (func_index, name) in &module_data.func_exports {
+            if (*func_index as usize) < module_data.func_imports.len() {
+                continue;
+            }
+            let Some(ty) = module_data.func_type(*func_index) else {
+                continue;
+            };
+            if !ty.params().is_empty() || !ty.results().is_empty() {
+                continue;
+            }
+            let Some(&addr) = module_data.func_addrs.get(*func_index as usize) else {
+                continue;
+            };
+            self.add_tag(addr, &tag_type, name, false);
+            self.set_comment_at(
+                addr,
+                "NEAR method: arguments are read with input() and read_register(), and the result is returned with value_return()",
+            );
+            methods.push(Ref::<Metadata>::from(name.as_str()));
+        }
+        info!("Found {} NEAR methods", methods.len());
+
+        let mut metadata: HashMap<&str, Ref<Metadata>> = HashMap::new();
+        metadata.insert("methods", methods.into());
+        self.store_metadata("wasm.near", metadata, true);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::binja::parse::module_data::{FunctionData, ModuleData, Trampoline};
+use crate::binja::parse::pointers::find_accesses;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::symbol::{Symbol, SymbolType};
+use log::info;
+use std::collections::BTreeMap;
+use wasmparser::{Operator, ValType};
+
+// Name prefixes of the wrappers that Binaryen adds around functions: to call them
+// through a table with a different type (fpcast-emu), and to pass i64s to and from JS
+// as pairs of i32s (legalization).
+const WRAPPER_PREFIXES: &[&str] = &["byn$fpcast-emu$", "legalstub$", "legalfunc$"];
+
+const DYNCALL_PREFIX: &str = "dynCall_";
+
+// Emscripten imports these from its JS runtime to call a function in the table inside
+// a JS try/catch, for C++ exceptions and setjmp/longjmp.
+const ENV_MODULE: &str = "env";
+const INVOKE_PREFIX: &str = "invoke_";
+
+pub(crate) const TRAMPOLINE_TAG: &str = "Trampoline";
+
+// Trampolines can call trampolines; past this many, the chain is assumed to be a loop.
+const MAX_CHAIN_LEN: usize = 8;
+
+// The letter of a value type in Emscripten's signature strings, e.g. `vii`.
+fn signature_char(ty: &ValType) -> char {
+    match ty {
+        ValType::I32 => 'i',
+        ValType::I64 => 'j',
+        ValType::F32 => 'f',
+        ValType::F64 => 'd',
+        _ => 'p',
+    }
+}
+
+// The function that a wrapper forwards to: its only call. Everything else converts the
+// arguments and results.
+fn wrapped_func(func: &FunctionData) -> Option<u32> {
+    let mut callee = None;
+    for (_, op) in func.reachable_ops() {
+        match op.op {
+            Operator::Call { function_index } | Operator::ReturnCall { function_index }
+                if callee.replace(function_index).is_some() =>
+            {
+                return None;
+            }
+            Operator::CallIndirect { .. }
+            | Operator::CallRef { .. }
+            | Operator::ReturnCallIndirect { .. }
+            | Operator::ReturnCallRef { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Br { .. }
+            | Operator::BrIf { .. }
+            | Operator::BrTable { .. } => return None,
+            _ => {}
+        }
+    }
+    callee.filter(|callee| *callee != func.func_index)
+}
+
+// Whether a function is shaped like `dynCall_*`: it passes its arguments after the
+// first to the function at the table index in the first, and does nothing else.
+//
+//     local.get 1
+//     local.get 2
+//     local.get 0
+//     call_indirect (type $vii)
+//
+// Returns the table it calls through.
+fn dyncall_table(module_data: &ModuleData, func: &FunctionData) -> Option<u32> {
+    let ty = module_data.func_type(func.func_index)?;
+    let n_args = ty.params().len().checked_sub(1)? as u32;
+    if ty.params()[0] != ValType::I32 {
+        return None;
+    }
+    let ops = func
+        .reachable_ops()
+        .map(|(_, op)| &op.op)
+        .collect::<Vec<_>>();
+    let [args @ .., Operator::LocalGet { local_index: 0 }, Operator::CallIndirect {
+        type_index,
+        table_index,
+    }, Operator::End] = ops.as_slice()
+    else {
+        return None;
+    };
+    let forwards_args = args.len() == n_args as usize
+        && args
+            .iter()
+            .zip(1..)
+            .all(|(op, i)| matches!(op, Operator::LocalGet { local_index } if *local_index == i));
+    let callee_ty = module_data.types.get(*type_index as usize)?;
+    let same_type = callee_ty.params() == &ty.params()[1..] && callee_ty.results() == ty.results();
+    (forwards_args && same_type).then_some(*table_index)
+}
+
+fn is_invoke(module_data: &ModuleData, func_index: u32) -> bool {
+    module_data
+        .func_imports
+        .get(func_index as usize)
+        .is_some_and(|import| import.module == ENV_MODULE && import.name.starts_with(INVOKE_PREFIX))
+}
+
+fn find_trampolines(module_data: &ModuleData) -> BTreeMap<u32, Trampoline> {
+    let mut trampolines = BTreeMap::new();
+    for func_index in 0..module_data.func_imports.len() as u32 {
+        let takes_index = module_data
+            .func_type(func_index)
+            .is_some_and(|ty| ty.params().first() == Some(&ValType::I32));
+        if is_invoke(module_data, func_index) && takes_index {
+            trampolines.insert(func_index, Trampoline::Table { table_index: 0 });
+        }
+    }
+    for (_, func) in module_data.funcs.iter() {
+        let func = func.as_ref();
+        let name = module_data.func_name(func.func_index);
+        let trampoline = if WRAPPER_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+        {
+            wrapped_func(func).map(Trampoline::Direct)
+        } else {
+            dyncall_table(module_data, func).map(|table_index| Trampoline::Table { table_index })
+        };
+        if let Some(trampoline) = trampoline {
+            trampolines.insert(func.func_index, trampoline);
+        }
+    }
+    trampolines
+}
+
+// The function that calling `func_index` ends up in, through any direct trampolines.
+fn resolve_direct(trampolines: &BTreeMap<u32, Trampoline>, func_index: u32) -> u32 {
+    let mut func_index = func_index;
+    for _ in 0..MAX_CHAIN_LEN {
+        match trampolines.get(&func_index) {
+            Some(Trampoline::Direct(callee)) => func_index = *callee,
+            _ => break,
+        }
+    }
+    func_index
+}
+
+// The functions that a call through a table trampoline can reach: the function at the
+// table index, if it is a constant, or else every function in the table of the type
+// that the trampoline passes its arguments on with.
+fn table_call_targets(
+    module_data: &ModuleData,
+    trampoline: u32,
+    table_index: u32,
+    func_table_index: Option<u64>,
+) -> Vec<u32> {
+    if let Some(func_table_index) = func_table_index {
+        return module_data
+            .table_contents(table_index)
+            .get(&func_table_index)
+            .map_or_else(Vec::new, |func_index| vec![*func_index]);
+    }
+    let Some(ty) = module_data.func_type(trampoline) else {
+        return Vec::new();
+    };
+    let Some(params) = ty.params().get(1..) else {
+        return Vec::new();
+    };
+    let type_index = module_data
+        .types
+        .iter()
+        .position(|callee_ty| callee_ty.params() == params && callee_ty.results() == ty.results());
+    match type_index {
+        Some(type_index) => module_data.indirect_call_candidates(type_index as u32, table_index),
+        None => Vec::new(),
+    }
+}
+
+impl WebAssemblyView {
+    // Emscripten and Binaryen route calls through trampolines: `dynCall_*` and the
+    // `invoke_*` imports call the function at a table index, and the fpcast-emu and
+    // legalization wrappers call the function they wrap. Find and tag them, and resolve what calls to them, and calls
+    // through the table to the wrappers, go on to call, so that the call graph shows
+    // the real callees rather than a wall of identical trampolines.
+    pub(crate) fn resolve_trampolines(&mut self, module_data: &mut ModuleData) {
+        let trampolines = find_trampolines(module_data);
+        module_data.trampolines.extend(trampolines);
+        if module_data.trampolines.is_empty() {
+            return;
+        }
+
+        let mut targets = BTreeMap::new();
+        let mut invokes = Vec::new();
+        for (_, func) in module_data.funcs.iter() {
+            let accesses = find_accesses(module_data, func.as_ref());
+            for (addr, func_index, args) in &accesses.calls {
+                let callees = match module_data.trampolines.get(func_index) {
+                    Some(Trampoline::Direct(_)) => {
+                        vec![resolve_direct(&module_data.trampolines, *func_index)]
+                    }
+                    Some(Trampoline::Table { table_index }) => {
+                        let func_table_index = args.first().copied().flatten();
+                        table_call_targets(module_data, *func_index, *table_index, func_table_index)
+                    }
+                    None => continue,
+                };
+                if let ([callee], true) = (callees.as_slice(), is_invoke(module_data, *func_index))
+                {
+                    invokes.push((*addr, *callee));
+                }
+                targets.insert(*addr, callees);
+            }
+        }
+        for callees in module_data.indirect_call_targets.values_mut() {
+            for callee in callees.iter_mut() {
+                *callee = resolve_direct(&module_data.trampolines, *callee);
+            }
+            callees.sort();
+            callees.dedup();
+        }
+        let n_calls = targets.len();
+        module_data.indirect_call_targets.extend(targets);
+        for (addr, callee) in invokes {
+            let comment = format!(
+                "Invokes {}, catching exceptions and longjmps",
+                module_data.func_name(callee)
+            );
+            self.set_comment_at(addr, &comment);
+        }
+
+        let tag_type = match self.tag_type_by_name(TRAMPOLINE_TAG) {
+            Some(tag_type) => tag_type,
+            None => self.create_tag_type(TRAMPOLINE_TAG, "↪"),
+        };
+        for (func_index, trampoline) in &module_data.trampolines {
+            let Some(&addr) = module_data.func_addrs.get(*func_index as usize) else {
+                continue;
+            };
+            let data = match trampoline {
+                Trampoline::Direct(callee) => format!(
+                    "Wrapper: calls {}",
+                    module_data.func_name(resolve_direct(&module_data.trampolines, *callee))
+                ),
+                Trampoline::Table { table_index } => {
+                    format!("Calls the function at a table index in table {table_index}")
+                }
+            };
+            self.add_tag(addr, &tag_type, &data, false);
+            if (*func_index as usize) < module_data.func_imports.len() {
+                continue;
+            }
+
+            // Name the `dynCall_*` functions of stripped modules by their signatures.
+            let named = module_data.func_names.contains_key(func_index)
+                || module_data.func_exports.contains_key(func_index);
+            let Some(ty) = module_data.func_type(*func_index) else {
+                continue;
+            };
+            if let (Trampoline::Table { .. }, false) = (trampoline, named) {
+                let result = ty.results().first().map_or('v', signature_char);
+                let params = ty.params()[1..].iter().map(signature_char);
+                let signature = [result].into_iter().chain(params).collect::<String>();
+                let name = format!("{DYNCALL_PREFIX}{signature}");
+                let symbol = Symbol::builder(SymbolType::Function, &name, addr).create();
+                self.define_auto_symbol(&symbol);
+                module_data.func_names.insert(*func_index, name);
+            }
+        }
+        info!(
+            "Found {} trampolines and resolved {n_calls} calls through them",
+            module_data.trampolines.len()
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use crate::binja::parse::data_parse::DataImage;
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::parse::pointers::find_accesses;
+use crate::binja::typelib::wasi::PREVIEW1;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+use log::info;
+
+// Strings longer than this are cut off in comments.
+const MAX_STRING_LEN: usize = 64;
+
+// Functions whose `void*` parameter is an array of iovecs that is written out, so
+// that the data they point to is worth showing.
+const GATHER_FUNCS: &[&str] = &["fd_write", "fd_pwrite", "sock_send"];
+
+fn quote(data: &[u8]) -> String {
+    let text = String::from_utf8_lossy(data);
+    let mut quoted = text
+        .chars()
+        .take(MAX_STRING_LEN)
+        .flat_map(char::escape_debug)
+        .collect::<String>();
+    if text.chars().count() > MAX_STRING_LEN {
+        quoted.push_str("...");
+    }
+    format!("\"{quoted}\"")
+}
+
+// The bytes that `len` iovecs at `addr` point to, one after the other. Each iovec is
+// a pointer to a buffer and the buffer's length.
+fn gather(image: &DataImage, addr: u64, len: u64) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    for i in 0..len {
+        let buf = image.read_u32(addr + 8 * i)? as u64;
+        let buf_len = image.read_u32(addr + 8 * i + 4)? as usize;
+        data.extend_from_slice(image.read(buf, buf_len.min(MAX_STRING_LEN + 1))?);
+        if data.len() > MAX_STRING_LEN {
+            break;
+        }
+    }
+    Some(data)
+}
+
+// Shows a value of one of the WASI enumerations by the names of its members, or of its
+// flag bits, e.g. `OFLAGS_CREAT|OFLAGS_TRUNC`.
+fn enum_value(ty: &str, members: &[(&str, u64)], value: u64) -> String {
+    let is_flags = ty.contains("flags") || ty.contains("rights");
+    if !is_flags {
+        return match members.iter().find(|(_, member)| *member == value) {
+            Some((name, _)) => name.to_string(),
+            None => value.to_string(),
+        };
+    }
+    let mut names = Vec::new();
+    let mut rest = value;
+    for (name, bit) in members {
+        if rest & bit != 0 {
+            names.push(name.to_string());
+            rest &= !bit;
+        }
+    }
+    if rest != 0 || names.is_empty() {
+        names.push(format!("{rest:#x}"));
+    }
+    names.join("|")
+}
+
+// Shows the arguments of a call to the WASI function `name` with the parameters
+// `params`, e.g. `fd_write(fd=1, "hello\n", nwritten=0x1040)`. Arguments that aren't
+// constant are shown as `?`.
+fn describe_call(
+    image: &DataImage,
+    name: &str,
+    params: &[(&str, &str)],
+    args: &[Option<u64>],
+) -> String {
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < params.len() {
+        let (ty, param) = params[i];
+        let (arg, len) = (args[i], args.get(i + 1).copied().flatten());
+        let has_len = params
+            .get(i + 1)
+            .is_some_and(|(_, next)| *next == format!("{param}_len"));
+
+        // A buffer and its length, shown together as the data in the buffer.
+        let data = match (has_len, ty, arg, len) {
+            (true, "char*", Some(addr), Some(len)) => {
+                image.read(addr, len as usize).map(|data| data.to_vec())
+            }
+            (true, "void*", Some(addr), Some(len)) if GATHER_FUNCS.contains(&name) => {
+                gather(image, addr, len)
+            }
+            _ => None,
+        };
+        if let Some(data) = data {
+            parts.push(quote(&data));
+            i += 2;
+            continue;
+        }
+
+        let value = match arg {
+            None => "?".to_string(),
+            Some(value) => match PREVIEW1.enum_members(ty) {
+                Some(members) => enum_value(ty, members, value),
+                None if ty.ends_with('*') => format!("{value:#x}"),
+                None => value.to_string(),
+            },
+        };
+        parts.push(format!("{param}={value}"));
+        i += 1;
+    }
+    format!("{name}({})", parts.join(", "))
+}
+
+impl WebAssemblyView {
+    // Comment calls to WASI functions with their arguments, where they are constants,
+    // decoded by the types of the parameters: the strings that paths and written
+    // buffers point to, and the names of flags and enumeration members.
+    pub(crate) fn annotate_wasi_calls(&mut self, parent: &BinaryView, module_data: &ModuleData) {
+        let has_wasi = module_data
+            .func_imports
+            .iter()
+            .any(|import| PREVIEW1.modules.contains(&import.module.as_str()));
+        if !has_wasi {
+            return;
+        }
+
+        let image = DataImage::new(parent, module_data);
+        let mut n_calls = 0;
+        for (_, func) in module_data.funcs.iter() {
+            let accesses = find_accesses(module_data, func.as_ref());
+            for (addr, func_index, args) in &accesses.calls {
+                let Some(import) = module_data.func_imports.get(*func_index as usize) else {
+                    continue;
+                };
+                if !PREVIEW1.modules.contains(&import.module.as_str()) {
+                    continue;
+                }
+                let Some(params) = PREVIEW1.params(&import.name) else {
+                    continue;
+                };
+                if params.len() != args.len() || args.iter().all(Option::is_none) {
+                    continue;
+                }
+                let comment = describe_call(&image, &import.name, &params, args);
+                self.set_comment_at(*addr, &comment);
+                n_calls += 1;
+            }
+        }
+        if n_calls > 0 {
+            info!("Decoded the arguments of {n_calls} WASI calls");
+        }
+    }
+}
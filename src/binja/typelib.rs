@@ -0,0 +1,256 @@
+use crate::binja::parse::module_data::ModuleData;
+use crate::binja::parse::pointers::memory_pointer;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::rc::Ref;
+use binaryninja::types::{
+    EnumerationBuilder, FunctionParameter, MemberAccess, MemberScope, StructureBuilder, Type,
+};
+use log::{info, warn};
+use std::num::NonZeroUsize;
+
+pub(crate) mod alloc;
+pub(crate) mod cosmwasm;
+pub(crate) mod emscripten;
+pub(crate) mod go;
+pub(crate) mod near;
+pub(crate) mod pallet_contracts;
+pub(crate) mod string;
+pub(crate) mod wasi;
+
+// An enumeration used by prototypes: (name, width, members). Sets of flags are
+// enumerations of the flag bits, which binja shows OR'ed together.
+pub(crate) type Enumeration = (&'static str, usize, &'static [(&'static str, u64)]);
+
+// A library of prototypes for the functions a host provides under some import
+// modules. Prototypes are written as C declarations, in terms of the wasm-level ABI:
+// every parameter and result is a 32- or 64-bit value, and pointers are 32-bit
+// offsets into linear memory.
+pub(crate) struct TypeLibrary {
+    pub name: &'static str,
+    pub modules: &'static [&'static str],
+    pub prototypes: &'static [&'static str],
+
+    // Integer typedefs used by the prototypes: (name, width, signed).
+    pub typedefs: &'static [(&'static str, usize, bool)],
+
+    // Structures used by the prototypes, with their fields declared like parameters.
+    pub structs: &'static [(&'static str, &'static [&'static str])],
+
+    // Enumerations used by the prototypes.
+    pub enums: &'static [Enumeration],
+}
+
+const LIBRARIES: &[&TypeLibrary] = &[&wasi::PREVIEW1];
+
+struct Prototype<'a> {
+    name: &'a str,
+    result: &'a str,
+    params: Vec<(&'a str, &'a str)>,
+}
+
+// Splits a declaration such as `u32* x` into its type and name.
+fn split_declaration(decl: &str) -> Option<(&str, &str)> {
+    let decl = decl.trim();
+    let name_start = decl.rfind(|c: char| c.is_whitespace() || c == '*')? + 1;
+    let (ty, name) = decl.split_at(name_start);
+    (!name.is_empty()).then_some((ty.trim(), name))
+}
+
+fn parse_prototype(prototype: &str) -> Option<Prototype<'_>> {
+    let (head, params) = prototype.strip_suffix(')')?.split_once('(')?;
+    let (result, name) = split_declaration(head)?;
+    let params = params
+        .split(',')
+        .filter(|param| !param.trim().is_empty())
+        .map(split_declaration)
+        .collect::<Option<Vec<_>>>()?;
+    Some(Prototype {
+        name,
+        result,
+        params,
+    })
+}
+
+fn enum_type(width: usize, members: &[(&str, u64)]) -> Option<Ref<Type>> {
+    let mut builder = EnumerationBuilder::new();
+    for (name, value) in members {
+        builder.insert(name, *value);
+    }
+    Some(Type::enumeration(
+        &builder.finalize(),
+        NonZeroUsize::new(width)?,
+        false,
+    ))
+}
+
+impl TypeLibrary {
+    pub(crate) fn prototype(&self, name: &str) -> Option<&'static str> {
+        self.prototypes
+            .iter()
+            .find(|prototype| parse_prototype(prototype).is_some_and(|p| p.name == name))
+            .copied()
+    }
+
+    // The parameters of the function `name` as (type, name) pairs.
+    pub(crate) fn params(&self, name: &str) -> Option<Vec<(&'static str, &'static str)>> {
+        Some(parse_prototype(self.prototype(name)?)?.params)
+    }
+
+    // The members of the enumeration `ty`, if it is one of the library's.
+    pub(crate) fn enum_members(&self, ty: &str) -> Option<&'static [(&'static str, u64)]> {
+        let (_, _, members) = self.enums.iter().find(|(name, ..)| *name == ty)?;
+        Some(members)
+    }
+
+    // Pointers are offsets into linear memory, so they are based on where it is mapped.
+    fn lookup_type(&self, module_data: &ModuleData, ty: &str) -> Option<Ref<Type>> {
+        if let Some(pointee) = ty.strip_suffix('*') {
+            let pointee = self.lookup_type(module_data, pointee.trim())?;
+            return Some(memory_pointer(module_data, &pointee));
+        }
+        let ty = match ty {
+            "void" => Type::void(),
+            "char" => Type::char(),
+            "u8" => Type::int(1, false),
+            "u16" => Type::int(2, false),
+            "i32" => Type::int(4, true),
+            "u32" => Type::int(4, false),
+            "i64" => Type::int(8, true),
+            "u64" => Type::int(8, false),
+            "f32" => Type::float(4),
+            "f64" => Type::float(8),
+            _ => {
+                if let Some((name, fields)) = self.structs.iter().find(|(name, _)| *name == ty) {
+                    let structure = self.struct_type(module_data, fields)?;
+                    return Some(Type::named_type_from_type(name, &structure));
+                }
+                if let Some((name, width, members)) = self.enums.iter().find(|(name, ..)| *name == ty)
+                {
+                    let enumeration = enum_type(*width, members)?;
+                    return Some(Type::named_type_from_type(name, &enumeration));
+                }
+                let (name, width, signed) = self.typedefs.iter().find(|(name, ..)| *name == ty)?;
+                Type::named_int(*width, *signed, name)
+            }
+        };
+        Some(ty)
+    }
+
+    fn struct_type(&self, module_data: &ModuleData, fields: &[&str]) -> Option<Ref<Type>> {
+        let mut builder = StructureBuilder::new();
+        for field in fields {
+            let (ty, name) = split_declaration(field)?;
+            let ty = self.lookup_type(module_data, ty)?;
+            builder.append(&ty, name, MemberAccess::NoAccess, MemberScope::NoScope);
+        }
+        Some(Type::structure(&builder.finalize()))
+    }
+
+    fn function_type(&self, module_data: &ModuleData, prototype: &Prototype) -> Option<Ref<Type>> {
+        let result = self.lookup_type(module_data, prototype.result)?;
+        let params = prototype
+            .params
+            .iter()
+            .map(|(ty, name)| {
+                let ty = self.lookup_type(module_data, ty)?;
+                Some(FunctionParameter::new(ty, name.to_string(), None))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Type::function(&result, params, false))
+    }
+}
+
+impl WebAssemblyView {
+    // Give the stubs of imported functions the prototypes of the host functions they
+    // import, so that calls to them show named, typed arguments.
+    pub(crate) fn apply_type_libraries(&mut self, module_data: &ModuleData) {
+        for library in LIBRARIES {
+            self.apply_type_library(library, module_data);
+        }
+    }
+
+    pub(crate) fn apply_type_library(&mut self, library: &TypeLibrary, module_data: &ModuleData) {
+        for (name, fields) in library.structs {
+            match library.struct_type(module_data, fields) {
+                Some(ty) => {
+                    self.define_auto_type(name, library.name, &ty);
+                }
+                None => warn!("Invalid {} structure: {name}", library.name),
+            }
+        }
+        for (name, width, members) in library.enums {
+            match enum_type(*width, members) {
+                Some(ty) => {
+                    self.define_auto_type(name, library.name, &ty);
+                }
+                None => warn!("Invalid {} enumeration: {name}", library.name),
+            }
+        }
+
+        let mut n_applied = 0;
+        let mut n_missing = 0;
+        for (import, addr) in module_data.func_imports.iter().zip(&module_data.func_addrs) {
+            if !library.modules.contains(&import.module.as_str()) {
+                continue;
+            }
+            match library.prototype(&import.name) {
+                Some(prototype) if self.apply_prototype(library, module_data, *addr, prototype) => {
+                    n_applied += 1
+                }
+                Some(_) => {}
+                None => n_missing += 1,
+            }
+        }
+        if n_applied > 0 || n_missing > 0 {
+            info!(
+                "Applied {n_applied} {} prototypes to imports; {n_missing} imports have none",
+                library.name
+            );
+        }
+    }
+
+    // Gives the stub at `addr` the type of `prototype`, which may use the types of
+    // `library`.
+    pub(crate) fn apply_prototype(
+        &mut self,
+        library: &TypeLibrary,
+        module_data: &ModuleData,
+        addr: u64,
+        prototype: &str,
+    ) -> bool {
+        let Some(ty) =
+            parse_prototype(prototype).and_then(|p| library.function_type(module_data, &p))
+        else {
+            warn!("Invalid {} prototype: {prototype}", library.name);
+            return false;
+        };
+        self.define_auto_data_var(addr, &ty);
+        true
+    }
+
+    // Gives the function at `addr`, which is defined in the module, the type of
+    // `prototype`. Does nothing if no function was created there.
+    pub(crate) fn apply_function_prototype(
+        &mut self,
+        library: &TypeLibrary,
+        module_data: &ModuleData,
+        addr: u64,
+        prototype: &str,
+    ) -> bool {
+        let Some(ty) =
+            parse_prototype(prototype).and_then(|p| library.function_type(module_data, &p))
+        else {
+            warn!("Invalid {} prototype: {prototype}", library.name);
+            return false;
+        };
+        let Some(platform) = self.default_platform() else {
+            return false;
+        };
+        let Some(func) = self.function_at(&platform, addr) else {
+            return false;
+        };
+        func.set_auto_type(&ty);
+        true
+    }
+}
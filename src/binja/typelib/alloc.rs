@@ -0,0 +1,22 @@
+use crate::binja::typelib::TypeLibrary;
+
+// The allocator entry points of C (dlmalloc, as shipped by wasi-libc and Emscripten)
+// and of Rust, whose global allocator (dlmalloc or wee_alloc) is reached through the
+// `__rust_*` shims. These are defined in the module rather than imported.
+pub(crate) const ALLOC: TypeLibrary = TypeLibrary {
+    name: "allocator",
+    modules: &[],
+    typedefs: &[("size_t", 4, false)],
+    structs: &[],
+    enums: &[],
+    prototypes: &[
+        "void* malloc(size_t size)",
+        "void free(void* ptr)",
+        "void* calloc(size_t count, size_t size)",
+        "void* realloc(void* ptr, size_t size)",
+        "void* __rust_alloc(size_t size, size_t align)",
+        "void __rust_dealloc(void* ptr, size_t size, size_t align)",
+        "void* __rust_realloc(void* ptr, size_t old_size, size_t align, size_t new_size)",
+        "void* __rust_alloc_zeroed(size_t size, size_t align)",
+    ],
+};
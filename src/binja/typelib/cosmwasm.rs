@@ -0,0 +1,67 @@
+use crate::binja::typelib::TypeLibrary;
+
+// The imports a CosmWasm VM provides under `env`. Variable-length data crosses the
+// boundary as a pointer to a `Region` describing a buffer in the contract's memory.
+// Functions that can fail return a pointer to a `Region` holding an error message,
+// or 0 on success.
+pub(crate) const ENV: TypeLibrary = TypeLibrary {
+    name: "CosmWasm",
+    modules: &["env"],
+    typedefs: &[],
+    structs: &[("Region", &["u32 offset", "u32 capacity", "u32 length"])],
+    enums: &[],
+    prototypes: &[
+        "Region* db_read(Region* key)",
+        "void db_write(Region* key, Region* value)",
+        "void db_remove(Region* key)",
+        "u32 db_scan(Region* start, Region* end, i32 order)",
+        "Region* db_next(u32 iterator_id)",
+        "Region* db_next_key(u32 iterator_id)",
+        "Region* db_next_value(u32 iterator_id)",
+        "Region* addr_validate(Region* source)",
+        "Region* addr_canonicalize(Region* source, Region* destination)",
+        "Region* addr_humanize(Region* source, Region* destination)",
+        "u32 secp256k1_verify(Region* message_hash, Region* signature, Region* public_key)",
+        "u64 secp256k1_recover_pubkey(Region* message_hash, Region* signature, u32 recovery_param)",
+        "u32 secp256r1_verify(Region* message_hash, Region* signature, Region* public_key)",
+        "u64 secp256r1_recover_pubkey(Region* message_hash, Region* signature, u32 recovery_param)",
+        "u32 ed25519_verify(Region* message, Region* signature, Region* public_key)",
+        "u32 ed25519_batch_verify(Region* messages, Region* signatures, Region* public_keys)",
+        "u32 bls12_381_aggregate_g1(Region* g1s, Region* out)",
+        "u32 bls12_381_aggregate_g2(Region* g2s, Region* out)",
+        "u32 bls12_381_pairing_equality(Region* ps, Region* qs, Region* r, Region* s)",
+        "u32 bls12_381_hash_to_g1(u32 hash_function, Region* msg, Region* dst, Region* out)",
+        "u32 bls12_381_hash_to_g2(u32 hash_function, Region* msg, Region* dst, Region* out)",
+        "void debug(Region* message)",
+        "Region* query_chain(Region* request)",
+        "void abort(Region* message)",
+    ],
+};
+
+// The functions a contract exports for the VM to call. Entry points take the JSON
+// environment, message info and message as `Region`s, and return a `Region` holding
+// the JSON result.
+pub(crate) const EXPORTS: TypeLibrary = TypeLibrary {
+    name: "CosmWasm",
+    modules: &[],
+    typedefs: &[],
+    structs: ENV.structs,
+    enums: &[],
+    prototypes: &[
+        "Region* instantiate(Region* env, Region* info, Region* msg)",
+        "Region* execute(Region* env, Region* info, Region* msg)",
+        "Region* query(Region* env, Region* msg)",
+        "Region* migrate(Region* env, Region* msg)",
+        "Region* sudo(Region* env, Region* msg)",
+        "Region* reply(Region* env, Region* msg)",
+        "Region* ibc_channel_open(Region* env, Region* msg)",
+        "Region* ibc_channel_connect(Region* env, Region* msg)",
+        "Region* ibc_channel_close(Region* env, Region* msg)",
+        "Region* ibc_packet_receive(Region* env, Region* msg)",
+        "Region* ibc_packet_ack(Region* env, Region* msg)",
+        "Region* ibc_packet_timeout(Region* env, Region* msg)",
+        "Region* allocate(u32 size)",
+        "void deallocate(Region* region)",
+        "void interface_version_8()",
+    ],
+};
@@ -0,0 +1,144 @@
+use crate::binja::typelib::TypeLibrary;
+
+// The JS library functions and syscalls an Emscripten runtime provides under `env`.
+// Syscalls that take variadic arguments receive a pointer to them.
+pub(crate) const ENV: TypeLibrary = TypeLibrary {
+    name: "Emscripten",
+    modules: &["env"],
+    typedefs: &[("time_t", 8, true), ("size_t", 4, false)],
+    structs: &[],
+    enums: &[],
+    prototypes: &[
+        "void emscripten_memcpy_js(void* dest, void* src, size_t n)",
+        "void emscripten_memcpy_big(void* dest, void* src, size_t n)",
+        "i32 emscripten_resize_heap(size_t requested_size)",
+        "size_t emscripten_get_heap_max()",
+        "f64 emscripten_date_now()",
+        "f64 emscripten_get_now()",
+        "i32 _emscripten_get_now_is_monotonic()",
+        "i32 emscripten_asm_const_int(char* code, char* sig, void* args)",
+        "f64 emscripten_asm_const_double(char* code, char* sig, void* args)",
+        "void emscripten_console_log(char* str)",
+        "void emscripten_console_warn(char* str)",
+        "void emscripten_console_error(char* str)",
+        "void emscripten_run_script(char* script)",
+        "i32 emscripten_run_script_int(char* script)",
+        "void emscripten_set_main_loop(void* func, i32 fps, i32 simulate_infinite_loop)",
+        "void emscripten_cancel_main_loop()",
+        "void _emscripten_throw_longjmp()",
+        "void _abort_js()",
+        "void abort()",
+        "void exit(i32 status)",
+        "void __assert_fail(char* assertion, char* file, u32 line, char* function)",
+        "void __cxa_throw(void* thrown_exception, void* tinfo, void* dest)",
+        "void* __cxa_begin_catch(void* exception)",
+        "void __cxa_end_catch()",
+        "void _tzset_js(i32* timezone, i32* daylight, char* std_name, char* dst_name)",
+        "void _localtime_js(time_t time, void* tm)",
+        "void _gmtime_js(time_t time, void* tm)",
+        "time_t _mktime_js(void* tm)",
+        "i32 __syscall_openat(i32 dirfd, char* path, i32 flags, void* varargs)",
+        "i32 __syscall_ioctl(i32 fd, i32 op, void* varargs)",
+        "i32 __syscall_fcntl64(i32 fd, i32 cmd, void* varargs)",
+        "i32 __syscall_getcwd(char* buf, size_t size)",
+        "i32 __syscall_chdir(char* path)",
+        "i32 __syscall_stat64(char* path, void* buf)",
+        "i32 __syscall_lstat64(char* path, void* buf)",
+        "i32 __syscall_fstat64(i32 fd, void* buf)",
+        "i32 __syscall_newfstatat(i32 dirfd, char* path, void* buf, i32 flags)",
+        "i32 __syscall_getdents64(i32 fd, void* dirp, size_t count)",
+        "i32 __syscall_mkdirat(i32 dirfd, char* path, i32 mode)",
+        "i32 __syscall_rmdir(char* path)",
+        "i32 __syscall_unlinkat(i32 dirfd, char* path, i32 flags)",
+        "i32 __syscall_renameat(i32 olddirfd, char* oldpath, i32 newdirfd, char* newpath)",
+        "i32 __syscall_readlinkat(i32 dirfd, char* path, char* buf, size_t bufsize)",
+        "i32 __syscall_faccessat(i32 dirfd, char* path, i32 amode, i32 flags)",
+        "i32 __syscall_dup(i32 fd)",
+        "i32 __syscall_dup3(i32 fd, i32 newfd, i32 flags)",
+        "i32 __syscall_ftruncate64(i32 fd, i64 length)",
+        "i32 __syscall_poll(void* fds, i32 nfds, i32 timeout)",
+        "i32 __syscall_socket(i32 domain, i32 type, i32 protocol, i32 unused0, i32 unused1, i32 unused2)",
+        "i32 __syscall_connect(i32 fd, void* addr, size_t len, i32 unused0, i32 unused1, i32 unused2)",
+    ],
+};
+
+// Linux i386 numbers of the syscalls Emscripten implements in JS, by the name of
+// their `__syscall_*` import. musl, which Emscripten's libc is based on, uses the
+// i386 numbering for the wasm32 target.
+pub(crate) const SYSCALL_NUMBERS: &[(&str, u32)] = &[
+    ("chdir", 12),
+    ("chmod", 15),
+    ("getpid", 20),
+    ("sync", 36),
+    ("kill", 37),
+    ("rmdir", 40),
+    ("dup", 41),
+    ("pipe", 42),
+    ("ioctl", 54),
+    ("setpgid", 57),
+    ("umask", 60),
+    ("setsid", 66),
+    ("getrusage", 77),
+    ("fchmod", 94),
+    ("wait4", 114),
+    ("uname", 122),
+    ("mprotect", 125),
+    ("getpgid", 132),
+    ("fchdir", 133),
+    ("_newselect", 142),
+    ("msync", 144),
+    ("fdatasync", 148),
+    ("poll", 168),
+    ("getcwd", 183),
+    ("ugetrlimit", 191),
+    ("truncate64", 193),
+    ("ftruncate64", 194),
+    ("stat64", 195),
+    ("lstat64", 196),
+    ("fstat64", 197),
+    ("getuid32", 199),
+    ("getgid32", 200),
+    ("geteuid32", 201),
+    ("getegid32", 202),
+    ("fchown32", 207),
+    ("mincore", 218),
+    ("madvise", 219),
+    ("getdents64", 220),
+    ("fcntl64", 221),
+    ("statfs64", 268),
+    ("fstatfs64", 269),
+    ("fadvise64", 272),
+    ("openat", 295),
+    ("mkdirat", 296),
+    ("mknodat", 297),
+    ("fchownat", 298),
+    ("newfstatat", 300),
+    ("unlinkat", 301),
+    ("renameat", 302),
+    ("linkat", 303),
+    ("symlinkat", 304),
+    ("readlinkat", 305),
+    ("fchmodat", 306),
+    ("faccessat", 307),
+    ("pselect6", 308),
+    ("utimensat", 320),
+    ("fallocate", 324),
+    ("dup3", 330),
+    ("pipe2", 331),
+    ("prlimit64", 340),
+    ("socket", 359),
+    ("socketpair", 360),
+    ("bind", 361),
+    ("connect", 362),
+    ("listen", 363),
+    ("accept4", 364),
+    ("getsockopt", 365),
+    ("setsockopt", 366),
+    ("getsockname", 367),
+    ("getpeername", 368),
+    ("sendto", 369),
+    ("sendmsg", 370),
+    ("recvfrom", 371),
+    ("recvmsg", 372),
+    ("shutdown", 373),
+];
@@ -0,0 +1,58 @@
+use crate::binja::typelib::TypeLibrary;
+
+// The imports that `wasm_exec.js` provides to GOOS=js modules. Each one takes the Go
+// stack pointer and reads its arguments from, and writes its results to, the Go
+// stack, so the prototypes only have the stack pointer.
+pub(crate) const JS: TypeLibrary = TypeLibrary {
+    name: "Go",
+    modules: &["gojs", "go"],
+    typedefs: &[],
+    structs: &[],
+    enums: &[],
+    prototypes: &[],
+};
+
+// Go signatures of the `wasm_exec.js` imports. Arguments and results are laid out on
+// the Go stack from SP+8, each aligned to its size.
+pub(crate) const JS_SIGNATURES: &[(&str, &str)] = &[
+    ("runtime.wasmExit", "func(code int32)"),
+    (
+        "runtime.wasmWrite",
+        "func(fd uintptr, p unsafe.Pointer, n int32)",
+    ),
+    ("runtime.resetMemoryDataView", "func()"),
+    ("runtime.nanotime1", "func() int64"),
+    ("runtime.walltime", "func() (sec int64, nsec int32)"),
+    ("runtime.scheduleTimeoutEvent", "func(delay int64) int32"),
+    ("runtime.clearTimeoutEvent", "func(id int32)"),
+    ("runtime.getRandomData", "func(r []byte)"),
+    ("syscall/js.finalizeRef", "func(v ref)"),
+    ("syscall/js.stringVal", "func(value string) ref"),
+    ("syscall/js.valueGet", "func(v ref, p string) ref"),
+    ("syscall/js.valueSet", "func(v ref, p string, x ref)"),
+    ("syscall/js.valueDelete", "func(v ref, p string)"),
+    ("syscall/js.valueIndex", "func(v ref, i int) ref"),
+    ("syscall/js.valueSetIndex", "func(v ref, i int, x ref)"),
+    (
+        "syscall/js.valueCall",
+        "func(v ref, m string, args []ref) (ref, bool)",
+    ),
+    (
+        "syscall/js.valueInvoke",
+        "func(v ref, args []ref) (ref, bool)",
+    ),
+    ("syscall/js.valueNew", "func(v ref, args []ref) (ref, bool)"),
+    ("syscall/js.valueLength", "func(v ref) int"),
+    ("syscall/js.valuePrepareString", "func(v ref) (ref, int)"),
+    ("syscall/js.valueLoadString", "func(v ref, b []byte)"),
+    ("syscall/js.valueInstanceOf", "func(v ref, t ref) bool"),
+    (
+        "syscall/js.copyBytesToGo",
+        "func(dst []byte, src ref) (int, bool)",
+    ),
+    (
+        "syscall/js.copyBytesToJS",
+        "func(dst ref, src []byte) (int, bool)",
+    ),
+    ("debug", "func(value int64)"),
+];
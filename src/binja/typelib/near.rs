@@ -0,0 +1,75 @@
+use crate::binja::typelib::TypeLibrary;
+
+// Host functions of the NEAR runtime, as declared by near-sdk. Every parameter is a
+// 64-bit value, including pointers into linear memory (`*_ptr`). Data of unknown
+// length is returned by writing it to a register, whose contents the contract then
+// copies into memory with `read_register`.
+pub(crate) const ENV: TypeLibrary = TypeLibrary {
+    name: "NEAR",
+    modules: &["env"],
+    typedefs: &[
+        ("register_id_t", 8, false),
+        ("promise_index_t", 8, false),
+    ],
+    structs: &[],
+    enums: &[],
+    prototypes: &[
+        "void read_register(register_id_t register_id, u64 ptr)",
+        "u64 register_len(register_id_t register_id)",
+        "void write_register(register_id_t register_id, u64 data_len, u64 data_ptr)",
+        "void current_account_id(register_id_t register_id)",
+        "void signer_account_id(register_id_t register_id)",
+        "void signer_account_pk(register_id_t register_id)",
+        "void predecessor_account_id(register_id_t register_id)",
+        "void input(register_id_t register_id)",
+        "u64 block_index()",
+        "u64 block_timestamp()",
+        "u64 epoch_height()",
+        "u64 storage_usage()",
+        "void account_balance(u64 balance_ptr)",
+        "void account_locked_balance(u64 balance_ptr)",
+        "void attached_deposit(u64 balance_ptr)",
+        "u64 prepaid_gas()",
+        "u64 used_gas()",
+        "void random_seed(register_id_t register_id)",
+        "void sha256(u64 value_len, u64 value_ptr, register_id_t register_id)",
+        "void keccak256(u64 value_len, u64 value_ptr, register_id_t register_id)",
+        "void keccak512(u64 value_len, u64 value_ptr, register_id_t register_id)",
+        "void ripemd160(u64 value_len, u64 value_ptr, register_id_t register_id)",
+        "u64 ecrecover(u64 hash_len, u64 hash_ptr, u64 sig_len, u64 sig_ptr, u64 v, u64 malleability_flag, register_id_t register_id)",
+        "u64 ed25519_verify(u64 sig_len, u64 sig_ptr, u64 msg_len, u64 msg_ptr, u64 pub_key_len, u64 pub_key_ptr)",
+        "void alt_bn128_g1_multiexp(u64 value_len, u64 value_ptr, register_id_t register_id)",
+        "void alt_bn128_g1_sum(u64 value_len, u64 value_ptr, register_id_t register_id)",
+        "u64 alt_bn128_pairing_check(u64 value_len, u64 value_ptr)",
+        "void value_return(u64 value_len, u64 value_ptr)",
+        "void panic()",
+        "void panic_utf8(u64 len, u64 ptr)",
+        "void log_utf8(u64 len, u64 ptr)",
+        "void log_utf16(u64 len, u64 ptr)",
+        "void abort(u32 msg_ptr, u32 filename_ptr, u32 line, u32 col)",
+        "promise_index_t promise_create(u64 account_id_len, u64 account_id_ptr, u64 function_name_len, u64 function_name_ptr, u64 arguments_len, u64 arguments_ptr, u64 amount_ptr, u64 gas)",
+        "promise_index_t promise_then(promise_index_t promise_index, u64 account_id_len, u64 account_id_ptr, u64 function_name_len, u64 function_name_ptr, u64 arguments_len, u64 arguments_ptr, u64 amount_ptr, u64 gas)",
+        "promise_index_t promise_and(u64 promise_idx_ptr, u64 promise_idx_count)",
+        "promise_index_t promise_batch_create(u64 account_id_len, u64 account_id_ptr)",
+        "promise_index_t promise_batch_then(promise_index_t promise_index, u64 account_id_len, u64 account_id_ptr)",
+        "void promise_batch_action_create_account(promise_index_t promise_index)",
+        "void promise_batch_action_deploy_contract(promise_index_t promise_index, u64 code_len, u64 code_ptr)",
+        "void promise_batch_action_function_call(promise_index_t promise_index, u64 function_name_len, u64 function_name_ptr, u64 arguments_len, u64 arguments_ptr, u64 amount_ptr, u64 gas)",
+        "void promise_batch_action_function_call_weight(promise_index_t promise_index, u64 function_name_len, u64 function_name_ptr, u64 arguments_len, u64 arguments_ptr, u64 amount_ptr, u64 gas, u64 gas_weight)",
+        "void promise_batch_action_transfer(promise_index_t promise_index, u64 amount_ptr)",
+        "void promise_batch_action_stake(promise_index_t promise_index, u64 amount_ptr, u64 public_key_len, u64 public_key_ptr)",
+        "void promise_batch_action_add_key_with_full_access(promise_index_t promise_index, u64 public_key_len, u64 public_key_ptr, u64 nonce)",
+        "void promise_batch_action_add_key_with_function_call(promise_index_t promise_index, u64 public_key_len, u64 public_key_ptr, u64 nonce, u64 allowance_ptr, u64 receiver_id_len, u64 receiver_id_ptr, u64 function_names_len, u64 function_names_ptr)",
+        "void promise_batch_action_delete_key(promise_index_t promise_index, u64 public_key_len, u64 public_key_ptr)",
+        "void promise_batch_action_delete_account(promise_index_t promise_index, u64 beneficiary_id_len, u64 beneficiary_id_ptr)",
+        "u64 promise_results_count()",
+        "u64 promise_result(u64 result_idx, register_id_t register_id)",
+        "void promise_return(promise_index_t promise_index)",
+        "u64 storage_write(u64 key_len, u64 key_ptr, u64 value_len, u64 value_ptr, register_id_t register_id)",
+        "u64 storage_read(u64 key_len, u64 key_ptr, register_id_t register_id)",
+        "u64 storage_remove(u64 key_len, u64 key_ptr, register_id_t register_id)",
+        "u64 storage_has_key(u64 key_len, u64 key_ptr)",
+        "void validator_stake(u64 account_id_len, u64 account_id_ptr, u64 stake_ptr)",
+        "void validator_total_stake(u64 stake_ptr)",
+    ],
+};
@@ -0,0 +1,92 @@
+use crate::binja::typelib::TypeLibrary;
+
+// Host functions of Substrate's pallet-contracts, which ink! contracts import. Each
+// version of a function lives in its own module, with the newest in the highest
+// `sealN`. Older runtimes export every name with a `seal_` prefix as well, so names
+// here are without it (except for `seal_return`, which has no unprefixed alias).
+// Output buffers are passed as a pointer and a pointer to their length, which the
+// host overwrites with the number of bytes written.
+const TYPEDEFS: &[(&str, usize, bool)] = &[("ReturnCode", 4, false)];
+
+pub(crate) const SEAL0: TypeLibrary = TypeLibrary {
+    name: "pallet-contracts seal0",
+    modules: &["seal0"],
+    typedefs: TYPEDEFS,
+    structs: &[],
+    enums: &[],
+    prototypes: &[
+        "void input(u8* buf, u32* buf_len)",
+        "void seal_return(u32 flags, u8* data, u32 data_len)",
+        "void caller(u8* out, u32* out_len)",
+        "u32 caller_is_origin()",
+        "u32 caller_is_root()",
+        "void address(u8* out, u32* out_len)",
+        "void balance(u8* out, u32* out_len)",
+        "void value_transferred(u8* out, u32* out_len)",
+        "void now(u8* out, u32* out_len)",
+        "void block_number(u8* out, u32* out_len)",
+        "void minimum_balance(u8* out, u32* out_len)",
+        "void gas_left(u8* out, u32* out_len)",
+        "void weight_to_fee(u64 gas, u8* out, u32* out_len)",
+        "void set_storage(u8* key, u8* value, u32 value_len)",
+        "ReturnCode get_storage(u8* key, u8* out, u32* out_len)",
+        "void clear_storage(u8* key)",
+        "u32 contains_storage(u8* key, u32 key_len)",
+        "ReturnCode take_storage(u8* key, u32 key_len, u8* out, u32* out_len)",
+        "ReturnCode transfer(u8* account, u32 account_len, u8* value, u32 value_len)",
+        "ReturnCode call(u8* callee, u32 callee_len, u64 gas, u8* value, u32 value_len, u8* input_data, u32 input_data_len, u8* output, u32* output_len)",
+        "ReturnCode delegate_call(u32 flags, u8* code_hash, u8* input_data, u32 input_data_len, u8* output, u32* output_len)",
+        "ReturnCode instantiate(u8* code_hash, u32 code_hash_len, u64 gas, u8* value, u32 value_len, u8* input_data, u32 input_data_len, u8* address, u32* address_len, u8* output, u32* output_len, u8* salt, u32 salt_len)",
+        "void terminate(u8* beneficiary, u32 beneficiary_len)",
+        "void deposit_event(u8* topics, u32 topics_len, u8* data, u32 data_len)",
+        "void hash_sha2_256(u8* input, u32 input_len, u8* output)",
+        "void hash_keccak_256(u8* input, u32 input_len, u8* output)",
+        "void hash_blake2_256(u8* input, u32 input_len, u8* output)",
+        "void hash_blake2_128(u8* input, u32 input_len, u8* output)",
+        "u32 call_chain_extension(u32 id, u8* input, u32 input_len, u8* output, u32* output_len)",
+        "ReturnCode debug_message(u8* str, u32 str_len)",
+        "ReturnCode call_runtime(u8* call, u32 call_len)",
+        "ReturnCode ecdsa_recover(u8* signature, u8* message_hash, u8* output)",
+        "ReturnCode ecdsa_to_eth_address(u8* key, u8* output)",
+        "ReturnCode sr25519_verify(u8* signature, u8* pub_key, u32 message_len, u8* message)",
+        "u32 is_contract(u8* account)",
+        "ReturnCode code_hash(u8* account, u8* out, u32* out_len)",
+        "void own_code_hash(u8* out, u32* out_len)",
+        "ReturnCode set_code_hash(u8* code_hash)",
+        "u32 reentrance_count()",
+        "u32 account_reentrance_count(u8* account)",
+        "u64 instantiation_nonce()",
+    ],
+};
+
+pub(crate) const SEAL1: TypeLibrary = TypeLibrary {
+    name: "pallet-contracts seal1",
+    modules: &["seal1"],
+    typedefs: TYPEDEFS,
+    structs: &[],
+    enums: &[],
+    prototypes: &[
+        "u32 set_storage(u8* key, u8* value, u32 value_len)",
+        "ReturnCode get_storage(u8* key, u32 key_len, u8* out, u32* out_len)",
+        "u32 clear_storage(u8* key, u32 key_len)",
+        "u32 contains_storage(u8* key, u32 key_len)",
+        "void gas_left(u8* out, u32* out_len)",
+        "void weight_to_fee(u64 ref_time_limit, u64 proof_size_limit, u8* out, u32* out_len)",
+        "ReturnCode call(u32 flags, u8* callee, u64 gas, u8* value, u8* input_data, u32 input_data_len, u8* output, u32* output_len)",
+        "ReturnCode instantiate(u8* code_hash, u64 gas, u8* value, u8* input, u32 input_len, u8* address, u32* address_len, u8* output, u32* output_len, u8* salt, u32 salt_len)",
+        "void terminate(u8* beneficiary)",
+    ],
+};
+
+pub(crate) const SEAL2: TypeLibrary = TypeLibrary {
+    name: "pallet-contracts seal2",
+    modules: &["seal2"],
+    typedefs: TYPEDEFS,
+    structs: &[],
+    enums: &[],
+    prototypes: &[
+        "u32 set_storage(u8* key, u32 key_len, u8* value, u32 value_len)",
+        "ReturnCode call(u32 flags, u8* callee, u64 ref_time_limit, u64 proof_size_limit, u8* deposit, u8* value, u8* input_data, u32 input_data_len, u8* output, u32* output_len)",
+        "ReturnCode instantiate(u8* code_hash, u64 ref_time_limit, u64 proof_size_limit, u8* deposit, u8* value, u8* input, u32 input_len, u8* address, u32* address_len, u8* output, u32* output_len, u8* salt, u32 salt_len)",
+    ],
+};
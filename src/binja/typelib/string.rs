@@ -0,0 +1,16 @@
+use crate::binja::typelib::TypeLibrary;
+
+// The memory functions of the C library, which compilers also call for copies and
+// fills of aggregates. Like the allocator, they are defined in the module.
+pub(crate) const STRING: TypeLibrary = TypeLibrary {
+    name: "string",
+    modules: &[],
+    typedefs: &[("size_t", 4, false)],
+    structs: &[],
+    enums: &[],
+    prototypes: &[
+        "void* memcpy(void* dest, void* src, size_t n)",
+        "void* memmove(void* dest, void* src, size_t n)",
+        "void* memset(void* dest, i32 c, size_t n)",
+    ],
+};
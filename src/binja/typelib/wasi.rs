@@ -0,0 +1,265 @@
+use crate::binja::typelib::TypeLibrary;
+
+// WASI preview 1, as implemented by wasi-libc and the Rust standard library. Opaque
+// structures (iovecs, stats, subscriptions) are passed as `void*`.
+pub(crate) const PREVIEW1: TypeLibrary = TypeLibrary {
+    name: "WASI preview1",
+    modules: &["wasi_snapshot_preview1", "wasi_unstable"],
+    typedefs: &[
+        ("__wasi_fd_t", 4, false),
+        ("__wasi_size_t", 4, false),
+        ("__wasi_filesize_t", 8, false),
+        ("__wasi_filedelta_t", 8, true),
+        ("__wasi_timestamp_t", 8, false),
+        ("__wasi_exitcode_t", 4, false),
+    ],
+    structs: &[],
+    // Narrower WASI types (`whence`, `oflags`, ...) are passed as i32, so their
+    // enumerations are 32-bit.
+    enums: &[
+        (
+            "__wasi_errno_t",
+            4,
+            &[
+                ("ERRNO_SUCCESS", 0),
+                ("ERRNO_2BIG", 1),
+                ("ERRNO_ACCES", 2),
+                ("ERRNO_ADDRINUSE", 3),
+                ("ERRNO_ADDRNOTAVAIL", 4),
+                ("ERRNO_AFNOSUPPORT", 5),
+                ("ERRNO_AGAIN", 6),
+                ("ERRNO_ALREADY", 7),
+                ("ERRNO_BADF", 8),
+                ("ERRNO_BADMSG", 9),
+                ("ERRNO_BUSY", 10),
+                ("ERRNO_CANCELED", 11),
+                ("ERRNO_CHILD", 12),
+                ("ERRNO_CONNABORTED", 13),
+                ("ERRNO_CONNREFUSED", 14),
+                ("ERRNO_CONNRESET", 15),
+                ("ERRNO_DEADLK", 16),
+                ("ERRNO_DESTADDRREQ", 17),
+                ("ERRNO_DOM", 18),
+                ("ERRNO_DQUOT", 19),
+                ("ERRNO_EXIST", 20),
+                ("ERRNO_FAULT", 21),
+                ("ERRNO_FBIG", 22),
+                ("ERRNO_HOSTUNREACH", 23),
+                ("ERRNO_IDRM", 24),
+                ("ERRNO_ILSEQ", 25),
+                ("ERRNO_INPROGRESS", 26),
+                ("ERRNO_INTR", 27),
+                ("ERRNO_INVAL", 28),
+                ("ERRNO_IO", 29),
+                ("ERRNO_ISCONN", 30),
+                ("ERRNO_ISDIR", 31),
+                ("ERRNO_LOOP", 32),
+                ("ERRNO_MFILE", 33),
+                ("ERRNO_MLINK", 34),
+                ("ERRNO_MSGSIZE", 35),
+                ("ERRNO_MULTIHOP", 36),
+                ("ERRNO_NAMETOOLONG", 37),
+                ("ERRNO_NETDOWN", 38),
+                ("ERRNO_NETRESET", 39),
+                ("ERRNO_NETUNREACH", 40),
+                ("ERRNO_NFILE", 41),
+                ("ERRNO_NOBUFS", 42),
+                ("ERRNO_NODEV", 43),
+                ("ERRNO_NOENT", 44),
+                ("ERRNO_NOEXEC", 45),
+                ("ERRNO_NOLCK", 46),
+                ("ERRNO_NOLINK", 47),
+                ("ERRNO_NOMEM", 48),
+                ("ERRNO_NOMSG", 49),
+                ("ERRNO_NOPROTOOPT", 50),
+                ("ERRNO_NOSPC", 51),
+                ("ERRNO_NOSYS", 52),
+                ("ERRNO_NOTCONN", 53),
+                ("ERRNO_NOTDIR", 54),
+                ("ERRNO_NOTEMPTY", 55),
+                ("ERRNO_NOTRECOVERABLE", 56),
+                ("ERRNO_NOTSOCK", 57),
+                ("ERRNO_NOTSUP", 58),
+                ("ERRNO_NOTTY", 59),
+                ("ERRNO_NXIO", 60),
+                ("ERRNO_OVERFLOW", 61),
+                ("ERRNO_OWNERDEAD", 62),
+                ("ERRNO_PERM", 63),
+                ("ERRNO_PIPE", 64),
+                ("ERRNO_PROTO", 65),
+                ("ERRNO_PROTONOSUPPORT", 66),
+                ("ERRNO_PROTOTYPE", 67),
+                ("ERRNO_RANGE", 68),
+                ("ERRNO_ROFS", 69),
+                ("ERRNO_SPIPE", 70),
+                ("ERRNO_SRCH", 71),
+                ("ERRNO_STALE", 72),
+                ("ERRNO_TIMEDOUT", 73),
+                ("ERRNO_TXTBSY", 74),
+                ("ERRNO_XDEV", 75),
+                ("ERRNO_NOTCAPABLE", 76),
+            ],
+        ),
+        (
+            "__wasi_rights_t",
+            8,
+            &[
+                ("RIGHTS_FD_DATASYNC", 1 << 0),
+                ("RIGHTS_FD_READ", 1 << 1),
+                ("RIGHTS_FD_SEEK", 1 << 2),
+                ("RIGHTS_FD_FDSTAT_SET_FLAGS", 1 << 3),
+                ("RIGHTS_FD_SYNC", 1 << 4),
+                ("RIGHTS_FD_TELL", 1 << 5),
+                ("RIGHTS_FD_WRITE", 1 << 6),
+                ("RIGHTS_FD_ADVISE", 1 << 7),
+                ("RIGHTS_FD_ALLOCATE", 1 << 8),
+                ("RIGHTS_PATH_CREATE_DIRECTORY", 1 << 9),
+                ("RIGHTS_PATH_CREATE_FILE", 1 << 10),
+                ("RIGHTS_PATH_LINK_SOURCE", 1 << 11),
+                ("RIGHTS_PATH_LINK_TARGET", 1 << 12),
+                ("RIGHTS_PATH_OPEN", 1 << 13),
+                ("RIGHTS_FD_READDIR", 1 << 14),
+                ("RIGHTS_PATH_READLINK", 1 << 15),
+                ("RIGHTS_PATH_RENAME_SOURCE", 1 << 16),
+                ("RIGHTS_PATH_RENAME_TARGET", 1 << 17),
+                ("RIGHTS_PATH_FILESTAT_GET", 1 << 18),
+                ("RIGHTS_PATH_FILESTAT_SET_SIZE", 1 << 19),
+                ("RIGHTS_PATH_FILESTAT_SET_TIMES", 1 << 20),
+                ("RIGHTS_FD_FILESTAT_GET", 1 << 21),
+                ("RIGHTS_FD_FILESTAT_SET_SIZE", 1 << 22),
+                ("RIGHTS_FD_FILESTAT_SET_TIMES", 1 << 23),
+                ("RIGHTS_PATH_SYMLINK", 1 << 24),
+                ("RIGHTS_PATH_REMOVE_DIRECTORY", 1 << 25),
+                ("RIGHTS_PATH_UNLINK_FILE", 1 << 26),
+                ("RIGHTS_POLL_FD_READWRITE", 1 << 27),
+                ("RIGHTS_SOCK_SHUTDOWN", 1 << 28),
+                ("RIGHTS_SOCK_ACCEPT", 1 << 29),
+            ],
+        ),
+        (
+            "__wasi_clockid_t",
+            4,
+            &[
+                ("CLOCKID_REALTIME", 0),
+                ("CLOCKID_MONOTONIC", 1),
+                ("CLOCKID_PROCESS_CPUTIME_ID", 2),
+                ("CLOCKID_THREAD_CPUTIME_ID", 3),
+            ],
+        ),
+        (
+            "__wasi_advice_t",
+            4,
+            &[
+                ("ADVICE_NORMAL", 0),
+                ("ADVICE_SEQUENTIAL", 1),
+                ("ADVICE_RANDOM", 2),
+                ("ADVICE_WILLNEED", 3),
+                ("ADVICE_DONTNEED", 4),
+                ("ADVICE_NOREUSE", 5),
+            ],
+        ),
+        (
+            "__wasi_whence_t",
+            4,
+            &[
+                ("WHENCE_SET", 0),
+                ("WHENCE_CUR", 1),
+                ("WHENCE_END", 2),
+            ],
+        ),
+        (
+            "__wasi_fdflags_t",
+            4,
+            &[
+                ("FDFLAGS_APPEND", 1),
+                ("FDFLAGS_DSYNC", 2),
+                ("FDFLAGS_NONBLOCK", 4),
+                ("FDFLAGS_RSYNC", 8),
+                ("FDFLAGS_SYNC", 16),
+            ],
+        ),
+        (
+            "__wasi_fstflags_t",
+            4,
+            &[
+                ("FSTFLAGS_ATIM", 1),
+                ("FSTFLAGS_ATIM_NOW", 2),
+                ("FSTFLAGS_MTIM", 4),
+                ("FSTFLAGS_MTIM_NOW", 8),
+            ],
+        ),
+        (
+            "__wasi_lookupflags_t",
+            4,
+            &[
+                ("LOOKUPFLAGS_SYMLINK_FOLLOW", 1),
+            ],
+        ),
+        (
+            "__wasi_oflags_t",
+            4,
+            &[
+                ("OFLAGS_CREAT", 1),
+                ("OFLAGS_DIRECTORY", 2),
+                ("OFLAGS_EXCL", 4),
+                ("OFLAGS_TRUNC", 8),
+            ],
+        ),
+        (
+            "__wasi_sdflags_t",
+            4,
+            &[
+                ("SDFLAGS_RD", 1),
+                ("SDFLAGS_WR", 2),
+            ],
+        ),
+    ],
+    prototypes: &[
+        "__wasi_errno_t args_get(u8** argv, u8* argv_buf)",
+        "__wasi_errno_t args_sizes_get(__wasi_size_t* argc, __wasi_size_t* argv_buf_size)",
+        "__wasi_errno_t environ_get(u8** environ, u8* environ_buf)",
+        "__wasi_errno_t environ_sizes_get(__wasi_size_t* environc, __wasi_size_t* environ_buf_size)",
+        "__wasi_errno_t clock_res_get(__wasi_clockid_t id, __wasi_timestamp_t* resolution)",
+        "__wasi_errno_t clock_time_get(__wasi_clockid_t id, __wasi_timestamp_t precision, __wasi_timestamp_t* time)",
+        "__wasi_errno_t fd_advise(__wasi_fd_t fd, __wasi_filesize_t offset, __wasi_filesize_t len, __wasi_advice_t advice)",
+        "__wasi_errno_t fd_allocate(__wasi_fd_t fd, __wasi_filesize_t offset, __wasi_filesize_t len)",
+        "__wasi_errno_t fd_close(__wasi_fd_t fd)",
+        "__wasi_errno_t fd_datasync(__wasi_fd_t fd)",
+        "__wasi_errno_t fd_fdstat_get(__wasi_fd_t fd, void* stat)",
+        "__wasi_errno_t fd_fdstat_set_flags(__wasi_fd_t fd, __wasi_fdflags_t flags)",
+        "__wasi_errno_t fd_fdstat_set_rights(__wasi_fd_t fd, __wasi_rights_t fs_rights_base, __wasi_rights_t fs_rights_inheriting)",
+        "__wasi_errno_t fd_filestat_get(__wasi_fd_t fd, void* buf)",
+        "__wasi_errno_t fd_filestat_set_size(__wasi_fd_t fd, __wasi_filesize_t size)",
+        "__wasi_errno_t fd_filestat_set_times(__wasi_fd_t fd, __wasi_timestamp_t atim, __wasi_timestamp_t mtim, __wasi_fstflags_t fst_flags)",
+        "__wasi_errno_t fd_pread(__wasi_fd_t fd, void* iovs, __wasi_size_t iovs_len, __wasi_filesize_t offset, __wasi_size_t* nread)",
+        "__wasi_errno_t fd_prestat_get(__wasi_fd_t fd, void* buf)",
+        "__wasi_errno_t fd_prestat_dir_name(__wasi_fd_t fd, char* path, __wasi_size_t path_len)",
+        "__wasi_errno_t fd_pwrite(__wasi_fd_t fd, void* iovs, __wasi_size_t iovs_len, __wasi_filesize_t offset, __wasi_size_t* nwritten)",
+        "__wasi_errno_t fd_read(__wasi_fd_t fd, void* iovs, __wasi_size_t iovs_len, __wasi_size_t* nread)",
+        "__wasi_errno_t fd_readdir(__wasi_fd_t fd, u8* buf, __wasi_size_t buf_len, u64 cookie, __wasi_size_t* bufused)",
+        "__wasi_errno_t fd_renumber(__wasi_fd_t fd, __wasi_fd_t to)",
+        "__wasi_errno_t fd_seek(__wasi_fd_t fd, __wasi_filedelta_t offset, __wasi_whence_t whence, __wasi_filesize_t* newoffset)",
+        "__wasi_errno_t fd_sync(__wasi_fd_t fd)",
+        "__wasi_errno_t fd_tell(__wasi_fd_t fd, __wasi_filesize_t* offset)",
+        "__wasi_errno_t fd_write(__wasi_fd_t fd, void* iovs, __wasi_size_t iovs_len, __wasi_size_t* nwritten)",
+        "__wasi_errno_t path_create_directory(__wasi_fd_t fd, char* path, __wasi_size_t path_len)",
+        "__wasi_errno_t path_filestat_get(__wasi_fd_t fd, __wasi_lookupflags_t flags, char* path, __wasi_size_t path_len, void* buf)",
+        "__wasi_errno_t path_filestat_set_times(__wasi_fd_t fd, __wasi_lookupflags_t flags, char* path, __wasi_size_t path_len, __wasi_timestamp_t atim, __wasi_timestamp_t mtim, __wasi_fstflags_t fst_flags)",
+        "__wasi_errno_t path_link(__wasi_fd_t old_fd, __wasi_lookupflags_t old_flags, char* old_path, __wasi_size_t old_path_len, __wasi_fd_t new_fd, char* new_path, __wasi_size_t new_path_len)",
+        "__wasi_errno_t path_open(__wasi_fd_t fd, __wasi_lookupflags_t dirflags, char* path, __wasi_size_t path_len, __wasi_oflags_t oflags, __wasi_rights_t fs_rights_base, __wasi_rights_t fs_rights_inheriting, __wasi_fdflags_t fdflags, __wasi_fd_t* opened_fd)",
+        "__wasi_errno_t path_readlink(__wasi_fd_t fd, char* path, __wasi_size_t path_len, u8* buf, __wasi_size_t buf_len, __wasi_size_t* bufused)",
+        "__wasi_errno_t path_remove_directory(__wasi_fd_t fd, char* path, __wasi_size_t path_len)",
+        "__wasi_errno_t path_rename(__wasi_fd_t fd, char* old_path, __wasi_size_t old_path_len, __wasi_fd_t new_fd, char* new_path, __wasi_size_t new_path_len)",
+        "__wasi_errno_t path_symlink(char* old_path, __wasi_size_t old_path_len, __wasi_fd_t fd, char* new_path, __wasi_size_t new_path_len)",
+        "__wasi_errno_t path_unlink_file(__wasi_fd_t fd, char* path, __wasi_size_t path_len)",
+        "__wasi_errno_t poll_oneoff(void* in, void* out, __wasi_size_t nsubscriptions, __wasi_size_t* nevents)",
+        "void proc_exit(__wasi_exitcode_t rval)",
+        "__wasi_errno_t proc_raise(u32 sig)",
+        "__wasi_errno_t sched_yield()",
+        "__wasi_errno_t random_get(u8* buf, __wasi_size_t buf_len)",
+        "__wasi_errno_t sock_accept(__wasi_fd_t fd, __wasi_fdflags_t flags, __wasi_fd_t* result_fd)",
+        "__wasi_errno_t sock_recv(__wasi_fd_t fd, void* ri_data, __wasi_size_t ri_data_len, u32 ri_flags, __wasi_size_t* ro_datalen, u16* ro_flags)",
+        "__wasi_errno_t sock_send(__wasi_fd_t fd, void* si_data, __wasi_size_t si_data_len, u32 si_flags, __wasi_size_t* so_datalen)",
+        "__wasi_errno_t sock_shutdown(__wasi_fd_t fd, __wasi_sdflags_t how)",
+    ],
+};
@@ -1,16 +1,39 @@
 use crate::binja::parse::module_data::{ModuleData, MODULE_DATA};
-use binaryninja::architecture::{ArchitectureExt, CoreArchitecture};
+use crate::binja::platform::WebAssemblyPlatform;
+use binaryninja::architecture::CoreArchitecture;
 use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
 use binaryninja::custom_binary_view::CustomBinaryView;
 use binaryninja::interaction::{show_message_box, MessageBoxButtonSet, MessageBoxIcon};
 use binaryninja::Endianness;
 use log::error;
-use std::sync::Mutex;
+
+// Where the address of the start function and the width of addresses are kept, in the
+// view's own metadata rather than in `MODULE_DATA`, since binja may query them while
+// the module is being parsed. Reparsing the module updates them there too.
+const ENTRY_POINT_KEY: &str = "wasm.entryPoint";
+const ADDRESS_SIZE_KEY: &str = "wasm.addressSize";
 
 pub struct WebAssemblyView {
     handle: binaryninja::rc::Ref<BinaryView>,
 }
 
+impl WebAssemblyView {
+    pub(crate) fn set_entry_point(&mut self, addr: u64) {
+        self.store_metadata(ENTRY_POINT_KEY, addr, true);
+    }
+
+    // Width of addresses in the view, which is chosen once the module's memories and
+    // layout are known.
+    pub(crate) fn set_address_size(&mut self, address_size: usize) {
+        self.store_metadata(ADDRESS_SIZE_KEY, address_size as u64, true);
+    }
+
+    fn metadata_integer(&self, key: &str) -> Option<u64> {
+        self.query_metadata(key)
+            .and_then(|metadata| metadata.get_unsigned_integer())
+    }
+}
+
 impl AsRef<BinaryView> for WebAssemblyView {
     fn as_ref(&self) -> &BinaryView {
         &self.handle
@@ -19,7 +42,7 @@ impl AsRef<BinaryView> for WebAssemblyView {
 
 impl BinaryViewBase for WebAssemblyView {
     fn entry_point(&self) -> u64 {
-        0
+        self.metadata_integer(ENTRY_POINT_KEY).unwrap_or(0)
     }
 
     fn default_endianness(&self) -> Endianness {
@@ -27,14 +50,15 @@ impl BinaryViewBase for WebAssemblyView {
     }
 
     fn address_size(&self) -> usize {
-        4
+        self.metadata_integer(ADDRESS_SIZE_KEY)
+            .map_or(4, |address_size| address_size as usize)
     }
 }
 
-static SHOULD_PARSE: Mutex<bool> = Mutex::new(false);
-
 unsafe impl CustomBinaryView for WebAssemblyView {
-    type Args = ();
+    // Whether to parse the module. Views that binja only creates to read the load
+    // settings of a file are left empty.
+    type Args = bool;
 
     fn new(handle: &BinaryView, _args: &Self::Args) -> binaryninja::binary_view::Result<Self> {
         Ok(Self {
@@ -42,18 +66,15 @@ unsafe impl CustomBinaryView for WebAssemblyView {
         })
     }
 
-    fn init(&mut self, _args: Self::Args) -> binaryninja::binary_view::Result<()> {
+    fn init(&mut self, parse: Self::Args) -> binaryninja::binary_view::Result<()> {
         let arch = CoreArchitecture::by_name("wasm").ok_or(())?;
-        let platform = arch.standalone_platform().ok_or(())?;
+        // The platform is refined once the imports have been parsed.
+        let platform = WebAssemblyPlatform::Bare.platform().ok_or(())?;
 
         self.set_default_arch(&arch);
         self.set_default_platform(&platform);
 
-        // For some reason, binja will ask us to create a BinaryView twice...
-        // but it only expects the second one to actually parse the file.
-        let mut should_parse = SHOULD_PARSE.lock().unwrap();
-        if !*should_parse {
-            *should_parse = true;
+        if !parse {
             return Ok(());
         }
 
@@ -1,6 +1,29 @@
-use binaryninja::binary_view::{BinaryView, BinaryViewBase};
+use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
 use binaryninja::custom_binary_view::{BinaryViewType, BinaryViewTypeBase, CustomBinaryViewType, CustomView, CustomViewBuilder};
+use binaryninja::rc::Ref;
+use binaryninja::settings::Settings;
+use crate::binja::parse::embedded::find_embedded_modules;
+use crate::binja::parse::js_carrier::{
+    may_be_script, probe_carries_module, read_carried_modules, script_carries_module,
+};
+use crate::binja::parse::text::{assemble_text_module, starts_like_text_module};
+use crate::binja::settings::{detect_embedded_modules, register_load_settings};
 use crate::binja::view::WebAssemblyView;
+use log::{error, info};
+
+const TEXT_PROBE_LEN: usize = 4096;
+
+// The file assembled into a binary module, once it looks like a module in the text
+// format. Files are only assembled when the view is created, since it takes a read of
+// the whole file.
+fn text_module(data: &BinaryView) -> Option<Vec<u8>> {
+    let mut text = Vec::new();
+    let len = data.len() as usize;
+    if data.read_into_vec(&mut text, 0, len) != len {
+        return None;
+    }
+    assemble_text_module(&text)
+}
 
 pub struct WebAssemblyViewType {
     handle: BinaryViewType,
@@ -14,13 +37,41 @@ impl WebAssemblyViewType {
 
 impl BinaryViewTypeBase for WebAssemblyViewType {
     fn is_valid_for(&self, data: &BinaryView) -> bool {
-        let mut buf = [0; 8];
+        // Text files are recognized by their first form, which is usually near the start.
+        let mut buf = [0; TEXT_PROBE_LEN];
         let len = BinaryViewBase::read(data, &mut buf, 0);
-        if len != 8 {
+        if starts_like_text_module(&buf[..len]) {
+            return true;
+        }
+        if len < 8 {
             return false;
         }
 
-        buf == "\0asm\x01\0\0\0".as_bytes()
+        // Core modules have version 1; components have version 0xd and layer 1.
+        let header = &buf[..8];
+        if header == "\0asm\x01\0\0\0".as_bytes() || header == "\0asm\x0d\0\x01\0".as_bytes() {
+            return true;
+        }
+
+        // Scripts that carry modules inline, e.g. as base64 strings, if one starts near
+        // the start of the file. The modules are only decoded once the view is created.
+        if probe_carries_module(&buf[..len]) {
+            return true;
+        }
+
+        // Other files are only scanned if the user asked for it, since it takes a read
+        // of the whole file.
+        if !detect_embedded_modules(data) {
+            return false;
+        }
+        (may_be_script(&buf[..len]) && script_carries_module(data))
+            || !find_embedded_modules(data).is_empty()
+    }
+
+    fn load_settings_for_data(&self, data: &BinaryView) -> Option<Ref<Settings>> {
+        let settings = self.default_load_settings_for_data(data)?;
+        register_load_settings(&settings);
+        Some(settings)
     }
 }
 
@@ -36,6 +87,59 @@ impl CustomBinaryViewType for WebAssemblyViewType {
         data: &BinaryView,
         builder: CustomViewBuilder<'builder, Self>,
     ) -> binaryninja::binary_view::Result<CustomView<'builder>> {
-        builder.create::<WebAssemblyView>(data, ())
+        let mut buf = [0; TEXT_PROBE_LEN];
+        let len = BinaryViewBase::read(data, &mut buf, 0);
+        let is_text = starts_like_text_module(&buf[..len]);
+
+        // Scripts are loaded as the largest module they carry, which is decoded in
+        // memory, like text modules are assembled.
+        if !is_text && may_be_script(&buf[..len]) {
+            let mut modules = read_carried_modules(data);
+            modules.sort_by_key(|module| std::cmp::Reverse(module.data.len()));
+            if let Some(module) = modules.first() {
+                for other in &modules[1..] {
+                    info!(
+                        "Script also carries a {}-byte module in a {} at {:#x}",
+                        other.data.len(),
+                        other.encoding,
+                        other.offset
+                    );
+                }
+                info!(
+                    "Decoded {}-byte module from a {} at {:#x}",
+                    module.data.len(),
+                    module.encoding,
+                    module.offset
+                );
+                let parent = BinaryView::from_data(&data.file(), &module.data)?;
+                return builder.create::<WebAssemblyView>(&parent, true);
+            }
+        }
+
+        // Binary modules, and other files with modules embedded in them, which the view
+        // picks one of when it parses the file.
+        if !is_text {
+            return builder.create::<WebAssemblyView>(data, true);
+        }
+
+        // Text modules are assembled in memory, and the view is built on top of the
+        // resulting binary instead of the file.
+        let Some(binary) = text_module(data) else {
+            error!("The file looks like a text module, but it does not assemble");
+            return Err(());
+        };
+        info!("Assembled text module into {} bytes", binary.len());
+        let parent = BinaryView::from_data(&data.file(), &binary)?;
+        builder.create::<WebAssemblyView>(&parent, true)
+    }
+
+    // Binja creates a view just to read the load settings of a file before it opens
+    // the file, which doesn't need the module.
+    fn create_custom_view_for_parse<'builder>(
+        &self,
+        data: &BinaryView,
+        builder: CustomViewBuilder<'builder, Self>,
+    ) -> binaryninja::binary_view::Result<CustomView<'builder>> {
+        builder.create::<WebAssemblyView>(data, false)
     }
 }
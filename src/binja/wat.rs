@@ -0,0 +1,856 @@
+use crate::binja::arch::opcode_name;
+use crate::binja::arch::stack_effect::stack_effect;
+use crate::binja::parse::module_data::{
+    BranchTarget, DataMode, ElementMode, FunctionData, ModuleData, OperatorData,
+};
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use wasmparser::{BlockType, Catch, MemArg, Operator, ValType};
+
+// Characters that WAT identifiers may hold besides letters and digits.
+const ID_CHARS: &str = "!#$%&'*+-./:<=>?@\\^_`|~";
+
+// Indirect calls are commented with at most this many of the functions they can call.
+const MAX_LISTED_CALLEES: usize = 8;
+
+// Data segments are listed with this many bytes to a line.
+const DATA_LINE_LEN: usize = 16;
+
+// An identifier for a name, e.g. `$malloc`. Characters that identifiers can't hold are
+// replaced with `_`.
+fn id(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() || ID_CHARS.contains(c) {
+                true => c,
+                false => '_',
+            },
+        )
+        .collect::<String>();
+    format!("${name}")
+}
+
+// Identifiers for `names` by index. Names that would give the same identifier get the
+// index appended, e.g. `$foo.12`.
+fn unique_ids(names: impl Iterator<Item = (u32, String)>) -> BTreeMap<u32, String> {
+    let ids = names
+        .map(|(index, name)| (index, id(&name)))
+        .collect::<Vec<_>>();
+    let mut counts = BTreeMap::<&str, usize>::new();
+    for (_, id) in &ids {
+        *counts.entry(id).or_default() += 1;
+    }
+    ids.iter()
+        .map(|(index, id)| match counts[id.as_str()] {
+            1 => (*index, id.clone()),
+            _ => (*index, format!("{id}.{index}")),
+        })
+        .collect()
+}
+
+// The identifiers that functions and globals are referred to by, from the names the
+// plugin resolved for them.
+pub(crate) struct Names {
+    funcs: BTreeMap<u32, String>,
+    globals: BTreeMap<u32, String>,
+}
+
+impl Names {
+    pub(crate) fn new(module_data: &ModuleData) -> Self {
+        let n_funcs = module_data.func_addrs.len() as u32;
+        let funcs = (0..n_funcs).map(|func_index| (func_index, module_data.func_name(func_index)));
+        let globals = module_data
+            .global_names
+            .iter()
+            .map(|(global_index, name)| (*global_index, name.clone()));
+        Self {
+            funcs: unique_ids(funcs),
+            globals: unique_ids(globals),
+        }
+    }
+
+    pub(crate) fn func(&self, func_index: u32) -> String {
+        match self.funcs.get(&func_index) {
+            Some(id) => id.clone(),
+            None => func_index.to_string(),
+        }
+    }
+
+    pub(crate) fn global(&self, global_index: u32) -> String {
+        match self.globals.get(&global_index) {
+            Some(id) => id.clone(),
+            None => global_index.to_string(),
+        }
+    }
+}
+
+// A string literal. Bytes other than printable ASCII are escaped, as `\hh`.
+fn string(data: &[u8]) -> String {
+    let mut text = String::from("\"");
+    for byte in data {
+        match byte {
+            b'"' | b'\\' => text.push_str(&format!("\\{}", *byte as char)),
+            0x20..=0x7e => text.push(*byte as char),
+            _ => text.push_str(&format!("\\{byte:02x}")),
+        }
+    }
+    text.push('"');
+    text
+}
+
+// A float constant, exactly: the shortest decimal that reads back as the same value,
+// or the sign and payload of a NaN.
+fn float(value: f64, is_nan: bool, negative: bool, payload: u64) -> String {
+    let sign = if negative { "-" } else { "" };
+    if is_nan {
+        format!("{sign}nan:{payload:#x}")
+    } else if value.is_infinite() {
+        format!("{sign}inf")
+    } else {
+        format!("{value:?}")
+    }
+}
+
+fn f32_text(bits: u32) -> String {
+    let value = f32::from_bits(bits);
+    if value.is_nan() || value.is_infinite() {
+        return float(
+            value as f64,
+            value.is_nan(),
+            bits >> 31 != 0,
+            (bits & 0x7fffff) as u64,
+        );
+    }
+    format!("{value:?}")
+}
+
+fn f64_text(bits: u64) -> String {
+    let value = f64::from_bits(bits);
+    float(
+        value,
+        value.is_nan(),
+        bits >> 63 != 0,
+        bits & 0xfffffffffffff,
+    )
+}
+
+pub(crate) fn value_types(keyword: &str, types: &[ValType]) -> Option<String> {
+    if types.is_empty() {
+        return None;
+    }
+    let types = types.iter().map(|ty| format!("{ty}")).collect::<Vec<_>>();
+    Some(format!("({keyword} {})", types.join(" ")))
+}
+
+fn block_type(blockty: &BlockType) -> Option<String> {
+    match *blockty {
+        BlockType::Empty => None,
+        BlockType::Type(ty) => value_types("result", &[ty]),
+        BlockType::FuncType(type_index) => Some(format!("(type {type_index})")),
+    }
+}
+
+fn memarg(op: &Operator) -> Option<MemArg> {
+    match *op {
+        Operator::I32Load { memarg }
+        | Operator::I64Load { memarg }
+        | Operator::F32Load { memarg }
+        | Operator::F64Load { memarg }
+        | Operator::I32Load8S { memarg }
+        | Operator::I32Load8U { memarg }
+        | Operator::I32Load16S { memarg }
+        | Operator::I32Load16U { memarg }
+        | Operator::I64Load8S { memarg }
+        | Operator::I64Load8U { memarg }
+        | Operator::I64Load16S { memarg }
+        | Operator::I64Load16U { memarg }
+        | Operator::I64Load32S { memarg }
+        | Operator::I64Load32U { memarg }
+        | Operator::I32Store { memarg }
+        | Operator::I64Store { memarg }
+        | Operator::F32Store { memarg }
+        | Operator::F64Store { memarg }
+        | Operator::I32Store8 { memarg }
+        | Operator::I32Store16 { memarg }
+        | Operator::I64Store8 { memarg }
+        | Operator::I64Store16 { memarg }
+        | Operator::I64Store32 { memarg } => Some(memarg),
+        _ => None,
+    }
+}
+
+fn memarg_text(memarg: &MemArg) -> Vec<String> {
+    let mut immediates = Vec::new();
+    if memarg.memory != 0 {
+        immediates.push(memarg.memory.to_string());
+    }
+    if memarg.offset != 0 {
+        immediates.push(format!("offset={}", memarg.offset));
+    }
+    if memarg.align != memarg.max_align {
+        immediates.push(format!("align={}", 1u64 << memarg.align));
+    }
+    immediates
+}
+
+// Writes the operators of a function as WAT.
+struct FuncWriter<'a> {
+    module_data: &'a ModuleData,
+    names: &'a Names,
+    func: &'a FunctionData,
+    locals: BTreeMap<u32, String>,
+    folded: bool,
+    annotated: bool,
+    lines: Vec<String>,
+
+    // How many blocks the current operator is in. wasm2wat numbers the labels of
+    // blocks by this, e.g. `block  ;; label = @1`, and refers to them in branches, e.g.
+    // `br 0 (;@1;)`.
+    depth: u32,
+
+    // With `folded`, the expressions whose values are on the stack and not used yet,
+    // in the order they are evaluated.
+    pending: Vec<String>,
+}
+
+impl FuncWriter<'_> {
+    fn line(&mut self, text: String) {
+        let indent = "  ".repeat(self.depth as usize + 1);
+        self.lines.push(format!("{indent}{text}"));
+    }
+
+    fn flush(&mut self) {
+        for expr in std::mem::take(&mut self.pending) {
+            self.line(expr);
+        }
+    }
+
+    fn local(&self, local_index: u32) -> String {
+        match self.locals.get(&local_index) {
+            Some(id) => id.clone(),
+            None => local_index.to_string(),
+        }
+    }
+
+    fn label(&self, relative_depth: u32) -> String {
+        format!(
+            "{relative_depth} (;@{};)",
+            self.depth.saturating_sub(relative_depth)
+        )
+    }
+
+    // With `annotated`, what the analysis found about an operator: the addresses that a
+    // branch goes to, and the functions that an indirect call can call.
+    fn annotation(&self, addr: u64, op: &OperatorData) -> Option<String> {
+        if !self.annotated {
+            return None;
+        }
+        if let Some(callees) = self.module_data.indirect_call_targets.get(&addr) {
+            let mut names = callees
+                .iter()
+                .take(MAX_LISTED_CALLEES)
+                .map(|callee| self.names.func(*callee))
+                .collect::<Vec<_>>();
+            if callees.len() > MAX_LISTED_CALLEES {
+                names.push("...".to_string());
+            }
+            return Some(format!("calls {}", names.join(", ")));
+        }
+        let (Operator::Br { .. } | Operator::BrIf { .. } | Operator::BrTable { .. }) = op.op else {
+            return None;
+        };
+        Some(match op.target.as_ref()? {
+            BranchTarget::Unconditional(target) => format!("to {target:#x}"),
+            BranchTarget::Conditional { true_target, .. } => format!("to {true_target:#x}"),
+            BranchTarget::Table {
+                targets,
+                default_target,
+            } => {
+                let targets = targets.iter().map(|target| format!("{target:#x}"));
+                let targets = targets.collect::<Vec<_>>().join(" ");
+                format!("to {targets}, default {default_target:#x}")
+            }
+            BranchTarget::FunctionEnd => "returns".to_string(),
+        })
+    }
+
+    // The immediates of an operator as WAT writes them after its name.
+    fn immediates(&self, op: &Operator) -> Vec<String> {
+        if let Some(memarg) = memarg(op) {
+            return memarg_text(&memarg);
+        }
+        match op {
+            Operator::Block { blockty }
+            | Operator::Loop { blockty }
+            | Operator::If { blockty }
+            | Operator::Try { blockty } => block_type(blockty).into_iter().collect(),
+            Operator::TryTable { try_table } => {
+                let mut immediates = block_type(&try_table.ty).into_iter().collect::<Vec<_>>();
+                for catch in &try_table.catches {
+                    immediates.push(match *catch {
+                        Catch::One { tag, label } => format!("(catch {tag} {label})"),
+                        Catch::OneRef { tag, label } => format!("(catch_ref {tag} {label})"),
+                        Catch::All { label } => format!("(catch_all {label})"),
+                        Catch::AllRef { label } => format!("(catch_all_ref {label})"),
+                    });
+                }
+                immediates
+            }
+            Operator::Br { relative_depth }
+            | Operator::BrIf { relative_depth }
+            | Operator::Rethrow { relative_depth }
+            | Operator::Delegate { relative_depth } => vec![self.label(*relative_depth)],
+            Operator::BrTable { targets } => {
+                let mut immediates = targets
+                    .targets()
+                    .flatten()
+                    .map(|target| target.to_string())
+                    .collect::<Vec<_>>();
+                immediates.push(self.label(targets.default()));
+                immediates
+            }
+            Operator::Throw { tag_index } | Operator::Catch { tag_index } => {
+                vec![tag_index.to_string()]
+            }
+            Operator::Call { function_index } => vec![self.names.func(*function_index)],
+            Operator::CallIndirect {
+                type_index,
+                table_index,
+            } => vec![table_index.to_string(), format!("(type {type_index})")],
+            Operator::LocalGet { local_index }
+            | Operator::LocalSet { local_index }
+            | Operator::LocalTee { local_index } => vec![self.local(*local_index)],
+            Operator::GlobalGet { global_index } | Operator::GlobalSet { global_index } => {
+                vec![self.names.global(*global_index)]
+            }
+            Operator::TableInit { elem_index, table } => {
+                vec![table.to_string(), elem_index.to_string()]
+            }
+            Operator::ElemDrop { elem_index } => vec![elem_index.to_string()],
+            Operator::TableCopy {
+                dst_table,
+                src_table,
+            } => vec![dst_table.to_string(), src_table.to_string()],
+            Operator::MemorySize { mem } | Operator::MemoryGrow { mem } if *mem != 0 => {
+                vec![mem.to_string()]
+            }
+            Operator::I32Const { value } => vec![value.to_string()],
+            Operator::I64Const { value } => vec![value.to_string()],
+            Operator::F32Const { value } => vec![f32_text(value.bits())],
+            Operator::F64Const { value } => vec![f64_text(value.bits())],
+            _ => Vec::new(),
+        }
+    }
+
+    // Writes the operator at `addr`. Blocks are written as their opening and closing
+    // instructions, so the output reads like the disassembly; with `folded`, the
+    // operands of other instructions are nested in them where they can be.
+    fn write(&mut self, addr: u64, op: &OperatorData) {
+        let Some(name) = opcode_name(self.module_data, op) else {
+            self.flush();
+            self.line(format!(";; unsupported: {:?}", op.op));
+            return;
+        };
+        let mut text = [name]
+            .into_iter()
+            .chain(self.immediates(&op.op))
+            .collect::<Vec<_>>()
+            .join(" ");
+        // A block comment, since folded instructions can be followed by their operands.
+        if let Some(annotation) = self.annotation(addr, op) {
+            text.push_str(&format!(" (; {annotation} ;)"));
+        }
+
+        match op.op {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. }
+            | Operator::TryTable { .. } => {
+                self.flush();
+                self.line(format!("{text}  ;; label = @{}", self.depth + 1));
+                self.depth += 1;
+            }
+            Operator::Else | Operator::Catch { .. } | Operator::CatchAll => {
+                self.flush();
+                self.depth -= 1;
+                self.line(text);
+                self.depth += 1;
+            }
+            Operator::End | Operator::Delegate { .. } => {
+                self.flush();
+                // The function's own `end` is the closing parenthesis.
+                if addr + op.size as u64 == self.func.end {
+                    return;
+                }
+                self.depth = self.depth.saturating_sub(1);
+                self.line(text);
+            }
+            _ if self.folded => self.fold(&op.op, text),
+            _ => self.line(text),
+        }
+    }
+
+    fn fold(&mut self, op: &Operator, text: String) {
+        let effect = stack_effect(self.module_data, Some(self.func), op)
+            .filter(|effect| effect.pushes.len() <= 1);
+        let Some(effect) = effect else {
+            self.flush();
+            self.line(text);
+            return;
+        };
+        // The operands that are on the stack from before aren't folded, which keeps
+        // them first, as they are evaluated first.
+        let n_operands = effect.pops.len().min(self.pending.len());
+        let operands = self.pending.split_off(self.pending.len() - n_operands);
+        let expr = format!(
+            "({})",
+            [text]
+                .into_iter()
+                .chain(operands)
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+        match effect.pushes.len() {
+            1 => self.pending.push(expr),
+            _ => {
+                // Anything evaluated before this must still be evaluated before it.
+                self.flush();
+                self.line(expr);
+            }
+        }
+    }
+}
+
+// Writes a function as WAT: its signature and locals with the names the plugin gave
+// them, and its body as instructions, `folded` into S-expressions or not. With
+// `annotated`, branches and indirect calls are commented with where they go.
+pub(crate) fn func_wat(
+    module_data: &ModuleData,
+    names: &Names,
+    func: &FunctionData,
+    folded: bool,
+    annotated: bool,
+) -> String {
+    let params = module_data
+        .func_type(func.func_index)
+        .map_or(&[][..], |ty| ty.params());
+    let results = module_data
+        .func_type(func.func_index)
+        .map_or(&[][..], |ty| ty.results());
+    let locals = params.iter().copied().chain(
+        func.locals
+            .iter()
+            .flat_map(|(count, ty)| std::iter::repeat_n(*ty, *count as usize)),
+    );
+    let locals = locals.collect::<Vec<_>>();
+    let local_ids = unique_ids((0..locals.len() as u32).map(|local_index| {
+        (
+            local_index,
+            module_data.local_name(func.func_index, local_index),
+        )
+    }));
+
+    let mut header = vec![format!("(func {}", names.func(func.func_index))];
+    if let Some(name) = module_data.func_exports.get(&func.func_index) {
+        header.push(format!("(export {})", string(name.as_bytes())));
+    }
+    if let Some(type_index) = module_data.func_types.get(func.func_index as usize) {
+        header.push(format!("(type {type_index})"));
+    }
+    for (local_index, ty) in params.iter().enumerate() {
+        header.push(format!("(param {} {ty})", local_ids[&(local_index as u32)]));
+    }
+    header.extend(value_types("result", results));
+
+    let mut writer = FuncWriter {
+        module_data,
+        names,
+        func,
+        locals: local_ids,
+        folded,
+        annotated,
+        lines: vec![header.join(" ")],
+        depth: 0,
+        pending: Vec::new(),
+    };
+    for (local_index, ty) in locals.iter().enumerate().skip(params.len()) {
+        let id = writer.local(local_index as u32);
+        writer.line(format!("(local {id} {ty})"));
+    }
+    for (addr, op) in &func.ops {
+        writer.write(*addr, op);
+    }
+    writer.flush();
+    writer.lines.push(")".to_string());
+    writer.lines.join("\n")
+}
+
+// A constant expression for an offset or initial value of type `ty`.
+// Writes the instructions of a function that start in `range` as WAT, with the names
+// and labels they have in the whole function, and branches and indirect calls
+// commented with where they go. The instructions are indented relative to the first.
+pub(crate) fn range_wat(
+    module_data: &ModuleData,
+    names: &Names,
+    func: &FunctionData,
+    range: Range<u64>,
+) -> String {
+    let n_params = module_data
+        .func_type(func.func_index)
+        .map_or(0, |ty| ty.params().len());
+    let n_locals = n_params
+        + func
+            .locals
+            .iter()
+            .map(|(count, _)| *count as usize)
+            .sum::<usize>();
+    let local_ids = unique_ids((0..n_locals as u32).map(|local_index| {
+        (
+            local_index,
+            module_data.local_name(func.func_index, local_index),
+        )
+    }));
+
+    // Labels are numbered by how many blocks are open, so count those opened before.
+    let mut depth = 0u32;
+    for (addr, op) in func.ops.range(func.ops_start..range.start) {
+        match op.op {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. }
+            | Operator::TryTable { .. } => depth += 1,
+            Operator::End | Operator::Delegate { .. } if addr + op.size as u64 != func.end => {
+                depth = depth.saturating_sub(1)
+            }
+            _ => {}
+        }
+    }
+
+    let mut writer = FuncWriter {
+        module_data,
+        names,
+        func,
+        locals: local_ids,
+        folded: false,
+        annotated: true,
+        lines: Vec::new(),
+        depth,
+        pending: Vec::new(),
+    };
+    for (addr, op) in func.ops.range(range) {
+        writer.write(*addr, op);
+    }
+    writer.flush();
+    let indent = writer
+        .lines
+        .iter()
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let lines = writer.lines.iter().map(|line| &line[indent..]);
+    lines.collect::<Vec<_>>().join("\n")
+}
+
+fn const_expr(ty: ValType, value: u64) -> String {
+    match ty {
+        ValType::I64 => format!("(i64.const {})", value as i64),
+        ValType::F32 => format!("(f32.const {})", f32_text(value as u32)),
+        ValType::F64 => format!("(f64.const {})", f64_text(value)),
+        _ => format!("(i32.const {})", value as u32 as i32),
+    }
+}
+
+// Writes the whole module as WAT, as `func_wat` writes functions with `annotated`, and
+// with the contents of the data segments listed a line at a time alongside the
+// addresses they are loaded at. Tables, memories and globals are written as they are
+// defined whether or not they are imported, since only the names of imported functions
+// are kept.
+// The type section, as WAT.
+fn type_lines(module_data: &ModuleData) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (type_index, ty) in module_data.types.iter().enumerate() {
+        let signature = [
+            value_types("param", ty.params()),
+            value_types("result", ty.results()),
+        ];
+        let signature = signature.into_iter().flatten();
+        let func = ["func".to_string()].into_iter().chain(signature);
+        let func = func.collect::<Vec<_>>().join(" ");
+        lines.push(format!("  (type (;{type_index};) ({func}))"));
+    }
+    lines
+}
+
+// A module to assemble `text`, a new version of function `func_index`, in. `text` is
+// either a whole `(func ...)`, as `func_wat` writes it, or the locals and instructions
+// that follow the signature. The module has the same types, and functions and globals
+// at the same indices and with the identifiers that `func_wat` refers to them by, so
+// that the new body refers to the same things. The other functions are imported or
+// left `unreachable`, so the new body is the first in the code section. Tables,
+// memories and tags are only referred to by index, so they aren't declared.
+pub(crate) fn context_wat(
+    module_data: &ModuleData,
+    names: &Names,
+    func_index: u32,
+    text: &str,
+) -> String {
+    let func_header = |func_index: u32| {
+        let mut func = vec![format!("func {}", names.func(func_index))];
+        if let Some(type_index) = module_data.func_types.get(func_index as usize) {
+            func.push(format!("(type {type_index})"));
+        }
+        func.join(" ")
+    };
+
+    let mut lines = vec!["(module".to_string()];
+    lines.extend(type_lines(module_data));
+    for func_index in 0..func_index {
+        lines.push(format!(
+            "  (import \"\" \"\" ({}))",
+            func_header(func_index)
+        ));
+    }
+    for (global_index, global) in module_data.globals.iter().enumerate() {
+        let ty = global.ty.content_type;
+        let ty_text = match global.ty.mutable {
+            true => format!("(mut {ty})"),
+            false => ty.to_string(),
+        };
+        let id = names.global(global_index as u32);
+        let id = match id.starts_with('$') {
+            true => id,
+            false => format!("(;{global_index};)"),
+        };
+        lines.push(format!("  (import \"\" \"\" (global {id} {ty_text}))"));
+    }
+
+    if text.trim_start().starts_with("(func") {
+        lines.push(text.to_string());
+    } else {
+        let params = module_data
+            .func_type(func_index)
+            .map_or(&[][..], |ty| ty.params());
+        let param_ids = unique_ids(
+            (0..params.len() as u32)
+                .map(|local_index| (local_index, module_data.local_name(func_index, local_index))),
+        );
+        let mut header = vec![func_header(func_index)];
+        for (local_index, ty) in params.iter().enumerate() {
+            header.push(format!("(param {} {ty})", param_ids[&(local_index as u32)]));
+        }
+        if let Some(ty) = module_data.func_type(func_index) {
+            header.extend(value_types("result", ty.results()));
+        }
+        lines.push(format!("  ({}\n{text}\n  )", header.join(" ")));
+    }
+
+    let n_funcs = module_data.func_addrs.len() as u32;
+    for func_index in func_index + 1..n_funcs {
+        lines.push(format!("  ({} unreachable)", func_header(func_index)));
+    }
+    lines.push(")".to_string());
+    lines.join("\n")
+}
+
+pub(crate) fn module_wat(
+    view: &BinaryView,
+    module_data: &ModuleData,
+    names: &Names,
+    folded: bool,
+) -> String {
+    let mut lines = vec!["(module".to_string()];
+    lines.extend(type_lines(module_data));
+    for (func_index, import) in module_data.func_imports.iter().enumerate() {
+        let func_index = func_index as u32;
+        let mut func = vec![format!("func {}", names.func(func_index))];
+        if let Some(type_index) = module_data.func_types.get(func_index as usize) {
+            func.push(format!("(type {type_index})"));
+        }
+        if let Some(ty) = module_data.func_type(func_index) {
+            func.extend(value_types("param", ty.params()));
+            func.extend(value_types("result", ty.results()));
+        }
+        lines.push(format!(
+            "  (import {} {} ({}))",
+            string(import.module.as_bytes()),
+            string(import.name.as_bytes()),
+            func.join(" ")
+        ));
+    }
+
+    for (_, func) in module_data.funcs.iter() {
+        let func = func.as_ref();
+        lines.push(format!("  ;; {:#x}", func.size_start));
+        for line in func_wat(module_data, names, func, folded, true).lines() {
+            lines.push(format!("  {line}"));
+        }
+    }
+
+    for (table_index, table) in module_data.tables.iter().enumerate() {
+        let index_type = if table.ty.table64 { "i64 " } else { "" };
+        let maximum = table.ty.maximum.map(|maximum| format!(" {maximum}"));
+        lines.push(format!(
+            "  (table (;{table_index};) {index_type}{}{} {}){}",
+            table.ty.initial,
+            maximum.unwrap_or_default(),
+            table.ty.element_type,
+            if table.imported { "  ;; imported" } else { "" },
+        ));
+    }
+    for (memory_index, memory) in module_data.memories.iter().enumerate() {
+        let mut limits = vec![memory.ty.initial.to_string()];
+        limits.extend(memory.ty.maximum.map(|maximum| maximum.to_string()));
+        if memory.ty.shared {
+            limits.push("shared".to_string());
+        }
+        if let Some(page_size_log2) = memory.ty.page_size_log2 {
+            limits.push(format!("(pagesize {})", 1u64 << page_size_log2));
+        }
+        let index_type = if memory.ty.memory64 { "i64 " } else { "" };
+        lines.push(format!(
+            "  (memory (;{memory_index};) {index_type}{}){}",
+            limits.join(" "),
+            if memory.imported { "  ;; imported" } else { "" },
+        ));
+    }
+    for (tag_index, tag) in module_data.tags.iter().enumerate() {
+        lines.push(format!(
+            "  (tag (;{tag_index};) (type {})){}",
+            tag.ty.func_type_idx,
+            if tag.imported { "  ;; imported" } else { "" },
+        ));
+    }
+    for (global_index, global) in module_data.globals.iter().enumerate() {
+        let ty = global.ty.content_type;
+        let ty_text = match global.ty.mutable {
+            true => format!("(mut {ty})"),
+            false => ty.to_string(),
+        };
+        let init = match global.init {
+            Some(init) => format!(" {}", const_expr(ty, init)),
+            None => " (; not a constant ;)".to_string(),
+        };
+        lines.push(format!(
+            "  (global {} {ty_text}{init})",
+            names.global(global_index as u32)
+        ));
+    }
+
+    if let Some(start_func) = module_data.start_func {
+        lines.push(format!("  (start {})", names.func(start_func)));
+    }
+
+    for (elem_index, elem) in module_data.elements.iter().enumerate() {
+        let mode = match elem.mode {
+            ElementMode::Active {
+                table_index,
+                offset,
+            } => {
+                let table = match table_index {
+                    0 => String::new(),
+                    _ => format!(" (table {table_index})"),
+                };
+                let index_type =
+                    module_data
+                        .tables
+                        .get(table_index as usize)
+                        .map_or(ValType::I32, |table| match table.ty.table64 {
+                            true => ValType::I64,
+                            false => ValType::I32,
+                        });
+                let offset = match offset {
+                    Some(offset) => const_expr(index_type, offset),
+                    None => "(; offset is not a constant ;)".to_string(),
+                };
+                format!("{table} {offset}")
+            }
+            ElementMode::Passive => String::new(),
+            ElementMode::Declared => " declare".to_string(),
+        };
+        let items = match elem.items.iter().all(Option::is_some) {
+            true => elem
+                .items
+                .iter()
+                .flatten()
+                .map(|func_index| format!(" {}", names.func(*func_index)))
+                .collect::<String>(),
+            false => elem
+                .items
+                .iter()
+                .map(|item| match item {
+                    Some(func_index) => format!(" (ref.func {})", names.func(*func_index)),
+                    None => " (ref.null func)".to_string(),
+                })
+                .collect::<String>(),
+        };
+        let ty = match elem.items.iter().all(Option::is_some) {
+            true => "func",
+            false => "funcref",
+        };
+        lines.push(format!("  (elem (;{elem_index};){mode} {ty}{items})"));
+    }
+
+    let data_ids = unique_ids(
+        module_data
+            .data_names
+            .iter()
+            .map(|(data_index, name)| (*data_index, name.clone())),
+    );
+    for (data_index, segment) in module_data.data_segments.iter().enumerate() {
+        let id = match data_ids.get(&(data_index as u32)) {
+            Some(id) => id.clone(),
+            None => format!("(;{data_index};)"),
+        };
+        let (mode, base) = match segment.mode {
+            DataMode::Active {
+                memory_index,
+                offset,
+            } => {
+                let memory = match memory_index {
+                    0 => String::new(),
+                    _ => format!(" (memory {memory_index})"),
+                };
+                let index_type = module_data.memories.get(memory_index as usize).map_or(
+                    ValType::I32,
+                    |memory| match memory.ty.memory64 {
+                        true => ValType::I64,
+                        false => ValType::I32,
+                    },
+                );
+                match offset {
+                    Some(offset) => (
+                        format!("{memory} {}", const_expr(index_type, offset)),
+                        segment.memory_range().map(|range| range.start),
+                    ),
+                    None => (format!("{memory} (; offset is not a constant ;)"), None),
+                }
+            }
+            DataMode::Passive => (String::new(), None),
+        };
+        lines.push(format!("  (data {id}{mode}"));
+
+        let len = (segment.data_range.end - segment.data_range.start) as usize;
+        let mut data = Vec::new();
+        if view.read_into_vec(&mut data, segment.data_range.start, len) != len {
+            lines.push("    (; contents could not be read ;)".to_string());
+        }
+        for (i, chunk) in data.chunks(DATA_LINE_LEN).enumerate() {
+            let offset = (i * DATA_LINE_LEN) as u64;
+            match base {
+                Some(base) => lines.push(format!(
+                    "    {}  ;; {:#x}",
+                    string(chunk),
+                    module_data.memory_base + base + offset
+                )),
+                None => lines.push(format!("    {}  ;; +{offset:#x}", string(chunk))),
+            }
+        }
+        lines.push("  )".to_string());
+    }
+    lines.push(")".to_string());
+    lines.join("\n")
+}
@@ -0,0 +1,242 @@
+use crate::binja::parse::local_types::local_vars;
+use crate::binja::parse::module_data::{FunctionData, ModuleData, MODULE_DATA};
+use crate::binja::parse::pointers::data_refs;
+use crate::binja::parse::shadow_stack::frame_vars;
+use crate::binja::parse::signature::refine_signature;
+use crate::binja::view::WebAssemblyView;
+use binaryninja::function::Function;
+use binaryninja::rc::Ref;
+use binaryninja::settings::{QueryOptions, Settings};
+use binaryninja::types::Type;
+use binaryninja::variable::{Variable, VariableSourceType};
+use binaryninja::workflow::{Activity, AnalysisContext, Workflow};
+use log::{info, warn};
+use serde_json::json;
+
+pub const WORKFLOW_NAME: &str = "core.function.wasm";
+const BASE_WORKFLOW: &str = "core.function.metaAnalysis";
+const FUNCTION_WORKFLOW: &str = "analysis.workflows.functionWorkflow";
+
+// The activities run before medium level IL is generated, so that the types and
+// variables they define are there when it is.
+const INSERT_BEFORE: &str = "core.function.generateMediumLevelIL";
+
+const DEVIRTUALIZE: (&str, &str, &str) = (
+    "wasm.function.devirtualizeIndirectCalls",
+    "Devirtualize Indirect Calls",
+    "Add references from each call_indirect to the functions in its table whose type matches (or only to the one it was pinned to), and from each call to a trampoline to the functions it goes on to call.",
+);
+const DATA_REFS: (&str, &str, &str) = (
+    "wasm.function.dataReferences",
+    "Data References",
+    "Add references to the data in linear memory that is accessed at constant addresses, and that constants point into.",
+);
+const PROMOTE_POINTERS: (&str, &str, &str) = (
+    "wasm.function.promotePointers",
+    "Promote Pointers",
+    "Type parameters used as load and store addresses as pointers, and multiple results as structs.",
+);
+const STACK_FRAME: (&str, &str, &str) = (
+    "wasm.function.stackFrame",
+    "Shadow Stack Frame",
+    "Define stack variables for the accesses to the function's frame on the shadow stack.",
+);
+const LOCAL_TYPES: (&str, &str, &str) = (
+    "wasm.function.localTypes",
+    "Local Variable Types",
+    "Type locals by how they are used: as pointers, as bools, and as unsigned integers.",
+);
+
+// Past this many candidates, the references are more noise than help.
+const MAX_CANDIDATE_REFS: usize = 64;
+
+// A change that an activity makes to the function being analyzed. Changes are worked
+// out from the module data, and made once the lock on it is released, since making
+// them can set off more analysis.
+enum FunctionChange {
+    CodeRef { from: u64, to: u64 },
+    Type(Ref<Type>),
+    Var(Variable, Ref<Type>, String),
+    StackVar(i64, Ref<Type>, String),
+}
+
+impl FunctionChange {
+    // Makes the change, unless the function already has it, so that analysis doesn't
+    // start over for nothing. References are a set, so adding one again does nothing.
+    fn apply(&self, bn_func: &Function) {
+        match self {
+            Self::CodeRef { from, to } => bn_func.add_auto_code_ref(*from, *to, None),
+            Self::Type(ty) => {
+                if *bn_func.function_type() != **ty {
+                    bn_func.set_auto_type(ty);
+                }
+            }
+            Self::Var(var, ty, name) => {
+                if !has_var(bn_func, var, ty, name) {
+                    bn_func.create_auto_var(var, ty, name, false);
+                }
+            }
+            Self::StackVar(offset, ty, name) => {
+                let var = Variable::new(VariableSourceType::StackVariableSourceType, 0, *offset);
+                if !has_var(bn_func, &var, ty, name) {
+                    bn_func.create_auto_stack_var(*offset, ty, name);
+                }
+            }
+        }
+    }
+}
+
+fn has_var(bn_func: &Function, var: &Variable, ty: &Type, name: &str) -> bool {
+    let same_type = bn_func
+        .variable_type(var)
+        .is_some_and(|existing| *existing.contents == *ty);
+    same_type && bn_func.variable_name(var) == name
+}
+
+// Works out the changes that `changes` makes to the function being analyzed, if it is
+// a function of the module, and makes them.
+fn change_function(
+    ctx: &AnalysisContext,
+    changes: impl FnOnce(&ModuleData, &FunctionData) -> Vec<FunctionChange>,
+) {
+    let bn_func = ctx.function();
+    let changes = {
+        let module_data_lock = MODULE_DATA.lock().unwrap();
+        let Some(module_data) = module_data_lock.as_ref() else {
+            return;
+        };
+        let Some(func) = module_data.funcs.get(&bn_func.start()) else {
+            return;
+        };
+        changes(module_data, func.as_ref())
+    };
+    for change in &changes {
+        change.apply(&bn_func);
+    }
+}
+
+// The functions that the indirect call at `addr` is taken to call: the one it was
+// pinned to, or else its candidates, unless there are too many.
+pub(crate) fn call_targets(module_data: &ModuleData, addr: u64) -> Vec<u32> {
+    match module_data.call_target_overrides.get(&addr) {
+        Some(func_index) => vec![*func_index],
+        None => match module_data.indirect_call_targets.get(&addr) {
+            Some(candidates) if candidates.len() <= MAX_CANDIDATE_REFS => candidates.clone(),
+            _ => Vec::new(),
+        },
+    }
+}
+
+// Addresses of the functions that the indirect call at `addr` gets references to.
+pub(crate) fn call_target_refs(module_data: &ModuleData, addr: u64) -> Vec<u64> {
+    call_targets(module_data, addr)
+        .iter()
+        .filter_map(|func_index| module_data.func_addrs.get(*func_index as usize).copied())
+        .collect()
+}
+
+fn devirtualize_indirect_calls(
+    module_data: &ModuleData,
+    func: &FunctionData,
+) -> Vec<FunctionChange> {
+    let calls = module_data
+        .indirect_call_targets
+        .range(func.ops_start..func.end);
+    calls
+        .flat_map(|(addr, _)| {
+            call_target_refs(module_data, *addr)
+                .into_iter()
+                .map(|to| FunctionChange::CodeRef { from: *addr, to })
+        })
+        .collect()
+}
+
+// An activity that makes the changes `changes` works out to each function of the
+// module. It can be turned off in the settings, and is on by default.
+fn activity(
+    (name, title, description): (&str, &str, &str),
+    changes: impl Fn(&ModuleData, &FunctionData) -> Vec<FunctionChange> + Copy + 'static,
+) -> Ref<Activity> {
+    let config = json!({
+        "name": name,
+        "title": title,
+        "description": description,
+        "eligibility": {"auto": {"default": true}},
+    });
+    Activity::new_with_action(&config.to_string(), move |ctx| {
+        change_function(ctx, changes)
+    })
+}
+
+// Registers the function workflow that wasm views are analyzed with: binja's own
+// analysis, plus the wasm-specific passes.
+pub fn register_workflow() {
+    let workflow = Workflow::instance(BASE_WORKFLOW).clone_to(WORKFLOW_NAME);
+    let activities = [
+        (
+            DEVIRTUALIZE,
+            activity(DEVIRTUALIZE, devirtualize_indirect_calls),
+        ),
+        (
+            DATA_REFS,
+            activity(DATA_REFS, |module_data, func| {
+                data_refs(module_data, func)
+                    .into_iter()
+                    .map(|(from, to)| FunctionChange::CodeRef { from, to })
+                    .collect()
+            }),
+        ),
+        (
+            PROMOTE_POINTERS,
+            activity(PROMOTE_POINTERS, |module_data, func| {
+                refine_signature(module_data, func)
+                    .into_iter()
+                    .map(FunctionChange::Type)
+                    .collect()
+            }),
+        ),
+        (
+            LOCAL_TYPES,
+            activity(LOCAL_TYPES, |module_data, func| {
+                local_vars(module_data, func)
+                    .into_iter()
+                    .map(|(var, ty, name)| FunctionChange::Var(var, ty, name))
+                    .collect()
+            }),
+        ),
+        (
+            STACK_FRAME,
+            activity(STACK_FRAME, |module_data, func| {
+                frame_vars(module_data, func)
+                    .into_iter()
+                    .map(|(offset, ty, name)| FunctionChange::StackVar(offset, ty, name))
+                    .collect()
+            }),
+        ),
+    ];
+    for ((name, ..), activity) in &activities {
+        if workflow.register_activity(activity).is_err() {
+            warn!("Failed to register workflow activity {name}");
+            return;
+        }
+    }
+    let names = activities.iter().map(|((name, ..), _)| *name);
+    workflow.insert(INSERT_BEFORE, names);
+    if workflow.register().is_err() {
+        warn!("Failed to register workflow {WORKFLOW_NAME}");
+    }
+}
+
+impl WebAssemblyView {
+    // Analyzes the view's functions with the wasm workflow, unless the user picked
+    // another one when opening the file.
+    pub(crate) fn select_workflow(&self) {
+        let settings = Settings::new();
+        let mut options = QueryOptions::new_with_view(self.as_ref());
+        if settings.get_string_with_opts(FUNCTION_WORKFLOW, &mut options) != BASE_WORKFLOW {
+            return;
+        }
+        info!("Using workflow {WORKFLOW_NAME}");
+        settings.set_string_with_opts(FUNCTION_WORKFLOW, WORKFLOW_NAME, &mut options);
+    }
+}
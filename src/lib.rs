@@ -1,11 +1,18 @@
 mod binja;
 mod util;
 
+use crate::binja::commands::register_commands;
+use crate::binja::dwarf::register_dwarf_parser;
+use crate::binja::platform::register_platforms;
+use crate::binja::settings::{register_settings, VIEW_TYPE_NAME};
 use crate::binja::view_type::WebAssemblyViewType;
+use crate::binja::workflow::register_workflow;
 use binaryninja::architecture::register_architecture;
 use binaryninja::custom_binary_view::register_view_type;
 use binaryninja::logger::Logger;
-use binja::arch::WebAssemblyArchitecture;
+use binaryninja::relocation::register_relocation_handler;
+use binaryninja::settings::Settings;
+use binja::arch::{WebAssemblyArchitecture, WebAssemblyRelocationHandler};
 use log::LevelFilter;
 
 #[allow(non_snake_case)]
@@ -14,7 +21,13 @@ pub extern "C" fn CorePluginInit() -> bool {
     Logger::new("WebAssembly Plugin")
         .with_level(LevelFilter::Trace)
         .init();
-    register_architecture("wasm", WebAssemblyArchitecture::new);
-    register_view_type("wasm", "WebAssembly", WebAssemblyViewType::new);
+    register_settings(&Settings::new());
+    let arch = register_architecture("wasm", WebAssemblyArchitecture::new);
+    register_platforms(arch);
+    register_relocation_handler(arch.as_ref(), VIEW_TYPE_NAME, WebAssemblyRelocationHandler::new);
+    register_workflow();
+    register_commands();
+    register_dwarf_parser();
+    register_view_type(VIEW_TYPE_NAME, "WebAssembly", WebAssemblyViewType::new);
     true
 }